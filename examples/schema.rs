@@ -1,9 +1,11 @@
 use std::env::current_dir;
 use std::fs::create_dir_all;
 
-use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
+use cosmwasm_schema::{
+    export_schema, export_schema_with_title, remove_schemas, schema_for, QueryResponses,
+};
 
-use restricted_marker_transfer::msg::{ExecuteMsg, InstantiateMsg};
+use restricted_marker_transfer::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, SudoMsg};
 use restricted_marker_transfer::state::State;
 
 fn main() {
@@ -14,6 +16,11 @@ fn main() {
 
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
-    // export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(SudoMsg), &out_dir);
     export_schema(&schema_for!(State), &out_dir);
+
+    for (query, response) in QueryMsg::response_schemas().unwrap() {
+        export_schema_with_title(&response, &out_dir, &format!("{}_response", query));
+    }
 }
@@ -1,107 +1,434 @@
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::fmt;
 
 use cosmwasm_std::{
-    attr, to_binary, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError, StdResult,
-    Uint128,
+    attr, to_binary, Attribute, BankMsg, Binary, BlockInfo, Deps, DepsMut, Empty, Env, Event,
+    MessageInfo, Order, Reply, Response, StdError, StdResult, Storage, SubMsg, SubMsgResult,
+    Timestamp, Uint128,
 };
 use cosmwasm_std::{entry_point, Addr};
+use cw_storage_plus::Bound;
 use provwasm_std::types::cosmos::base::v1beta1::Coin;
 use provwasm_std::types::provenance::marker::v1::{
-    Access, MarkerAccount, MarkerQuerier, MsgTransferRequest,
+    Access, MarkerAccount, MarkerQuerier, MarkerStatus, MarkerType, MsgTransferRequest,
 };
+use provwasm_std::types::provenance::msgfees::v1::MsgAssessCustomMsgFeeRequest;
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, QueryMsg, Validate};
-use crate::state::{get_all_transfers, Transfer, CONFIG, TRANSFER_STORAGE};
+use crate::msg::{
+    AccruedFeesResponse, AllowlistResponse, ApprovalsResponse, ApproversResponse, AuditLogResponse,
+    AvailableAction, BlocklistResponse, ContractInfoResponse, ContractSettingsResponse,
+    DelegationsResponse, DenomConfigsResponse, DenomSettings, EscrowReconciliationResponse,
+    EstimateFeesResponse, ExecuteMsg, ExportStateResponse, FailedSettlementsResponse,
+    FeeExemptionsResponse, MarkerDetails, PendingAction, PendingActionsResponse, QueryMsg,
+    RegisteredDenomsResponse, RoleMembersResponse, RolesResponse, SequenceResponse, SudoMsg,
+    TemplatesResponse, TransferDetailedResponse, TransferFreezeResponse, TransferInput,
+    TransferLookup, TransferLookupsResponse, TransferResponse, TransfersResponse, Validate,
+    VelocityResponse, VersionInfoResponse, EXPORT_FORMAT_VERSION, MAX_BATCH_SIZE,
+};
+use crate::state::{
+    append_audit_entry, count_pending_by_sender, creations_since, derived_transfer_id, fee_denom,
+    find_pending_transfers, get_all_transfers, get_allowlist, get_approval_delegations,
+    get_archived_transfers, get_audit_log, get_pending_by_denom, get_pending_queue,
+    get_registered_approvers, get_templates, get_transfer_history, get_transfers_by_sender,
+    get_transfers_by_tag, get_transfers_created, holds_role, load_transfer, may_load_transfer,
+    migrate_legacy_transfers, move_to_archive, next_position, next_sequence, prune_history,
+    record_creation, record_velocity, save_transfer, transfer_key, velocity_used, Allowance,
+    Approval, ApprovalDelegation, CachedMarker, Creation, CreationDeposit, CreationRateLimit,
+    DenomConfig, FailedSettlement, FeeDestination, FeeSchedule, MsgFees, PageRequest, Recovery,
+    RecurringTransfer, Role, ScheduleInterval, SettlementAttempt, SettlementFee, StandingApproval,
+    StorageTransaction, StoredTransfer, Swap, SwapLeg, SwapStatus, Transfer, TransferFreeze,
+    TransferLeg, TransferStatus, TransferTemplate, VelocityLimit, Vesting, ACCRUED_FEES,
+    ALLOWANCE_STORAGE, ALLOWLIST_ENABLED, APPROVAL_DELEGATIONS, APPROVAL_QUORUM, APPROVER_REGISTRY,
+    AUTO_APPROVE_THRESHOLDS, BLOCKLIST, CONFIG, CREATION_DEPOSIT, CREATION_RATE_LIMIT,
+    DEFAULT_FEE_DENOM, DENOM_CONFIG, DENOM_COUNTERS, DENOM_FEE_SCHEDULES, DENOM_VETO, DENOM_VOLUME,
+    FAILED_SETTLEMENTS, FEE_DENOM, FEE_EXEMPTIONS, FROZEN_DENOMS, GLOBAL_VETO, MARKER_CACHE,
+    MAX_PAGE_LIMIT, MAX_TRANSFER_AMOUNT, MSG_FEES, NEXT_SETTLEMENT_REPLY_ID, PAGE_LIMIT, PAUSED,
+    PENDING_ADMIN, PENDING_LIMIT, PENDING_RECOVERY, RECIPIENT_ALLOWLIST, RECOVERY_CURSOR,
+    RECOVERY_DELAY, RECURRING_TRANSFER_STORAGE, REGISTERED_DENOMS, RETENTION_PERIOD, ROLE_MEMBERS,
+    SENDER_SEQUENCE, SETTLEMENT_ATTEMPTS, SETTLEMENT_FEE, STANDING_APPROVALS, SWAP_STORAGE,
+    TRANSFER_DEPOSITS, TRANSFER_FREEZES, TRANSFER_INCENTIVES, TRANSFER_STATS, TRANSFER_STORAGE,
+    TRANSFER_TEMPLATES, TRANSFER_TOTALS, VELOCITY_LIMITS,
+};
 
 pub const CRATE_NAME: &str = env!("CARGO_CRATE_NAME");
 pub const PACKAGE_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Reply id of the submessages escrowing the coins of new transfers
+pub const ESCROW_REPLY_ID: u64 = 1;
+
 // smart contract execute entrypoint
 #[entry_point]
 pub fn execute(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     msg.validate()?;
 
+    if PAUSED.may_load(deps.storage)?.unwrap_or(false) && blocked_while_paused(&msg) {
+        return Err(ContractError::Paused);
+    }
+
+    let actor = info.sender.to_owned();
+    let response = execute_msg(deps.branch(), env.clone(), info, msg)?;
+    record_audit_entry(deps.storage, &env, &actor, &response)?;
+    Ok(response)
+}
+
+fn execute_msg(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::ApproveTransfer { id } => approve_transfer(deps, env, info, id),
+        ExecuteMsg::AcceptAdmin {} => accept_admin(deps, info),
+        ExecuteMsg::AcceptTransfer { id } => accept_transfer(deps, env, info, id),
+        ExecuteMsg::ArchiveTransfers { ids } => archive_transfers(deps, env, info, ids),
+        ExecuteMsg::ApproveSwap { id } => approve_swap(deps, env, info, id),
+        ExecuteMsg::ApproveTransfer { id } => approve_transfer(deps, env, info, id, None),
+        ExecuteMsg::ApproveTransferPartial { id, amount } => {
+            approve_transfer(deps, env, info, id, Some(amount))
+        }
+        ExecuteMsg::ApproveTransfers { ids } => approve_transfers(deps, env, info, ids),
+        ExecuteMsg::BatchTransfer { transfers } => batch_transfer(deps, env, info, transfers),
+        ExecuteMsg::CancelAllBySender { denom } => cancel_all_by_sender(deps, env, info, denom),
+        ExecuteMsg::CancelRecovery {} => cancel_recovery(deps, env, info),
+        ExecuteMsg::CancelSwap { id } => cancel_swap(deps, env, info, id),
+        ExecuteMsg::Claim { id } => claim(deps, env, info, id),
         ExecuteMsg::CancelTransfer { id } => cancel_transfer(deps, env, info, id),
-        ExecuteMsg::RejectTransfer { id } => reject_transfer(deps, env, info, id),
+        ExecuteMsg::CreateFromTemplate {
+            template_id,
+            id,
+            amount,
+        } => create_from_template(deps, env, info, template_id, id, amount),
+        ExecuteMsg::CreateRecurringTransfer {
+            id,
+            denom,
+            amount,
+            recipient,
+            interval,
+            occurrences,
+        } => create_recurring_transfer(
+            deps,
+            env,
+            info,
+            id,
+            denom,
+            amount,
+            recipient,
+            interval,
+            occurrences,
+        ),
+        ExecuteMsg::DeclineTransfer { id } => decline_transfer(deps, env, info, id),
+        ExecuteMsg::DeleteTemplate { template_id } => delete_template(deps, info, template_id),
+        ExecuteMsg::ExecuteRecovery { limit } => execute_recovery(deps, env, info, limit),
+        ExecuteMsg::ExecuteScheduled { id } => execute_scheduled(deps, env, info, id),
+        ExecuteMsg::InitiateRecovery { denom } => initiate_recovery(deps, env, info, denom),
+        ExecuteMsg::ForceCancelTransfer { id } => force_cancel_transfer(deps, env, info, id),
+        ExecuteMsg::FreezeTransfer { id, reason } => freeze_transfer(deps, env, info, id, reason),
+        ExecuteMsg::UnfreezeTransfer { id } => unfreeze_transfer(deps, env, info, id),
+        ExecuteMsg::FundSwap { id } => fund_swap(deps, env, info, id),
+        ExecuteMsg::GrantAllowance {
+            id,
+            recipient,
+            denom,
+            amount,
+        } => grant_allowance(deps, info, id, recipient, denom, amount),
+        ExecuteMsg::Pause {} => set_paused(deps, env, info, true),
+        ExecuteMsg::ProposeSwap {
+            id,
+            denom,
+            amount,
+            counterparty,
+            counterparty_denom,
+            counterparty_amount,
+        } => propose_swap(
+            deps,
+            env,
+            info,
+            id,
+            denom,
+            amount,
+            counterparty,
+            counterparty_denom,
+            counterparty_amount,
+        ),
+        ExecuteMsg::MigrateTransfers { limit } => migrate_transfers(deps, env, info, limit),
+        ExecuteMsg::PruneHistory { older_than } => {
+            prune_transfer_history(deps, env, info, older_than)
+        }
+        ExecuteMsg::PurgeSettled { before, limit } => purge_settled(deps, env, info, before, limit),
+        ExecuteMsg::PullTransfer {
+            allowance_id,
+            amount,
+        } => pull_transfer(deps, env, info, allowance_id, amount),
+        ExecuteMsg::RefreshMarkerCache { denom } => refresh_marker_cache(deps, env, info, denom),
+        ExecuteMsg::ReduceTransfer { id, new_amount } => {
+            reduce_transfer(deps, env, info, id, new_amount)
+        }
+        ExecuteMsg::RejectSwap { id, reason } => reject_swap(deps, env, info, id, reason),
+        ExecuteMsg::RejectTransfer { id, reason } => reject_transfer(deps, env, info, id, reason),
+        ExecuteMsg::RejectTransfers { ids, reason } => {
+            reject_transfers(deps, env, info, ids, reason)
+        }
+        ExecuteMsg::RetryFailed { id } => retry_failed(deps, env, info, id),
+        ExecuteMsg::RevokeAllowance { id } => revoke_allowance(deps, info, id),
+        ExecuteMsg::GrantRole { role, address } => grant_role(deps, env, info, role, address),
+        ExecuteMsg::DelegateApproval {
+            denom,
+            delegate,
+            expires_at,
+        } => delegate_approval(deps, env, info, denom, delegate, expires_at),
+        ExecuteMsg::RevokeDelegation { denom } => revoke_delegation(deps, info, denom),
+        ExecuteMsg::RevokeRole { role, address } => revoke_role(deps, env, info, role, address),
+        ExecuteMsg::RevokeStandingApproval {
+            sender,
+            recipient,
+            denom,
+        } => revoke_standing_approval(deps, info, sender, recipient, denom),
+        ExecuteMsg::SaveTemplate {
+            template_id,
+            denom,
+            recipient,
+            amount,
+            memo,
+        } => save_template(deps, info, template_id, denom, recipient, amount, memo),
+        ExecuteMsg::RegisterDenom { denom } => set_denom_registered(deps, info, denom, true),
+        ExecuteMsg::DeregisterDenom { denom } => set_denom_registered(deps, info, denom, false),
+        ExecuteMsg::FreezeDenom { denom } => set_denom_frozen(deps, env, info, denom, true),
+        ExecuteMsg::UnfreezeDenom { denom } => set_denom_frozen(deps, env, info, denom, false),
+        ExecuteMsg::SetAllowlistEnabled { denom, enabled } => {
+            set_allowlist_enabled(deps, info, denom, enabled)
+        }
+        ExecuteMsg::SetApprovalQuorum { denom, quorum } => {
+            set_approval_quorum(deps, info, denom, quorum)
+        }
+        ExecuteMsg::SetAutoApproveThreshold { denom, threshold } => {
+            set_auto_approve_threshold(deps, info, denom, threshold)
+        }
+        ExecuteMsg::SetDenomConfig {
+            denom,
+            min_amount,
+            max_amount,
+            default_expiration,
+        } => set_denom_config(
+            deps,
+            env,
+            info,
+            denom,
+            DenomConfig {
+                min_amount,
+                max_amount,
+                default_expiration,
+            },
+        ),
+        ExecuteMsg::SetMaxTransferAmount { amount } => {
+            set_max_transfer_amount(deps, env, info, amount)
+        }
+        ExecuteMsg::SetPageLimit { limit } => set_page_limit(deps, env, info, limit),
+        ExecuteMsg::SetPendingLimit { limit } => set_pending_limit(deps, env, info, limit),
+        ExecuteMsg::SetSettlementFee { fee } => set_settlement_fee(deps, env, info, fee),
+        ExecuteMsg::WithdrawFees { denom, to } => withdraw_fees(deps, env, info, denom, to),
+        ExecuteMsg::SetMsgFees { fees } => set_msg_fees(deps, env, info, fees),
+        ExecuteMsg::SetFeeDenom { denom } => set_fee_denom(deps, env, info, denom),
+        ExecuteMsg::SetCreationDeposit { deposit } => {
+            set_creation_deposit(deps, env, info, deposit)
+        }
+        ExecuteMsg::SetDenomFeeSchedule { denom, schedule } => {
+            set_denom_fee_schedule(deps, env, info, denom, schedule)
+        }
+        ExecuteMsg::SetCreationRateLimit { limit } => {
+            set_creation_rate_limit(deps, env, info, limit)
+        }
+        ExecuteMsg::SetVelocityLimit { denom, limit } => {
+            set_velocity_limit(deps, env, info, denom, limit)
+        }
+        ExecuteMsg::SetRetentionPeriod { seconds } => {
+            set_retention_period(deps, env, info, seconds)
+        }
+        ExecuteMsg::SetStandingApproval {
+            sender,
+            recipient,
+            denom,
+            max_amount,
+            expires,
+        } => set_standing_approval(
+            deps, env, info, sender, recipient, denom, max_amount, expires,
+        ),
+        ExecuteMsg::SetTransferPriority { id, priority } => {
+            set_transfer_priority(deps, env, info, id, priority)
+        }
+        ExecuteMsg::SetVetoAddress { denom, address } => {
+            set_veto_address(deps, env, info, denom, address)
+        }
+        ExecuteMsg::SweepOrphanedFunds { denom } => sweep_orphaned_funds(deps, env, info, denom),
         ExecuteMsg::Transfer {
             id,
             denom,
             amount,
             recipient,
-        } => create_transfer(deps, env, info, id, denom, amount, recipient),
+            require_acceptance,
+            approvers,
+            recipients,
+            additional_coins,
+            vesting_duration,
+            memo,
+            tags,
+        } => create_transfer(
+            deps,
+            env,
+            info,
+            TransferInput {
+                id,
+                denom,
+                amount,
+                recipient,
+                require_acceptance,
+                approvers,
+                recipients,
+                additional_coins,
+                vesting_duration,
+                memo,
+                tags,
+            },
+        ),
+        ExecuteMsg::Unpause {} => set_paused(deps, env, info, false),
+        ExecuteMsg::UpdateBlocklist { add, remove } => {
+            update_blocklist(deps, env, info, add, remove)
+        }
+        ExecuteMsg::UpdateFeeExemptions { add, remove } => {
+            update_fee_exemptions(deps, env, info, add, remove)
+        }
+        ExecuteMsg::UpdateAllowlist { denom, add, remove } => {
+            update_allowlist(deps, info, denom, add, remove)
+        }
+        ExecuteMsg::UpdateApprovers { denom, add, remove } => {
+            update_approvers(deps, info, denom, add, remove)
+        }
+        ExecuteMsg::UpdateAdmin { new_admin } => update_admin(deps, env, info, new_admin),
+        ExecuteMsg::UpdateConfig {
+            name,
+            page_limit,
+            pending_limit,
+            max_transfer_amount,
+        } => update_config(
+            deps,
+            env,
+            info,
+            name,
+            page_limit,
+            pending_limit,
+            max_transfer_amount,
+        ),
+        ExecuteMsg::UpdateTransfer {
+            id,
+            new_recipient,
+            new_amount,
+        } => update_transfer(deps, env, info, id, new_recipient, new_amount),
+        ExecuteMsg::VetoTransfer { id, reason } => veto_transfer(deps, env, info, id, reason),
     }
 }
 
-fn create_transfer(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    id: String,
-    denom: String,
-    amount: Uint128,
-    recipient: String,
-) -> Result<Response, ContractError> {
-    let transfer = Transfer {
-        id,
-        sender: info.sender.to_owned(),
-        denom,
-        amount,
-        recipient: deps.api.addr_validate(&recipient)?,
+/// Appends the action of a response to the audit log, identified by its `action` and `id`
+/// attributes
+fn record_audit_entry(
+    storage: &mut dyn Storage,
+    env: &Env,
+    actor: &Addr,
+    response: &Response,
+) -> StdResult<()> {
+    let attribute = |key: &str| {
+        response
+            .attributes
+            .iter()
+            .find(|attribute| attribute.key == key)
+            .map(|attribute| attribute.value.to_owned())
     };
+    let action = match attribute("action") {
+        Some(action) => action,
+        None => return Ok(()),
+    };
+    append_audit_entry(storage, &env.block, action, actor, attribute("id"))?;
+    Ok(())
+}
 
-    let querier = MarkerQuerier::new(&deps.querier);
+/// Recorded in the audit log as the actor of the actions governance takes through `sudo`
+const GOVERNANCE_ACTOR: &str = "governance";
 
-    let is_restricted_marker = matches!(
-        get_marker_by_denom(transfer.denom.clone(), &querier),
-        Ok(MarkerAccount {
-            marker_type: 2, // MarkerType::Restricted,
-            ..
-        })
-    );
+/// Lets chain governance intervene without the contract admin's keys. Only the chain can call
+/// this entry point, so there is no sender to authorize.
+#[entry_point]
+pub fn sudo(deps: DepsMut, env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    msg.validate()?;
 
-    match is_restricted_marker {
-        // funds should not be sent
-        true => {
-            if !info.funds.is_empty() {
-                return Err(ContractError::SentFundsUnsupported);
-            }
+    let response = match msg {
+        SudoMsg::ForceRejectTransfer { id, reason } => {
+            force_reject_transfer(deps.storage, &env, id, reason)?
         }
-        false => {
-            return Err(ContractError::UnsupportedMarkerType);
+        SudoMsg::Pause {} => save_paused(deps.storage, true)?,
+        SudoMsg::Unpause {} => save_paused(deps.storage, false)?,
+        SudoMsg::SetDenomConfig {
+            denom,
+            min_amount,
+            max_amount,
+            default_expiration,
+        } => save_denom_config(
+            deps.storage,
+            &denom,
+            &DenomConfig {
+                min_amount,
+                max_amount,
+                default_expiration,
+            },
+        )?,
+        SudoMsg::SetMaxTransferAmount { amount } => save_max_transfer_amount(deps.storage, amount)?,
+        SudoMsg::SetPendingLimit { limit } => save_pending_limit(deps.storage, limit)?,
+        SudoMsg::SetVelocityLimit { denom, limit } => {
+            save_velocity_limit(deps.storage, &denom, limit)?
+        }
+        SudoMsg::UpdateAdmin { new_admin } => {
+            let new_admin = deps.api.addr_validate(&new_admin)?;
+            PENDING_ADMIN.remove(deps.storage);
+            CONFIG.update(deps.storage, |mut state| -> StdResult<_> {
+                state.admin = Some(new_admin.to_owned());
+                Ok(state)
+            })?;
+            Response::new().add_attributes(vec![
+                attr("action", Action::UpdateAdmin.to_string()),
+                attr("new_admin", &new_admin),
+            ])
         }
     }
+    .add_attribute("authority", GOVERNANCE_ACTOR);
 
-    // Ensure the sender holds enough denom to cover the transfer.
-    let balance = deps
-        .querier
-        .query_balance(info.sender.clone(), transfer.denom.clone())?;
-
-    if balance.amount < transfer.amount {
-        return Err(ContractError::InsufficientFunds);
-    }
+    record_audit_entry(
+        deps.storage,
+        &env,
+        &Addr::unchecked(GOVERNANCE_ACTOR),
+        &response,
+    )?;
+    Ok(response)
+}
 
-    if TRANSFER_STORAGE
-        .may_load(deps.storage, transfer.id.as_bytes())?
-        .is_some()
-    {
-        return Err(ContractError::InvalidFields {
-            fields: vec![String::from("id")],
-        });
-    }
+/// Rejects a transfer still holding escrow on behalf of governance, refunding its sender
+fn force_reject_transfer(
+    storage: &mut dyn Storage,
+    env: &Env,
+    transfer_id: String,
+    reason: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut transfer = load_transfer(storage, &transfer_id)
+        .map_err(|error| ContractError::LoadTransferFailed { error })?;
 
-    TRANSFER_STORAGE.save(deps.storage, transfer.id.as_bytes(), &transfer)?;
+    transfer.transition(TransferStatus::Rejected)?;
+    transfer.rejection_reason = reason;
 
     let mut response = Response::new().add_attributes(vec![
-        attr("action", Action::Transfer.to_string()),
+        attr("action", Action::ForceReject.to_string()),
         attr("id", &transfer.id),
         attr("denom", &transfer.denom),
         attr("amount", transfer.amount.to_string()),
@@ -109,1536 +436,17708 @@ fn create_transfer(
         attr("recipient", &transfer.recipient),
     ]);
 
-    let coin = Coin {
-        denom: transfer.denom.to_owned(),
-        amount: transfer.amount.into(),
-    };
+    if let Some(reason) = &transfer.rejection_reason {
+        response = response.add_attribute("reason", reason);
+    }
 
-    response = response.add_message(MsgTransferRequest {
-        amount: Some(coin),
-        to_address: env.contract.address.to_string(),
-        from_address: transfer.sender.to_string(),
-        administrator: env.contract.address.to_string(),
-    });
+    response = response.add_submessages(settlement_submessages(
+        storage,
+        &transfer.id,
+        refund_messages(&transfer, &env.contract.address),
+    )?);
+    response = response.add_messages(release_held_funds(storage, &transfer, true)?);
+
+    transfer.updated_at = Some(env.block.time);
+    save_transfer(storage, env.block.height, &transfer)?;
 
     Ok(response)
 }
 
-pub fn cancel_transfer(
+/// Escrow replies are only requested when escrowing coins fails. Failing the reply reverts
+/// the transaction along with the transfer or swap it stored. Every settlement replies, a
+/// failed one is recorded so it can be retried while the rest of the transaction stands.
+#[entry_point]
+pub fn reply(mut deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let response = match (msg.id, msg.result) {
+        (ESCROW_REPLY_ID, SubMsgResult::Err(error)) => Err(ContractError::EscrowFailed { error }),
+        (ESCROW_REPLY_ID, _) => Err(ContractError::UnknownReplyId {
+            id: ESCROW_REPLY_ID,
+        }),
+        (id, result) => match SETTLEMENT_ATTEMPTS.may_load(deps.storage, id)? {
+            Some(attempt) => settlement_reply(deps.branch(), env.clone(), id, attempt, result),
+            None => Err(ContractError::UnknownReplyId { id }),
+        },
+    }?;
+
+    // a settlement changing the transfer is the contract's own action
+    record_audit_entry(deps.storage, &env, &env.contract.address, &response)?;
+    Ok(response)
+}
+
+/// Moves a settled transfer to `Failed` when one of its marker transfers fails, and back to
+/// `Settled` once the last of its failed settlements goes through
+fn settlement_reply(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo,
-    transfer_id: String,
+    id: u64,
+    attempt: SettlementAttempt,
+    result: SubMsgResult,
 ) -> Result<Response, ContractError> {
-    let transfer = TRANSFER_STORAGE
-        .load(deps.storage, transfer_id.as_bytes())
-        .map_err(|error| ContractError::LoadTransferFailed { error })?;
+    SETTLEMENT_ATTEMPTS.remove(deps.storage, id);
+    let transfer = may_load_transfer(deps.storage, &attempt.transfer_id)?;
 
-    if !info.funds.is_empty() {
-        return Err(ContractError::SentFundsUnsupported);
-    }
+    let error = match result {
+        SubMsgResult::Ok(..) => {
+            let key = transfer_key(&attempt.transfer_id)?;
+            let retried = FAILED_SETTLEMENTS
+                .prefix(&key)
+                .range(deps.storage, None, None, Order::Ascending)
+                .next()
+                .is_none();
+            let mut response = Response::new();
+            if let Some(mut transfer) =
+                transfer.filter(|transfer| retried && transfer.status == TransferStatus::Failed)
+            {
+                transfer.transition(TransferStatus::Settled)?;
+                transfer.updated_at = Some(env.block.time);
+                save_transfer(deps.storage, env.block.height, &transfer)?;
+                response = response.add_attributes(vec![
+                    attr("action", Action::SettlementRetried.to_string()),
+                    attr("id", &transfer.id),
+                    attr("status", "settled"),
+                ]);
+            }
+            return Ok(response);
+        }
+        SubMsgResult::Err(error) => error,
+    };
 
-    if !info.sender.eq(&transfer.sender) {
-        return Err(ContractError::Unauthorized {
-            error: String::from("Only original sender can cancel"),
-        });
-    }
+    // the coins stay in escrow, held for the recipient or sender until the settlement is retried
+    FAILED_SETTLEMENTS.save(
+        deps.storage,
+        (&transfer_key(&attempt.transfer_id)?, id),
+        &FailedSettlement {
+            transfer_id: attempt.transfer_id.to_owned(),
+            error: error.to_owned(),
+            msg: attempt.msg,
+        },
+    )?;
 
     let mut response = Response::new().add_attributes(vec![
-        attr("action", Action::Cancel.to_string()),
-        attr("id", &transfer.id),
-        attr("denom", &transfer.denom),
-        attr("amount", transfer.amount.to_string()),
-        attr("sender", &transfer.sender),
+        attr("action", Action::SettlementFailed.to_string()),
+        attr("id", attempt.transfer_id),
+        attr("error", error),
     ]);
 
-    let coin = Coin {
-        denom: transfer.denom.to_owned(),
-        amount: transfer.amount.into(),
-    };
+    // refunds failing leave the transfer as it was, only a payout fails the transfer
+    if let Some(mut transfer) =
+        transfer.filter(|transfer| transfer.status == TransferStatus::Settled)
+    {
+        transfer.transition(TransferStatus::Failed)?;
+        transfer.updated_at = Some(env.block.time);
+        save_transfer(deps.storage, env.block.height, &transfer)?;
+        response = response.add_attribute("status", "failed");
+    }
 
-    response = response.add_message(MsgTransferRequest {
-        amount: Some(coin),
-        to_address: transfer.sender.to_string(),
-        from_address: env.contract.address.to_string(),
-        administrator: env.contract.address.to_string(),
-    });
+    Ok(response)
+}
+
+fn create_transfer(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    input: TransferInput,
+) -> Result<Response, ContractError> {
+    let (deposit, incentive) = take_funds(deps.storage, &info, 1)?;
+    let mut transfer = new_transfer(deps.branch(), &env, &info.sender, input)?;
+    hold_funds(deps.storage, &transfer, &deposit, &incentive)?;
+
+    let mut response = Response::new()
+        .add_attributes(created_transfer_attributes(&transfer))
+        .add_attributes(
+            incentive
+                .as_ref()
+                .map(|incentive| attr("incentive", incentive.to_string())),
+        )
+        .add_messages(assess_msg_fee(
+            deps.storage,
+            &env,
+            &info.sender,
+            "transfer_creation",
+            |fees| fees.creation_fee,
+            1,
+        )?)
+        .add_submessages(transfer.coins().iter().map(|coin| {
+            escrow_submessage(transfer_marker_coins(
+                coin.amount,
+                &coin.denom,
+                &env.contract.address,
+                &transfer.sender,
+                &env.contract.address,
+            ))
+        }));
 
-    // finally remove the transfer from storage
-    TRANSFER_STORAGE.remove(deps.storage, transfer_id.as_bytes());
+    // the settlement follows the escrow, so the coins are paid out once they are held
+    let approval = approve_new_transfer(deps.branch(), &env, &mut transfer)?;
+    response = response
+        .add_attributes(approval.attributes)
+        .add_submessages(approval.messages)
+        .add_events(approval.events);
+
+    // a failed escrow fails the reply and with it the transaction, so the transfer is only
+    // kept once its coins are held
+    save_transfer(deps.storage, env.block.height, &transfer)?;
 
     Ok(response)
 }
 
-pub fn reject_transfer(
+/// Approves a transfer as it is created when it falls below the auto-approve threshold of its
+/// denom or a standing approval of its route covers it. Every path creating a transfer calls
+/// this once the transfer's funds are held, and returns the attribute naming the threshold or
+/// approver with the submessages and fee events settling the transfer, which must follow its
+/// escrow.
+fn approve_new_transfer(
     deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    transfer_id: String,
+    env: &Env,
+    transfer: &mut Transfer,
 ) -> Result<Response, ContractError> {
-    let transfer = TRANSFER_STORAGE
-        .load(deps.storage, transfer_id.as_bytes())
-        .map_err(|error| ContractError::LoadTransferFailed { error })?;
+    if let Some(threshold) = auto_approve_threshold(deps.storage, transfer)? {
+        let settlement = approve_at_creation(deps, env, transfer, env.contract.address.to_owned())?;
+        return Ok(settlement.add_attribute("auto_approve_threshold", threshold));
+    }
 
-    if !info.funds.is_empty() {
-        return Err(ContractError::SentFundsUnsupported);
+    match apply_standing_approval(deps, env, transfer)? {
+        Some(settlement) => {
+            Ok(settlement.add_attribute("standing_approver", &transfer.approvals[0].approver))
+        }
+        None => Ok(Response::new()),
+    }
+}
+
+/// Approves a new transfer with the standing approval of its route when one covers it, and
+/// returns the submessages and fee events settling it. Only transfers of a single denom settled by a single
+/// approval qualify, and the approver must still hold transfer permission on the marker.
+fn apply_standing_approval(
+    deps: DepsMut,
+    env: &Env,
+    transfer: &mut Transfer,
+) -> Result<Option<Response>, ContractError> {
+    let route = (
+        transfer.denom.as_str(),
+        &transfer.sender,
+        &transfer.recipient,
+    );
+    let mut standing_approval = match STANDING_APPROVALS.may_load(deps.storage, route)? {
+        Some(standing_approval) => standing_approval,
+        None => return Ok(None),
+    };
+
+    if !transfer.additional_coins.is_empty()
+        || !transfer.required_approvers.is_empty()
+        || approval_quorum(deps.storage, transfer)? > 1
+        || !standing_approval.covers(&env.block, transfer.amount)
+    {
+        return Ok(None);
     }
 
     let querier = MarkerQuerier::new(&deps.querier);
-    let marker = get_marker_by_denom(transfer.denom.clone(), &querier)?;
+    let marker = get_marker_by_denom(transfer.denom.to_owned(), &querier)?;
+    if !has_marker_access_transfer(standing_approval.approver.to_owned(), marker) {
+        return Ok(None);
+    }
 
-    if !has_marker_access_transfer(info.sender.to_owned(), marker) {
-        return Err(ContractError::Unauthorized {
-            error: String::from("ACCESS_TRANSFER permission is required to reject transfers"),
-        });
+    standing_approval.used += transfer.amount;
+    STANDING_APPROVALS.save(deps.storage, route, &standing_approval)?;
+
+    Ok(Some(approve_at_creation(
+        deps,
+        env,
+        transfer,
+        standing_approval.approver,
+    )?))
+}
+
+/// Returns the threshold of the transfer's denom when the transfer falls below it. Only
+/// transfers of a single denom without required approvers qualify.
+fn auto_approve_threshold(
+    storage: &dyn Storage,
+    transfer: &Transfer,
+) -> StdResult<Option<Uint128>> {
+    if !transfer.additional_coins.is_empty() || !transfer.required_approvers.is_empty() {
+        return Ok(None);
     }
 
-    let mut response = Response::new().add_attributes(vec![
-        attr("action", Action::Reject.to_string()),
-        attr("id", &transfer.id),
-        attr("denom", &transfer.denom),
-        attr("amount", transfer.amount.to_string()),
-        attr("sender", &transfer.sender),
-        attr("admin", info.sender.to_owned()),
-    ]);
+    Ok(AUTO_APPROVE_THRESHOLDS
+        .may_load(storage, &transfer.denom)?
+        .filter(|threshold| transfer.amount < *threshold))
+}
 
-    let coin = Coin {
-        denom: transfer.denom.to_owned(),
-        amount: transfer.amount.into(),
+/// Records the approval of a transfer as it is created and returns the submessages and fee
+/// events settling it
+fn approve_at_creation(
+    mut deps: DepsMut,
+    env: &Env,
+    transfer: &mut Transfer,
+    approver: Addr,
+) -> Result<Response, ContractError> {
+    // a standing approver earns the incentive, the contract's own approvals leave it to refund
+    let incentive = match approver != env.contract.address {
+        true => pay_incentive(deps.storage, transfer, &approver)?,
+        false => None,
     };
-
-    response = response.add_message(MsgTransferRequest {
-        amount: Some(coin),
-        to_address: transfer.sender.to_string(),
-        from_address: env.contract.address.to_string(),
-        administrator: env.contract.address.to_string(),
+    transfer.approvals.push(Approval {
+        approver,
+        height: env.block.height,
+        time: env.block.time,
+        amount: Some(transfer.amount),
     });
+    transfer.transition(TransferStatus::Approved)?;
+    let response = Response::new().add_messages(incentive);
 
-    // finally remove the transfer from storage
-    TRANSFER_STORAGE.remove(deps.storage, transfer_id.as_bytes());
+    // as with an approval, vesting and acceptance keep the coins in escrow
+    if let Some(vesting) = &mut transfer.vesting {
+        vesting.start = Some(env.block.time);
+        return Ok(response);
+    }
+    if transfer.require_acceptance {
+        return Ok(response);
+    }
 
-    Ok(response)
+    transfer.transition(TransferStatus::Settled)?;
+    let (msgs, fee_events) = settlement_messages(deps.branch(), transfer, &env.contract.address)?;
+    Ok(response
+        .add_submessages(settlement_submessages(deps.storage, &transfer.id, msgs)?)
+        .add_messages(release_held_funds(deps.storage, transfer, false)?)
+        .add_events(fee_events))
 }
 
-pub fn approve_transfer(
-    deps: DepsMut,
+/// Creates several transfers in one transaction. The sender's coins are escrowed with a single
+/// marker transfer per denom, covering every transfer of that denom.
+pub fn batch_transfer(
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    transfer_id: String,
+    inputs: Vec<TransferInput>,
 ) -> Result<Response, ContractError> {
-    let transfer = TRANSFER_STORAGE
-        .load(deps.storage, transfer_id.as_bytes())
-        .map_err(|error| ContractError::LoadTransferFailed { error })?;
+    let mut response = Response::new();
+    let mut settlements = Response::new();
+    let mut totals: Vec<(String, Uint128)> = vec![];
 
-    if !info.funds.is_empty() {
-        return Err(ContractError::SentFundsUnsupported);
-    }
+    let (deposit, _) = take_funds(deps.storage, &info, inputs.len() as u128)?;
+    for input in inputs {
+        let mut transfer = new_transfer(deps.branch(), &env, &info.sender, input)?;
+        hold_funds(deps.storage, &transfer, &deposit, &None)?;
+        let approval = approve_new_transfer(deps.branch(), &env, &mut transfer)?;
 
-    let querier = MarkerQuerier::new(&deps.querier);
-    let marker = get_marker_by_denom(transfer.denom.clone(), &querier)?;
+        for coin in transfer.coins() {
+            match totals.iter_mut().find(|(denom, _)| denom == &coin.denom) {
+                Some((_, total)) => {
+                    *total = total.checked_add(coin.amount).map_err(StdError::from)?
+                }
+                None => totals.push((coin.denom, coin.amount)),
+            }
+        }
 
-    if !has_marker_access_transfer(info.sender.to_owned(), marker) {
-        return Err(ContractError::Unauthorized {
-            error: String::from("ACCESS_TRANSFER permission is required to approve transfers"),
-        });
+        save_transfer(deps.storage, env.block.height, &transfer)?;
+        response = response.add_event(
+            Event::new(Action::BatchTransfer.to_string())
+                .add_attributes(created_transfer_attributes(&transfer))
+                .add_attributes(approval.attributes),
+        );
+        settlements = settlements
+            .add_submessages(approval.messages)
+            .add_events(approval.events);
     }
 
-    let mut response = Response::new().add_attributes(vec![
-        attr("action", Action::Approve.to_string()),
-        attr("id", &transfer.id),
-        attr("denom", &transfer.denom),
-        attr("amount", transfer.amount.to_string()),
-        attr("sender", &transfer.sender),
-        attr("recipient", &transfer.recipient),
-        attr("admin", &info.sender),
-    ]);
-
-    let coin = Coin {
-        denom: transfer.denom.to_owned(),
-        amount: transfer.amount.into(),
-    };
+    let count = response.events.len();
+    Ok(response
+        .add_attributes(vec![
+            attr("action", Action::BatchTransfer.to_string()),
+            attr("count", count.to_string()),
+            attr("sender", &info.sender),
+        ])
+        .add_messages(assess_msg_fee(
+            deps.storage,
+            &env,
+            &info.sender,
+            "transfer_creation",
+            |fees| fees.creation_fee,
+            count as u128,
+        )?)
+        .add_submessages(totals.iter().map(|(denom, total)| {
+            escrow_submessage(transfer_marker_coins(
+                *total,
+                denom,
+                &env.contract.address,
+                &info.sender,
+                &env.contract.address,
+            ))
+        }))
+        // the settlements follow the escrows, so the coins are paid out once they are held
+        .add_submessages(settlements.messages)
+        .add_events(settlements.events))
+}
 
-    response = response.add_message(MsgTransferRequest {
-        amount: Some(coin),
-        to_address: transfer.recipient.to_owned().to_string(),
-        from_address: env.contract.address.to_string(),
-        administrator: env.contract.address.to_string(),
-    });
+/// splits the funds sent into the creation deposit of `count` transfers and the incentive of a
+/// single transfer, any nhash beyond the deposit. Returns the deposit each transfer is
+/// held to and the incentive, no other funds may be sent
+fn take_funds(
+    storage: &dyn Storage,
+    info: &MessageInfo,
+    count: u128,
+) -> Result<(Option<CreationDeposit>, Option<cosmwasm_std::Coin>), ContractError> {
+    let deposit = CREATION_DEPOSIT.may_load(storage)?;
+    let mut funds = info.funds.to_owned();
 
-    // finally remove the transfer from storage
-    TRANSFER_STORAGE.remove(deps.storage, transfer_id.as_bytes());
-    Ok(response)
-}
+    if let Some(deposit) = &deposit {
+        let required = cosmwasm_std::Coin {
+            denom: deposit.amount.denom.to_owned(),
+            amount: deposit
+                .amount
+                .amount
+                .checked_mul(Uint128::new(count))
+                .map_err(StdError::from)?,
+        };
+        match funds
+            .iter_mut()
+            .find(|coin| coin.denom == required.denom && coin.amount >= required.amount)
+        {
+            Some(coin) => coin.amount -= required.amount,
+            None => {
+                return Err(ContractError::DepositRequired {
+                    deposit: required.to_string(),
+                })
+            }
+        }
+        funds.retain(|coin| !coin.amount.is_zero());
+    }
 
-/// returns true if the sender has marker transfer permissions for the given marker
-fn has_marker_access_transfer(sender: Addr, marker: MarkerAccount) -> bool {
-    let access_transfer: i32 = Access::Transfer.into();
-    marker.access_control.iter().any(|grant| {
-        grant.address == sender
-            && grant
-                .permissions
-                .iter()
-                .any(|marker_access| *marker_access == access_transfer)
-    })
+    match funds.as_slice() {
+        [] => Ok((deposit, None)),
+        [incentive] if incentive.denom == DEFAULT_FEE_DENOM && count == 1 => {
+            Ok((deposit, Some(incentive.to_owned())))
+        }
+        _ => Err(ContractError::SentFundsUnsupported),
+    }
 }
 
-fn get_marker_by_denom(denom: String, querier: &MarkerQuerier<Empty>) -> StdResult<MarkerAccount> {
-    let response = querier.marker(denom)?;
-    if let Some(marker) = response.marker {
-        return if let Ok(account) = MarkerAccount::try_from(marker) {
-            Ok(account)
-        } else {
-            Err(StdError::generic_err("unable to type-cast marker account"))
-        };
+/// holds the deposit and incentive taken for a new transfer until it is settled or refunded
+fn hold_funds(
+    storage: &mut dyn Storage,
+    transfer: &Transfer,
+    deposit: &Option<CreationDeposit>,
+    incentive: &Option<cosmwasm_std::Coin>,
+) -> StdResult<()> {
+    let key = transfer_key(&transfer.id)?;
+    if let Some(deposit) = deposit {
+        TRANSFER_DEPOSITS.save(storage, &key, deposit)?;
     }
-    Err(StdError::generic_err("no marker found for denom"))
+    if let Some(incentive) = incentive {
+        TRANSFER_INCENTIVES.save(storage, &key, incentive)?;
+    }
+    Ok(())
 }
 
-#[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    msg.validate()?;
+/// releases the funds still held for the transfer: its deposit, returned to the sender or
+/// forfeited to its collector, and the incentive no approver was paid, returned to the sender
+fn release_held_funds(
+    storage: &mut dyn Storage,
+    transfer: &Transfer,
+    forfeit: bool,
+) -> StdResult<Vec<BankMsg>> {
+    let key = transfer_key(&transfer.id)?;
+    let mut msgs = vec![];
 
-    match msg {
-        QueryMsg::GetContractInfo {} => to_binary(&CONFIG.load(deps.storage)?),
-        QueryMsg::GetVersionInfo {} => to_binary(&cw2::get_contract_version(deps.storage)?),
-        QueryMsg::GetTransfer { id: transfer_id } => {
-            to_binary(&TRANSFER_STORAGE.load(deps.storage, transfer_id.as_bytes())?)
-        }
-        QueryMsg::GetAllTransfers {} => to_binary(&get_all_transfers(deps.storage)),
+    if let Some(deposit) = TRANSFER_DEPOSITS.may_load(storage, &key)? {
+        TRANSFER_DEPOSITS.remove(storage, &key);
+        let to_address = match forfeit {
+            true => deposit.collector,
+            false => transfer.sender.to_owned(),
+        };
+        msgs.push(BankMsg::Send {
+            to_address: to_address.to_string(),
+            amount: vec![deposit.amount],
+        });
     }
+
+    msgs.extend(pay_incentive(storage, transfer, &transfer.sender)?);
+    Ok(msgs)
 }
 
-enum Action {
-    Transfer,
-    Approve,
-    Reject,
-    Cancel,
+/// pays the incentive attached to the transfer to the address, the approver settling it
+fn pay_incentive(
+    storage: &mut dyn Storage,
+    transfer: &Transfer,
+    to: &Addr,
+) -> StdResult<Option<BankMsg>> {
+    let key = transfer_key(&transfer.id)?;
+    let incentive = match TRANSFER_INCENTIVES.may_load(storage, &key)? {
+        Some(incentive) => incentive,
+        None => return Ok(None),
+    };
+    TRANSFER_INCENTIVES.remove(storage, &key);
+
+    Ok(Some(BankMsg::Send {
+        to_address: to.to_string(),
+        amount: vec![incentive],
+    }))
+}
+
+/// builds a pending transfer from the sender's input, checking its markers, approvers and id
+fn new_transfer(
+    deps: DepsMut,
+    env: &Env,
+    sender: &Addr,
+    input: TransferInput,
+) -> Result<Transfer, ContractError> {
+    let mut transfer = Transfer {
+        id: input.id,
+        sender: sender.to_owned(),
+        denom: input.denom,
+        amount: input.amount,
+        recipient: deps.api.addr_validate(&input.recipient)?,
+        status: TransferStatus::Pending,
+        rejection_reason: None,
+        require_acceptance: input.require_acceptance.unwrap_or(false),
+        approvals: vec![],
+        required_approvers: input
+            .approvers
+            .unwrap_or_default()
+            .iter()
+            .map(|approver| deps.api.addr_validate(approver))
+            .collect::<StdResult<Vec<Addr>>>()?,
+        legs: input
+            .recipients
+            .unwrap_or_default()
+            .iter()
+            .map(|leg| -> StdResult<TransferLeg> {
+                Ok(TransferLeg {
+                    address: deps.api.addr_validate(&leg.address)?,
+                    amount: leg.amount,
+                })
+            })
+            .collect::<StdResult<Vec<TransferLeg>>>()?,
+        additional_coins: input.additional_coins.unwrap_or_default(),
+        vesting: input.vesting_duration.map(|duration| Vesting {
+            duration,
+            start: None,
+        }),
+        claimed: Uint128::zero(),
+        memo: input.memo,
+        created: Some(Creation {
+            height: env.block.height,
+            time: env.block.time,
+        }),
+        updated_at: Some(env.block.time),
+        sequence: None,
+        tags: input.tags.unwrap_or_default(),
+        position: None,
+        priority: false,
+        expires: None,
+    };
+
+    let querier = MarkerQuerier::new(&deps.querier);
+    let state = CONFIG.load(deps.storage)?;
+
+    for coin in transfer.coins() {
+        if state
+            .allowed_denoms
+            .as_ref()
+            .is_some_and(|denoms| !denoms.contains(&coin.denom))
+        {
+            return Err(ContractError::DenomNotAllowed { denom: coin.denom });
+        }
+
+        if !is_restricted_marker(deps.storage, &querier, &coin.denom) {
+            return Err(ContractError::UnsupportedMarkerType);
+        }
+
+        check_denom_registered(deps.storage, &coin.denom)?;
+        check_denom_not_frozen(deps.storage, &coin.denom)?;
+
+        check_amount_limits(deps.storage, &coin.denom, coin.amount)?;
+
+        // a transfer of several denoms expires with the first of their default expirations,
+        // the contract's default standing in for denoms without one
+        let default_expiration = DENOM_CONFIG
+            .may_load(deps.storage, &coin.denom)?
+            .and_then(|config| config.default_expiration)
+            .or(state.default_expiration);
+        if let Some(seconds) = default_expiration {
+            let expires = env.block.time.plus_seconds(seconds);
+            transfer.expires = Some(transfer.expires.map_or(expires, |e| e.min(expires)));
+        }
+    }
+
+    // every required approver must be able to approve, and together they must meet the quorum
+    if !transfer.required_approvers.is_empty() {
+        let quorum = approval_quorum(deps.storage, &transfer)?;
+        let markers = transfer
+            .coins()
+            .into_iter()
+            .map(|coin| get_marker_by_denom(coin.denom, &querier))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        if transfer.required_approvers.len() < quorum as usize
+            || !transfer.required_approvers.iter().all(|approver| {
+                markers
+                    .iter()
+                    .all(|marker| has_marker_access_transfer(approver.to_owned(), marker.clone()))
+            })
+        {
+            return Err(ContractError::InvalidFields {
+                fields: vec![String::from("approvers")],
+            });
+        }
+    }
+
+    if may_load_transfer(deps.storage, &transfer.id)?.is_some() {
+        return Err(ContractError::InvalidFields {
+            fields: vec![String::from("id")],
+        });
+    }
+
+    if let Some(limit) = PENDING_LIMIT.may_load(deps.storage)? {
+        if count_pending_by_sender(deps.storage, &transfer.sender)? >= limit {
+            return Err(ContractError::TooManyPendingTransfers { limit });
+        }
+    }
+
+    check_creation_rate(deps.storage, env, &transfer.sender)?;
+
+    check_allowlist(deps.storage, &transfer)?;
+
+    if let Some(address) = blocked_party(deps.storage, &transfer) {
+        return Err(ContractError::AddressBlocked {
+            address: address.to_string(),
+        });
+    }
+
+    for coin in transfer.coins() {
+        check_velocity(
+            deps.storage,
+            env,
+            &transfer.sender,
+            &coin.denom,
+            coin.amount,
+        )?;
+    }
+
+    transfer.sequence = Some(next_sequence(deps.storage, &transfer.sender)?);
+    transfer.position = Some(next_position(deps.storage)?);
+    Ok(transfer)
+}
+
+/// the first party of the transfer on the blocklist, its sender or one of its payees
+fn blocked_party<'a>(storage: &dyn Storage, transfer: &'a Transfer) -> Option<&'a Addr> {
+    let mut parties = transfer.payees();
+    parties.insert(0, &transfer.sender);
+    parties
+        .into_iter()
+        .find(|party| BLOCKLIST.has(storage, party))
+}
+
+/// returns the first owner of a leg of the swap who is on the blocklist
+fn blocked_swap_party<'a>(storage: &dyn Storage, swap: &'a Swap) -> Option<&'a Addr> {
+    vec![&swap.initiator.owner, &swap.counterparty.owner]
+        .into_iter()
+        .find(|owner| BLOCKLIST.has(storage, owner))
+}
+
+/// Rejects and refunds a transfer about to settle when one of its parties has been blocked since
+/// it was created, returning the response refusing it. The refusal succeeds, so its event is kept.
+fn refuse_blocked(
+    storage: &mut dyn Storage,
+    env: &Env,
+    transfer: &mut Transfer,
+) -> Result<Option<Response>, ContractError> {
+    let address = match blocked_party(storage, transfer) {
+        Some(address) => address.to_owned(),
+        None => return Ok(None),
+    };
+
+    transfer.transition(TransferStatus::Rejected)?;
+    transfer.rejection_reason = Some(format!("{} is blocked", address));
+
+    let response = Response::new()
+        .add_attributes(vec![
+            attr("action", Action::RefuseBlocked.to_string()),
+            attr("id", &transfer.id),
+            attr("address", &address),
+        ])
+        .add_event(
+            Event::new("blocked_party_refused")
+                .add_attribute("id", &transfer.id)
+                .add_attribute("address", &address),
+        )
+        .add_submessages(settlement_submessages(
+            storage,
+            &transfer.id,
+            refund_messages(transfer, &env.contract.address),
+        )?)
+        .add_messages(release_held_funds(storage, transfer, true)?);
+
+    transfer.updated_at = Some(env.block.time);
+    save_transfer(storage, env.block.height, transfer)?;
+
+    Ok(Some(response))
+}
+
+/// fails when the transfer pays an address missing from the enabled allowlist of one of its denoms
+fn check_allowlist(storage: &dyn Storage, transfer: &Transfer) -> Result<(), ContractError> {
+    for coin in transfer.coins() {
+        if !ALLOWLIST_ENABLED.has(storage, &coin.denom) {
+            continue;
+        }
+
+        for payee in transfer.payees() {
+            if !RECIPIENT_ALLOWLIST.has(storage, (&coin.denom, payee)) {
+                return Err(ContractError::RecipientNotAllowed {
+                    recipient: payee.to_string(),
+                    denom: coin.denom,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Counts the amount towards the sender's velocity limit of the denom, failing when it would take
+/// them past the limit
+fn check_velocity(
+    storage: &mut dyn Storage,
+    env: &Env,
+    sender: &Addr,
+    denom: &str,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let limit = match VELOCITY_LIMITS.may_load(storage, denom)? {
+        Some(limit) => limit,
+        None => return Ok(()),
+    };
+
+    let now = env.block.time.seconds();
+    let used = velocity_used(storage, sender, denom, now.saturating_sub(limit.window))?;
+    if used + amount > limit.amount {
+        return Err(ContractError::VelocityLimitExceeded {
+            denom: denom.to_owned(),
+            remaining: limit.amount.saturating_sub(used),
+        });
+    }
+
+    record_velocity(storage, sender, denom, now, limit.window, amount)?;
+    Ok(())
+}
+
+/// fails when the sender already created as many transfers as the creation rate limit allows
+/// within its window, and otherwise counts the new transfer
+fn check_creation_rate(
+    storage: &mut dyn Storage,
+    env: &Env,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    let limit = match CREATION_RATE_LIMIT.may_load(storage)? {
+        Some(limit) => limit,
+        None => return Ok(()),
+    };
+
+    let height = env.block.height;
+    if creations_since(storage, sender, height.saturating_sub(limit.blocks))? >= limit.max_creations
+    {
+        return Err(ContractError::CreationRateExceeded {
+            max_creations: limit.max_creations,
+            blocks: limit.blocks,
+        });
+    }
+
+    record_creation(storage, sender, height, limit.blocks)?;
+    Ok(())
+}
+
+/// fails when the amount is outside those allowed for transfers of the denom, or above the cap
+/// on transfers of every denom
+fn check_amount_limits(
+    storage: &dyn Storage,
+    denom: &str,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    if let Some(cap) = MAX_TRANSFER_AMOUNT.may_load(storage)? {
+        if amount > cap {
+            return Err(ContractError::AmountExceedsCap { cap });
+        }
+    }
+
+    let config = DENOM_CONFIG.may_load(storage, denom)?.unwrap_or_default();
+
+    if let Some(min) = config.min_amount {
+        if amount < min {
+            return Err(ContractError::AmountBelowMinimum {
+                denom: denom.to_owned(),
+                min,
+            });
+        }
+    }
+
+    if let Some(max) = config.max_amount {
+        if amount > max {
+            return Err(ContractError::AmountAboveMaximum {
+                denom: denom.to_owned(),
+                max,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// the attributes describing a newly created transfer
+fn created_transfer_attributes(transfer: &Transfer) -> Vec<Attribute> {
+    let mut attributes = vec![
+        attr("action", Action::Transfer.to_string()),
+        attr("id", &transfer.id),
+        attr("denom", &transfer.denom),
+        attr("amount", transfer.amount.to_string()),
+        attr("sender", &transfer.sender),
+        attr("recipient", &transfer.recipient),
+    ];
+
+    if !transfer.additional_coins.is_empty() {
+        attributes.push(attr(
+            "additional_coins",
+            transfer
+                .additional_coins
+                .iter()
+                .map(|coin| coin.to_string())
+                .collect::<Vec<String>>()
+                .join(","),
+        ));
+    }
+
+    if let Some(memo) = &transfer.memo {
+        attributes.push(attr("memo", memo));
+    }
+
+    if !transfer.tags.is_empty() {
+        attributes.push(attr("tags", transfer.tags.join(",")));
+    }
+
+    if let Some(sequence) = transfer.sequence {
+        attributes.push(attr("sequence", sequence.to_string()));
+    }
+
+    attributes
+}
+
+/// Allows the sender to change the recipient or amount of a transfer that is still pending.
+/// Escrow is topped up from, or refunded to, the sender when the amount changes.
+pub fn update_transfer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfer_id: String,
+    new_recipient: Option<String>,
+    new_amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let mut transfer = load_transfer(deps.storage, &transfer_id)
+        .map_err(|error| ContractError::LoadTransferFailed { error })?;
+
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !info.sender.eq(&transfer.sender) {
+        return Err(ContractError::Unauthorized {
+            error: String::from("Only original sender can update"),
+        });
+    }
+
+    if transfer.status != TransferStatus::Pending {
+        return Err(ContractError::TransferNotPending {
+            status: transfer.status,
+        });
+    }
+    check_transfer_not_frozen(deps.storage, &transfer)?;
+
+    // the legs of a split transfer would no longer match the new terms
+    if !transfer.legs.is_empty() {
+        return Err(ContractError::InvalidFields {
+            fields: vec![String::from("recipients")],
+        });
+    }
+
+    if let Some(recipient) = new_recipient {
+        transfer.recipient = deps.api.addr_validate(&recipient)?;
+    }
+
+    // the new terms are held to the checks a new transfer is
+    check_denom_not_frozen(deps.storage, &transfer.denom)?;
+    check_allowlist(deps.storage, &transfer)?;
+    if let Some(address) = blocked_party(deps.storage, &transfer) {
+        return Err(ContractError::AddressBlocked {
+            address: address.to_string(),
+        });
+    }
+
+    // approvals were given for the previous terms
+    transfer.approvals.clear();
+
+    let mut response = Response::new();
+
+    if let Some(amount) = new_amount {
+        check_amount_limits(deps.storage, &transfer.denom, amount)?;
+
+        if amount > transfer.amount {
+            let additional_amount = amount - transfer.amount;
+            check_velocity(
+                deps.storage,
+                &env,
+                &transfer.sender,
+                &transfer.denom,
+                additional_amount,
+            )?;
+
+            response = response.add_submessage(escrow_submessage(transfer_marker_coins(
+                additional_amount,
+                &transfer.denom,
+                &env.contract.address,
+                &transfer.sender,
+                &env.contract.address,
+            )));
+        } else if amount < transfer.amount {
+            response = response.add_submessages(settlement_submessages(
+                deps.storage,
+                &transfer.id,
+                vec![transfer_marker_coins(
+                    transfer.amount - amount,
+                    &transfer.denom,
+                    &transfer.sender,
+                    &env.contract.address,
+                    &env.contract.address,
+                )],
+            )?);
+        }
+        transfer.amount = amount;
+    }
+
+    transfer.updated_at = Some(env.block.time);
+    save_transfer(deps.storage, env.block.height, &transfer)?;
+
+    Ok(response.add_attributes(vec![
+        attr("action", Action::Update.to_string()),
+        attr("id", &transfer.id),
+        attr("denom", &transfer.denom),
+        attr("amount", transfer.amount.to_string()),
+        attr("sender", &transfer.sender),
+        attr("recipient", &transfer.recipient),
+    ]))
+}
+
+/// Allows the sender to shrink a pending transfer, refunding the difference straight away
+pub fn reduce_transfer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfer_id: String,
+    new_amount: Uint128,
+) -> Result<Response, ContractError> {
+    let mut transfer = load_transfer(deps.storage, &transfer_id)
+        .map_err(|error| ContractError::LoadTransferFailed { error })?;
+
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !info.sender.eq(&transfer.sender) {
+        return Err(ContractError::Unauthorized {
+            error: String::from("Only original sender can reduce"),
+        });
+    }
+
+    if transfer.status != TransferStatus::Pending {
+        return Err(ContractError::TransferNotPending {
+            status: transfer.status,
+        });
+    }
+    check_transfer_not_frozen(deps.storage, &transfer)?;
+
+    // the legs of a split transfer would no longer add up to the amount
+    if !transfer.legs.is_empty() {
+        return Err(ContractError::InvalidFields {
+            fields: vec![String::from("recipients")],
+        });
+    }
+
+    if new_amount >= transfer.amount {
+        return Err(ContractError::InvalidFields {
+            fields: vec![String::from("new_amount")],
+        });
+    }
+
+    let refund_amount = transfer.amount - new_amount;
+    transfer.amount = new_amount;
+
+    // approvals were given for the previous amount
+    transfer.approvals.clear();
+
+    transfer.updated_at = Some(env.block.time);
+    save_transfer(deps.storage, env.block.height, &transfer)?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", Action::Reduce.to_string()),
+            attr("id", &transfer.id),
+            attr("denom", &transfer.denom),
+            attr("amount", transfer.amount.to_string()),
+            attr("refund_amount", refund_amount.to_string()),
+            attr("sender", &transfer.sender),
+        ])
+        .add_submessages(settlement_submessages(
+            deps.storage,
+            &transfer.id,
+            vec![transfer_marker_coins(
+                refund_amount,
+                &transfer.denom,
+                &transfer.sender,
+                &env.contract.address,
+                &env.contract.address,
+            )],
+        )?))
+}
+
+pub fn cancel_transfer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfer_id: String,
+) -> Result<Response, ContractError> {
+    let mut transfer = load_transfer(deps.storage, &transfer_id)
+        .map_err(|error| ContractError::LoadTransferFailed { error })?;
+
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !info.sender.eq(&transfer.sender) {
+        return Err(ContractError::Unauthorized {
+            error: String::from("Only original sender can cancel"),
+        });
+    }
+
+    check_transfer_not_frozen(deps.storage, &transfer)?;
+
+    // only the compliance role can cancel a transfer once it is approved
+    if transfer.status == TransferStatus::Approved {
+        return Err(ContractError::InvalidStatusTransition {
+            from: transfer.status,
+            to: TransferStatus::Cancelled,
+        });
+    }
+
+    // transfers left to expire forfeit their deposit
+    let expired = transfer.is_expired(&env.block);
+    transfer.transition(TransferStatus::Cancelled)?;
+
+    let mut response = Response::new().add_attributes(vec![
+        attr("action", Action::Cancel.to_string()),
+        attr("id", &transfer.id),
+        attr("denom", &transfer.denom),
+        attr("amount", transfer.amount.to_string()),
+        attr("sender", &transfer.sender),
+    ]);
+
+    response = response.add_submessages(settlement_submessages(
+        deps.storage,
+        &transfer.id,
+        refund_messages(&transfer, &env.contract.address),
+    )?);
+    response = response.add_messages(release_held_funds(deps.storage, &transfer, expired)?);
+
+    // finally record the cancellation
+    transfer.updated_at = Some(env.block.time);
+    save_transfer(deps.storage, env.block.height, &transfer)?;
+
+    Ok(response)
+}
+
+/// Cancels and refunds the caller's pending transfers, optionally only those of one denom. Frozen
+/// transfers are left as they are, and at most `MAX_BATCH_SIZE` transfers are cancelled per call.
+pub fn cancel_all_by_sender(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: Option<String>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    let transfer_ids = TRANSFER_STORAGE
+        .idx
+        .sender
+        .prefix(info.sender.to_owned())
+        .range(deps.storage, None, None, Order::Ascending)
+        .filter_map(|item| match item {
+            Ok((key, StoredTransfer(transfer)))
+                if transfer.status == TransferStatus::Pending
+                    && denom.as_ref().is_none_or(|denom| denom == &transfer.denom)
+                    && !TRANSFER_FREEZES.has(deps.storage, &key) =>
+            {
+                Some(Ok(transfer.id))
+            }
+            Ok(..) => None,
+            Err(error) => Some(Err(error)),
+        })
+        .take(MAX_BATCH_SIZE)
+        .collect::<StdResult<Vec<String>>>()?;
+
+    execute_batch(
+        deps,
+        transfer_ids,
+        Action::CancelAllBySender,
+        |deps, transfer_id| cancel_transfer(deps, env.clone(), info.clone(), transfer_id),
+    )
+}
+
+/// Allows the recipient to refuse a pending transfer, refunding the escrow to the sender
+pub fn decline_transfer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfer_id: String,
+) -> Result<Response, ContractError> {
+    let mut transfer = load_transfer(deps.storage, &transfer_id)
+        .map_err(|error| ContractError::LoadTransferFailed { error })?;
+
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !info.sender.eq(&transfer.recipient) {
+        return Err(ContractError::Unauthorized {
+            error: String::from("Only the recipient can decline"),
+        });
+    }
+
+    check_transfer_not_frozen(deps.storage, &transfer)?;
+
+    transfer.transition(TransferStatus::Declined)?;
+
+    let mut response = Response::new().add_attributes(vec![
+        attr("action", Action::Decline.to_string()),
+        attr("id", &transfer.id),
+        attr("denom", &transfer.denom),
+        attr("amount", transfer.amount.to_string()),
+        attr("sender", &transfer.sender),
+        attr("recipient", &transfer.recipient),
+    ]);
+
+    response = response.add_submessages(settlement_submessages(
+        deps.storage,
+        &transfer.id,
+        refund_messages(&transfer, &env.contract.address),
+    )?);
+    response = response.add_messages(release_held_funds(deps.storage, &transfer, false)?);
+
+    transfer.updated_at = Some(env.block.time);
+    save_transfer(deps.storage, env.block.height, &transfer)?;
+
+    Ok(response)
+}
+
+/// Allows the compliance role to refund a transfer that is stuck in escrow, whatever its status
+pub fn force_cancel_transfer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfer_id: String,
+) -> Result<Response, ContractError> {
+    let mut transfer = load_transfer(deps.storage, &transfer_id)
+        .map_err(|error| ContractError::LoadTransferFailed { error })?;
+
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::Compliance)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The compliance role is required to force cancel"),
+        });
+    }
+
+    check_transfer_not_frozen(deps.storage, &transfer)?;
+
+    // coins that already left escrow cannot be refunded a second time
+    transfer.transition(TransferStatus::Cancelled)?;
+
+    let mut response = Response::new().add_attributes(vec![
+        attr("action", Action::ForceCancel.to_string()),
+        attr("id", &transfer.id),
+        attr("denom", &transfer.denom),
+        attr("amount", transfer.amount.to_string()),
+        attr("sender", &transfer.sender),
+        attr("admin", &info.sender),
+    ]);
+
+    response = response.add_submessages(settlement_submessages(
+        deps.storage,
+        &transfer.id,
+        refund_messages(&transfer, &env.contract.address),
+    )?);
+    response = response.add_messages(release_held_funds(deps.storage, &transfer, false)?);
+
+    transfer.updated_at = Some(env.block.time);
+    save_transfer(deps.storage, env.block.height, &transfer)?;
+
+    Ok(response)
+}
+
+/// Allows the compliance role to hold a pending transfer in escrow while it is investigated,
+/// recording why
+pub fn freeze_transfer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfer_id: String,
+    reason: String,
+) -> Result<Response, ContractError> {
+    let transfer = load_transfer(deps.storage, &transfer_id)
+        .map_err(|error| ContractError::LoadTransferFailed { error })?;
+
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::Compliance)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The compliance role is required to freeze transfers"),
+        });
+    }
+
+    if transfer.status != TransferStatus::Pending {
+        return Err(ContractError::TransferNotPending {
+            status: transfer.status,
+        });
+    }
+
+    TRANSFER_FREEZES.save(
+        deps.storage,
+        &transfer_key(&transfer.id)?,
+        &TransferFreeze {
+            reason: reason.to_owned(),
+            frozen_by: info.sender.to_owned(),
+            frozen_at: env.block.time,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::FreezeTransfer.to_string()),
+        attr("id", &transfer.id),
+        attr("reason", reason),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows the compliance role to release a frozen transfer
+pub fn unfreeze_transfer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfer_id: String,
+) -> Result<Response, ContractError> {
+    let transfer = load_transfer(deps.storage, &transfer_id)
+        .map_err(|error| ContractError::LoadTransferFailed { error })?;
+
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::Compliance)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The compliance role is required to unfreeze transfers"),
+        });
+    }
+
+    TRANSFER_FREEZES.remove(deps.storage, &transfer_key(&transfer.id)?);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::UnfreezeTransfer.to_string()),
+        attr("id", &transfer.id),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// fails while the compliance role holds the transfer frozen
+fn check_transfer_not_frozen(
+    storage: &dyn Storage,
+    transfer: &Transfer,
+) -> Result<(), ContractError> {
+    if let Some(freeze) = TRANSFER_FREEZES.may_load(storage, &transfer_key(&transfer.id)?)? {
+        return Err(ContractError::TransferFrozen {
+            reason: freeze.reason,
+        });
+    }
+    Ok(())
+}
+
+/// Allows the contract admin, or a holder of the admin role, to start the delay before the escrow
+/// of every open transfer of the denom may be returned to its sender
+pub fn initiate_recovery(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: String,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::Admin)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The admin role is required to initiate an escrow recovery"),
+        });
+    }
+
+    let recovery = Recovery {
+        denom,
+        initiated_by: info.sender.to_owned(),
+        initiated_at: env.block.time,
+        executable_at: env.block.time.plus_seconds(RECOVERY_DELAY),
+    };
+    PENDING_RECOVERY.save(deps.storage, &recovery)?;
+    RECOVERY_CURSOR.remove(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::InitiateRecovery.to_string()),
+        attr("denom", &recovery.denom),
+        attr("executable_at", recovery.executable_at.to_string()),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows the contract admin, or a holder of the admin role, to drop the pending escrow recovery
+pub fn cancel_recovery(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::Admin)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The admin role is required to cancel an escrow recovery"),
+        });
+    }
+
+    let recovery = PENDING_RECOVERY
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoRecoveryPending)?;
+    PENDING_RECOVERY.remove(deps.storage);
+    RECOVERY_CURSOR.remove(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::CancelRecovery.to_string()),
+        attr("denom", &recovery.denom),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows the contract admin, or a holder of the admin role, to refund the senders of the open
+/// transfers of the recovered denom once the delay has passed. Each call looks at the next
+/// `limit` open transfers, at most `MAX_BATCH_SIZE`, and the recovery is complete once a call
+/// reaches the last of them.
+pub fn execute_recovery(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::Admin)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The admin role is required to execute an escrow recovery"),
+        });
+    }
+
+    let recovery = PENDING_RECOVERY
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoRecoveryPending)?;
+    if env.block.time < recovery.executable_at {
+        return Err(ContractError::RecoveryLocked {
+            executable_at: recovery.executable_at,
+        });
+    }
+
+    // each batch looks at the next open transfers after the cursor, so a recovery of a denom
+    // with many transfers takes several
+    let limit = limit
+        .map_or(MAX_BATCH_SIZE, |limit| limit as usize)
+        .min(MAX_BATCH_SIZE);
+    let cursor = RECOVERY_CURSOR.may_load(deps.storage)?;
+    let transfers = TRANSFER_STORAGE
+        .range(
+            deps.storage,
+            cursor.as_deref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    let complete = transfers.len() < limit;
+    match transfers.last() {
+        Some((key, _)) if !complete => RECOVERY_CURSOR.save(deps.storage, key)?,
+        _ => RECOVERY_CURSOR.remove(deps.storage),
+    }
+
+    // frozen transfers are left to the compliance role that froze them
+    let transfer_ids = transfers
+        .into_iter()
+        .filter(|(key, StoredTransfer(transfer))| {
+            transfer.status.holds_escrow()
+                && transfer
+                    .coins()
+                    .iter()
+                    .any(|coin| coin.denom == recovery.denom)
+                && !TRANSFER_FREEZES.has(deps.storage, key)
+        })
+        .map(|(_, StoredTransfer(transfer))| transfer.id)
+        .collect();
+
+    let response = execute_batch(
+        deps.branch(),
+        transfer_ids,
+        Action::ExecuteRecovery,
+        |deps, transfer_id| recover_transfer(deps.storage, &env, transfer_id),
+    )?;
+    if complete {
+        PENDING_RECOVERY.remove(deps.storage);
+    }
+
+    Ok(response.add_attributes(vec![
+        attr("denom", &recovery.denom),
+        attr("complete", complete.to_string()),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// cancels a pending transfer, or rejects an approved one still holding its escrow, refunding
+/// the escrow to the sender
+fn recover_transfer(
+    storage: &mut dyn Storage,
+    env: &Env,
+    transfer_id: String,
+) -> Result<Response, ContractError> {
+    let mut transfer = load_transfer(storage, &transfer_id)
+        .map_err(|error| ContractError::LoadTransferFailed { error })?;
+    check_transfer_not_frozen(storage, &transfer)?;
+
+    // an approved transfer can no longer be cancelled
+    match transfer.status {
+        TransferStatus::Approved => {
+            transfer.transition(TransferStatus::Rejected)?;
+            transfer.rejection_reason = Some(String::from("escrow recovered"));
+        }
+        _ => transfer.transition(TransferStatus::Cancelled)?,
+    }
+
+    let mut response = Response::new().add_attributes(vec![
+        attr("id", &transfer.id),
+        attr("denom", &transfer.denom),
+        attr("amount", transfer.amount.to_string()),
+        attr("sender", &transfer.sender),
+    ]);
+
+    response = response.add_submessages(settlement_submessages(
+        storage,
+        &transfer.id,
+        refund_messages(&transfer, &env.contract.address),
+    )?);
+    response = response.add_messages(release_held_funds(storage, &transfer, false)?);
+
+    transfer.updated_at = Some(env.block.time);
+    save_transfer(storage, env.block.height, &transfer)?;
+
+    Ok(response)
+}
+
+pub fn reject_transfer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfer_id: String,
+    reason: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut transfer = load_transfer(deps.storage, &transfer_id)
+        .map_err(|error| ContractError::LoadTransferFailed { error })?;
+
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    let querier = MarkerQuerier::new(&deps.querier);
+
+    if !has_transfer_access_for_all(deps.storage, &env.block, &info.sender, &transfer, &querier)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("ACCESS_TRANSFER permission is required to reject transfers"),
+        });
+    }
+
+    check_transfer_not_frozen(deps.storage, &transfer)?;
+
+    transfer.transition(TransferStatus::Rejected)?;
+    transfer.rejection_reason = reason;
+
+    let mut response = Response::new().add_attributes(vec![
+        attr("action", Action::Reject.to_string()),
+        attr("id", &transfer.id),
+        attr("denom", &transfer.denom),
+        attr("amount", transfer.amount.to_string()),
+        attr("sender", &transfer.sender),
+        attr("recipient", &transfer.recipient),
+        attr("admin", info.sender.to_owned()),
+    ]);
+
+    if let Some(reason) = &transfer.rejection_reason {
+        response = response.add_attribute("reason", reason);
+    }
+
+    response = response.add_submessages(settlement_submessages(
+        deps.storage,
+        &transfer.id,
+        refund_messages(&transfer, &env.contract.address),
+    )?);
+    response = response.add_messages(release_held_funds(deps.storage, &transfer, true)?);
+
+    // finally record the rejection
+    transfer.updated_at = Some(env.block.time);
+    save_transfer(deps.storage, env.block.height, &transfer)?;
+
+    Ok(response)
+}
+
+/// Rejects and refunds several transfers in one transaction. A transfer that cannot be rejected is
+/// reported in the attributes instead of failing the whole batch.
+pub fn reject_transfers(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfer_ids: Vec<String>,
+    reason: Option<String>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    execute_batch(
+        deps,
+        transfer_ids,
+        Action::RejectTransfers,
+        |deps, transfer_id| {
+            reject_transfer(deps, env.clone(), info.clone(), transfer_id, reason.clone())
+        },
+    )
+}
+
+/// Approve a pending transfer and release the escrowed coins to the recipient. When an amount is
+/// given only that portion is settled and the remainder is refunded to the sender.
+pub fn approve_transfer(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfer_id: String,
+    amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let mut transfer = load_transfer(deps.storage, &transfer_id)
+        .map_err(|error| ContractError::LoadTransferFailed { error })?;
+
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    let querier = MarkerQuerier::new(&deps.querier);
+
+    if !has_transfer_access_for_all(deps.storage, &env.block, &info.sender, &transfer, &querier)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("ACCESS_TRANSFER permission is required to approve transfers"),
+        });
+    }
+
+    if !transfer.status.can_transition_to(TransferStatus::Approved) {
+        return Err(ContractError::InvalidStatusTransition {
+            from: transfer.status,
+            to: TransferStatus::Approved,
+        });
+    }
+
+    if let Some(expires) = transfer.expires.filter(|_| transfer.is_expired(&env.block)) {
+        return Err(ContractError::TransferExpired { expires });
+    }
+
+    for coin in transfer.coins() {
+        check_denom_not_frozen(deps.storage, &coin.denom)?;
+    }
+    check_transfer_not_frozen(deps.storage, &transfer)?;
+
+    // the allowlist may have changed since the transfer was created
+    check_allowlist(deps.storage, &transfer)?;
+
+    if let Some(response) = refuse_blocked(deps.storage, &env, &mut transfer)? {
+        return Ok(response);
+    }
+
+    if !transfer.required_approvers.is_empty()
+        && !transfer.required_approvers.contains(&info.sender)
+    {
+        return Err(ContractError::Unauthorized {
+            error: String::from("Only the required approvers can approve this transfer"),
+        });
+    }
+
+    if transfer
+        .approvals
+        .iter()
+        .any(|approval| approval.approver == info.sender)
+    {
+        return Err(ContractError::DuplicateApproval {
+            approver: info.sender.to_string(),
+        });
+    }
+
+    transfer.approvals.push(Approval {
+        approver: info.sender.to_owned(),
+        height: env.block.height,
+        time: env.block.time,
+        amount: Some(amount.unwrap_or(transfer.amount)),
+    });
+
+    let quorum = required_approvals(deps.storage, &transfer)?;
+    let quorum_reached = transfer.approvals.len() >= quorum as usize;
+
+    // a reduced amount can only be settled by the approval that completes the quorum, and
+    // split or multi-denom transfers are settled in full or not at all
+    let approved_amount = amount.unwrap_or(transfer.amount);
+    if approved_amount > transfer.amount
+        || (approved_amount != transfer.amount
+            && (!quorum_reached
+                || !transfer.legs.is_empty()
+                || !transfer.additional_coins.is_empty()))
+    {
+        return Err(ContractError::InvalidFields {
+            fields: vec![String::from("amount")],
+        });
+    }
+    let refund_amount = transfer.amount - approved_amount;
+
+    let mut response = Response::new()
+        .add_attributes(vec![
+            attr("action", Action::Approve.to_string()),
+            attr("id", &transfer.id),
+            attr("denom", &transfer.denom),
+            attr("amount", approved_amount.to_string()),
+            attr("sender", &transfer.sender),
+            attr("recipient", &transfer.recipient),
+            attr("admin", &info.sender),
+            attr("approved_height", env.block.height.to_string()),
+            attr("approved_time", env.block.time.to_string()),
+            attr("approval_count", transfer.approvals.len().to_string()),
+            attr("quorum", quorum.to_string()),
+        ])
+        .add_messages(assess_msg_fee(
+            deps.storage,
+            &env,
+            &info.sender,
+            "transfer_approval",
+            |fees| fees.approval_fee,
+            1,
+        )?);
+
+    if let Some(memo) = &transfer.memo {
+        response = response.add_attribute("memo", memo);
+    }
+
+    if !quorum_reached {
+        transfer.updated_at = Some(env.block.time);
+        save_transfer(deps.storage, env.block.height, &transfer)?;
+        return Ok(response);
+    }
+
+    transfer.transition(TransferStatus::Approved)?;
+
+    // the record keeps the approved amount, which is all that remains in escrow
+    transfer.amount = approved_amount;
+
+    if let Some(vesting) = &mut transfer.vesting {
+        // the coins stay in escrow and are claimed by the recipient as they vest
+        vesting.start = Some(env.block.time);
+    } else if !transfer.require_acceptance {
+        // the escrowed coins move to the recipients in this same transaction
+        transfer.transition(TransferStatus::Settled)?;
+    }
+
+    // the settlement fees are queried before the first write, so nothing is left half written
+    // when an approval in a batch fails
+    let settlement = match transfer.status {
+        TransferStatus::Settled => Some(settlement_messages(
+            deps.branch(),
+            &transfer,
+            &env.contract.address,
+        )?),
+        _ => None,
+    };
+    response = response.add_messages(pay_incentive(deps.storage, &transfer, &info.sender)?);
+
+    if let Some((msgs, fee_events)) = settlement {
+        response = response
+            .add_submessages(settlement_submessages(deps.storage, &transfer.id, msgs)?)
+            .add_messages(release_held_funds(deps.storage, &transfer, false)?)
+            .add_events(fee_events);
+    }
+
+    if !refund_amount.is_zero() {
+        response = response
+            .add_attribute("refund_amount", refund_amount.to_string())
+            .add_submessages(settlement_submessages(
+                deps.storage,
+                &transfer.id,
+                vec![transfer_marker_coins(
+                    refund_amount,
+                    &transfer.denom,
+                    &transfer.sender,
+                    &env.contract.address,
+                    &env.contract.address,
+                )],
+            )?);
+    }
+
+    transfer.updated_at = Some(env.block.time);
+    save_transfer(deps.storage, env.block.height, &transfer)?;
+    Ok(response)
+}
+
+/// Approves several transfers in one transaction. A transfer that cannot be approved is reported
+/// in the attributes instead of failing the whole batch.
+pub fn approve_transfers(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfer_ids: Vec<String>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    execute_batch(
+        deps,
+        transfer_ids,
+        Action::ApproveTransfers,
+        |deps, transfer_id| approve_transfer(deps, env.clone(), info.clone(), transfer_id, None),
+    )
+}
+
+/// runs the handler for every id in turn, keeping the writes of those that succeed. Each success
+/// is emitted as an event carrying that transfer's attributes, followed by the events of the
+/// handler, and each failure as a `failed` attribute with the id and error.
+fn execute_batch<F>(
+    deps: DepsMut,
+    ids: Vec<String>,
+    action: Action,
+    mut handler: F,
+) -> Result<Response, ContractError>
+where
+    F: FnMut(DepsMut, String) -> Result<Response, ContractError>,
+{
+    let mut response = Response::new();
+    let mut succeeded = 0;
+    let mut failures = vec![];
+
+    for id in ids {
+        // each id runs in a transaction of its own, so a failing id leaves no writes behind
+        let mut transaction = StorageTransaction::new(deps.storage);
+        let result = handler(
+            DepsMut {
+                storage: &mut transaction,
+                api: deps.api,
+                querier: deps.querier,
+            },
+            id.to_owned(),
+        );
+        let writes = transaction.into_writes();
+        match result {
+            Ok(result) => {
+                writes.commit(deps.storage);
+                succeeded += 1;
+                response = response
+                    .add_submessages(result.messages)
+                    .add_event(Event::new(action.to_string()).add_attributes(result.attributes))
+                    .add_events(result.events);
+            }
+            Err(error) => failures.push(attr("failed", format!("{}: {}", id, error))),
+        }
+    }
+
+    Ok(response
+        .add_attributes(vec![
+            attr("action", action.to_string()),
+            attr("succeeded", succeeded.to_string()),
+            attr("failed_count", failures.len().to_string()),
+        ])
+        .add_attributes(failures))
+}
+
+/// Allows the recipient of an approved transfer that requires acceptance to receive the coins
+pub fn accept_transfer(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfer_id: String,
+) -> Result<Response, ContractError> {
+    let mut transfer = load_transfer(deps.storage, &transfer_id)
+        .map_err(|error| ContractError::LoadTransferFailed { error })?;
+
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !info.sender.eq(&transfer.recipient) {
+        return Err(ContractError::Unauthorized {
+            error: String::from("Only the recipient can accept"),
+        });
+    }
+
+    // other approved transfers are either already settled or released by vesting
+    if !transfer.require_acceptance || !transfer.status.can_transition_to(TransferStatus::Settled) {
+        return Err(ContractError::InvalidStatusTransition {
+            from: transfer.status,
+            to: TransferStatus::Settled,
+        });
+    }
+
+    if let Some(response) = refuse_blocked(deps.storage, &env, &mut transfer)? {
+        return Ok(response);
+    }
+
+    transfer.transition(TransferStatus::Settled)?;
+
+    let mut response = Response::new().add_attributes(vec![
+        attr("action", Action::Accept.to_string()),
+        attr("id", &transfer.id),
+        attr("denom", &transfer.denom),
+        attr("amount", transfer.amount.to_string()),
+        attr("sender", &transfer.sender),
+        attr("recipient", &transfer.recipient),
+    ]);
+
+    let (msgs, fee_events) = settlement_messages(deps.branch(), &transfer, &env.contract.address)?;
+    response = response
+        .add_submessages(settlement_submessages(deps.storage, &transfer.id, msgs)?)
+        .add_messages(release_held_funds(deps.storage, &transfer, false)?)
+        .add_events(fee_events);
+
+    transfer.updated_at = Some(env.block.time);
+    save_transfer(deps.storage, env.block.height, &transfer)?;
+
+    Ok(response)
+}
+
+/// Pays the recipient of an approved vesting transfer whatever has vested since their last claim.
+/// The transfer settles once the full amount has been claimed.
+pub fn claim(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfer_id: String,
+) -> Result<Response, ContractError> {
+    let mut transfer = load_transfer(deps.storage, &transfer_id)
+        .map_err(|error| ContractError::LoadTransferFailed { error })?;
+
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !info.sender.eq(&transfer.recipient) {
+        return Err(ContractError::Unauthorized {
+            error: String::from("Only the recipient can claim"),
+        });
+    }
+
+    let vested = match (&transfer.vesting, transfer.status) {
+        (Some(vesting), TransferStatus::Approved) => {
+            vesting.vested(transfer.amount, env.block.time)
+        }
+        _ => return Err(ContractError::NothingToClaim),
+    };
+    let claim_amount = vested - transfer.claimed;
+
+    if claim_amount.is_zero() {
+        return Err(ContractError::NothingToClaim);
+    }
+
+    if let Some(response) = refuse_blocked(deps.storage, &env, &mut transfer)? {
+        return Ok(response);
+    }
+
+    let claimed_before = transfer.claimed;
+    transfer.claimed = vested;
+    let mut held_funds = vec![];
+    if transfer.claimed == transfer.amount {
+        transfer.transition(TransferStatus::Settled)?;
+        held_funds = release_held_funds(deps.storage, &transfer, false)?;
+    }
+
+    transfer.updated_at = Some(env.block.time);
+    save_transfer(deps.storage, env.block.height, &transfer)?;
+
+    // the fee on the whole amount is charged in step with what has been claimed of it
+    let fee = settlement_fee(
+        deps.as_ref(),
+        &transfer.sender,
+        &transfer.denom,
+        transfer.amount,
+        &env.contract.address,
+    )?
+    .map(|(fee, collector)| {
+        let owed = |claimed| fee.multiply_ratio(claimed, transfer.amount);
+        (owed(transfer.claimed) - owed(claimed_before), collector)
+    });
+    accrue_fee(deps.storage, &transfer.denom, &fee, &env.contract.address)?;
+    let (msgs, fee_events) = fee_deducted_payouts(
+        &transfer.id,
+        &transfer.denom,
+        vec![(&transfer.recipient, claim_amount)],
+        fee,
+        &env.contract.address,
+    );
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", Action::Claim.to_string()),
+            attr("id", &transfer.id),
+            attr("denom", &transfer.denom),
+            attr("amount", claim_amount.to_string()),
+            attr("claimed", transfer.claimed.to_string()),
+            attr("recipient", &transfer.recipient),
+        ])
+        .add_submessages(settlement_submessages(deps.storage, &transfer.id, msgs)?)
+        .add_messages(held_funds)
+        .add_events(fee_events))
+}
+
+/// Wraps a marker transfer escrowing coins in a submessage that replies on error, failing the
+/// transaction with an escrow error
+fn escrow_submessage(msg: MsgTransferRequest) -> SubMsg {
+    SubMsg::reply_on_error(msg, ESCROW_REPLY_ID)
+}
+
+/// Wraps marker transfers moving a transfer's escrow out in submessages that always reply, so
+/// a failed one is recorded instead of reverting the transaction
+fn settlement_submessages(
+    storage: &mut dyn Storage,
+    transfer_id: &str,
+    msgs: Vec<MsgTransferRequest>,
+) -> StdResult<Vec<SubMsg>> {
+    let mut reply_id = NEXT_SETTLEMENT_REPLY_ID
+        .may_load(storage)?
+        .unwrap_or(ESCROW_REPLY_ID + 1);
+
+    let mut submessages = vec![];
+    for msg in msgs {
+        SETTLEMENT_ATTEMPTS.save(
+            storage,
+            reply_id,
+            &SettlementAttempt {
+                transfer_id: transfer_id.to_owned(),
+                msg: msg.clone(),
+            },
+        )?;
+        submessages.push(SubMsg::reply_always(msg, reply_id));
+        reply_id += 1;
+    }
+
+    NEXT_SETTLEMENT_REPLY_ID.save(storage, &reply_id)?;
+    Ok(submessages)
+}
+
+/// builds the msg-fees assessment levying the fee `count` times on the transaction, none when
+/// the fee is not set or its signer is exempt from fees
+fn assess_msg_fee(
+    storage: &dyn Storage,
+    env: &Env,
+    signer: &Addr,
+    name: &str,
+    fee: impl Fn(&MsgFees) -> Option<Uint128>,
+    count: u128,
+) -> StdResult<Vec<MsgAssessCustomMsgFeeRequest>> {
+    let fees = match MSG_FEES.may_load(storage)? {
+        Some(fees) if !FEE_EXEMPTIONS.has(storage, signer) => fees,
+        _ => return Ok(vec![]),
+    };
+    let amount = match fee(&fees) {
+        Some(amount) if count > 0 => amount.checked_mul(Uint128::new(count))?,
+        _ => return Ok(vec![]),
+    };
+
+    Ok(vec![MsgAssessCustomMsgFeeRequest {
+        name: name.to_owned(),
+        amount: Some(Coin {
+            denom: fee_denom(storage)?,
+            amount: amount.to_string(),
+        }),
+        recipient: fees
+            .recipient
+            .map(|recipient| recipient.to_string())
+            .unwrap_or_default(),
+        from: env.contract.address.to_string(),
+        recipient_basis_points: fees.recipient_bps.to_string(),
+    }])
+}
+
+/// builds the marker transfers releasing a transfer's escrow, one per recipient and denom, less
+/// the settlement fee of each denom, along with the events of the fees charged. Fees the contract
+/// keeps are only recorded once every fee has been queried.
+fn settlement_messages(
+    deps: DepsMut,
+    transfer: &Transfer,
+    contract_address: &Addr,
+) -> StdResult<(Vec<MsgTransferRequest>, Vec<Event>)> {
+    let payouts: Vec<(String, Vec<(&Addr, Uint128)>)> = if transfer.legs.is_empty() {
+        transfer
+            .coins()
+            .into_iter()
+            .map(|coin| (coin.denom, vec![(&transfer.recipient, coin.amount)]))
+            .collect()
+    } else {
+        vec![(
+            transfer.denom.to_owned(),
+            transfer
+                .legs
+                .iter()
+                .map(|leg| (&leg.address, leg.amount))
+                .collect(),
+        )]
+    };
+
+    let mut fees = vec![];
+    for (denom, payouts) in &payouts {
+        let amount = payouts.iter().map(|(_, amount)| amount).sum();
+        fees.push(settlement_fee(
+            deps.as_ref(),
+            &transfer.sender,
+            denom,
+            amount,
+            contract_address,
+        )?);
+    }
+
+    let mut msgs = vec![];
+    let mut events = vec![];
+    for ((denom, payouts), fee) in payouts.into_iter().zip(fees) {
+        accrue_fee(deps.storage, &denom, &fee, contract_address)?;
+        let (payout_msgs, fee_events) =
+            fee_deducted_payouts(&transfer.id, &denom, payouts, fee, contract_address);
+        msgs.extend(payout_msgs);
+        events.extend(fee_events);
+    }
+    Ok((msgs, events))
+}
+
+/// The settlement fee charged on `amount` of the denom sent by `sender` and the address it is
+/// paid to, the contract's own for fees it accrues. None is charged without a settlement fee, to
+/// senders exempt from fees, or when it is paid to a marker admin the marker lacks.
+fn settlement_fee(
+    deps: Deps,
+    sender: &Addr,
+    denom: &str,
+    amount: Uint128,
+    contract_address: &Addr,
+) -> StdResult<Option<(Uint128, Addr)>> {
+    let fee = match SETTLEMENT_FEE.may_load(deps.storage)? {
+        Some(fee) if !FEE_EXEMPTIONS.has(deps.storage, sender) => fee,
+        _ => return Ok(None),
+    };
+
+    let collector = match fee.destination {
+        FeeDestination::Collector { address } => address,
+        FeeDestination::MarkerAdmin {} => {
+            let querier = MarkerQuerier::new(&deps.querier);
+            match marker_admin(get_marker_by_denom(denom.to_owned(), &querier)?) {
+                Some(admin) => admin,
+                None => return Ok(None),
+            }
+        }
+        FeeDestination::Accrue {} => contract_address.to_owned(),
+    };
+
+    let schedule = DENOM_FEE_SCHEDULES
+        .may_load(deps.storage, denom)?
+        .unwrap_or(FeeSchedule::Bps { bps: fee.bps });
+    Ok(Some((schedule.fee(amount), collector)))
+}
+
+/// records a settlement fee the contract keeps in the fee ledger
+fn accrue_fee(
+    storage: &mut dyn Storage,
+    denom: &str,
+    fee: &Option<(Uint128, Addr)>,
+    contract_address: &Addr,
+) -> StdResult<()> {
+    if let Some((fee, _)) = fee
+        .as_ref()
+        .filter(|(fee, collector)| !fee.is_zero() && collector == contract_address)
+    {
+        ACCRUED_FEES.update(storage, denom, |accrued| -> StdResult<_> {
+            Ok(accrued.unwrap_or_default().checked_add(*fee)?)
+        })?;
+    }
+    Ok(())
+}
+
+/// builds the marker transfers paying out the denom, each payout less its share of the fee, and
+/// the transfer of the fee to its collector with an event recording it. Shares follow the
+/// running total, so together they come to the whole fee.
+fn fee_deducted_payouts(
+    transfer_id: &str,
+    denom: &str,
+    payouts: Vec<(&Addr, Uint128)>,
+    fee: Option<(Uint128, Addr)>,
+    contract_address: &Addr,
+) -> (Vec<MsgTransferRequest>, Vec<Event>) {
+    let payout =
+        |amount, to| transfer_marker_coins(amount, denom, to, contract_address, contract_address);
+
+    let (fee, collector) = match fee.filter(|(fee, _)| !fee.is_zero()) {
+        Some(fee) => fee,
+        None => {
+            let msgs = payouts
+                .into_iter()
+                .map(|(to, amount)| payout(amount, to))
+                .collect();
+            return (msgs, vec![]);
+        }
+    };
+
+    let total: Uint128 = payouts.iter().map(|(_, amount)| amount).sum();
+    let mut paid = Uint128::zero();
+    let mut charged = Uint128::zero();
+    let mut msgs = vec![];
+    for (to, amount) in payouts {
+        paid += amount;
+        let share = fee.multiply_ratio(paid, total) - charged;
+        charged += share;
+        if amount > share {
+            msgs.push(payout(amount - share, to));
+        }
+    }
+    // fees the contract accrues stay where they are
+    if collector != *contract_address {
+        msgs.push(payout(fee, &collector));
+    }
+
+    let event = Event::new(Action::SettlementFee.to_string()).add_attributes(vec![
+        attr("id", transfer_id),
+        attr("denom", denom),
+        attr("amount", fee.to_string()),
+        attr("collector", &collector),
+    ]);
+    (msgs, vec![event])
+}
+
+/// builds the marker transfers returning a transfer's escrow to the sender, one per denom
+fn refund_messages(transfer: &Transfer, contract_address: &Addr) -> Vec<MsgTransferRequest> {
+    transfer
+        .coins()
+        .iter()
+        .map(|coin| {
+            transfer_marker_coins(
+                coin.amount,
+                &coin.denom,
+                &transfer.sender,
+                contract_address,
+                contract_address,
+            )
+        })
+        .collect()
+}
+
+/// builds a marker transfer of the given coins, performed with the contract's marker permissions
+fn transfer_marker_coins(
+    amount: Uint128,
+    denom: &str,
+    to: &Addr,
+    from: &Addr,
+    administrator: &Addr,
+) -> MsgTransferRequest {
+    MsgTransferRequest {
+        amount: Some(Coin {
+            denom: denom.to_owned(),
+            amount: amount.into(),
+        }),
+        to_address: to.to_string(),
+        from_address: from.to_string(),
+        administrator: administrator.to_string(),
+    }
+}
+
+/// Allows a marker admin to require more than one approval before transfers of the denom settle
+pub fn set_approval_quorum(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    quorum: u32,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    let querier = MarkerQuerier::new(&deps.querier);
+    let marker = get_marker_by_denom(denom.clone(), &querier)?;
+
+    if !has_marker_access_admin(info.sender.to_owned(), marker) {
+        return Err(ContractError::Unauthorized {
+            error: String::from("ACCESS_ADMIN permission is required to set the approval quorum"),
+        });
+    }
+
+    APPROVAL_QUORUM.save(deps.storage, &denom, &quorum)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::SetApprovalQuorum.to_string()),
+        attr("denom", &denom),
+        attr("quorum", quorum.to_string()),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows a marker admin to have transfers of the denom below a threshold approved as they are
+/// created, or to remove the threshold
+pub fn set_auto_approve_threshold(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    threshold: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    let querier = MarkerQuerier::new(&deps.querier);
+    let marker = get_marker_by_denom(denom.clone(), &querier)?;
+
+    if !has_marker_access_admin(info.sender.to_owned(), marker) {
+        return Err(ContractError::Unauthorized {
+            error: String::from(
+                "ACCESS_ADMIN permission is required to set the auto-approve threshold",
+            ),
+        });
+    }
+
+    match threshold {
+        Some(threshold) => AUTO_APPROVE_THRESHOLDS.save(deps.storage, &denom, &threshold)?,
+        None => AUTO_APPROVE_THRESHOLDS.remove(deps.storage, &denom),
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::SetAutoApproveThreshold.to_string()),
+        attr("denom", &denom),
+        attr(
+            "threshold",
+            threshold.map_or_else(String::new, |threshold| threshold.to_string()),
+        ),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Sets the amounts and default expiration of new transfers of a denom, removing them when
+/// nothing is set
+pub fn set_denom_config(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: String,
+    config: DenomConfig,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    let querier = MarkerQuerier::new(&deps.querier);
+    let marker = get_marker_by_denom(denom.clone(), &querier)?;
+
+    if !has_marker_access_admin(info.sender.to_owned(), marker)
+        && !has_role(deps.as_ref(), &env, &info.sender, Role::Compliance)?
+    {
+        return Err(ContractError::Unauthorized {
+            error: String::from(
+                "The compliance role or ACCESS_ADMIN permission is required to set the denom config",
+            ),
+        });
+    }
+
+    Ok(save_denom_config(deps.storage, &denom, &config)?.add_attribute("admin", &info.sender))
+}
+
+/// stores the config of the denom, dropping it once it sets nothing
+fn save_denom_config(
+    storage: &mut dyn Storage,
+    denom: &str,
+    config: &DenomConfig,
+) -> StdResult<Response> {
+    if *config == DenomConfig::default() {
+        DENOM_CONFIG.remove(storage, denom);
+    } else {
+        DENOM_CONFIG.save(storage, denom, config)?;
+    }
+
+    let optional = |value: Option<String>| value.unwrap_or_default();
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::SetDenomConfig.to_string()),
+        attr("denom", denom),
+        attr(
+            "min_amount",
+            optional(config.min_amount.map(|amount| amount.to_string())),
+        ),
+        attr(
+            "max_amount",
+            optional(config.max_amount.map(|amount| amount.to_string())),
+        ),
+        attr(
+            "default_expiration",
+            optional(config.default_expiration.map(|seconds| seconds.to_string())),
+        ),
+    ]))
+}
+
+/// Allows the compliance role to block addresses from sending or receiving transfers, or to
+/// unblock them
+pub fn update_blocklist(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::Compliance)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The compliance role is required to update the blocklist"),
+        });
+    }
+
+    for address in &add {
+        BLOCKLIST.save(deps.storage, &deps.api.addr_validate(address)?, &Empty {})?;
+    }
+    for address in &remove {
+        BLOCKLIST.remove(deps.storage, &deps.api.addr_validate(address)?);
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::UpdateBlocklist.to_string()),
+        attr("added", add.join(",")),
+        attr("removed", remove.join(",")),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows the admin to exempt addresses from the contract's fees, or to charge them again
+pub fn update_fee_exemptions(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::Admin)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The admin role is required to update fee exemptions"),
+        });
+    }
+
+    for address in &add {
+        FEE_EXEMPTIONS.save(deps.storage, &deps.api.addr_validate(address)?, &Empty {})?;
+    }
+    for address in &remove {
+        FEE_EXEMPTIONS.remove(deps.storage, &deps.api.addr_validate(address)?);
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::UpdateFeeExemptions.to_string()),
+        attr("added", add.join(",")),
+        attr("removed", remove.join(",")),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows the marker's admin to restrict the recipients of transfers of the denom to its allowlist
+pub fn set_allowlist_enabled(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    check_marker_admin(deps.as_ref(), &info.sender, &denom, "allowlist")?;
+
+    match enabled {
+        true => ALLOWLIST_ENABLED.save(deps.storage, &denom, &Empty {})?,
+        false => ALLOWLIST_ENABLED.remove(deps.storage, &denom),
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::SetAllowlistEnabled.to_string()),
+        attr("denom", &denom),
+        attr("enabled", enabled.to_string()),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows the marker's admin to add and remove addresses on the allowlist of the denom
+pub fn update_allowlist(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    check_marker_admin(deps.as_ref(), &info.sender, &denom, "allowlist")?;
+
+    for address in &add {
+        let address = deps.api.addr_validate(address)?;
+        RECIPIENT_ALLOWLIST.save(deps.storage, (&denom, &address), &Empty {})?;
+    }
+    for address in &remove {
+        let address = deps.api.addr_validate(address)?;
+        RECIPIENT_ALLOWLIST.remove(deps.storage, (&denom, &address));
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::UpdateAllowlist.to_string()),
+        attr("denom", &denom),
+        attr("added", add.join(",")),
+        attr("removed", remove.join(",")),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows the marker's admin to consent to transfers of the denom going through the contract, or
+/// to withdraw that consent
+pub fn set_denom_registered(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    registered: bool,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    check_marker_admin(deps.as_ref(), &info.sender, &denom, "denom registration")?;
+
+    let action = if registered {
+        REGISTERED_DENOMS.save(deps.storage, &denom, &Empty {})?;
+        Action::RegisterDenom
+    } else {
+        REGISTERED_DENOMS.remove(deps.storage, &denom);
+        Action::DeregisterDenom
+    };
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", action.to_string()),
+        attr("denom", &denom),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// fails unless the marker admin of the denom registered it with the contract
+fn check_denom_registered(storage: &dyn Storage, denom: &str) -> Result<(), ContractError> {
+    if !REGISTERED_DENOMS.has(storage, denom) {
+        return Err(ContractError::DenomNotRegistered {
+            denom: denom.to_owned(),
+        });
+    }
+    Ok(())
+}
+
+/// Allows the admin role or the marker's admin to halt new transfers and approvals of the
+/// denom, or to lift the freeze
+pub fn set_denom_frozen(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: String,
+    frozen: bool,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    let querier = MarkerQuerier::new(&deps.querier);
+    let marker = get_marker_by_denom(denom.clone(), &querier)?;
+
+    if !has_marker_access_admin(info.sender.to_owned(), marker)
+        && !has_role(deps.as_ref(), &env, &info.sender, Role::Admin)?
+    {
+        return Err(ContractError::Unauthorized {
+            error: String::from(
+                "The admin role or ACCESS_ADMIN permission is required to freeze the denom",
+            ),
+        });
+    }
+
+    let action = if frozen {
+        FROZEN_DENOMS.save(deps.storage, &denom, &Empty {})?;
+        Action::FreezeDenom
+    } else {
+        FROZEN_DENOMS.remove(deps.storage, &denom);
+        Action::UnfreezeDenom
+    };
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", action.to_string()),
+        attr("denom", &denom),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// fails while the denom is frozen
+fn check_denom_not_frozen(storage: &dyn Storage, denom: &str) -> Result<(), ContractError> {
+    if FROZEN_DENOMS.has(storage, denom) {
+        return Err(ContractError::DenomFrozen {
+            denom: denom.to_owned(),
+        });
+    }
+    Ok(())
+}
+
+/// Allows the marker's admin to register and unregister approvers of the denom
+pub fn update_approvers(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    check_marker_admin(deps.as_ref(), &info.sender, &denom, "approvers")?;
+
+    for address in &add {
+        let address = deps.api.addr_validate(address)?;
+        APPROVER_REGISTRY.save(deps.storage, (&denom, &address), &Empty {})?;
+    }
+    for address in &remove {
+        let address = deps.api.addr_validate(address)?;
+        APPROVER_REGISTRY.remove(deps.storage, (&denom, &address));
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::UpdateApprovers.to_string()),
+        attr("denom", &denom),
+        attr("added", add.join(",")),
+        attr("removed", remove.join(",")),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows an approver of the denom to lend their approval rights to another address until
+/// `expires_at`. Delegated rights cannot be delegated again.
+pub fn delegate_approval(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: String,
+    delegate: String,
+    expires_at: Timestamp,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    let querier = MarkerQuerier::new(&deps.querier);
+    if !has_own_transfer_access(deps.storage, &info.sender, &denom, &querier)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from(
+                "ACCESS_TRANSFER permission or a registered approver is required to delegate approvals",
+            ),
+        });
+    }
+
+    if expires_at <= env.block.time {
+        return Err(ContractError::InvalidFields {
+            fields: vec![String::from("expires_at")],
+        });
+    }
+
+    let delegation = ApprovalDelegation {
+        denom,
+        delegator: info.sender.to_owned(),
+        delegate: deps.api.addr_validate(&delegate)?,
+        expires_at,
+    };
+    APPROVAL_DELEGATIONS.save(
+        deps.storage,
+        (&delegation.denom, &delegation.delegator),
+        &delegation,
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::DelegateApproval.to_string()),
+        attr("denom", &delegation.denom),
+        attr("delegator", &delegation.delegator),
+        attr("delegate", &delegation.delegate),
+        attr("expires_at", expires_at.to_string()),
+    ]))
+}
+
+/// Ends the sender's delegation of approval rights for the denom
+pub fn revoke_delegation(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    let delegation = APPROVAL_DELEGATIONS
+        .may_load(deps.storage, (&denom, &info.sender))?
+        .ok_or_else(|| ContractError::InvalidFields {
+            fields: vec![String::from("denom")],
+        })?;
+    APPROVAL_DELEGATIONS.remove(deps.storage, (&denom, &info.sender));
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::RevokeDelegation.to_string()),
+        attr("denom", &denom),
+        attr("delegator", &info.sender),
+        attr("delegate", &delegation.delegate),
+    ]))
+}
+
+/// fails unless the address holds admin permission on the marker of the denom
+fn check_marker_admin(
+    deps: Deps,
+    address: &Addr,
+    denom: &str,
+    managed: &str,
+) -> Result<(), ContractError> {
+    let querier = MarkerQuerier::new(&deps.querier);
+    let marker = get_marker_by_denom(denom.to_owned(), &querier)?;
+
+    if !has_marker_access_admin(address.to_owned(), marker) {
+        return Err(ContractError::Unauthorized {
+            error: format!(
+                "ACCESS_ADMIN permission is required to manage the {}",
+                managed
+            ),
+        });
+    }
+
+    Ok(())
+}
+
+/// Sets how much of a denom each sender may transfer within a rolling window, or removes the limit
+pub fn set_velocity_limit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: String,
+    limit: Option<VelocityLimit>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    let querier = MarkerQuerier::new(&deps.querier);
+    let marker = get_marker_by_denom(denom.clone(), &querier)?;
+
+    if !has_marker_access_admin(info.sender.to_owned(), marker)
+        && !has_role(deps.as_ref(), &env, &info.sender, Role::Compliance)?
+    {
+        return Err(ContractError::Unauthorized {
+            error: String::from(
+                "The compliance role or ACCESS_ADMIN permission is required to set the velocity limit",
+            ),
+        });
+    }
+
+    Ok(save_velocity_limit(deps.storage, &denom, limit)?.add_attribute("admin", &info.sender))
+}
+
+/// stores the velocity limit of the denom, or removes it without a limit
+fn save_velocity_limit(
+    storage: &mut dyn Storage,
+    denom: &str,
+    limit: Option<VelocityLimit>,
+) -> StdResult<Response> {
+    match &limit {
+        Some(limit) => VELOCITY_LIMITS.save(storage, denom, limit)?,
+        None => VELOCITY_LIMITS.remove(storage, denom),
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::SetVelocityLimit.to_string()),
+        attr("denom", denom),
+        attr(
+            "amount",
+            limit
+                .as_ref()
+                .map(|limit| limit.amount.to_string())
+                .unwrap_or_default(),
+        ),
+        attr(
+            "window",
+            limit
+                .as_ref()
+                .map(|limit| limit.window.to_string())
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// Assigns the compliance address that may veto transfers, either for one denom or for all denoms
+pub fn set_veto_address(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: Option<String>,
+    address: Option<String>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    let address = address
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+
+    match &denom {
+        Some(denom) => {
+            let querier = MarkerQuerier::new(&deps.querier);
+            let marker = get_marker_by_denom(denom.clone(), &querier)?;
+
+            if !has_marker_access_admin(info.sender.to_owned(), marker) {
+                return Err(ContractError::Unauthorized {
+                    error: String::from(
+                        "ACCESS_ADMIN permission is required to set the denom veto address",
+                    ),
+                });
+            }
+
+            match &address {
+                Some(address) => DENOM_VETO.save(deps.storage, denom, address)?,
+                None => DENOM_VETO.remove(deps.storage, denom),
+            }
+        }
+        None => {
+            if !has_role(deps.as_ref(), &env, &info.sender, Role::Compliance)? {
+                return Err(ContractError::Unauthorized {
+                    error: String::from(
+                        "The compliance role is required to set the global veto address",
+                    ),
+                });
+            }
+
+            match &address {
+                Some(address) => GLOBAL_VETO.save(deps.storage, address)?,
+                None => GLOBAL_VETO.remove(deps.storage),
+            }
+        }
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::SetVetoAddress.to_string()),
+        attr("denom", denom.unwrap_or_default()),
+        attr(
+            "veto_address",
+            address
+                .map(|address| address.to_string())
+                .unwrap_or_default(),
+        ),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Pauses or unpauses the contract
+pub fn set_paused(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::Operator)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The operator role is required to pause or unpause the contract"),
+        });
+    }
+
+    Ok(save_paused(deps.storage, paused)?.add_attribute("admin", &info.sender))
+}
+
+/// Pauses or unpauses the contract, returning the response announcing it
+fn save_paused(storage: &mut dyn Storage, paused: bool) -> StdResult<Response> {
+    PAUSED.save(storage, &paused)?;
+
+    let action = if paused {
+        Action::Pause
+    } else {
+        Action::Unpause
+    };
+    Ok(Response::new().add_attribute("action", action.to_string()))
+}
+
+/// Allows the contract admin, or a holder of the admin role, to grant a role to an address
+pub fn grant_role(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    role: Role,
+    address: String,
+) -> Result<Response, ContractError> {
+    update_role(deps, env, info, role, address, true)
+}
+
+/// Allows the contract admin, or a holder of the admin role, to revoke a role from an address
+pub fn revoke_role(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    role: Role,
+    address: String,
+) -> Result<Response, ContractError> {
+    update_role(deps, env, info, role, address, false)
+}
+
+fn update_role(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    role: Role,
+    address: String,
+    granted: bool,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::Admin)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The admin role is required to grant or revoke roles"),
+        });
+    }
+
+    let address = deps.api.addr_validate(&address)?;
+    let key = (role.to_string(), address.clone());
+    let action = if granted {
+        ROLE_MEMBERS.save(deps.storage, (&key.0, &key.1), &Empty {})?;
+        Action::GrantRole
+    } else {
+        ROLE_MEMBERS.remove(deps.storage, (&key.0, &key.1));
+        Action::RevokeRole
+    };
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", action.to_string()),
+        attr("role", role.to_string()),
+        attr("address", &address),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows the contract admin, or a holder of the admin role, to change the contract's settings
+/// after instantiation. Settings left out keep their values, and a `config_changed` event lists
+/// the ones that changed.
+pub fn update_config(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    name: Option<String>,
+    page_limit: Option<u32>,
+    pending_limit: Option<u32>,
+    max_transfer_amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::Admin)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The admin role is required to update the config"),
+        });
+    }
+
+    let mut changes = vec![];
+    if let Some(name) = name {
+        let mut state = CONFIG.load(deps.storage)?;
+        changes.push(attr("previous_name", &state.name));
+        changes.push(attr("name", &name));
+        state.name = name;
+        CONFIG.save(deps.storage, &state)?;
+    }
+    if let Some(limit) = page_limit {
+        PAGE_LIMIT.save(deps.storage, &limit)?;
+        changes.push(attr("page_limit", limit.to_string()));
+    }
+    if let Some(limit) = pending_limit {
+        PENDING_LIMIT.save(deps.storage, &limit)?;
+        changes.push(attr("pending_limit", limit.to_string()));
+    }
+    if let Some(amount) = max_transfer_amount {
+        MAX_TRANSFER_AMOUNT.save(deps.storage, &amount)?;
+        changes.push(attr("max_transfer_amount", amount.to_string()));
+    }
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", Action::UpdateConfig.to_string()),
+            attr("admin", &info.sender),
+        ])
+        .add_event(Event::new("config_changed").add_attributes(changes)))
+}
+
+/// Proposes another address as the contract admin, which takes the role once it accepts
+pub fn update_admin(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_admin: String,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !is_contract_admin(deps.as_ref(), &env, &info.sender)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("Only the contract admin can update the admin"),
+        });
+    }
+
+    let new_admin = deps.api.addr_validate(&new_admin)?;
+    PENDING_ADMIN.save(deps.storage, &new_admin)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::UpdateAdmin.to_string()),
+        attr("new_admin", &new_admin),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Makes the proposed admin the contract admin
+pub fn accept_admin(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if PENDING_ADMIN.may_load(deps.storage)?.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {
+            error: String::from("Only the proposed admin can accept the admin role"),
+        });
+    }
+
+    PENDING_ADMIN.remove(deps.storage);
+    CONFIG.update(deps.storage, |mut state| -> StdResult<_> {
+        state.admin = Some(info.sender.to_owned());
+        Ok(state)
+    })?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::AcceptAdmin.to_string()),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows the compliance role to cap the amount of any single transfer, or to lift the cap
+pub fn set_max_transfer_amount(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::Compliance)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from(
+                "The compliance role is required to set the maximum transfer amount",
+            ),
+        });
+    }
+
+    Ok(save_max_transfer_amount(deps.storage, amount)?.add_attribute("admin", &info.sender))
+}
+
+/// stores the cap on the amount of any single transfer, or lifts it without an amount
+fn save_max_transfer_amount(
+    storage: &mut dyn Storage,
+    amount: Option<Uint128>,
+) -> StdResult<Response> {
+    match amount {
+        Some(amount) => MAX_TRANSFER_AMOUNT.save(storage, &amount)?,
+        None => MAX_TRANSFER_AMOUNT.remove(storage),
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::SetMaxTransferAmount.to_string()),
+        attr(
+            "amount",
+            amount.map(|amount| amount.to_string()).unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// Sets the most transfers a paged query may return, or restores the default without a limit
+pub fn set_page_limit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::Operator)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The operator role is required to set the page limit"),
+        });
+    }
+
+    match limit {
+        Some(limit) => PAGE_LIMIT.save(deps.storage, &limit)?,
+        None => PAGE_LIMIT.remove(deps.storage),
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::SetPageLimit.to_string()),
+        attr(
+            "limit",
+            limit.map(|limit| limit.to_string()).unwrap_or_default(),
+        ),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows the operator role to drop completed transfers that no longer need to be queried
+pub fn prune_transfer_history(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    older_than: Timestamp,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::Operator)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The operator role is required to prune transfer history"),
+        });
+    }
+
+    let pruned = prune_history(deps.storage, older_than, MAX_BATCH_SIZE)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::PruneHistory.to_string()),
+        attr("older_than", older_than.to_string()),
+        attr("pruned", pruned.to_string()),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows the operator role to move completed transfers out of the history into the archive,
+/// keeping the ranges listing queries scan small. A transfer that is not in the history is
+/// reported in the attributes instead of failing the whole batch.
+pub fn archive_transfers(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfer_ids: Vec<String>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::Operator)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The operator role is required to archive transfers"),
+        });
+    }
+
+    let response = execute_batch(
+        deps,
+        transfer_ids,
+        Action::ArchiveTransfers,
+        |deps, transfer_id| {
+            if !move_to_archive(deps.storage, &transfer_key(&transfer_id)?)? {
+                return Err(ContractError::NotInHistory { id: transfer_id });
+            }
+            Ok(Response::new().add_attribute("id", transfer_id))
+        },
+    )?;
+    Ok(response.add_attribute("admin", &info.sender))
+}
+
+/// Allows the operator role to move transfers still stored under their legacy key, in batches
+/// small enough to fit in a block
+pub fn migrate_transfers(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::Operator)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The operator role is required to migrate transfers"),
+        });
+    }
+
+    let limit = limit.map_or(MAX_BATCH_SIZE, |limit| limit as usize);
+    let (migrated, complete) = migrate_legacy_transfers(deps.storage, limit.min(MAX_BATCH_SIZE))?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::MigrateTransfers.to_string()),
+        attr("migrated", migrated.to_string()),
+        attr("complete", complete.to_string()),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows the operator role to resend the failed settlements of a transfer
+pub fn retry_failed(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::Operator)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The operator role is required to retry failed settlements"),
+        });
+    }
+
+    let key = transfer_key(&id)?;
+    let failed = FAILED_SETTLEMENTS
+        .prefix(&key)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    if failed.is_empty() {
+        return Err(ContractError::NoFailedSettlements { id });
+    }
+
+    let mut msgs = vec![];
+    for (reply_id, settlement) in failed {
+        FAILED_SETTLEMENTS.remove(deps.storage, (&key, reply_id));
+        msgs.push(settlement.msg);
+    }
+    let retried = msgs.len();
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", Action::RetryFailed.to_string()),
+            attr("id", &id),
+            attr("retried", retried.to_string()),
+            attr("admin", &info.sender),
+        ])
+        .add_submessages(settlement_submessages(deps.storage, &id, msgs)?))
+}
+
+/// Removes a batch of transfers completed longer ago than the retention period, and before
+/// `before` when given. Anyone may purge them once the period has passed.
+pub fn purge_settled(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    before: Option<Timestamp>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    let retention_period = RETENTION_PERIOD
+        .may_load(deps.storage)?
+        .ok_or(ContractError::RetentionPeriodNotSet)?;
+    let expired = Timestamp::from_nanos(
+        env.block
+            .time
+            .nanos()
+            .saturating_sub(retention_period.saturating_mul(1_000_000_000)),
+    );
+    let before = before.map_or(expired, |before| before.min(expired));
+
+    let limit = limit.map_or(MAX_BATCH_SIZE, |limit| limit as usize);
+    let purged = prune_history(deps.storage, before, limit.min(MAX_BATCH_SIZE))?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::PurgeSettled.to_string()),
+        attr("before", before.to_string()),
+        attr("purged", purged.to_string()),
+        attr("sender", &info.sender),
+    ]))
+}
+
+/// Allows the operator role to set how long completed transfers are kept before they may be
+/// purged
+pub fn set_retention_period(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    seconds: Option<u64>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::Operator)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The operator role is required to set the retention period"),
+        });
+    }
+
+    match seconds {
+        Some(seconds) => RETENTION_PERIOD.save(deps.storage, &seconds)?,
+        None => RETENTION_PERIOD.remove(deps.storage),
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::SetRetentionPeriod.to_string()),
+        attr(
+            "seconds",
+            seconds
+                .map(|seconds| seconds.to_string())
+                .unwrap_or_default(),
+        ),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows the operator role to return the balance of a denom the contract holds beyond the
+/// escrow of its transfers, swaps and failed settlements and its accrued fees to an admin of the
+/// denom's marker
+pub fn sweep_orphaned_funds(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: String,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::Operator)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The operator role is required to sweep orphaned funds"),
+        });
+    }
+
+    let reconciliation = get_escrow_reconciliation(deps.as_ref(), &env, denom.to_owned())?;
+    let escrow = reconciliation
+        .transfer_escrow
+        .saturating_add(reconciliation.swap_escrow)
+        .saturating_add(reconciliation.failed_settlement_escrow)
+        .saturating_add(reconciliation.accrued_fees);
+    let orphaned = reconciliation.balance.saturating_sub(escrow);
+    if orphaned.is_zero() {
+        return Err(ContractError::NoOrphanedFunds { denom });
+    }
+
+    let marker = get_marker_by_denom(denom.to_owned(), &MarkerQuerier::new(&deps.querier))?;
+    let marker_admin =
+        marker_admin(marker).ok_or_else(|| StdError::generic_err("no admin found for marker"))?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", Action::SweepOrphanedFunds.to_string()),
+            attr("denom", &denom),
+            attr("amount", orphaned.to_string()),
+            attr("recipient", &marker_admin),
+            attr("admin", &info.sender),
+        ])
+        .add_message(transfer_marker_coins(
+            orphaned,
+            &denom,
+            &marker_admin,
+            &env.contract.address,
+            &env.contract.address,
+        )))
+}
+
+/// Allows the operator role to cap how many transfers each sender may have pending at once
+pub fn set_pending_limit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::Operator)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The operator role is required to set the pending limit"),
+        });
+    }
+
+    Ok(save_pending_limit(deps.storage, limit)?.add_attribute("admin", &info.sender))
+}
+
+/// stores the most transfers a sender may have pending, or removes the cap without a limit
+fn save_pending_limit(storage: &mut dyn Storage, limit: Option<u32>) -> StdResult<Response> {
+    match limit {
+        Some(limit) => PENDING_LIMIT.save(storage, &limit)?,
+        None => PENDING_LIMIT.remove(storage),
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::SetPendingLimit.to_string()),
+        attr(
+            "limit",
+            limit.map(|limit| limit.to_string()).unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// Allows the operator role to cap how many transfers each sender may create within a number of
+/// blocks, or to remove the cap
+pub fn set_creation_rate_limit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    limit: Option<CreationRateLimit>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::Operator)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The operator role is required to set the creation rate limit"),
+        });
+    }
+
+    match &limit {
+        Some(limit) => CREATION_RATE_LIMIT.save(deps.storage, limit)?,
+        None => CREATION_RATE_LIMIT.remove(deps.storage),
+    }
+
+    let optional = |value: Option<String>| value.unwrap_or_default();
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::SetCreationRateLimit.to_string()),
+        attr(
+            "max_creations",
+            optional(limit.as_ref().map(|limit| limit.max_creations.to_string())),
+        ),
+        attr(
+            "blocks",
+            optional(limit.as_ref().map(|limit| limit.blocks.to_string())),
+        ),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows the fee manager to deduct a fee from every transfer as it settles, or to stop charging
+/// one
+pub fn set_settlement_fee(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    fee: Option<SettlementFee>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::FeeManager)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The fee manager role is required to set the settlement fee"),
+        });
+    }
+
+    match &fee {
+        Some(fee) => {
+            if let FeeDestination::Collector { address } = &fee.destination {
+                deps.api.addr_validate(address.as_str())?;
+            }
+            SETTLEMENT_FEE.save(deps.storage, fee)?
+        }
+        None => SETTLEMENT_FEE.remove(deps.storage),
+    }
+
+    let optional = |value: Option<String>| value.unwrap_or_default();
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::SetSettlementFee.to_string()),
+        attr("bps", optional(fee.as_ref().map(|fee| fee.bps.to_string()))),
+        attr(
+            "destination",
+            optional(fee.map(|fee| match fee.destination {
+                FeeDestination::Collector { address } => address.to_string(),
+                FeeDestination::MarkerAdmin {} => String::from("marker_admin"),
+                FeeDestination::Accrue {} => String::from("accrue"),
+            })),
+        ),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows the fee manager to pay out the fees of a denom the contract accrued
+pub fn withdraw_fees(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: String,
+    to: String,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::FeeManager)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The fee manager role is required to withdraw fees"),
+        });
+    }
+
+    let to = deps.api.addr_validate(&to)?;
+    let amount = ACCRUED_FEES
+        .may_load(deps.storage, &denom)?
+        .filter(|amount| !amount.is_zero())
+        .ok_or_else(|| ContractError::NoFeesAccrued {
+            denom: denom.to_owned(),
+        })?;
+    ACCRUED_FEES.remove(deps.storage, &denom);
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", Action::WithdrawFees.to_string()),
+            attr("denom", &denom),
+            attr("amount", amount.to_string()),
+            attr("to", &to),
+            attr("admin", &info.sender),
+        ])
+        .add_message(transfer_marker_coins(
+            amount,
+            &denom,
+            &to,
+            &env.contract.address,
+            &env.contract.address,
+        )))
+}
+
+/// Allows the fee manager to require a deposit with each transfer created, or to stop requiring
+/// one. Deposits already held are released on the terms they were made
+pub fn set_creation_deposit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    deposit: Option<CreationDeposit>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::FeeManager)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The fee manager role is required to set the creation deposit"),
+        });
+    }
+
+    match &deposit {
+        Some(deposit) => {
+            deps.api.addr_validate(deposit.collector.as_str())?;
+            CREATION_DEPOSIT.save(deps.storage, deposit)?
+        }
+        None => CREATION_DEPOSIT.remove(deps.storage),
+    }
+
+    let optional = |value: Option<String>| value.unwrap_or_default();
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::SetCreationDeposit.to_string()),
+        attr(
+            "deposit",
+            optional(deposit.as_ref().map(|deposit| deposit.amount.to_string())),
+        ),
+        attr(
+            "collector",
+            optional(deposit.map(|deposit| deposit.collector.to_string())),
+        ),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows the fee manager to collect msg fees in another denom, or to return to nhash. Any denom
+/// other than nhash must be an active unrestricted coin marker, so the contract can hold and
+/// forward it
+pub fn set_fee_denom(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: Option<String>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::FeeManager)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The fee manager role is required to set the fee denom"),
+        });
+    }
+
+    match denom.filter(|denom| denom != DEFAULT_FEE_DENOM) {
+        Some(denom) => {
+            let invalid = || ContractError::InvalidFeeDenom {
+                denom: denom.to_owned(),
+            };
+            let querier = MarkerQuerier::new(&deps.querier);
+            let marker = get_marker_by_denom(denom.to_owned(), &querier).map_err(|_| invalid())?;
+            if MarkerType::from_repr(marker.marker_type) != Some(MarkerType::Coin)
+                || MarkerStatus::from_repr(marker.status) != Some(MarkerStatus::Active)
+            {
+                return Err(invalid());
+            }
+            FEE_DENOM.save(deps.storage, &denom)?
+        }
+        None => FEE_DENOM.remove(deps.storage),
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::SetFeeDenom.to_string()),
+        attr("denom", fee_denom(deps.storage)?),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows the fee manager to levy fees on creating and approving transfers through the msg-fees
+/// module, or to stop levying them
+pub fn set_msg_fees(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    fees: Option<MsgFees>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::FeeManager)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The fee manager role is required to set msg fees"),
+        });
+    }
+
+    match &fees {
+        Some(fees) => {
+            if let Some(recipient) = &fees.recipient {
+                deps.api.addr_validate(recipient.as_str())?;
+            }
+            MSG_FEES.save(deps.storage, fees)?
+        }
+        None => MSG_FEES.remove(deps.storage),
+    }
+
+    let fee = |fee: Option<Uint128>| fee.map(|fee| fee.to_string()).unwrap_or_default();
+    let fees = fees.as_ref();
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::SetMsgFees.to_string()),
+        attr("creation_fee", fee(fees.and_then(|fees| fees.creation_fee))),
+        attr("approval_fee", fee(fees.and_then(|fees| fees.approval_fee))),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows the fee manager or the marker's admin to charge transfers of a denom by a fee schedule
+/// of their own, or to return them to the settlement fee's rate
+pub fn set_denom_fee_schedule(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: String,
+    schedule: Option<FeeSchedule>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    let querier = MarkerQuerier::new(&deps.querier);
+    let marker = get_marker_by_denom(denom.clone(), &querier)?;
+
+    if !has_marker_access_admin(info.sender.to_owned(), marker)
+        && !has_role(deps.as_ref(), &env, &info.sender, Role::FeeManager)?
+    {
+        return Err(ContractError::Unauthorized {
+            error: String::from(
+                "The fee manager role or ACCESS_ADMIN permission is required to set fee schedules",
+            ),
+        });
+    }
+
+    let kind = match &schedule {
+        Some(FeeSchedule::Flat { .. }) => "flat",
+        Some(FeeSchedule::Bps { .. }) => "bps",
+        Some(FeeSchedule::Tiered { .. }) => "tiered",
+        None => "",
+    };
+    match schedule {
+        Some(schedule) => DENOM_FEE_SCHEDULES.save(deps.storage, &denom, &schedule)?,
+        None => DENOM_FEE_SCHEDULES.remove(deps.storage, &denom),
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::SetDenomFeeSchedule.to_string()),
+        attr("denom", &denom),
+        attr("schedule", kind),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows the operator role to queue a pending transfer ahead of those waiting for approval,
+/// or to put it back in creation order
+pub fn set_transfer_priority(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfer_id: String,
+    priority: bool,
+) -> Result<Response, ContractError> {
+    let mut transfer = load_transfer(deps.storage, &transfer_id)
+        .map_err(|error| ContractError::LoadTransferFailed { error })?;
+
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::Operator)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The operator role is required to set the transfer priority"),
+        });
+    }
+
+    if transfer.status != TransferStatus::Pending {
+        return Err(ContractError::TransferNotPending {
+            status: transfer.status,
+        });
+    }
+
+    transfer.priority = priority;
+    transfer.updated_at = Some(env.block.time);
+    save_transfer(deps.storage, env.block.height, &transfer)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::SetTransferPriority.to_string()),
+        attr("id", &transfer.id),
+        attr("priority", priority.to_string()),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows a compliance address to block a transfer at any point before its coins leave escrow
+pub fn veto_transfer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfer_id: String,
+    reason: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut transfer = load_transfer(deps.storage, &transfer_id)
+        .map_err(|error| ContractError::LoadTransferFailed { error })?;
+
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    let mut is_veto_address = GLOBAL_VETO.may_load(deps.storage)?.as_ref() == Some(&info.sender);
+    for coin in transfer.coins() {
+        is_veto_address |=
+            DENOM_VETO.may_load(deps.storage, &coin.denom)?.as_ref() == Some(&info.sender);
+    }
+
+    if !is_veto_address {
+        return Err(ContractError::Unauthorized {
+            error: String::from("Only a veto address can veto transfers"),
+        });
+    }
+
+    transfer.transition(TransferStatus::Rejected)?;
+    transfer.rejection_reason = reason;
+
+    let mut response = Response::new().add_attributes(vec![
+        attr("action", Action::Veto.to_string()),
+        attr("id", &transfer.id),
+        attr("denom", &transfer.denom),
+        attr("amount", transfer.amount.to_string()),
+        attr("sender", &transfer.sender),
+        attr("recipient", &transfer.recipient),
+        attr("veto_address", &info.sender),
+    ]);
+
+    if let Some(reason) = &transfer.rejection_reason {
+        response = response.add_attribute("reason", reason);
+    }
+
+    response = response.add_submessages(settlement_submessages(
+        deps.storage,
+        &transfer.id,
+        refund_messages(&transfer, &env.contract.address),
+    )?);
+    response = response.add_messages(release_held_funds(deps.storage, &transfer, true)?);
+
+    transfer.updated_at = Some(env.block.time);
+    save_transfer(deps.storage, env.block.height, &transfer)?;
+
+    Ok(response)
+}
+
+/// Stores a schedule that creates a pending transfer of the same terms once per interval. Coins
+/// are escrowed from the sender as each occurrence is created.
+#[allow(clippy::too_many_arguments)]
+pub fn create_recurring_transfer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+    denom: String,
+    amount: Uint128,
+    recipient: String,
+    interval: ScheduleInterval,
+    occurrences: u32,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    let querier = MarkerQuerier::new(&deps.querier);
+    if !is_restricted_marker(deps.storage, &querier, &denom) {
+        return Err(ContractError::UnsupportedMarkerType);
+    }
+
+    if RECURRING_TRANSFER_STORAGE.has(deps.storage, &transfer_key(&id)?) {
+        return Err(ContractError::InvalidFields {
+            fields: vec![String::from("id")],
+        });
+    }
+
+    let recurring_transfer = RecurringTransfer {
+        id,
+        sender: info.sender.to_owned(),
+        denom,
+        amount,
+        recipient: deps.api.addr_validate(&recipient)?,
+        interval,
+        occurrences,
+        executed: 0,
+        start_height: env.block.height,
+        start_time: env.block.time,
+    };
+
+    RECURRING_TRANSFER_STORAGE.save(
+        deps.storage,
+        &transfer_key(&recurring_transfer.id)?,
+        &recurring_transfer,
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::CreateRecurringTransfer.to_string()),
+        attr("id", &recurring_transfer.id),
+        attr("denom", &recurring_transfer.denom),
+        attr("amount", recurring_transfer.amount.to_string()),
+        attr("sender", &recurring_transfer.sender),
+        attr("recipient", &recurring_transfer.recipient),
+        attr("occurrences", recurring_transfer.occurrences.to_string()),
+    ]))
+}
+
+/// Creates the next pending transfer of a recurring transfer once it is due, checked as any new
+/// transfer of its sender is. Anyone may call this, the coins are escrowed from the schedule's
+/// sender while a deposit or incentive is paid by the caller.
+pub fn execute_scheduled(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recurring_transfer_id: String,
+) -> Result<Response, ContractError> {
+    let key = transfer_key(&recurring_transfer_id)?;
+    let mut recurring_transfer = RECURRING_TRANSFER_STORAGE
+        .load(deps.storage, &key)
+        .map_err(|error| ContractError::LoadRecurringTransferFailed { error })?;
+
+    if !recurring_transfer.is_due(&env.block) {
+        return Err(ContractError::ScheduleNotDue);
+    }
+
+    recurring_transfer.executed += 1;
+
+    let (deposit, incentive) = take_funds(deps.storage, &info, 1)?;
+    let mut transfer = new_transfer(
+        deps.branch(),
+        &env,
+        &recurring_transfer.sender,
+        unconditional_transfer(
+            derived_transfer_id("recurring_transfer", &key, recurring_transfer.executed),
+            &recurring_transfer.denom,
+            recurring_transfer.amount,
+            &recurring_transfer.recipient,
+        ),
+    )?;
+
+    hold_funds(deps.storage, &transfer, &deposit, &incentive)?;
+    let approval = approve_new_transfer(deps.branch(), &env, &mut transfer)?;
+    save_transfer(deps.storage, env.block.height, &transfer)?;
+    RECURRING_TRANSFER_STORAGE.save(deps.storage, &key, &recurring_transfer)?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", Action::ExecuteScheduled.to_string()),
+            attr("id", &recurring_transfer.id),
+            attr("transfer_id", &transfer.id),
+            attr("occurrence", recurring_transfer.executed.to_string()),
+            attr("denom", &transfer.denom),
+            attr("amount", transfer.amount.to_string()),
+            attr("sender", &transfer.sender),
+            attr("recipient", &transfer.recipient),
+        ])
+        .add_attributes(
+            transfer
+                .sequence
+                .map(|sequence| attr("sequence", sequence.to_string())),
+        )
+        .add_attributes(approval.attributes)
+        .add_messages(assess_msg_fee(
+            deps.storage,
+            &env,
+            &info.sender,
+            "transfer_creation",
+            |fees| fees.creation_fee,
+            1,
+        )?)
+        .add_submessage(escrow_submessage(transfer_marker_coins(
+            transfer.amount,
+            &transfer.denom,
+            &env.contract.address,
+            &transfer.sender,
+            &env.contract.address,
+        )))
+        .add_submessages(approval.messages)
+        .add_events(approval.events))
+}
+
+/// the input of a plain transfer of the amount to the recipient, as the occurrences of a
+/// recurring transfer and the transfers pulled from an allowance are
+fn unconditional_transfer(
+    id: String,
+    denom: &str,
+    amount: Uint128,
+    recipient: &Addr,
+) -> TransferInput {
+    TransferInput {
+        id,
+        denom: denom.to_owned(),
+        amount,
+        recipient: recipient.to_string(),
+        require_acceptance: None,
+        approvers: None,
+        recipients: None,
+        additional_coins: None,
+        vesting_duration: None,
+        memo: None,
+        tags: None,
+    }
+}
+
+/// Stores terms the sender can create transfers from, replacing their template of the same id
+pub fn save_template(
+    deps: DepsMut,
+    info: MessageInfo,
+    template_id: String,
+    denom: String,
+    recipient: String,
+    amount: Option<Uint128>,
+    memo: Option<String>,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    let querier = MarkerQuerier::new(&deps.querier);
+    if !is_restricted_marker(deps.storage, &querier, &denom) {
+        return Err(ContractError::UnsupportedMarkerType);
+    }
+
+    let template = TransferTemplate {
+        id: template_id,
+        sender: info.sender.to_owned(),
+        denom,
+        recipient: deps.api.addr_validate(&recipient)?,
+        amount,
+        memo,
+    };
+    TRANSFER_TEMPLATES.save(deps.storage, (&template.sender, &template.id), &template)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::SaveTemplate.to_string()),
+        attr("template_id", &template.id),
+        attr("denom", &template.denom),
+        attr("sender", &template.sender),
+        attr("recipient", &template.recipient),
+    ]))
+}
+
+/// Removes one of the sender's templates, transfers already created from it are unaffected
+pub fn delete_template(
+    deps: DepsMut,
+    info: MessageInfo,
+    template_id: String,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    TRANSFER_TEMPLATES
+        .load(deps.storage, (&info.sender, &template_id))
+        .map_err(|error| ContractError::LoadTemplateFailed { error })?;
+    TRANSFER_TEMPLATES.remove(deps.storage, (&info.sender, &template_id));
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::DeleteTemplate.to_string()),
+        attr("template_id", template_id),
+        attr("sender", &info.sender),
+    ]))
+}
+
+/// Creates a transfer on the terms of one of the sender's templates, escrowing it like
+/// `Transfer`. The amount given replaces the template's default one.
+pub fn create_from_template(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    template_id: String,
+    id: String,
+    amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let template = TRANSFER_TEMPLATES
+        .load(deps.storage, (&info.sender, &template_id))
+        .map_err(|error| ContractError::LoadTemplateFailed { error })?;
+
+    let amount = amount
+        .or(template.amount)
+        .ok_or_else(|| ContractError::InvalidFields {
+            fields: vec![String::from("amount")],
+        })?;
+
+    let input = TransferInput {
+        id,
+        denom: template.denom,
+        amount,
+        recipient: template.recipient.into_string(),
+        require_acceptance: None,
+        approvers: None,
+        recipients: None,
+        additional_coins: None,
+        vesting_duration: None,
+        memo: template.memo,
+        tags: None,
+    };
+
+    Ok(create_transfer(deps, env, info, input)?.add_attribute("template_id", template_id))
+}
+
+/// Allows a holder of transfer permission on the marker to approve the transfers of a route in
+/// advance, up to a cumulative amount and until the approval expires
+#[allow(clippy::too_many_arguments)]
+pub fn set_standing_approval(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: String,
+    recipient: String,
+    denom: String,
+    max_amount: Uint128,
+    expires: Timestamp,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    let querier = MarkerQuerier::new(&deps.querier);
+    let marker = get_marker_by_denom(denom.to_owned(), &querier)?;
+    if !has_marker_access_transfer(info.sender.to_owned(), marker) {
+        return Err(ContractError::Unauthorized {
+            error: String::from("ACCESS_TRANSFER permission is required to set standing approvals"),
+        });
+    }
+
+    if expires <= env.block.time {
+        return Err(ContractError::InvalidFields {
+            fields: vec![String::from("expires")],
+        });
+    }
+
+    let standing_approval = StandingApproval {
+        approver: info.sender.to_owned(),
+        sender: deps.api.addr_validate(&sender)?,
+        recipient: deps.api.addr_validate(&recipient)?,
+        denom,
+        max_amount,
+        used: Uint128::zero(),
+        expires,
+    };
+    STANDING_APPROVALS.save(
+        deps.storage,
+        (
+            &standing_approval.denom,
+            &standing_approval.sender,
+            &standing_approval.recipient,
+        ),
+        &standing_approval,
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::SetStandingApproval.to_string()),
+        attr("denom", &standing_approval.denom),
+        attr("sender", &standing_approval.sender),
+        attr("recipient", &standing_approval.recipient),
+        attr("max_amount", max_amount.to_string()),
+        attr("expires", expires.to_string()),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows a holder of transfer permission on the marker to remove the standing approval of a
+/// route, whoever set it
+pub fn revoke_standing_approval(
+    deps: DepsMut,
+    info: MessageInfo,
+    sender: String,
+    recipient: String,
+    denom: String,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    let querier = MarkerQuerier::new(&deps.querier);
+    let marker = get_marker_by_denom(denom.to_owned(), &querier)?;
+    if !has_marker_access_transfer(info.sender.to_owned(), marker) {
+        return Err(ContractError::Unauthorized {
+            error: String::from(
+                "ACCESS_TRANSFER permission is required to revoke standing approvals",
+            ),
+        });
+    }
+
+    let sender = deps.api.addr_validate(&sender)?;
+    let recipient = deps.api.addr_validate(&recipient)?;
+    STANDING_APPROVALS.load(deps.storage, (&denom, &sender, &recipient))?;
+    STANDING_APPROVALS.remove(deps.storage, (&denom, &sender, &recipient));
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::RevokeStandingApproval.to_string()),
+        attr("denom", denom),
+        attr("sender", sender),
+        attr("recipient", recipient),
+        attr("admin", &info.sender),
+    ]))
+}
+
+/// Allows the recipient to pull transfers of the denom from the sender, up to the amount in total
+pub fn grant_allowance(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: String,
+    recipient: String,
+    denom: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    let querier = MarkerQuerier::new(&deps.querier);
+    if !is_restricted_marker(deps.storage, &querier, &denom) {
+        return Err(ContractError::UnsupportedMarkerType);
+    }
+
+    if ALLOWANCE_STORAGE.has(deps.storage, &transfer_key(&id)?) {
+        return Err(ContractError::InvalidFields {
+            fields: vec![String::from("id")],
+        });
+    }
+
+    let allowance = Allowance {
+        id,
+        sender: info.sender.to_owned(),
+        recipient: deps.api.addr_validate(&recipient)?,
+        denom,
+        amount,
+        pulled: Uint128::zero(),
+        pulls: 0,
+    };
+    ALLOWANCE_STORAGE.save(deps.storage, &transfer_key(&allowance.id)?, &allowance)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::GrantAllowance.to_string()),
+        attr("id", &allowance.id),
+        attr("denom", &allowance.denom),
+        attr("amount", allowance.amount.to_string()),
+        attr("sender", &allowance.sender),
+        attr("recipient", &allowance.recipient),
+    ]))
+}
+
+/// Allows the sender of an allowance to end it
+pub fn revoke_allowance(
+    deps: DepsMut,
+    info: MessageInfo,
+    allowance_id: String,
+) -> Result<Response, ContractError> {
+    let key = transfer_key(&allowance_id)?;
+    let allowance = ALLOWANCE_STORAGE
+        .load(deps.storage, &key)
+        .map_err(|error| ContractError::LoadAllowanceFailed { error })?;
+
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if info.sender != allowance.sender {
+        return Err(ContractError::Unauthorized {
+            error: String::from("Only the sender can revoke an allowance"),
+        });
+    }
+
+    ALLOWANCE_STORAGE.remove(deps.storage, &key);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::RevokeAllowance.to_string()),
+        attr("id", &allowance.id),
+        attr("sender", &allowance.sender),
+        attr("pulled", allowance.pulled.to_string()),
+    ]))
+}
+
+/// Allows the recipient of an allowance to create a pending transfer to themselves from its
+/// sender, escrowing the coins from the sender. The transfer is checked and approved as any new
+/// transfer of the sender is, a deposit or incentive is paid by the recipient.
+pub fn pull_transfer(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    allowance_id: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let key = transfer_key(&allowance_id)?;
+    let mut allowance = ALLOWANCE_STORAGE
+        .load(deps.storage, &key)
+        .map_err(|error| ContractError::LoadAllowanceFailed { error })?;
+
+    if info.sender != allowance.recipient {
+        return Err(ContractError::Unauthorized {
+            error: String::from("Only the recipient can pull from an allowance"),
+        });
+    }
+
+    let remaining = allowance.amount - allowance.pulled;
+    if amount > remaining {
+        return Err(ContractError::AllowanceExceeded { remaining });
+    }
+
+    allowance.pulled += amount;
+    allowance.pulls += 1;
+
+    let (deposit, incentive) = take_funds(deps.storage, &info, 1)?;
+    let mut transfer = new_transfer(
+        deps.branch(),
+        &env,
+        &allowance.sender,
+        unconditional_transfer(
+            derived_transfer_id("allowance", &key, allowance.pulls),
+            &allowance.denom,
+            amount,
+            &allowance.recipient,
+        ),
+    )?;
+
+    hold_funds(deps.storage, &transfer, &deposit, &incentive)?;
+    let approval = approve_new_transfer(deps.branch(), &env, &mut transfer)?;
+    save_transfer(deps.storage, env.block.height, &transfer)?;
+    ALLOWANCE_STORAGE.save(deps.storage, &key, &allowance)?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", Action::PullTransfer.to_string()),
+            attr("id", &allowance.id),
+            attr("transfer_id", &transfer.id),
+            attr("denom", &transfer.denom),
+            attr("amount", transfer.amount.to_string()),
+            attr("sender", &transfer.sender),
+            attr("recipient", &transfer.recipient),
+            attr(
+                "remaining",
+                (allowance.amount - allowance.pulled).to_string(),
+            ),
+        ])
+        .add_attributes(approval.attributes)
+        .add_messages(assess_msg_fee(
+            deps.storage,
+            &env,
+            &info.sender,
+            "transfer_creation",
+            |fees| fees.creation_fee,
+            1,
+        )?)
+        .add_submessage(escrow_submessage(transfer_marker_coins(
+            transfer.amount,
+            &transfer.denom,
+            &env.contract.address,
+            &transfer.sender,
+            &env.contract.address,
+        )))
+        .add_submessages(approval.messages)
+        .add_events(approval.events))
+}
+
+/// Opens a delivery-versus-payment swap, escrowing the initiator's leg until the counterparty
+/// escrows theirs
+#[allow(clippy::too_many_arguments)]
+pub fn propose_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+    denom: String,
+    amount: Uint128,
+    counterparty: String,
+    counterparty_denom: String,
+    counterparty_amount: Uint128,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    let counterparty = deps.api.addr_validate(&counterparty)?;
+    if counterparty == info.sender {
+        return Err(ContractError::InvalidFields {
+            fields: vec![String::from("counterparty")],
+        });
+    }
+
+    let querier = MarkerQuerier::new(&deps.querier);
+    for denom in [&denom, &counterparty_denom] {
+        if !is_restricted_marker(deps.storage, &querier, denom) {
+            return Err(ContractError::UnsupportedMarkerType);
+        }
+        check_denom_registered(deps.storage, denom)?;
+        check_denom_not_frozen(deps.storage, denom)?;
+    }
+
+    if SWAP_STORAGE
+        .may_load(deps.storage, id.as_bytes())?
+        .is_some()
+    {
+        return Err(ContractError::InvalidFields {
+            fields: vec![String::from("id")],
+        });
+    }
+
+    let swap = Swap {
+        id,
+        initiator: SwapLeg {
+            owner: info.sender.to_owned(),
+            denom,
+            amount,
+            approval: None,
+        },
+        counterparty: SwapLeg {
+            owner: counterparty,
+            denom: counterparty_denom,
+            amount: counterparty_amount,
+            approval: None,
+        },
+        status: SwapStatus::Proposed,
+        rejection_reason: None,
+    };
+
+    if let Some(address) = blocked_swap_party(deps.storage, &swap) {
+        return Err(ContractError::AddressBlocked {
+            address: address.to_string(),
+        });
+    }
+
+    SWAP_STORAGE.save(deps.storage, swap.id.as_bytes(), &swap)?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", Action::ProposeSwap.to_string()),
+            attr("id", &swap.id),
+            attr("denom", &swap.initiator.denom),
+            attr("amount", swap.initiator.amount.to_string()),
+            attr("counterparty_denom", &swap.counterparty.denom),
+            attr("counterparty_amount", swap.counterparty.amount.to_string()),
+            attr("sender", &swap.initiator.owner),
+            attr("counterparty", &swap.counterparty.owner),
+        ])
+        .add_submessage(escrow_submessage(transfer_marker_coins(
+            swap.initiator.amount,
+            &swap.initiator.denom,
+            &env.contract.address,
+            &swap.initiator.owner,
+            &env.contract.address,
+        ))))
+}
+
+/// Allows the counterparty of a proposed swap to escrow their leg
+pub fn fund_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    swap_id: String,
+) -> Result<Response, ContractError> {
+    let mut swap = SWAP_STORAGE
+        .load(deps.storage, swap_id.as_bytes())
+        .map_err(|error| ContractError::LoadSwapFailed { error })?;
+
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !info.sender.eq(&swap.counterparty.owner) {
+        return Err(ContractError::Unauthorized {
+            error: String::from("Only the counterparty can fund a swap"),
+        });
+    }
+
+    if let Some(address) = blocked_swap_party(deps.storage, &swap) {
+        return Err(ContractError::AddressBlocked {
+            address: address.to_string(),
+        });
+    }
+
+    swap.transition(SwapStatus::Funded)?;
+
+    let mut response = Response::new()
+        .add_attributes(vec![
+            attr("action", Action::FundSwap.to_string()),
+            attr("id", &swap.id),
+            attr("denom", &swap.counterparty.denom),
+            attr("amount", swap.counterparty.amount.to_string()),
+            attr("counterparty", &swap.counterparty.owner),
+        ])
+        .add_submessage(escrow_submessage(transfer_marker_coins(
+            swap.counterparty.amount,
+            &swap.counterparty.denom,
+            &env.contract.address,
+            &swap.counterparty.owner,
+            &env.contract.address,
+        )));
+
+    // both legs may already have been approved while the swap was proposed
+    if swap.is_ready_to_settle() {
+        response = settle_swap(deps.storage, &mut swap, response, &env.contract.address)?;
+    }
+
+    SWAP_STORAGE.save(deps.storage, swap_id.as_bytes(), &swap)?;
+
+    Ok(response)
+}
+
+/// Approves every leg of a swap the sender holds marker transfer permission for. The swap
+/// settles as soon as both legs are escrowed and approved.
+pub fn approve_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    swap_id: String,
+) -> Result<Response, ContractError> {
+    let mut swap = SWAP_STORAGE
+        .load(deps.storage, swap_id.as_bytes())
+        .map_err(|error| ContractError::LoadSwapFailed { error })?;
+
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !swap.status.is_open() {
+        return Err(ContractError::InvalidSwapStatusTransition {
+            from: swap.status,
+            to: SwapStatus::Settled,
+        });
+    }
+
+    for leg in [&swap.initiator, &swap.counterparty] {
+        check_denom_not_frozen(deps.storage, &leg.denom)?;
+    }
+
+    let querier = MarkerQuerier::new(&deps.querier);
+    let mut has_access = false;
+    let mut approved_denoms = vec![];
+
+    for leg in [&mut swap.initiator, &mut swap.counterparty] {
+        let marker = get_marker_by_denom(leg.denom.clone(), &querier)?;
+        if !has_marker_access_transfer(info.sender.to_owned(), marker) {
+            continue;
+        }
+        has_access = true;
+
+        if leg.approval.is_none() {
+            leg.approval = Some(Approval {
+                approver: info.sender.to_owned(),
+                height: env.block.height,
+                time: env.block.time,
+                amount: Some(leg.amount),
+            });
+            approved_denoms.push(leg.denom.clone());
+        }
+    }
+
+    if !has_access {
+        return Err(ContractError::Unauthorized {
+            error: String::from("ACCESS_TRANSFER permission is required to approve swaps"),
+        });
+    }
+
+    if approved_denoms.is_empty() {
+        return Err(ContractError::DuplicateApproval {
+            approver: info.sender.to_string(),
+        });
+    }
+
+    let mut response = Response::new().add_attributes(vec![
+        attr("action", Action::ApproveSwap.to_string()),
+        attr("id", &swap.id),
+        attr("approved_denoms", approved_denoms.join(",")),
+        attr("admin", &info.sender),
+        attr("approved_height", env.block.height.to_string()),
+        attr("approved_time", env.block.time.to_string()),
+    ]);
+
+    if swap.is_ready_to_settle() {
+        response = settle_swap(deps.storage, &mut swap, response, &env.contract.address)?;
+    }
+
+    SWAP_STORAGE.save(deps.storage, swap_id.as_bytes(), &swap)?;
+
+    Ok(response)
+}
+
+/// Allows either party to call off a swap before it settles, refunding every escrowed leg
+pub fn cancel_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    swap_id: String,
+) -> Result<Response, ContractError> {
+    let mut swap = SWAP_STORAGE
+        .load(deps.storage, swap_id.as_bytes())
+        .map_err(|error| ContractError::LoadSwapFailed { error })?;
+
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !info.sender.eq(&swap.initiator.owner) && !info.sender.eq(&swap.counterparty.owner) {
+        return Err(ContractError::Unauthorized {
+            error: String::from("Only a party to the swap can cancel"),
+        });
+    }
+
+    let refunds = swap_refund_messages(&swap, &env.contract.address);
+    swap.transition(SwapStatus::Cancelled)?;
+
+    SWAP_STORAGE.save(deps.storage, swap_id.as_bytes(), &swap)?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", Action::CancelSwap.to_string()),
+            attr("id", &swap.id),
+            attr("sender", &info.sender),
+        ])
+        .add_messages(refunds))
+}
+
+/// Allows an account with marker transfer permission for either leg to block a swap before it
+/// settles, refunding every escrowed leg
+pub fn reject_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    swap_id: String,
+    reason: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut swap = SWAP_STORAGE
+        .load(deps.storage, swap_id.as_bytes())
+        .map_err(|error| ContractError::LoadSwapFailed { error })?;
+
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    let querier = MarkerQuerier::new(&deps.querier);
+    let mut has_access = false;
+    for denom in [&swap.initiator.denom, &swap.counterparty.denom] {
+        let marker = get_marker_by_denom(denom.to_owned(), &querier)?;
+        has_access |= has_marker_access_transfer(info.sender.to_owned(), marker);
+    }
+
+    if !has_access {
+        return Err(ContractError::Unauthorized {
+            error: String::from("ACCESS_TRANSFER permission is required to reject swaps"),
+        });
+    }
+
+    let refunds = swap_refund_messages(&swap, &env.contract.address);
+    swap.transition(SwapStatus::Rejected)?;
+    swap.rejection_reason = reason;
+
+    let mut response = Response::new().add_attributes(vec![
+        attr("action", Action::RejectSwap.to_string()),
+        attr("id", &swap.id),
+        attr("admin", &info.sender),
+    ]);
+
+    if let Some(reason) = &swap.rejection_reason {
+        response = response.add_attribute("reason", reason);
+    }
+
+    SWAP_STORAGE.save(deps.storage, swap_id.as_bytes(), &swap)?;
+
+    Ok(response.add_messages(refunds))
+}
+
+/// delivers each escrowed leg of a swap to the other party. A swap whose party has been blocked
+/// since it was proposed is rejected and both legs refunded instead, and the refusal succeeds so
+/// its event is kept.
+fn settle_swap(
+    storage: &dyn Storage,
+    swap: &mut Swap,
+    response: Response,
+    contract_address: &Addr,
+) -> Result<Response, ContractError> {
+    if let Some(address) = blocked_swap_party(storage, swap).cloned() {
+        let refunds = swap_refund_messages(swap, contract_address);
+        swap.transition(SwapStatus::Rejected)?;
+        swap.rejection_reason = Some(format!("{} is blocked", address));
+
+        return Ok(response
+            .add_event(
+                Event::new("blocked_party_refused")
+                    .add_attribute("id", &swap.id)
+                    .add_attribute("address", &address),
+            )
+            .add_messages(refunds));
+    }
+
+    swap.transition(SwapStatus::Settled)?;
+
+    Ok(response
+        .add_attribute("settled", "true")
+        .add_message(transfer_marker_coins(
+            swap.initiator.amount,
+            &swap.initiator.denom,
+            &swap.counterparty.owner,
+            contract_address,
+            contract_address,
+        ))
+        .add_message(transfer_marker_coins(
+            swap.counterparty.amount,
+            &swap.counterparty.denom,
+            &swap.initiator.owner,
+            contract_address,
+            contract_address,
+        )))
+}
+
+/// builds the marker transfers returning each escrowed leg of a swap to its owner
+fn swap_refund_messages(swap: &Swap, contract_address: &Addr) -> Vec<MsgTransferRequest> {
+    let mut legs = vec![&swap.initiator];
+    if swap.status == SwapStatus::Funded {
+        legs.push(&swap.counterparty);
+    }
+
+    legs.into_iter()
+        .map(|leg| {
+            transfer_marker_coins(
+                leg.amount,
+                &leg.denom,
+                &leg.owner,
+                contract_address,
+                contract_address,
+            )
+        })
+        .collect()
+}
+
+/// returns true if the sender has marker transfer permissions for every denom of the transfer
+fn has_transfer_access_for_all(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    sender: &Addr,
+    transfer: &Transfer,
+    querier: &MarkerQuerier<Empty>,
+) -> StdResult<bool> {
+    for coin in transfer.coins() {
+        if !has_transfer_access(storage, block, sender, &coin.denom, querier)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// returns true if the address may approve transfers of the denom itself or through an active
+/// delegation from an address that may
+fn has_transfer_access(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    address: &Addr,
+    denom: &str,
+    querier: &MarkerQuerier<Empty>,
+) -> StdResult<bool> {
+    if has_own_transfer_access(storage, address, denom, querier)? {
+        return Ok(true);
+    }
+    for item in APPROVAL_DELEGATIONS
+        .prefix(denom)
+        .range(storage, None, None, Order::Ascending)
+    {
+        let (_, delegation) = item?;
+        if delegation.delegate == *address
+            && delegation.is_active(block)
+            && has_own_transfer_access(storage, &delegation.delegator, denom, querier)?
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// returns true if the address is a registered approver of the denom or holds transfer
+/// permission on its marker
+fn has_own_transfer_access(
+    storage: &dyn Storage,
+    address: &Addr,
+    denom: &str,
+    querier: &MarkerQuerier<Empty>,
+) -> StdResult<bool> {
+    if APPROVER_REGISTRY.has(storage, (denom, address)) {
+        return Ok(true);
+    }
+    let marker = get_marker_by_denom(denom.to_owned(), querier)?;
+    Ok(has_marker_access_transfer(address.to_owned(), marker))
+}
+
+/// returns the number of approvals that settle the transfer. Required approvers were checked
+/// against the denom quorum when the transfer was created and must all approve.
+fn required_approvals(storage: &dyn Storage, transfer: &Transfer) -> StdResult<u32> {
+    match transfer.required_approvers.len() {
+        0 => approval_quorum(storage, transfer),
+        required => Ok(required as u32),
+    }
+}
+
+/// returns the number of approvals a transfer needs, the highest quorum among its denoms
+fn approval_quorum(storage: &dyn Storage, transfer: &Transfer) -> StdResult<u32> {
+    let mut quorum = 1;
+    for coin in transfer.coins() {
+        quorum = quorum.max(APPROVAL_QUORUM.may_load(storage, &coin.denom)?.unwrap_or(1));
+    }
+    Ok(quorum)
+}
+
+/// returns true if the sender has marker transfer permissions for the given marker
+fn has_marker_access_transfer(sender: Addr, marker: MarkerAccount) -> bool {
+    has_marker_access(sender, marker, Access::Transfer)
+}
+
+/// returns true if the sender has marker admin permissions for the given marker
+fn has_marker_access_admin(sender: Addr, marker: MarkerAccount) -> bool {
+    has_marker_access(sender, marker, Access::Admin)
+}
+
+/// returns the first address holding admin permission on the marker
+fn marker_admin(marker: MarkerAccount) -> Option<Addr> {
+    let access: i32 = Access::Admin.into();
+    marker
+        .access_control
+        .into_iter()
+        .find(|grant| grant.permissions.contains(&access))
+        .map(|grant| Addr::unchecked(grant.address))
+}
+
+fn has_marker_access(sender: Addr, marker: MarkerAccount, access: Access) -> bool {
+    let access: i32 = access.into();
+    marker
+        .access_control
+        .iter()
+        .any(|grant| grant.address == sender && grant.permissions.contains(&access))
+}
+
+/// Whether the message is refused while the contract is paused. Messages that create, approve or
+/// settle transfers, or move escrowed coins anywhere but back to their sender, are refused.
+/// Cancellations, rejections and the admin's housekeeping are still allowed.
+fn blocked_while_paused(msg: &ExecuteMsg) -> bool {
+    matches!(
+        msg,
+        ExecuteMsg::AcceptTransfer { .. }
+            | ExecuteMsg::ApproveSwap { .. }
+            | ExecuteMsg::ApproveTransfer { .. }
+            | ExecuteMsg::ApproveTransferPartial { .. }
+            | ExecuteMsg::ApproveTransfers { .. }
+            | ExecuteMsg::BatchTransfer { .. }
+            | ExecuteMsg::Claim { .. }
+            | ExecuteMsg::CreateFromTemplate { .. }
+            | ExecuteMsg::CreateRecurringTransfer { .. }
+            | ExecuteMsg::ExecuteScheduled { .. }
+            | ExecuteMsg::FundSwap { .. }
+            | ExecuteMsg::GrantAllowance { .. }
+            | ExecuteMsg::ProposeSwap { .. }
+            | ExecuteMsg::PullTransfer { .. }
+            | ExecuteMsg::RetryFailed { .. }
+            | ExecuteMsg::SetStandingApproval { .. }
+            | ExecuteMsg::SweepOrphanedFunds { .. }
+            | ExecuteMsg::Transfer { .. }
+            | ExecuteMsg::UpdateTransfer { .. }
+    )
+}
+
+/// returns the admin of this contract instance, the wasm admin when the config records none
+fn contract_admin(deps: Deps, env: &Env) -> StdResult<Option<Addr>> {
+    if let Some(admin) = CONFIG.load(deps.storage)?.admin {
+        return Ok(Some(admin));
+    }
+
+    let contract_info = deps
+        .querier
+        .query_wasm_contract_info(env.contract.address.to_owned())?;
+    Ok(contract_info.admin.map(Addr::unchecked))
+}
+
+/// returns true if the address is the admin of this contract instance
+fn is_contract_admin(deps: Deps, env: &Env, address: &Addr) -> StdResult<bool> {
+    Ok(contract_admin(deps, env)?.as_ref() == Some(address))
+}
+
+/// returns true if the address is the contract admin, holds the admin role or holds the role
+fn has_role(deps: Deps, env: &Env, address: &Addr, role: Role) -> StdResult<bool> {
+    Ok(is_contract_admin(deps, env, address)?
+        || holds_role(deps.storage, Role::Admin, address)
+        || holds_role(deps.storage, role, address))
+}
+
+/// Whether the denom is a restricted marker, read from the marker cache when it is there. A
+/// marker found on chain is added to the cache.
+fn is_restricted_marker(
+    storage: &mut dyn Storage,
+    querier: &MarkerQuerier<Empty>,
+    denom: &str,
+) -> bool {
+    let cached = match MARKER_CACHE.may_load(storage, denom) {
+        Ok(Some(cached)) => Some(cached),
+        _ => cache_marker(storage, querier, denom).ok(),
+    };
+    cached.is_some_and(|cached| cached.marker_type == MarkerType::Restricted)
+}
+
+/// Reads the marker of the denom from the chain into the marker cache
+fn cache_marker(
+    storage: &mut dyn Storage,
+    querier: &MarkerQuerier<Empty>,
+    denom: &str,
+) -> StdResult<CachedMarker> {
+    let marker = get_marker_by_denom(denom.to_owned(), querier)?;
+    let cached = CachedMarker {
+        marker_type: MarkerType::from_repr(marker.marker_type).unwrap_or(MarkerType::Unspecified),
+    };
+    MARKER_CACHE.save(storage, denom, &cached)?;
+    Ok(cached)
+}
+
+/// Allows the operator role to reread a denom's marker into the marker cache
+pub fn refresh_marker_cache(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: String,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::SentFundsUnsupported);
+    }
+
+    if !has_role(deps.as_ref(), &env, &info.sender, Role::Operator)? {
+        return Err(ContractError::Unauthorized {
+            error: String::from("The operator role is required to refresh the marker cache"),
+        });
+    }
+
+    let querier = MarkerQuerier::new(&deps.querier);
+    let cached = cache_marker(deps.storage, &querier, &denom)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", Action::RefreshMarkerCache.to_string()),
+        attr("denom", denom),
+        attr("marker_type", cached.marker_type.as_str_name()),
+        attr("admin", &info.sender),
+    ]))
+}
+
+fn get_marker_by_denom(denom: String, querier: &MarkerQuerier<Empty>) -> StdResult<MarkerAccount> {
+    let response = querier.marker(denom)?;
+    if let Some(marker) = response.marker {
+        return if let Ok(account) = MarkerAccount::try_from(marker) {
+            Ok(account)
+        } else {
+            Err(StdError::generic_err("unable to type-cast marker account"))
+        };
+    }
+    Err(StdError::generic_err("no marker found for denom"))
+}
+
+#[entry_point]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    msg.validate()?;
+
+    match msg {
+        QueryMsg::GetContractInfo {} => {
+            let state = CONFIG.load(deps.storage)?;
+            to_binary(&ContractInfoResponse { name: state.name })
+        }
+        QueryMsg::GetVersionInfo {} => {
+            let version = cw2::get_contract_version(deps.storage)?;
+            to_binary(&VersionInfoResponse {
+                contract: version.contract,
+                version: version.version,
+            })
+        }
+        QueryMsg::GetTransfer { id: transfer_id } => to_binary(&TransferResponse {
+            transfer: load_transfer(deps.storage, &transfer_id)?,
+        }),
+        QueryMsg::GetApprovals { id: transfer_id } => {
+            let transfer = load_transfer(deps.storage, &transfer_id)?;
+            to_binary(&ApprovalsResponse {
+                quorum: required_approvals(deps.storage, &transfer)?,
+                approvals: transfer.approvals,
+            })
+        }
+        QueryMsg::GetTransferDetailed { id: transfer_id } => {
+            let transfer = load_transfer(deps.storage, &transfer_id)?;
+            to_binary(&get_transfer_detailed(deps, transfer)?)
+        }
+        QueryMsg::GetFailedSettlements { id: transfer_id } => {
+            let failed_settlements = FAILED_SETTLEMENTS
+                .prefix(&transfer_key(&transfer_id)?)
+                .range(deps.storage, None, None, Order::Ascending)
+                .map(|item| item.map(|(_, settlement)| settlement))
+                .collect::<StdResult<Vec<_>>>()?;
+            to_binary(&FailedSettlementsResponse { failed_settlements })
+        }
+        QueryMsg::GetTransferFreeze { id: transfer_id } => to_binary(&TransferFreezeResponse {
+            freeze: TRANSFER_FREEZES.may_load(deps.storage, &transfer_key(&transfer_id)?)?,
+        }),
+        QueryMsg::GetAllTransfers {
+            start_after,
+            limit,
+            order,
+        } => to_binary(&TransfersResponse {
+            transfers: get_all_transfers(
+                deps.storage,
+                PageRequest {
+                    start_after,
+                    limit,
+                    order,
+                },
+            )?,
+        }),
+        QueryMsg::GetApprovalQuorum { denom } => {
+            to_binary(&APPROVAL_QUORUM.may_load(deps.storage, &denom)?.unwrap_or(1))
+        }
+        QueryMsg::GetSwap { id: swap_id } => {
+            to_binary(&SWAP_STORAGE.load(deps.storage, swap_id.as_bytes())?)
+        }
+        QueryMsg::GetRecurringTransfer {
+            id: recurring_transfer_id,
+        } => to_binary(
+            &RECURRING_TRANSFER_STORAGE
+                .load(deps.storage, &transfer_key(&recurring_transfer_id)?)?,
+        ),
+        QueryMsg::GetStandingApproval {
+            sender,
+            recipient,
+            denom,
+        } => {
+            let sender = deps.api.addr_validate(&sender)?;
+            let recipient = deps.api.addr_validate(&recipient)?;
+            to_binary(&STANDING_APPROVALS.load(deps.storage, (&denom, &sender, &recipient))?)
+        }
+        QueryMsg::GetTemplates {
+            sender,
+            start_after,
+            limit,
+        } => {
+            let sender = deps.api.addr_validate(&sender)?;
+            let page = PageRequest {
+                limit,
+                ..PageRequest::default()
+            };
+            to_binary(&TemplatesResponse {
+                templates: get_templates(
+                    deps.storage,
+                    &sender,
+                    start_after.as_deref(),
+                    page.limit(deps.storage)?,
+                )?,
+            })
+        }
+        QueryMsg::GetAllowance { id: allowance_id } => {
+            to_binary(&ALLOWANCE_STORAGE.load(deps.storage, &transfer_key(&allowance_id)?)?)
+        }
+        QueryMsg::GetRoles { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_binary(&RolesResponse {
+                roles: Role::ALL
+                    .iter()
+                    .copied()
+                    .filter(|role| holds_role(deps.storage, *role, &address))
+                    .collect(),
+                address,
+            })
+        }
+        QueryMsg::GetRoleMembers {
+            role,
+            start_after,
+            limit,
+        } => {
+            let start_after = start_after
+                .map(|address| deps.api.addr_validate(&address))
+                .transpose()?;
+            let page = PageRequest {
+                limit,
+                ..PageRequest::default()
+            };
+            to_binary(&RoleMembersResponse {
+                addresses: ROLE_MEMBERS
+                    .prefix(&role.to_string())
+                    .keys(
+                        deps.storage,
+                        start_after.as_ref().map(Bound::exclusive),
+                        None,
+                        Order::Ascending,
+                    )
+                    .take(page.limit(deps.storage)?)
+                    .collect::<StdResult<Vec<Addr>>>()?,
+                role,
+            })
+        }
+        QueryMsg::GetRegisteredDenoms { start_after, limit } => {
+            let page = PageRequest {
+                limit,
+                ..PageRequest::default()
+            };
+            to_binary(&RegisteredDenomsResponse {
+                denoms: REGISTERED_DENOMS
+                    .keys(
+                        deps.storage,
+                        start_after.as_deref().map(Bound::exclusive),
+                        None,
+                        Order::Ascending,
+                    )
+                    .take(page.limit(deps.storage)?)
+                    .collect::<StdResult<Vec<String>>>()?,
+            })
+        }
+        QueryMsg::GetBlocklist { start_after, limit } => {
+            let start_after = start_after
+                .map(|address| deps.api.addr_validate(&address))
+                .transpose()?;
+            let page = PageRequest {
+                limit,
+                ..PageRequest::default()
+            };
+            to_binary(&BlocklistResponse {
+                addresses: BLOCKLIST
+                    .keys(
+                        deps.storage,
+                        start_after.as_ref().map(Bound::exclusive),
+                        None,
+                        Order::Ascending,
+                    )
+                    .take(page.limit(deps.storage)?)
+                    .collect::<StdResult<Vec<Addr>>>()?,
+            })
+        }
+        QueryMsg::EstimateFees {
+            sender,
+            denom,
+            amount,
+        } => {
+            let sender = deps.api.addr_validate(&sender)?;
+            let settlement_fee =
+                settlement_fee(deps, &sender, &denom, amount, &env.contract.address)?;
+            let msg_fees = MSG_FEES.may_load(deps.storage)?;
+            let creation_fee = msg_fees
+                .as_ref()
+                .filter(|_| !FEE_EXEMPTIONS.has(deps.storage, &sender))
+                .and_then(|fees| fees.creation_fee);
+            to_binary(&EstimateFeesResponse {
+                settlement_fee: settlement_fee
+                    .as_ref()
+                    .map(|(fee, _)| *fee)
+                    .unwrap_or_default(),
+                collector: settlement_fee.map(|(_, collector)| collector),
+                creation_fee: creation_fee.unwrap_or_default(),
+                approval_fee: msg_fees
+                    .and_then(|fees| fees.approval_fee)
+                    .unwrap_or_default(),
+            })
+        }
+        QueryMsg::GetAccruedFees { start_after, limit } => {
+            let page = PageRequest {
+                limit,
+                ..PageRequest::default()
+            };
+            to_binary(&AccruedFeesResponse {
+                fees: ACCRUED_FEES
+                    .range(
+                        deps.storage,
+                        start_after.as_deref().map(Bound::exclusive),
+                        None,
+                        Order::Ascending,
+                    )
+                    .take(page.limit(deps.storage)?)
+                    .map(|item| item.map(|(denom, amount)| cosmwasm_std::Coin { denom, amount }))
+                    .collect::<StdResult<Vec<_>>>()?,
+            })
+        }
+        QueryMsg::GetFeeExemptions { start_after, limit } => {
+            let start_after = start_after
+                .map(|address| deps.api.addr_validate(&address))
+                .transpose()?;
+            let page = PageRequest {
+                limit,
+                ..PageRequest::default()
+            };
+            to_binary(&FeeExemptionsResponse {
+                addresses: FEE_EXEMPTIONS
+                    .keys(
+                        deps.storage,
+                        start_after.as_ref().map(Bound::exclusive),
+                        None,
+                        Order::Ascending,
+                    )
+                    .take(page.limit(deps.storage)?)
+                    .collect::<StdResult<Vec<Addr>>>()?,
+            })
+        }
+        QueryMsg::GetApprovers {
+            denom,
+            start_after,
+            limit,
+        } => {
+            let start_after = start_after
+                .map(|address| deps.api.addr_validate(&address))
+                .transpose()?;
+            let page = PageRequest {
+                limit,
+                ..PageRequest::default()
+            };
+            to_binary(&ApproversResponse {
+                addresses: get_registered_approvers(
+                    deps.storage,
+                    &denom,
+                    start_after.as_ref(),
+                    page.limit(deps.storage)?,
+                )?,
+                denom,
+            })
+        }
+        QueryMsg::GetDelegations {
+            denom,
+            start_after,
+            limit,
+        } => {
+            let start_after = start_after
+                .map(|address| deps.api.addr_validate(&address))
+                .transpose()?;
+            let page = PageRequest {
+                limit,
+                ..PageRequest::default()
+            };
+            to_binary(&DelegationsResponse {
+                delegations: get_approval_delegations(
+                    deps.storage,
+                    &env.block,
+                    &denom,
+                    start_after.as_ref(),
+                    page.limit(deps.storage)?,
+                )?,
+            })
+        }
+        QueryMsg::GetAllowlist {
+            denom,
+            start_after,
+            limit,
+        } => {
+            let start_after = start_after
+                .map(|address| deps.api.addr_validate(&address))
+                .transpose()?;
+            let page = PageRequest {
+                limit,
+                ..PageRequest::default()
+            };
+            to_binary(&AllowlistResponse {
+                enabled: ALLOWLIST_ENABLED.has(deps.storage, &denom),
+                addresses: get_allowlist(
+                    deps.storage,
+                    &denom,
+                    start_after.as_ref(),
+                    page.limit(deps.storage)?,
+                )?,
+                denom,
+            })
+        }
+        QueryMsg::GetTransfersBySender {
+            sender,
+            start_after,
+            limit,
+            order,
+        } => {
+            let sender = deps.api.addr_validate(&sender)?;
+            let page = PageRequest {
+                start_after,
+                limit,
+                order,
+            };
+            to_binary(&TransfersResponse {
+                transfers: get_transfers_by_sender(deps.storage, &sender, page)?,
+            })
+        }
+        QueryMsg::GetTransfersByTag {
+            tag,
+            start_after,
+            limit,
+            order,
+        } => {
+            let page = PageRequest {
+                start_after,
+                limit,
+                order,
+            };
+            to_binary(&TransfersResponse {
+                transfers: get_transfers_by_tag(deps.storage, &tag, page)?,
+            })
+        }
+        QueryMsg::GetPendingApprovals {
+            approver,
+            start_after,
+            limit,
+            order,
+        } => {
+            let approver = deps.api.addr_validate(&approver)?;
+            let page = PageRequest {
+                start_after,
+                limit,
+                order,
+            };
+            to_binary(&TransfersResponse {
+                transfers: get_pending_approvals(deps, &env.block, &approver, page)?,
+            })
+        }
+        QueryMsg::GetPendingByDenom {
+            denom,
+            start_after,
+            limit,
+            order,
+        } => {
+            let page = PageRequest {
+                start_after,
+                limit,
+                order,
+            };
+            to_binary(&TransfersResponse {
+                transfers: get_pending_by_denom(deps.storage, &denom, page)?,
+            })
+        }
+        QueryMsg::GetPendingActions {
+            address,
+            start_after,
+            limit,
+            order,
+        } => {
+            let address = deps.api.addr_validate(&address)?;
+            let page = PageRequest {
+                start_after,
+                limit,
+                order,
+            };
+            to_binary(&PendingActionsResponse {
+                pending_actions: get_pending_actions(deps, &env.block, &address, page)?,
+            })
+        }
+        QueryMsg::GetStats {} => {
+            to_binary(&TRANSFER_STATS.may_load(deps.storage)?.unwrap_or_default())
+        }
+        QueryMsg::GetVelocity { sender, denom } => {
+            let sender = deps.api.addr_validate(&sender)?;
+            let limit = VELOCITY_LIMITS.may_load(deps.storage, &denom)?;
+            let used = match &limit {
+                Some(limit) => velocity_used(
+                    deps.storage,
+                    &sender,
+                    &denom,
+                    env.block.time.seconds().saturating_sub(limit.window),
+                )?,
+                None => Uint128::zero(),
+            };
+            to_binary(&VelocityResponse {
+                sender,
+                denom,
+                limit,
+                used,
+            })
+        }
+        QueryMsg::GetSequence { sender } => {
+            let sender = deps.api.addr_validate(&sender)?;
+            to_binary(&SequenceResponse {
+                sequence: SENDER_SEQUENCE
+                    .may_load(deps.storage, &sender)?
+                    .unwrap_or_default(),
+                sender,
+            })
+        }
+        QueryMsg::GetTotals {} => {
+            to_binary(&TRANSFER_TOTALS.may_load(deps.storage)?.unwrap_or_default())
+        }
+        QueryMsg::GetDenomVolume { denom } => to_binary(
+            &DENOM_VOLUME
+                .may_load(deps.storage, &denom)?
+                .unwrap_or_default(),
+        ),
+        QueryMsg::GetDenomCounters { denom, height } => {
+            let counters = match height {
+                Some(height) => DENOM_COUNTERS.may_load_at_height(deps.storage, &denom, height)?,
+                None => DENOM_COUNTERS.may_load(deps.storage, &denom)?,
+            };
+            to_binary(&counters.unwrap_or_default())
+        }
+        QueryMsg::GetTransfers { ids } => {
+            let results = ids
+                .into_iter()
+                .map(|id| {
+                    let transfer = may_load_transfer(deps.storage, &id)?;
+                    Ok(TransferLookup { id, transfer })
+                })
+                .collect::<StdResult<Vec<_>>>()?;
+            to_binary(&TransferLookupsResponse { results })
+        }
+        QueryMsg::GetContractSettings {} => to_binary(&get_contract_settings(deps, &env)?),
+        QueryMsg::GetAuditLog { start_after, limit } => {
+            let page = PageRequest {
+                limit,
+                ..PageRequest::default()
+            };
+            to_binary(&AuditLogResponse {
+                entries: get_audit_log(deps.storage, start_after, page.limit(deps.storage)?)?,
+            })
+        }
+        QueryMsg::ListDenomConfigs { start_after, limit } => {
+            let page = PageRequest {
+                limit,
+                ..PageRequest::default()
+            };
+            to_binary(&DenomConfigsResponse {
+                denoms: get_denom_settings(deps.storage, start_after, page.limit(deps.storage)?)?,
+            })
+        }
+        QueryMsg::ExportState {
+            start_after,
+            limit,
+            order,
+        } => to_binary(&ExportStateResponse {
+            format_version: EXPORT_FORMAT_VERSION,
+            config: CONFIG.load(deps.storage)?,
+            transfers: get_all_transfers(
+                deps.storage,
+                PageRequest {
+                    start_after,
+                    limit,
+                    order,
+                },
+            )?,
+        }),
+        QueryMsg::GetEscrowReconciliation { denom } => {
+            to_binary(&get_escrow_reconciliation(deps, &env, denom)?)
+        }
+        QueryMsg::GetArchivedTransfers {
+            start_after,
+            limit,
+            order,
+        } => {
+            let page = PageRequest {
+                start_after,
+                limit,
+                order,
+            };
+            to_binary(&TransfersResponse {
+                transfers: get_archived_transfers(deps.storage, page)?,
+            })
+        }
+        QueryMsg::GetTransferHistory {
+            start_after,
+            limit,
+            order,
+            filter,
+        } => {
+            let page = PageRequest {
+                start_after,
+                limit,
+                order,
+            };
+            to_binary(&TransfersResponse {
+                transfers: get_transfer_history(deps.storage, page, filter)?,
+            })
+        }
+        QueryMsg::GetTransfersCreated {
+            range,
+            start_after,
+            limit,
+            order,
+        } => {
+            let page = PageRequest {
+                start_after,
+                limit,
+                order,
+            };
+            to_binary(&TransfersResponse {
+                transfers: get_transfers_created(deps.storage, &range, page)?,
+            })
+        }
+        QueryMsg::FindPendingTransfers {
+            sender,
+            recipient,
+            denom,
+        } => {
+            let sender = deps.api.addr_validate(&sender)?;
+            let recipient = deps.api.addr_validate(&recipient)?;
+            to_binary(&TransfersResponse {
+                transfers: find_pending_transfers(deps.storage, &sender, &recipient, &denom)?,
+            })
+        }
+    }
+}
+
+/// Joins a transfer with the live marker account of each denom it moves
+fn get_transfer_detailed(deps: Deps, transfer: Transfer) -> StdResult<TransferDetailedResponse> {
+    let querier = MarkerQuerier::new(&deps.querier);
+    let markers = transfer
+        .coins()
+        .into_iter()
+        .map(|coin| {
+            let marker = get_marker_by_denom(coin.denom, &querier)?;
+            Ok(MarkerDetails {
+                marker_type: MarkerType::from_repr(marker.marker_type)
+                    .unwrap_or(MarkerType::Unspecified),
+                status: MarkerStatus::from_repr(marker.status).unwrap_or(MarkerStatus::Unspecified),
+                denom: marker.denom,
+                required_attributes: marker.required_attributes,
+                supply: marker.supply,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(TransferDetailedResponse { transfer, markers })
+}
+
+/// Compares the escrow recorded for open transfers and swaps of the denom, together with its
+/// accrued fees, to the contract balance
+fn get_escrow_reconciliation(
+    deps: Deps,
+    env: &Env,
+    denom: String,
+) -> StdResult<EscrowReconciliationResponse> {
+    let transfer_escrow = DENOM_VOLUME
+        .may_load(deps.storage, &denom)?
+        .unwrap_or_default()
+        .escrowed;
+
+    let mut swap_escrow = Uint128::zero();
+    for item in SWAP_STORAGE.range(deps.storage, None, None, Order::Ascending) {
+        let (_, swap) = item?;
+        // the initiator escrows on proposal, the counterparty when funding
+        let escrowed_legs = match swap.status {
+            SwapStatus::Proposed => vec![swap.initiator],
+            SwapStatus::Funded => vec![swap.initiator, swap.counterparty],
+            _ => vec![],
+        };
+        for leg in escrowed_legs.into_iter().filter(|leg| leg.denom == denom) {
+            swap_escrow = swap_escrow.checked_add(leg.amount)?;
+        }
+    }
+
+    let mut failed_settlement_escrow = Uint128::zero();
+    for item in FAILED_SETTLEMENTS.range(deps.storage, None, None, Order::Ascending) {
+        let (_, settlement) = item?;
+        if let Some(coin) = settlement.msg.amount.filter(|coin| coin.denom == denom) {
+            failed_settlement_escrow =
+                failed_settlement_escrow.checked_add(coin.amount.parse()?)?;
+        }
+    }
+
+    let accrued_fees = ACCRUED_FEES
+        .may_load(deps.storage, &denom)?
+        .unwrap_or_default();
+
+    let balance = deps
+        .querier
+        .query_balance(env.contract.address.to_owned(), denom.to_owned())?
+        .amount;
+
+    let escrow = transfer_escrow
+        .checked_add(swap_escrow)?
+        .checked_add(failed_settlement_escrow)?
+        .checked_add(accrued_fees)?;
+    Ok(EscrowReconciliationResponse {
+        denom,
+        transfer_escrow,
+        swap_escrow,
+        failed_settlement_escrow,
+        accrued_fees,
+        balance,
+        discrepancy: escrow != balance,
+    })
+}
+
+fn get_contract_settings(deps: Deps, env: &Env) -> StdResult<ContractSettingsResponse> {
+    let state = CONFIG.load(deps.storage)?;
+    Ok(ContractSettingsResponse {
+        name: state.name,
+        admin: contract_admin(deps, env)?.map(String::from),
+        pending_admin: PENDING_ADMIN.may_load(deps.storage)?,
+        paused: PAUSED.may_load(deps.storage)?.unwrap_or(false),
+        veto_address: GLOBAL_VETO.may_load(deps.storage)?,
+        page_limit: PAGE_LIMIT.may_load(deps.storage)?.unwrap_or(MAX_PAGE_LIMIT),
+        pending_limit: PENDING_LIMIT.may_load(deps.storage)?,
+        creation_rate_limit: CREATION_RATE_LIMIT.may_load(deps.storage)?,
+        default_expiration: state.default_expiration,
+        allowed_denoms: state.allowed_denoms,
+        max_transfer_amount: MAX_TRANSFER_AMOUNT.may_load(deps.storage)?,
+        retention_period: RETENTION_PERIOD.may_load(deps.storage)?,
+        pending_recovery: PENDING_RECOVERY.may_load(deps.storage)?,
+        settlement_fee: SETTLEMENT_FEE.may_load(deps.storage)?,
+        msg_fees: MSG_FEES.may_load(deps.storage)?,
+        creation_deposit: CREATION_DEPOSIT.may_load(deps.storage)?,
+        fee_denom: fee_denom(deps.storage)?,
+        denoms: get_denom_settings(deps.storage, None, usize::MAX)?,
+    })
+}
+
+/// Returns up to `limit` denoms with at least one setting, in denom order after `start_after`.
+/// Each settings map is sorted, so taking `limit` entries from each is enough to fill the page.
+fn get_denom_settings(
+    storage: &dyn Storage,
+    start_after: Option<String>,
+    limit: usize,
+) -> StdResult<Vec<DenomSettings>> {
+    let start = start_after.as_deref().map(Bound::exclusive);
+    let mut denoms: BTreeMap<String, DenomSettings> = BTreeMap::new();
+    let denom_settings = |denom: String| DenomSettings {
+        denom,
+        approval_quorum: 1,
+        veto_address: None,
+        min_amount: None,
+        max_amount: None,
+        default_expiration: None,
+        velocity_limit: None,
+        auto_approve_threshold: None,
+        frozen: false,
+        fee_schedule: None,
+    };
+    for item in APPROVAL_QUORUM
+        .range(storage, start.clone(), None, Order::Ascending)
+        .take(limit)
+    {
+        let (denom, quorum) = item?;
+        denoms
+            .entry(denom.to_owned())
+            .or_insert_with(|| denom_settings(denom))
+            .approval_quorum = quorum;
+    }
+    for item in DENOM_VETO
+        .range(storage, start.clone(), None, Order::Ascending)
+        .take(limit)
+    {
+        let (denom, address) = item?;
+        denoms
+            .entry(denom.to_owned())
+            .or_insert_with(|| denom_settings(denom))
+            .veto_address = Some(address);
+    }
+    for item in DENOM_CONFIG
+        .range(storage, start.clone(), None, Order::Ascending)
+        .take(limit)
+    {
+        let (denom, config) = item?;
+        let settings = denoms
+            .entry(denom.to_owned())
+            .or_insert_with(|| denom_settings(denom));
+        settings.min_amount = config.min_amount;
+        settings.max_amount = config.max_amount;
+        settings.default_expiration = config.default_expiration;
+    }
+    for item in VELOCITY_LIMITS
+        .range(storage, start.clone(), None, Order::Ascending)
+        .take(limit)
+    {
+        let (denom, velocity_limit) = item?;
+        denoms
+            .entry(denom.to_owned())
+            .or_insert_with(|| denom_settings(denom))
+            .velocity_limit = Some(velocity_limit);
+    }
+    for item in AUTO_APPROVE_THRESHOLDS
+        .range(storage, start.clone(), None, Order::Ascending)
+        .take(limit)
+    {
+        let (denom, threshold) = item?;
+        denoms
+            .entry(denom.to_owned())
+            .or_insert_with(|| denom_settings(denom))
+            .auto_approve_threshold = Some(threshold);
+    }
+    for item in FROZEN_DENOMS
+        .keys(storage, start.clone(), None, Order::Ascending)
+        .take(limit)
+    {
+        let denom = item?;
+        denoms
+            .entry(denom.to_owned())
+            .or_insert_with(|| denom_settings(denom))
+            .frozen = true;
+    }
+    for item in DENOM_FEE_SCHEDULES
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+    {
+        let (denom, schedule) = item?;
+        denoms
+            .entry(denom.to_owned())
+            .or_insert_with(|| denom_settings(denom))
+            .fee_schedule = Some(schedule);
+    }
+
+    Ok(denoms.into_values().take(limit).collect())
+}
+
+/// Returns a page of the pending transfers the approver may still approve in queue order.
+/// Marker access is looked up once per denom rather than once per transfer.
+fn get_pending_approvals(
+    deps: Deps,
+    block: &BlockInfo,
+    approver: &Addr,
+    page: PageRequest,
+) -> StdResult<Vec<Transfer>> {
+    let querier = MarkerQuerier::new(&deps.querier);
+    let mut access_by_denom: BTreeMap<String, bool> = BTreeMap::new();
+
+    get_pending_queue(deps.storage, page, |transfer| {
+        may_approve(
+            deps.storage,
+            block,
+            transfer,
+            approver,
+            &querier,
+            &mut access_by_denom,
+        )
+    })
+}
+
+/// Returns a page of the transfers the address can act on now, each with the actions open to it
+fn get_pending_actions(
+    deps: Deps,
+    block: &BlockInfo,
+    address: &Addr,
+    page: PageRequest,
+) -> StdResult<Vec<PendingAction>> {
+    let limit = page.limit(deps.storage)?;
+    let (min, max) = page.bounds()?;
+    let querier = MarkerQuerier::new(&deps.querier);
+    let mut access_by_denom: BTreeMap<String, bool> = BTreeMap::new();
+    let mut pending_actions = vec![];
+
+    for item in TRANSFER_STORAGE.range(deps.storage, min, max, page.order()) {
+        if pending_actions.len() >= limit {
+            break;
+        }
+
+        let (_, StoredTransfer(transfer)) = item?;
+        let mut actions = vec![];
+        if transfer.status == TransferStatus::Pending && transfer.sender == *address {
+            actions.push(AvailableAction::Cancel);
+        }
+        if may_approve(
+            deps.storage,
+            block,
+            &transfer,
+            address,
+            &querier,
+            &mut access_by_denom,
+        )? {
+            actions.push(AvailableAction::Approve);
+        }
+        if transfer.status == TransferStatus::Approved
+            && transfer.require_acceptance
+            && transfer.recipient == *address
+        {
+            actions.push(AvailableAction::Accept);
+        }
+
+        if !actions.is_empty() {
+            pending_actions.push(PendingAction { transfer, actions });
+        }
+    }
+
+    Ok(pending_actions)
+}
+
+/// Whether the approver may still approve the transfer, caching their marker access per denom
+fn may_approve(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    transfer: &Transfer,
+    approver: &Addr,
+    querier: &MarkerQuerier<Empty>,
+    access_by_denom: &mut BTreeMap<String, bool>,
+) -> StdResult<bool> {
+    if transfer.status != TransferStatus::Pending
+        || (!transfer.required_approvers.is_empty()
+            && !transfer.required_approvers.contains(approver))
+        || transfer
+            .approvals
+            .iter()
+            .any(|approval| approval.approver == *approver)
+    {
+        return Ok(false);
+    }
+
+    for coin in transfer.coins() {
+        let has_access = match access_by_denom.get(&coin.denom) {
+            Some(has_access) => *has_access,
+            None => {
+                let has_access =
+                    has_transfer_access(storage, block, approver, &coin.denom, querier)?;
+                access_by_denom.insert(coin.denom, has_access);
+                has_access
+            }
+        };
+        if !has_access {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+enum Action {
+    Transfer,
+    Accept,
+    AcceptAdmin,
+    Approve,
+    ApproveSwap,
+    ApproveTransfers,
+    ArchiveTransfers,
+    BatchTransfer,
+    CancelAllBySender,
+    CancelRecovery,
+    CancelSwap,
+    Claim,
+    CreateRecurringTransfer,
+    DeleteTemplate,
+    ExecuteScheduled,
+    DelegateApproval,
+    DeregisterDenom,
+    ExecuteRecovery,
+    FreezeDenom,
+    FreezeTransfer,
+    FundSwap,
+    GrantRole,
+    InitiateRecovery,
+    GrantAllowance,
+    ProposeSwap,
+    PullTransfer,
+    Reduce,
+    RejectSwap,
+    Reject,
+    RejectTransfers,
+    RetryFailed,
+    RegisterDenom,
+    RevokeAllowance,
+    RevokeDelegation,
+    RevokeRole,
+    RevokeStandingApproval,
+    SaveTemplate,
+    SetAllowlistEnabled,
+    SettlementFailed,
+    SettlementFee,
+    SettlementRetried,
+    Cancel,
+    Decline,
+    ForceCancel,
+    ForceReject,
+    MigrateTransfers,
+    Pause,
+    PruneHistory,
+    PurgeSettled,
+    RefreshMarkerCache,
+    RefuseBlocked,
+    SetApprovalQuorum,
+    SetAutoApproveThreshold,
+    SetCreationDeposit,
+    SetDenomFeeSchedule,
+    SetFeeDenom,
+    SetMsgFees,
+    SetCreationRateLimit,
+    SetDenomConfig,
+    SetMaxTransferAmount,
+    SetPageLimit,
+    SetPendingLimit,
+    SetRetentionPeriod,
+    SetSettlementFee,
+    SetStandingApproval,
+    SetTransferPriority,
+    SetVelocityLimit,
+    SetVetoAddress,
+    SweepOrphanedFunds,
+    UnfreezeDenom,
+    UnfreezeTransfer,
+    Unpause,
+    Update,
+    UpdateAdmin,
+    UpdateAllowlist,
+    UpdateApprovers,
+    UpdateBlocklist,
+    UpdateFeeExemptions,
+    WithdrawFees,
+    UpdateConfig,
+    Veto,
 }
 
 impl fmt::Display for Action {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Action::Transfer => write!(f, "create_transfer"),
+            Action::Accept => write!(f, "accept"),
+            Action::AcceptAdmin => write!(f, "accept_admin"),
             Action::Approve => write!(f, "approve"),
+            Action::ApproveSwap => write!(f, "approve_swap"),
+            Action::ApproveTransfers => write!(f, "approve_transfers"),
+            Action::ArchiveTransfers => write!(f, "archive_transfers"),
+            Action::BatchTransfer => write!(f, "batch_transfer"),
+            Action::CancelAllBySender => write!(f, "cancel_all_by_sender"),
+            Action::CancelRecovery => write!(f, "cancel_recovery"),
+            Action::CancelSwap => write!(f, "cancel_swap"),
+            Action::Claim => write!(f, "claim"),
+            Action::CreateRecurringTransfer => write!(f, "create_recurring_transfer"),
+            Action::DeleteTemplate => write!(f, "delete_template"),
+            Action::ExecuteScheduled => write!(f, "execute_scheduled"),
+            Action::DelegateApproval => write!(f, "delegate_approval"),
+            Action::DeregisterDenom => write!(f, "deregister_denom"),
+            Action::ExecuteRecovery => write!(f, "execute_recovery"),
+            Action::FreezeDenom => write!(f, "freeze_denom"),
+            Action::FreezeTransfer => write!(f, "freeze_transfer"),
+            Action::FundSwap => write!(f, "fund_swap"),
+            Action::GrantRole => write!(f, "grant_role"),
+            Action::InitiateRecovery => write!(f, "initiate_recovery"),
+            Action::GrantAllowance => write!(f, "grant_allowance"),
+            Action::ProposeSwap => write!(f, "propose_swap"),
+            Action::PullTransfer => write!(f, "pull_transfer"),
+            Action::Reduce => write!(f, "reduce"),
+            Action::RejectSwap => write!(f, "reject_swap"),
             Action::Reject => write!(f, "reject"),
+            Action::RejectTransfers => write!(f, "reject_transfers"),
+            Action::RetryFailed => write!(f, "retry_failed"),
+            Action::RegisterDenom => write!(f, "register_denom"),
+            Action::RevokeAllowance => write!(f, "revoke_allowance"),
+            Action::RevokeDelegation => write!(f, "revoke_delegation"),
+            Action::RevokeRole => write!(f, "revoke_role"),
+            Action::RevokeStandingApproval => write!(f, "revoke_standing_approval"),
+            Action::SaveTemplate => write!(f, "save_template"),
+            Action::SetAllowlistEnabled => write!(f, "set_allowlist_enabled"),
+            Action::SettlementFailed => write!(f, "settlement_failed"),
+            Action::SettlementFee => write!(f, "settlement_fee"),
+            Action::SettlementRetried => write!(f, "settlement_retried"),
             Action::Cancel => write!(f, "cancel"),
+            Action::Decline => write!(f, "decline"),
+            Action::ForceCancel => write!(f, "force_cancel"),
+            Action::ForceReject => write!(f, "force_reject"),
+            Action::MigrateTransfers => write!(f, "migrate_transfers"),
+            Action::Pause => write!(f, "pause"),
+            Action::PruneHistory => write!(f, "prune_history"),
+            Action::PurgeSettled => write!(f, "purge_settled"),
+            Action::RefreshMarkerCache => write!(f, "refresh_marker_cache"),
+            Action::RefuseBlocked => write!(f, "refuse_blocked"),
+            Action::SetApprovalQuorum => write!(f, "set_approval_quorum"),
+            Action::SetAutoApproveThreshold => write!(f, "set_auto_approve_threshold"),
+            Action::SetCreationDeposit => write!(f, "set_creation_deposit"),
+            Action::SetDenomFeeSchedule => write!(f, "set_denom_fee_schedule"),
+            Action::SetFeeDenom => write!(f, "set_fee_denom"),
+            Action::SetMsgFees => write!(f, "set_msg_fees"),
+            Action::SetCreationRateLimit => write!(f, "set_creation_rate_limit"),
+            Action::SetDenomConfig => write!(f, "set_denom_config"),
+            Action::SetMaxTransferAmount => write!(f, "set_max_transfer_amount"),
+            Action::SetPageLimit => write!(f, "set_page_limit"),
+            Action::SetPendingLimit => write!(f, "set_pending_limit"),
+            Action::SetRetentionPeriod => write!(f, "set_retention_period"),
+            Action::SetSettlementFee => write!(f, "set_settlement_fee"),
+            Action::SetStandingApproval => write!(f, "set_standing_approval"),
+            Action::SetTransferPriority => write!(f, "set_transfer_priority"),
+            Action::SetVelocityLimit => write!(f, "set_velocity_limit"),
+            Action::SetVetoAddress => write!(f, "set_veto_address"),
+            Action::SweepOrphanedFunds => write!(f, "sweep_orphaned_funds"),
+            Action::UnfreezeDenom => write!(f, "unfreeze_denom"),
+            Action::UnfreezeTransfer => write!(f, "unfreeze_transfer"),
+            Action::Unpause => write!(f, "unpause"),
+            Action::Update => write!(f, "update"),
+            Action::UpdateAdmin => write!(f, "update_admin"),
+            Action::UpdateAllowlist => write!(f, "update_allowlist"),
+            Action::UpdateApprovers => write!(f, "update_approvers"),
+            Action::UpdateBlocklist => write!(f, "update_blocklist"),
+            Action::UpdateFeeExemptions => write!(f, "update_fee_exemptions"),
+            Action::WithdrawFees => write!(f, "withdraw_fees"),
+            Action::UpdateConfig => write!(f, "update_config"),
+            Action::Veto => write!(f, "veto"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::{
+        transfer_key, AuditEntry, CreationRange, DenomCounters, DenomVolume, FeeTier, Role,
+        SortOrder, State, TransferStats, TransferTotals, CONFIG, LEGACY_MIGRATION_CURSOR,
+        SENDER_CREATIONS, SENDER_VELOCITY, TRANSFER_HISTORY, TRANSFER_INCENTIVES,
+    };
+    use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+    use cosmwasm_std::OwnedDeps;
+    use cosmwasm_std::{
+        coin, from_binary, Addr, ContractInfoResponse, ContractResult, CosmosMsg, ReplyOn, Storage,
+        SubMsgResponse, SystemError, SystemResult, Timestamp, WasmQuery,
+    };
+    use prost::Message;
+    use provwasm_mocks::{mock_provenance_dependencies, MockProvenanceQuerier};
+    use provwasm_std::shim::Any;
+    use provwasm_std::types::cosmos::auth::v1beta1::BaseAccount;
+    use provwasm_std::types::provenance::marker::v1::{
+        Access, AccessGrant, MarkerStatus, MarkerType, QueryMarkerRequest, QueryMarkerResponse,
+    };
+
+    use super::*;
+
+    const RESTRICTED_DENOM: &str = "restricted_1";
+    const OTHER_RESTRICTED_DENOM: &str = "restricted_2";
+    const TRANSFER_ID: &str = "56253028-12f5-4d2a-a691-ebdfd2a7b865";
+
+    #[test]
+    fn create_transfer_success() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let amount = Uint128::new(1);
+        let transfer_msg = ExecuteMsg::Transfer {
+            id: TRANSFER_ID.into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount,
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+
+        let sender_info = mock_info("sender", &[]);
+
+        let sender_balance = coin(1, RESTRICTED_DENOM);
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![sender_balance]);
+
+        let recipient = "transfer_to";
+
+        // execute create transfer
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender_info.clone(),
+            transfer_msg.clone(),
+        );
+
+        let expected_coin = Coin {
+            denom: RESTRICTED_DENOM.to_owned(),
+            amount: amount.into(),
+        };
+
+        // verify transfer response
+        match transfer_response {
+            Ok(response) => {
+                assert_eq!(response.attributes.len(), 7);
+                assert_eq!(
+                    response.attributes[0],
+                    attr("action", Action::Transfer.to_string())
+                );
+                assert_eq!(response.attributes[1], attr("id", TRANSFER_ID));
+                assert_eq!(response.attributes[2], attr("denom", RESTRICTED_DENOM));
+                assert_eq!(response.attributes[3], attr("amount", amount.to_string()));
+                assert_eq!(
+                    response.attributes[4],
+                    attr("sender", sender_info.clone().sender)
+                );
+                assert_eq!(response.attributes[5], attr("recipient", recipient));
+                assert_eq!(response.attributes[6], attr("sequence", "1"));
+
+                assert_eq!(response.messages.len(), 1);
+
+                let expected_message: Binary = MsgTransferRequest {
+                    amount: Some(expected_coin),
+                    from_address: sender_info.clone().sender.to_string(),
+                    to_address: MOCK_CONTRACT_ADDR.to_owned(),
+                    administrator: MOCK_CONTRACT_ADDR.to_owned(),
+                }
+                .into();
+
+                match &response.messages[0].msg {
+                    CosmosMsg::Stargate { type_url, value } => {
+                        assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
+                        assert_eq!(value, &expected_message);
+                    }
+                    _ => panic!("unexpected cosmos message"),
+                }
+            }
+            Err(error) => {
+                panic!("failed to create transfer: {:?}", error)
+            }
+        }
+
+        // verify transfer stored
+        match load_transfer(&deps.storage, TRANSFER_ID) {
+            Ok(stored_transfer) => {
+                assert_eq!(
+                    stored_transfer,
+                    Transfer {
+                        id: TRANSFER_ID.into(),
+                        sender: sender_info.sender.to_owned(),
+                        denom: RESTRICTED_DENOM.into(),
+                        amount,
+                        recipient: Addr::unchecked(recipient),
+                        status: TransferStatus::Pending,
+                        rejection_reason: None,
+                        require_acceptance: false,
+                        approvals: vec![],
+                        required_approvers: vec![],
+                        legs: vec![],
+                        additional_coins: vec![],
+                        vesting: None,
+                        claimed: Uint128::zero(),
+                        memo: None,
+                        created: Some(Creation {
+                            height: mock_env().block.height,
+                            time: mock_env().block.time,
+                        }),
+                        updated_at: Some(mock_env().block.time),
+                        sequence: Some(1),
+                        tags: vec![],
+                        position: Some(1),
+                        priority: false,
+                        expires: None,
+                    }
+                )
+            }
+            _ => {
+                panic!("transfer was not found in storage")
+            }
+        }
+    }
+
+    #[test]
+    fn create_transfer_with_funds_throws_error() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let amount = Uint128::new(1);
+        let transfer_msg = ExecuteMsg::Transfer {
+            id: "56253028-12f5-4d2a-a691-ebdfd2a7b865".into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount,
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+
+        let sender_info = mock_info("sender", &[coin(amount.u128(), RESTRICTED_DENOM)]);
+
+        let sender_balance = coin(1, RESTRICTED_DENOM);
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![sender_balance]);
+
+        // execute create transfer
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender_info.clone(),
+            transfer_msg.clone(),
+        );
+
+        assert_sent_funds_unsupported_error(transfer_response);
+    }
+
+    #[test]
+    fn transfers_are_numbered_per_sender() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let mut transfer = |sender: &str, id: &str| -> Response {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(sender, &[]),
+                ExecuteMsg::Transfer {
+                    id: id.into(),
+                    denom: RESTRICTED_DENOM.into(),
+                    amount: Uint128::new(1),
+                    recipient: "transfer_to".into(),
+                    require_acceptance: None,
+                    approvers: None,
+                    recipients: None,
+                    additional_coins: None,
+                    vesting_duration: None,
+                    memo: None,
+                    tags: None,
+                },
+            )
+            .unwrap()
+        };
+
+        let response = transfer("sender", TRANSFER_ID);
+        assert_eq!(response.attributes.last(), Some(&attr("sequence", "1")));
+        transfer("other_sender", "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61");
+        let response = transfer("sender", "1f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61");
+        assert_eq!(response.attributes.last(), Some(&attr("sequence", "2")));
+
+        assert_eq!(
+            Some(2),
+            load_transfer(&deps.storage, "1f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61")
+                .unwrap()
+                .sequence
+        );
+
+        let sequence = |deps: Deps, sender: &str| -> u64 {
+            let response = query(
+                deps,
+                mock_env(),
+                QueryMsg::GetSequence {
+                    sender: sender.into(),
+                },
+            )
+            .unwrap();
+            from_binary::<SequenceResponse>(&response).unwrap().sequence
+        };
+        assert_eq!(2, sequence(deps.as_ref(), "sender"));
+        assert_eq!(1, sequence(deps.as_ref(), "other_sender"));
+        assert_eq!(0, sequence(deps.as_ref(), "transfer_to"));
+    }
+
+    #[test]
+    fn create_transfer_escrow_failure_throws_error() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let amount = Uint128::new(2);
+        let transfer_msg = ExecuteMsg::Transfer {
+            id: TRANSFER_ID.into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount,
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+
+        let sender_info = mock_info("sender", &[]);
+
+        let sender_balance = coin(1, RESTRICTED_DENOM);
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![sender_balance]);
+
+        // the balance is not checked up front, the escrow reports the shortfall in its reply
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender_info.clone(),
+            transfer_msg.clone(),
+        )
+        .unwrap();
+        assert_eq!(1, response.messages.len());
+        assert_eq!(ReplyOn::Error, response.messages[0].reply_on);
+        assert_eq!(ESCROW_REPLY_ID, response.messages[0].id);
+
+        match reply(
+            deps.as_mut(),
+            mock_env(),
+            Reply {
+                id: ESCROW_REPLY_ID,
+                result: SubMsgResult::Err("insufficient funds".into()),
+            },
+        ) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::EscrowFailed { error } => assert_eq!("insufficient funds", error),
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+    }
+
+    #[test]
+    fn create_transfer_invalid_data() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let amount = Uint128::new(1);
+        let transfer_msg = ExecuteMsg::Transfer {
+            id: "".into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount,
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+
+        let sender_info = mock_info("sender", &[]);
+
+        let sender_balance = coin(1, RESTRICTED_DENOM);
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![sender_balance]);
+
+        // execute create transfer
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender_info.clone(),
+            transfer_msg.clone(),
+        );
+
+        // verify transfer response
+        match transfer_response {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::InvalidFields { fields } => {
+                    assert!(fields.contains(&"id".into()));
+                }
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+    }
+
+    #[test]
+    fn create_transfer_existing_id() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let amount = Uint128::new(1);
+        let sender_info = mock_info("sender", &[]);
+
+        store_test_transfer(
+            &mut deps.storage,
+            &Transfer {
+                id: TRANSFER_ID.into(),
+                sender: sender_info.sender.to_owned(),
+                denom: RESTRICTED_DENOM.into(),
+                amount,
+                recipient: Addr::unchecked("transfer_to"),
+                status: TransferStatus::Pending,
+                rejection_reason: None,
+                require_acceptance: false,
+                approvals: vec![],
+                required_approvers: vec![],
+                legs: vec![],
+                additional_coins: vec![],
+                vesting: None,
+                claimed: Uint128::zero(),
+                memo: None,
+                created: None,
+                updated_at: None,
+                sequence: None,
+                tags: vec![],
+                position: None,
+                priority: false,
+                expires: None,
+            },
+        );
+
+        let transfer_msg = ExecuteMsg::Transfer {
+            id: TRANSFER_ID.into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount,
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+
+        let sender_balance = coin(1, RESTRICTED_DENOM);
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![sender_balance]);
+
+        // execute create transfer
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender_info.clone(),
+            transfer_msg.clone(),
+        );
+
+        // verify transfer response
+        match transfer_response {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::InvalidFields { fields } => {
+                    assert!(fields.contains(&"id".into()));
+                }
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+    }
+
+    #[test]
+    fn batch_transfer_escrows_each_denom_once() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![coin(5, RESTRICTED_DENOM)]);
+
+        let other_transfer_id = "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61";
+        let transfer_input = |id: &str, recipient: &str, amount: u128| TransferInput {
+            id: id.into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount: Uint128::new(amount),
+            recipient: recipient.into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            ExecuteMsg::BatchTransfer {
+                transfers: vec![
+                    transfer_input(TRANSFER_ID, "holder_1", 3),
+                    transfer_input(other_transfer_id, "holder_2", 2),
+                ],
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            response.attributes[0],
+            attr("action", Action::BatchTransfer.to_string())
+        );
+        assert_eq!(response.attributes[1], attr("count", "2"));
+        assert_eq!(response.events.len(), 2);
+
+        let expected_message: Binary = MsgTransferRequest {
+            amount: Some(Coin {
+                denom: RESTRICTED_DENOM.to_owned(),
+                amount: "5".into(),
+            }),
+            from_address: "sender".to_owned(),
+            to_address: MOCK_CONTRACT_ADDR.to_owned(),
+            administrator: MOCK_CONTRACT_ADDR.to_owned(),
+        }
+        .into();
+        assert_eq!(response.messages.len(), 1);
+        match &response.messages[0].msg {
+            CosmosMsg::Stargate { type_url, value } => {
+                assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
+                assert_eq!(value, &expected_message);
+            }
+            _ => panic!("unexpected cosmos message"),
+        }
+
+        let stored_transfer = load_transfer(&deps.storage, other_transfer_id).unwrap();
+        assert_eq!(Addr::unchecked("holder_2"), stored_transfer.recipient);
+        assert_eq!(Uint128::new(2), stored_transfer.amount);
+    }
+
+    #[test]
+    fn batch_transfer_escrows_with_reply_on_error() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let transfer_input = |id: &str, amount: u128| TransferInput {
+            id: id.into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount: Uint128::new(amount),
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            ExecuteMsg::BatchTransfer {
+                transfers: vec![
+                    transfer_input(TRANSFER_ID, 3),
+                    transfer_input("0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61", 2),
+                ],
+            },
+        )
+        .unwrap();
+
+        // a single escrow of the total, reverting the whole batch if it fails
+        assert_eq!(1, response.messages.len());
+        assert_eq!(ReplyOn::Error, response.messages[0].reply_on);
+        assert_eq!(ESCROW_REPLY_ID, response.messages[0].id);
+    }
+
+    #[test]
+    fn create_transfer_with_memo() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![coin(5, RESTRICTED_DENOM)]);
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            ExecuteMsg::Transfer {
+                id: TRANSFER_ID.into(),
+                denom: RESTRICTED_DENOM.into(),
+                amount: Uint128::new(5),
+                recipient: "transfer_to".into(),
+                require_acceptance: None,
+                approvers: None,
+                recipients: None,
+                additional_coins: None,
+                vesting_duration: None,
+                memo: Some("invoice 1042".into()),
+                tags: None,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(response.attributes[6], attr("memo", "invoice 1042"));
+
+        let stored_transfer = load_transfer(&deps.storage, TRANSFER_ID).unwrap();
+        assert_eq!(Some("invoice 1042".to_string()), stored_transfer.memo);
+    }
+
+    #[test]
+    fn query_transfers_by_tag() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![coin(5, RESTRICTED_DENOM)]);
+
+        let ids = [
+            "0a1e5a3c-5f0e-4b8e-9b4a-1d5b0c6f7a01",
+            "0a1e5a3c-5f0e-4b8e-9b4a-1d5b0c6f7a02",
+            "0a1e5a3c-5f0e-4b8e-9b4a-1d5b0c6f7a03",
+        ];
+        let tags = [
+            vec!["tranche-A", "Q3-distribution"],
+            vec!["tranche-B"],
+            vec!["tranche-A"],
+        ];
+        for (id, tags) in ids.iter().zip(tags) {
+            let response = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("sender", &[]),
+                ExecuteMsg::Transfer {
+                    id: id.to_string(),
+                    denom: RESTRICTED_DENOM.into(),
+                    amount: Uint128::new(1),
+                    recipient: "transfer_to".into(),
+                    require_acceptance: None,
+                    approvers: None,
+                    recipients: None,
+                    additional_coins: None,
+                    vesting_duration: None,
+                    memo: None,
+                    tags: Some(tags.iter().map(|tag| tag.to_string()).collect()),
+                },
+            )
+            .unwrap();
+            assert!(response.attributes.contains(&attr("tags", tags.join(","))));
+        }
+
+        let tagged = |deps: Deps, tag: &str, start_after: Option<&str>| -> Vec<String> {
+            let response = query(
+                deps,
+                mock_env(),
+                QueryMsg::GetTransfersByTag {
+                    tag: tag.into(),
+                    start_after: start_after.map(String::from),
+                    limit: Some(1),
+                    order: None,
+                },
+            )
+            .unwrap();
+            from_binary::<TransfersResponse>(&response)
+                .unwrap()
+                .transfers
+                .into_iter()
+                .map(|transfer| transfer.id)
+                .collect()
+        };
+        assert_eq!(vec![ids[0]], tagged(deps.as_ref(), "tranche-A", None));
+        assert_eq!(
+            vec![ids[2]],
+            tagged(deps.as_ref(), "tranche-A", Some(ids[0]))
+        );
+        assert_eq!(vec![ids[1]], tagged(deps.as_ref(), "tranche-B", None));
+        assert!(tagged(deps.as_ref(), "tranche-C", None).is_empty());
+    }
+
+    #[test]
+    fn create_transfers_from_template() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![coin(10, RESTRICTED_DENOM)]);
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            ExecuteMsg::SaveTemplate {
+                template_id: "monthly-distribution".into(),
+                denom: RESTRICTED_DENOM.into(),
+                recipient: "transfer_to".into(),
+                amount: Some(Uint128::new(3)),
+                memo: Some("monthly distribution".into()),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                attr("action", Action::SaveTemplate.to_string()),
+                attr("template_id", "monthly-distribution"),
+                attr("denom", RESTRICTED_DENOM),
+                attr("sender", "sender"),
+                attr("recipient", "transfer_to"),
+            ],
+            response.attributes
+        );
+
+        let from_template = |id: &str, amount: Option<u128>| ExecuteMsg::CreateFromTemplate {
+            template_id: "monthly-distribution".into(),
+            id: id.into(),
+            amount: amount.map(Uint128::new),
+        };
+        let ids = [
+            "0a1e5a3c-5f0e-4b8e-9b4a-1d5b0c6f7a01",
+            "0a1e5a3c-5f0e-4b8e-9b4a-1d5b0c6f7a02",
+        ];
+        // the default amount is used unless one is given
+        for (id, amount, expected) in [(ids[0], None, 3), (ids[1], Some(2), 2)] {
+            let response = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("sender", &[]),
+                from_template(id, amount),
+            )
+            .unwrap();
+            assert!(response
+                .attributes
+                .contains(&attr("template_id", "monthly-distribution")));
+            assert_eq!(1, response.messages.len());
+
+            let transfer = load_transfer(&deps.storage, id).unwrap();
+            assert_eq!(Uint128::new(expected), transfer.amount);
+            assert_eq!(Addr::unchecked("transfer_to"), transfer.recipient);
+            assert_eq!(Some("monthly distribution".into()), transfer.memo);
+        }
+
+        // templates are only visible to their sender
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other_sender", &[]),
+            from_template("0a1e5a3c-5f0e-4b8e-9b4a-1d5b0c6f7a03", None),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::LoadTemplateFailed { .. }));
+
+        let templates = |deps: Deps| -> Vec<String> {
+            let response = query(
+                deps,
+                mock_env(),
+                QueryMsg::GetTemplates {
+                    sender: "sender".into(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+            from_binary::<TemplatesResponse>(&response)
+                .unwrap()
+                .templates
+                .into_iter()
+                .map(|template| template.id)
+                .collect()
+        };
+        assert_eq!(vec!["monthly-distribution"], templates(deps.as_ref()));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            ExecuteMsg::DeleteTemplate {
+                template_id: "monthly-distribution".into(),
+            },
+        )
+        .unwrap();
+        assert!(templates(deps.as_ref()).is_empty());
+
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            from_template("0a1e5a3c-5f0e-4b8e-9b4a-1d5b0c6f7a03", None),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::LoadTemplateFailed { .. }));
+    }
+
+    #[test]
+    fn standing_approval_settles_matching_transfers() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        mock_query_marker_response(&marker, &mut deps.querier);
+
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![coin(10, RESTRICTED_DENOM)]);
+
+        let expires = mock_env().block.time.plus_seconds(100);
+        let set_msg = ExecuteMsg::SetStandingApproval {
+            sender: "sender".into(),
+            recipient: "transfer_to".into(),
+            denom: RESTRICTED_DENOM.into(),
+            max_amount: Uint128::new(5),
+            expires,
+        };
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            set_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            set_msg,
+        )
+        .unwrap();
+
+        let transfer_msg = |id: &str, amount: u128| ExecuteMsg::Transfer {
+            id: id.into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount: Uint128::new(amount),
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+        let ids = [
+            "0a1e5a3c-5f0e-4b8e-9b4a-1d5b0c6f7a01",
+            "0a1e5a3c-5f0e-4b8e-9b4a-1d5b0c6f7a02",
+            "0a1e5a3c-5f0e-4b8e-9b4a-1d5b0c6f7a03",
+        ];
+
+        // escrowed and paid out in the same transaction
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            transfer_msg(ids[0], 3),
+        )
+        .unwrap();
+        assert!(response
+            .attributes
+            .contains(&attr("standing_approver", "approver")));
+        assert_eq!(2, response.messages.len());
+        assert_eq!(ReplyOn::Always, response.messages[1].reply_on);
+        let transfer = load_transfer(&deps.storage, ids[0]).unwrap();
+        assert_eq!(TransferStatus::Settled, transfer.status);
+        assert_eq!(Addr::unchecked("approver"), transfer.approvals[0].approver);
+
+        let standing_approval = |deps: Deps| {
+            query(
+                deps,
+                mock_env(),
+                QueryMsg::GetStandingApproval {
+                    sender: "sender".into(),
+                    recipient: "transfer_to".into(),
+                    denom: RESTRICTED_DENOM.into(),
+                },
+            )
+            .map(|response| from_binary::<StandingApproval>(&response).unwrap())
+        };
+        assert_eq!(
+            Uint128::new(3),
+            standing_approval(deps.as_ref()).unwrap().used
+        );
+
+        // a transfer past the maximum or the expiry waits for approval as usual
+        let mut expired_env = mock_env();
+        expired_env.block.time = expires;
+        for (env, id, amount) in [(mock_env(), ids[1], 3), (expired_env, ids[2], 1)] {
+            let response = execute(
+                deps.as_mut(),
+                env,
+                mock_info("sender", &[]),
+                transfer_msg(id, amount),
+            )
+            .unwrap();
+            assert_eq!(1, response.messages.len());
+            assert_eq!(
+                TransferStatus::Pending,
+                load_transfer(&deps.storage, id).unwrap().status
+            );
+        }
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            ExecuteMsg::RevokeStandingApproval {
+                sender: "sender".into(),
+                recipient: "transfer_to".into(),
+                denom: RESTRICTED_DENOM.into(),
+            },
+        )
+        .unwrap();
+        assert!(standing_approval(deps.as_ref()).is_err());
+    }
+
+    #[test]
+    fn auto_approve_threshold_settles_small_transfers() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let mut marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        marker.access_control.push(AccessGrant {
+            address: "marker_admin".into(),
+            permissions: vec![Access::Admin.into()],
+        });
+        mock_query_marker_response(&marker, &mut deps.querier);
+
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![coin(20, RESTRICTED_DENOM)]);
+
+        let set_msg = |threshold: Option<u128>| ExecuteMsg::SetAutoApproveThreshold {
+            denom: RESTRICTED_DENOM.into(),
+            threshold: threshold.map(Uint128::new),
+        };
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            set_msg(Some(5)),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("marker_admin", &[]),
+            set_msg(Some(5)),
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![
+                attr("action", Action::SetAutoApproveThreshold.to_string()),
+                attr("denom", RESTRICTED_DENOM),
+                attr("threshold", "5"),
+                attr("admin", "marker_admin"),
+            ]
+        );
+
+        let query_msg = QueryMsg::ListDenomConfigs {
+            start_after: None,
+            limit: None,
+        };
+        let settings: DenomConfigsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(
+            Some(Uint128::new(5)),
+            settings.denoms[0].auto_approve_threshold
+        );
+
+        let transfer_msg = |id: &str, amount: u128| ExecuteMsg::Transfer {
+            id: id.into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount: Uint128::new(amount),
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+        let ids = [
+            "7c2d1f4e-8a3b-4c5d-9e6f-0a1b2c3d4e01",
+            "7c2d1f4e-8a3b-4c5d-9e6f-0a1b2c3d4e02",
+            "7c2d1f4e-8a3b-4c5d-9e6f-0a1b2c3d4e03",
+        ];
+
+        // escrowed and paid out in the same transaction
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            transfer_msg(ids[0], 4),
+        )
+        .unwrap();
+        assert!(response
+            .attributes
+            .contains(&attr("auto_approve_threshold", "5")));
+        assert_eq!(2, response.messages.len());
+        let transfer = load_transfer(&deps.storage, ids[0]).unwrap();
+        assert_eq!(TransferStatus::Settled, transfer.status);
+        assert_eq!(mock_env().contract.address, transfer.approvals[0].approver);
+
+        // a transfer at the threshold, or after it is removed, waits for approval as usual
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            transfer_msg(ids[1], 5),
+        )
+        .unwrap();
+        assert_eq!(1, response.messages.len());
+        assert_eq!(
+            TransferStatus::Pending,
+            load_transfer(&deps.storage, ids[1]).unwrap().status
+        );
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("marker_admin", &[]),
+            set_msg(None),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            transfer_msg(ids[2], 1),
+        )
+        .unwrap();
+        assert_eq!(
+            TransferStatus::Pending,
+            load_transfer(&deps.storage, ids[2]).unwrap().status
+        );
+    }
+
+    #[test]
+    fn pull_transfers_from_allowance() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![coin(10, RESTRICTED_DENOM)]);
+
+        let allowance_id = "0a1e5a3c-5f0e-4b8e-9b4a-1d5b0c6f7a01";
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            ExecuteMsg::GrantAllowance {
+                id: allowance_id.into(),
+                recipient: "transfer_to".into(),
+                denom: RESTRICTED_DENOM.into(),
+                amount: Uint128::new(5),
+            },
+        )
+        .unwrap();
+
+        let pull = |amount: u128| ExecuteMsg::PullTransfer {
+            allowance_id: allowance_id.into(),
+            amount: Uint128::new(amount),
+        };
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other_recipient", &[]),
+            pull(1),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("transfer_to", &[]),
+            pull(3),
+        )
+        .unwrap();
+        let transfer_id = derived_transfer_id("allowance", &transfer_key(allowance_id).unwrap(), 1);
+        assert!(response
+            .attributes
+            .contains(&attr("transfer_id", &transfer_id)));
+        assert!(response.attributes.contains(&attr("remaining", "2")));
+        assert_eq!(1, response.messages.len());
+
+        // the transfer is escrowed from the sender and waits for approval
+        let transfer = load_transfer(&deps.storage, &transfer_id).unwrap();
+        assert_eq!(TransferStatus::Pending, transfer.status);
+        assert_eq!(Addr::unchecked("sender"), transfer.sender);
+        assert_eq!(Addr::unchecked("transfer_to"), transfer.recipient);
+        assert_eq!(Uint128::new(3), transfer.amount);
+
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("transfer_to", &[]),
+            pull(3),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ContractError::AllowanceExceeded { remaining } if remaining == Uint128::new(2)
+        ));
+
+        let revoke = ExecuteMsg::RevokeAllowance {
+            id: allowance_id.into(),
+        };
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("transfer_to", &[]),
+            revoke.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+
+        execute(deps.as_mut(), mock_env(), mock_info("sender", &[]), revoke).unwrap();
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("transfer_to", &[]),
+            pull(1),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::LoadAllowanceFailed { .. }));
+    }
+
+    #[test]
+    fn create_multi_denom_transfer_escrows_every_denom() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        deps.querier.mock_querier.update_balance(
+            Addr::unchecked("sender"),
+            vec![coin(5, RESTRICTED_DENOM), coin(2, OTHER_RESTRICTED_DENOM)],
+        );
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            ExecuteMsg::Transfer {
+                id: TRANSFER_ID.into(),
+                denom: RESTRICTED_DENOM.into(),
+                amount: Uint128::new(5),
+                recipient: "transfer_to".into(),
+                require_acceptance: None,
+                approvers: None,
+                recipients: None,
+                additional_coins: Some(vec![coin(2, OTHER_RESTRICTED_DENOM)]),
+                vesting_duration: None,
+                memo: None,
+                tags: None,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            response.attributes[6],
+            attr("additional_coins", format!("2{}", OTHER_RESTRICTED_DENOM))
+        );
+        assert_eq!(response.messages.len(), 2);
+
+        for (message, (denom, amount)) in response
+            .messages
+            .iter()
+            .zip([(RESTRICTED_DENOM, "5"), (OTHER_RESTRICTED_DENOM, "2")])
+        {
+            let expected_message: Binary = MsgTransferRequest {
+                amount: Some(Coin {
+                    denom: denom.to_owned(),
+                    amount: amount.into(),
+                }),
+                from_address: "sender".to_owned(),
+                to_address: MOCK_CONTRACT_ADDR.to_owned(),
+                administrator: MOCK_CONTRACT_ADDR.to_owned(),
+            }
+            .into();
+            match &message.msg {
+                CosmosMsg::Stargate { type_url, value } => {
+                    assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
+                    assert_eq!(value, &expected_message);
+                }
+                _ => panic!("unexpected cosmos message"),
+            }
+        }
+
+        let stored_transfer = load_transfer(&deps.storage, TRANSFER_ID).unwrap();
+        assert_eq!(
+            vec![coin(2, OTHER_RESTRICTED_DENOM)],
+            stored_transfer.additional_coins
+        );
+    }
+
+    #[test]
+    fn create_multi_denom_transfer_escrows_with_reply_on_error() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            ExecuteMsg::Transfer {
+                id: TRANSFER_ID.into(),
+                denom: RESTRICTED_DENOM.into(),
+                amount: Uint128::new(5),
+                recipient: "transfer_to".into(),
+                require_acceptance: None,
+                approvers: None,
+                recipients: None,
+                additional_coins: Some(vec![coin(2, OTHER_RESTRICTED_DENOM)]),
+                vesting_duration: None,
+                memo: None,
+                tags: None,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(2, response.messages.len());
+        assert!(response
+            .messages
+            .iter()
+            .all(|message| message.reply_on == ReplyOn::Error && message.id == ESCROW_REPLY_ID));
+    }
+
+    #[test]
+    fn create_transfer_with_unpermissioned_approver_throws_error() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let transfer_msg = ExecuteMsg::Transfer {
+            id: TRANSFER_ID.into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount: Uint128::new(1),
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: Some(vec!["approver".into(), "not_an_approver".into()]),
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![coin(1, RESTRICTED_DENOM)]);
+
+        // execute create transfer
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            transfer_msg,
+        );
+
+        match transfer_response {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::InvalidFields { fields } => {
+                    assert!(fields.contains(&"approvers".into()));
+                }
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+    }
+
+    #[test]
+    fn create_transfer_unrestricted_marker_throws_error() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let amount = Uint128::new(1);
+        let transfer_msg = ExecuteMsg::Transfer {
+            id: TRANSFER_ID.into(),
+            denom: "unrestricted-marker".into(),
+            amount,
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+
+        let sender_info = mock_info("sender", &[]);
+
+        let sender_balance = coin(amount.u128(), "unrestricted-marker");
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![sender_balance]);
+
+        // execute create transfer
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender_info.clone(),
+            transfer_msg.clone(),
+        );
+
+        // verify transfer response
+        match transfer_response {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::UnsupportedMarkerType => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+    }
+
+    #[test]
+    fn reject_transfers_refunds_each_transfer() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer_address = Addr::unchecked("transfer_address");
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let other_transfer_id = "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61";
+        let transfer = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            5,
+        );
+        store_test_transfer(&mut deps.storage, &transfer);
+        store_test_transfer(
+            &mut deps.storage,
+            &Transfer {
+                id: other_transfer_id.into(),
+                status: TransferStatus::Settled,
+                ..transfer
+            },
+        );
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(transfer_address.as_str(), &[]),
+            ExecuteMsg::RejectTransfers {
+                ids: vec![TRANSFER_ID.into(), other_transfer_id.into()],
+                reason: Some("closing the offering".into()),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(response.attributes[1], attr("succeeded", "1"));
+        assert_eq!(response.attributes[2], attr("failed_count", "1"));
+        assert!(response.attributes[3].value.starts_with(other_transfer_id));
+
+        assert_eq!(response.events.len(), 1);
+        assert!(response.events[0]
+            .attributes
+            .contains(&attr("reason", "closing the offering")));
+        assert_eq!(response.messages.len(), 1);
+
+        let stored_transfer = load_transfer(&deps.storage, TRANSFER_ID).unwrap();
+        assert_eq!(TransferStatus::Rejected, stored_transfer.status);
+        assert_eq!(Some(mock_env().block.time), stored_transfer.updated_at);
+    }
+
+    #[test]
+    fn approve_transfer_success() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer_address = Addr::unchecked("transfer_address");
+        let sender_address = Addr::unchecked("sender_address");
+        let recipient_address = Addr::unchecked("transfer_to");
+
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let amount = Uint128::new(1);
+        let sender_info = mock_info(transfer_address.as_str(), &[]);
+
+        store_test_transfer(
+            &mut deps.storage,
+            &Transfer {
+                id: TRANSFER_ID.into(),
+                sender: sender_address.to_owned(),
+                denom: RESTRICTED_DENOM.into(),
+                amount,
+                recipient: recipient_address.to_owned(),
+                status: TransferStatus::Pending,
+                rejection_reason: None,
+                require_acceptance: false,
+                approvals: vec![],
+                required_approvers: vec![],
+                legs: vec![],
+                additional_coins: vec![],
+                vesting: None,
+                claimed: Uint128::zero(),
+                memo: None,
+                created: None,
+                updated_at: None,
+                sequence: None,
+                tags: vec![],
+                position: None,
+                priority: false,
+                expires: None,
+            },
+        );
+
+        let approve_transfer_msg = ExecuteMsg::ApproveTransfer {
+            id: TRANSFER_ID.into(),
+        };
+
+        // execute approve transfer
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender_info.clone(),
+            approve_transfer_msg.clone(),
+        );
+
+        let expected_coin = Coin {
+            denom: RESTRICTED_DENOM.to_owned(),
+            amount: amount.into(),
+        };
+
+        // verify approve transfer response
+        match transfer_response {
+            Ok(response) => {
+                assert_eq!(response.attributes.len(), 11);
+                assert_eq!(
+                    response.attributes[0],
+                    attr("action", Action::Approve.to_string())
+                );
+                assert_eq!(response.attributes[1], attr("id", TRANSFER_ID));
+                assert_eq!(response.attributes[2], attr("denom", RESTRICTED_DENOM));
+                assert_eq!(response.attributes[3], attr("amount", amount.to_string()));
+                assert_eq!(response.attributes[4], attr("sender", sender_address));
+                assert_eq!(
+                    response.attributes[5],
+                    attr("recipient", recipient_address.to_owned())
+                );
+                assert_eq!(
+                    response.attributes[6],
+                    attr("admin", transfer_address.to_owned())
+                );
+                assert_eq!(
+                    response.attributes[7],
+                    attr("approved_height", mock_env().block.height.to_string())
+                );
+                assert_eq!(
+                    response.attributes[8],
+                    attr("approved_time", mock_env().block.time.to_string())
+                );
+                assert_eq!(response.attributes[9], attr("approval_count", "1"));
+                assert_eq!(response.attributes[10], attr("quorum", "1"));
+
+                assert_eq!(response.messages.len(), 1);
+
+                let expected_message: Binary = MsgTransferRequest {
+                    amount: Some(expected_coin),
+                    from_address: MOCK_CONTRACT_ADDR.to_owned(),
+                    to_address: recipient_address.to_string(),
+                    administrator: MOCK_CONTRACT_ADDR.to_owned(),
+                }
+                .into();
+
+                match &response.messages[0].msg {
+                    CosmosMsg::Stargate { type_url, value } => {
+                        assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
+                        assert_eq!(value, &expected_message);
+                    }
+                    _ => panic!("unexpected cosmos message"),
+                }
+            }
+            Err(error) => {
+                panic!("failed to create transfer: {:?}", error)
+            }
+        }
+
+        let stored_transfer = load_transfer(&deps.storage, TRANSFER_ID).unwrap();
+        assert_eq!(TransferStatus::Settled, stored_transfer.status);
+        assert_eq!(
+            vec![Approval {
+                approver: transfer_address,
+                height: mock_env().block.height,
+                time: mock_env().block.time,
+                amount: Some(amount),
+            }],
+            stored_transfer.approvals
+        );
+    }
+
+    #[test]
+    fn approve_transfer_sent_funds_returns_error() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer_address = Addr::unchecked("transfer_address");
+        let sender_address = Addr::unchecked("sender_address");
+        let recipient_address = Addr::unchecked("transfer_to");
+
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let amount = Uint128::new(1);
+        let sender_info = mock_info(transfer_address.as_str(), &[coin(1, RESTRICTED_DENOM)]);
+
+        let stored_transfer = Transfer {
+            id: TRANSFER_ID.into(),
+            sender: sender_address.to_owned(),
+            denom: RESTRICTED_DENOM.into(),
+            amount,
+            recipient: recipient_address.to_owned(),
+            status: TransferStatus::Pending,
+            rejection_reason: None,
+            require_acceptance: false,
+            approvals: vec![],
+            required_approvers: vec![],
+            legs: vec![],
+            additional_coins: vec![],
+            vesting: None,
+            claimed: Uint128::zero(),
+            memo: None,
+            created: None,
+            updated_at: None,
+            sequence: None,
+            tags: vec![],
+            position: None,
+            priority: false,
+            expires: None,
+        };
+        store_test_transfer(&mut deps.storage, &stored_transfer);
+
+        let approve_transfer_msg = ExecuteMsg::ApproveTransfer {
+            id: TRANSFER_ID.into(),
+        };
+
+        // execute approve transfer
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender_info.clone(),
+            approve_transfer_msg.clone(),
+        );
+
+        // verify approve transfer response
+        assert_sent_funds_unsupported_error(transfer_response);
+
+        assert_eq!(
+            stored_transfer,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap()
+        );
+    }
+
+    #[test]
+    fn approve_transfer_unauthorized() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer_address = Addr::unchecked("transfer_address");
+        let approver_address = Addr::unchecked("approver_address");
+        let sender_address = Addr::unchecked("sender_address");
+        let recipient_address = Addr::unchecked("transfer_to");
+
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let amount = Uint128::new(1);
+        let sender_info = mock_info(approver_address.as_str(), &[]);
+
+        let stored_transfer = Transfer {
+            id: TRANSFER_ID.into(),
+            sender: sender_address.to_owned(),
+            denom: RESTRICTED_DENOM.into(),
+            amount,
+            recipient: recipient_address.to_owned(),
+            status: TransferStatus::Pending,
+            rejection_reason: None,
+            require_acceptance: false,
+            approvals: vec![],
+            required_approvers: vec![],
+            legs: vec![],
+            additional_coins: vec![],
+            vesting: None,
+            claimed: Uint128::zero(),
+            memo: None,
+            created: None,
+            updated_at: None,
+            sequence: None,
+            tags: vec![],
+            position: None,
+            priority: false,
+            expires: None,
+        };
+        store_test_transfer(&mut deps.storage, &stored_transfer);
+
+        let approve_transfer_msg = ExecuteMsg::ApproveTransfer {
+            id: TRANSFER_ID.into(),
+        };
+
+        // execute approve transfer
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender_info.clone(),
+            approve_transfer_msg.clone(),
+        );
+
+        match transfer_response {
+            Ok(..) => {
+                panic!("expected error, but ok")
+            }
+            Err(error) => match error {
+                ContractError::Unauthorized { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        assert_eq!(
+            stored_transfer,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap()
+        );
+    }
+
+    #[test]
+    fn approve_transfer_unknown_transfer() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer_address = Addr::unchecked("transfer_address");
+        let sender_info = mock_info(transfer_address.as_str(), &[]);
+
+        let approve_transfer_msg = ExecuteMsg::ApproveTransfer {
+            id: TRANSFER_ID.into(),
+        };
+
+        // execute approve transfer
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender_info.clone(),
+            approve_transfer_msg.clone(),
+        );
+
+        assert_load_transfer_error(transfer_response);
+    }
+
+    #[test]
+    fn approve_transfer_partial_success() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer_address = Addr::unchecked("transfer_address");
+        let sender_address = Addr::unchecked("sender_address");
+        let recipient_address = Addr::unchecked("transfer_to");
+
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        store_test_transfer(
+            &mut deps.storage,
+            &Transfer {
+                id: TRANSFER_ID.into(),
+                sender: sender_address.to_owned(),
+                denom: RESTRICTED_DENOM.into(),
+                amount: Uint128::new(5),
+                recipient: recipient_address.to_owned(),
+                status: TransferStatus::Pending,
+                rejection_reason: None,
+                require_acceptance: false,
+                approvals: vec![],
+                required_approvers: vec![],
+                legs: vec![],
+                additional_coins: vec![],
+                vesting: None,
+                claimed: Uint128::zero(),
+                memo: None,
+                created: None,
+                updated_at: None,
+                sequence: None,
+                tags: vec![],
+                position: None,
+                priority: false,
+                expires: None,
+            },
+        );
+
+        let approve_transfer_msg = ExecuteMsg::ApproveTransferPartial {
+            id: TRANSFER_ID.into(),
+            amount: Uint128::new(3),
+        };
+
+        // execute partial approve transfer
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(transfer_address.as_str(), &[]),
+            approve_transfer_msg,
+        )
+        .unwrap();
+
+        assert_eq!(response.attributes.len(), 12);
+        assert_eq!(response.attributes[3], attr("amount", "3"));
+        assert_eq!(response.attributes[11], attr("refund_amount", "2"));
+
+        assert_eq!(response.messages.len(), 2);
+
+        let expected_settlement: Binary = MsgTransferRequest {
+            amount: Some(Coin {
+                denom: RESTRICTED_DENOM.to_owned(),
+                amount: "3".into(),
+            }),
+            from_address: MOCK_CONTRACT_ADDR.to_owned(),
+            to_address: recipient_address.to_string(),
+            administrator: MOCK_CONTRACT_ADDR.to_owned(),
+        }
+        .into();
+        let expected_refund: Binary = MsgTransferRequest {
+            amount: Some(Coin {
+                denom: RESTRICTED_DENOM.to_owned(),
+                amount: "2".into(),
+            }),
+            from_address: MOCK_CONTRACT_ADDR.to_owned(),
+            to_address: sender_address.to_string(),
+            administrator: MOCK_CONTRACT_ADDR.to_owned(),
+        }
+        .into();
+
+        for (message, expected_message) in response
+            .messages
+            .iter()
+            .zip([expected_settlement, expected_refund])
+        {
+            match &message.msg {
+                CosmosMsg::Stargate { type_url, value } => {
+                    assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
+                    assert_eq!(value, &expected_message);
+                }
+                _ => panic!("unexpected cosmos message"),
+            }
+        }
+
+        let stored_transfer = load_transfer(&deps.storage, TRANSFER_ID).unwrap();
+        assert_eq!(TransferStatus::Settled, stored_transfer.status);
+        assert_eq!(Uint128::new(3), stored_transfer.amount);
+    }
+
+    #[test]
+    fn approve_transfer_partial_exceeding_amount_returns_error() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer_address = Addr::unchecked("transfer_address");
+
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let stored_transfer = Transfer {
+            id: TRANSFER_ID.into(),
+            sender: Addr::unchecked("sender_address"),
+            denom: RESTRICTED_DENOM.into(),
+            amount: Uint128::new(5),
+            recipient: Addr::unchecked("transfer_to"),
+            status: TransferStatus::Pending,
+            rejection_reason: None,
+            require_acceptance: false,
+            approvals: vec![],
+            required_approvers: vec![],
+            legs: vec![],
+            additional_coins: vec![],
+            vesting: None,
+            claimed: Uint128::zero(),
+            memo: None,
+            created: None,
+            updated_at: None,
+            sequence: None,
+            tags: vec![],
+            position: None,
+            priority: false,
+            expires: None,
+        };
+        store_test_transfer(&mut deps.storage, &stored_transfer);
+
+        let approve_transfer_msg = ExecuteMsg::ApproveTransferPartial {
+            id: TRANSFER_ID.into(),
+            amount: Uint128::new(6),
+        };
+
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(transfer_address.as_str(), &[]),
+            approve_transfer_msg,
+        );
+
+        match transfer_response {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::InvalidFields { fields } => {
+                    assert!(fields.contains(&"amount".into()));
+                }
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        assert_eq!(
+            stored_transfer,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap()
+        );
+    }
+
+    #[test]
+    fn failed_settlement_is_recorded_and_retried() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let transfer_address = Addr::unchecked("transfer_address");
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        store_test_transfer(
+            &mut deps.storage,
+            &pending_test_transfer(
+                &Addr::unchecked("sender_address"),
+                &Addr::unchecked("transfer_to"),
+                2,
+            ),
+        );
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(transfer_address.as_str(), &[]),
+            ExecuteMsg::ApproveTransfer {
+                id: TRANSFER_ID.into(),
+            },
+        )
+        .unwrap();
+        assert_eq!(1, response.messages.len());
+        assert_eq!(ReplyOn::Always, response.messages[0].reply_on);
+        let settlement = response.messages[0].clone();
+
+        // the failure is recorded and the transfer fails until it is retried
+        let response = reply(
+            deps.as_mut(),
+            mock_env(),
+            Reply {
+                id: settlement.id,
+                result: SubMsgResult::Err("marker transfer failed".into()),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes[0],
+            attr("action", Action::SettlementFailed.to_string())
+        );
+        assert_eq!(response.attributes[3], attr("status", "failed"));
+        assert_eq!(
+            TransferStatus::Failed,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap().status
+        );
+        assert!(!TRANSFER_HISTORY.has(&deps.storage, &transfer_key(TRANSFER_ID).unwrap()));
+
+        let failed_settlements = |deps: Deps| -> Vec<FailedSettlement> {
+            let response = query(
+                deps,
+                mock_env(),
+                QueryMsg::GetFailedSettlements {
+                    id: TRANSFER_ID.into(),
+                },
+            )
+            .unwrap();
+            from_binary::<FailedSettlementsResponse>(&response)
+                .unwrap()
+                .failed_settlements
+        };
+        let failed = failed_settlements(deps.as_ref());
+        assert_eq!(1, failed.len());
+        assert_eq!(TRANSFER_ID, failed[0].transfer_id);
+        assert_eq!("marker transfer failed", failed[0].error);
+        assert_eq!(settlement.msg, CosmosMsg::from(failed[0].msg.clone()));
+
+        // only the contract admin can retry failed settlements
+        let retry_msg = ExecuteMsg::RetryFailed {
+            id: TRANSFER_ID.into(),
+        };
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other_address", &[]),
+            retry_msg.clone(),
+        ) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::Unauthorized { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            retry_msg.clone(),
+        )
+        .unwrap();
+        assert_eq!(response.attributes[2], attr("retried", "1"));
+        assert_eq!(1, response.messages.len());
+        assert_eq!(settlement.msg, response.messages[0].msg);
+        assert_ne!(settlement.id, response.messages[0].id);
+        assert!(failed_settlements(deps.as_ref()).is_empty());
+
+        // a settlement that goes through leaves nothing to retry and settles the transfer
+        let response = reply(
+            deps.as_mut(),
+            mock_env(),
+            Reply {
+                id: response.messages[0].id,
+                result: SubMsgResult::Ok(SubMsgResponse {
+                    events: vec![],
+                    data: None,
+                }),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes[0],
+            attr("action", Action::SettlementRetried.to_string())
+        );
+        assert!(failed_settlements(deps.as_ref()).is_empty());
+        assert_eq!(
+            TransferStatus::Settled,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap().status
+        );
+        let totals = TRANSFER_TOTALS.load(&deps.storage).unwrap();
+        assert_eq!((1, 1), (totals.settled, totals.failed));
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            retry_msg,
+        ) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::NoFailedSettlements { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        // replies are only expected for settlements still awaiting one
+        match reply(
+            deps.as_mut(),
+            mock_env(),
+            Reply {
+                id: settlement.id,
+                result: SubMsgResult::Err("marker transfer failed".into()),
+            },
+        ) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::UnknownReplyId { id } => assert_eq!(settlement.id, id),
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+    }
+
+    #[test]
+    fn approve_split_transfer_pays_each_recipient() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer_address = Addr::unchecked("transfer_address");
+        let sender_address = Addr::unchecked("sender_address");
+        let recipient_address = Addr::unchecked("transfer_to");
+        let other_recipient_address = Addr::unchecked("other_transfer_to");
+
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let mut transfer = pending_test_transfer(&sender_address, &recipient_address, 5);
+        transfer.legs = vec![
+            TransferLeg {
+                address: recipient_address.to_owned(),
+                amount: Uint128::new(3),
+            },
+            TransferLeg {
+                address: other_recipient_address.to_owned(),
+                amount: Uint128::new(2),
+            },
+        ];
+        store_test_transfer(&mut deps.storage, &transfer);
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(transfer_address.as_str(), &[]),
+            ExecuteMsg::ApproveTransfer {
+                id: TRANSFER_ID.into(),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(response.messages.len(), 2);
+
+        for (message, (to_address, amount)) in response
+            .messages
+            .iter()
+            .zip([(&recipient_address, "3"), (&other_recipient_address, "2")])
+        {
+            let expected_message: Binary = MsgTransferRequest {
+                amount: Some(Coin {
+                    denom: RESTRICTED_DENOM.to_owned(),
+                    amount: amount.into(),
+                }),
+                from_address: MOCK_CONTRACT_ADDR.to_owned(),
+                to_address: to_address.to_string(),
+                administrator: MOCK_CONTRACT_ADDR.to_owned(),
+            }
+            .into();
+            match &message.msg {
+                CosmosMsg::Stargate { type_url, value } => {
+                    assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
+                    assert_eq!(value, &expected_message);
+                }
+                _ => panic!("unexpected cosmos message"),
+            }
+        }
+
+        let stored_transfer = load_transfer(&deps.storage, TRANSFER_ID).unwrap();
+        assert_eq!(TransferStatus::Settled, stored_transfer.status);
+    }
+
+    #[test]
+    fn approve_split_transfer_partial_returns_error() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer_address = Addr::unchecked("transfer_address");
+        let recipient_address = Addr::unchecked("transfer_to");
+
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let mut transfer =
+            pending_test_transfer(&Addr::unchecked("sender_address"), &recipient_address, 5);
+        transfer.legs = vec![
+            TransferLeg {
+                address: recipient_address,
+                amount: Uint128::new(3),
+            },
+            TransferLeg {
+                address: Addr::unchecked("other_transfer_to"),
+                amount: Uint128::new(2),
+            },
+        ];
+        store_test_transfer(&mut deps.storage, &transfer);
+
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(transfer_address.as_str(), &[]),
+            ExecuteMsg::ApproveTransferPartial {
+                id: TRANSFER_ID.into(),
+                amount: Uint128::new(4),
+            },
+        );
+
+        match transfer_response {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::InvalidFields { fields } => {
+                    assert!(fields.contains(&"amount".into()));
+                }
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        assert_eq!(transfer, load_transfer(&deps.storage, TRANSFER_ID).unwrap());
+    }
+
+    #[test]
+    fn approve_multi_denom_transfer_settles_every_denom() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer_address = Addr::unchecked("transfer_address");
+        let recipient_address = Addr::unchecked("transfer_to");
+
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let mut transfer =
+            pending_test_transfer(&Addr::unchecked("sender_address"), &recipient_address, 5);
+        transfer.additional_coins = vec![coin(2, OTHER_RESTRICTED_DENOM)];
+        store_test_transfer(&mut deps.storage, &transfer);
+
+        // a partial approval cannot settle a multi-denom transfer
+        assert!(execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(transfer_address.as_str(), &[]),
+            ExecuteMsg::ApproveTransferPartial {
+                id: TRANSFER_ID.into(),
+                amount: Uint128::new(4),
+            },
+        )
+        .is_err());
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(transfer_address.as_str(), &[]),
+            ExecuteMsg::ApproveTransfer {
+                id: TRANSFER_ID.into(),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(response.messages.len(), 2);
+
+        for (message, (denom, amount)) in response
+            .messages
+            .iter()
+            .zip([(RESTRICTED_DENOM, "5"), (OTHER_RESTRICTED_DENOM, "2")])
+        {
+            let expected_message: Binary = MsgTransferRequest {
+                amount: Some(Coin {
+                    denom: denom.to_owned(),
+                    amount: amount.into(),
+                }),
+                from_address: MOCK_CONTRACT_ADDR.to_owned(),
+                to_address: recipient_address.to_string(),
+                administrator: MOCK_CONTRACT_ADDR.to_owned(),
+            }
+            .into();
+            match &message.msg {
+                CosmosMsg::Stargate { type_url, value } => {
+                    assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
+                    assert_eq!(value, &expected_message);
+                }
+                _ => panic!("unexpected cosmos message"),
+            }
+        }
+
+        let stored_transfer = load_transfer(&deps.storage, TRANSFER_ID).unwrap();
+        assert_eq!(TransferStatus::Settled, stored_transfer.status);
+    }
+
+    #[test]
+    fn approve_transfers_reports_failures() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer_address = Addr::unchecked("transfer_address");
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        store_test_transfer(
+            &mut deps.storage,
+            &pending_test_transfer(
+                &Addr::unchecked("sender_address"),
+                &Addr::unchecked("transfer_to"),
+                5,
+            ),
+        );
+        let unknown_id = "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61";
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(transfer_address.as_str(), &[]),
+            ExecuteMsg::ApproveTransfers {
+                ids: vec![TRANSFER_ID.into(), unknown_id.into()],
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            response.attributes[0],
+            attr("action", Action::ApproveTransfers.to_string())
+        );
+        assert_eq!(response.attributes[1], attr("succeeded", "1"));
+        assert_eq!(response.attributes[2], attr("failed_count", "1"));
+        assert_eq!(response.attributes[3].key, "failed");
+        assert!(response.attributes[3].value.starts_with(unknown_id));
+
+        assert_eq!(response.events.len(), 1);
+        assert!(response.events[0]
+            .attributes
+            .contains(&attr("id", TRANSFER_ID)));
+        assert_eq!(response.messages.len(), 1);
+
+        let stored_transfer = load_transfer(&deps.storage, TRANSFER_ID).unwrap();
+        assert_eq!(TransferStatus::Settled, stored_transfer.status);
+    }
+
+    #[test]
+    fn approve_transfer_requiring_acceptance_holds_escrow() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer_address = Addr::unchecked("transfer_address");
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let mut stored_transfer = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            3,
+        );
+        stored_transfer.require_acceptance = true;
+        store_test_transfer(&mut deps.storage, &stored_transfer);
+
+        let approve_transfer_msg = ExecuteMsg::ApproveTransfer {
+            id: TRANSFER_ID.into(),
+        };
+
+        // execute approve transfer
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(transfer_address.as_str(), &[]),
+            approve_transfer_msg,
+        )
+        .unwrap();
+
+        assert_eq!(
+            response.attributes[0],
+            attr("action", Action::Approve.to_string())
+        );
+        assert_eq!(response.messages.len(), 0);
+
+        assert_eq!(
+            TransferStatus::Approved,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap().status
+        );
+    }
+
+    #[test]
+    fn approve_transfer_waits_for_quorum() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let first_approver = Addr::unchecked("transfer_address");
+        let second_approver = Addr::unchecked("second_transfer_address");
+
+        let mut test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), first_approver.to_owned());
+        test_marker.access_control.push(AccessGrant {
+            address: second_approver.to_string(),
+            permissions: vec![Access::Transfer.into()],
+        });
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        APPROVAL_QUORUM
+            .save(&mut deps.storage, RESTRICTED_DENOM, &2)
+            .unwrap();
+        store_test_transfer(
+            &mut deps.storage,
+            &pending_test_transfer(
+                &Addr::unchecked("sender_address"),
+                &Addr::unchecked("transfer_to"),
+                3,
+            ),
+        );
+
+        let approve_transfer_msg = ExecuteMsg::ApproveTransfer {
+            id: TRANSFER_ID.into(),
+        };
+
+        // the first approval is recorded without settling
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(first_approver.as_str(), &[]),
+            approve_transfer_msg.clone(),
+        )
+        .unwrap();
+
+        assert_eq!(response.attributes[9], attr("approval_count", "1"));
+        assert_eq!(response.attributes[10], attr("quorum", "2"));
+        assert_eq!(response.messages.len(), 0);
+
+        let stored_transfer = load_transfer(&deps.storage, TRANSFER_ID).unwrap();
+        assert_eq!(TransferStatus::Pending, stored_transfer.status);
+        assert_eq!(1, stored_transfer.approvals.len());
+
+        // the same approver cannot approve twice
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(first_approver.as_str(), &[]),
+            approve_transfer_msg.clone(),
+        ) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::DuplicateApproval { approver } => {
+                    assert_eq!(first_approver.to_string(), approver)
+                }
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        // the second approval reaches quorum and settles, in a later block
+        let mut later = mock_env();
+        later.block.height += 5;
+        later.block.time = later.block.time.plus_seconds(30);
+        let response = execute(
+            deps.as_mut(),
+            later.clone(),
+            mock_info(second_approver.as_str(), &[]),
+            approve_transfer_msg,
+        )
+        .unwrap();
+
+        assert_eq!(response.attributes[9], attr("approval_count", "2"));
+        assert_eq!(response.messages.len(), 1);
+
+        // each approval records who approved and the block it was given in
+        let expected_approvals = vec![
+            Approval {
+                approver: first_approver,
+                height: mock_env().block.height,
+                time: mock_env().block.time,
+                amount: Some(Uint128::new(3)),
+            },
+            Approval {
+                approver: second_approver,
+                height: later.block.height,
+                time: later.block.time,
+                amount: Some(Uint128::new(3)),
+            },
+        ];
+        let stored_transfer = load_transfer(&deps.storage, TRANSFER_ID).unwrap();
+        assert_eq!(TransferStatus::Settled, stored_transfer.status);
+        assert_eq!(expected_approvals, stored_transfer.approvals);
+
+        let approvals: ApprovalsResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetApprovals {
+                    id: TRANSFER_ID.into(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(expected_approvals, approvals.approvals);
+        assert_eq!(2, approvals.quorum);
+    }
+
+    #[test]
+    fn approve_transfer_by_unlisted_approver_returns_error() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer_address = Addr::unchecked("transfer_address");
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let mut stored_transfer = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            3,
+        );
+        stored_transfer.required_approvers = vec![Addr::unchecked("required_approver")];
+        store_test_transfer(&mut deps.storage, &stored_transfer);
+
+        let approve_transfer_msg = ExecuteMsg::ApproveTransfer {
+            id: TRANSFER_ID.into(),
+        };
+
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(transfer_address.as_str(), &[]),
+            approve_transfer_msg,
+        ) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::Unauthorized { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        assert_eq!(
+            stored_transfer,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap()
+        );
+    }
+
+    #[test]
+    fn approve_transfer_not_pending_returns_error() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer_address = Addr::unchecked("transfer_address");
+        let sender_address = Addr::unchecked("sender_address");
+        let recipient_address = Addr::unchecked("transfer_to");
+
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let stored_transfer = Transfer {
+            id: TRANSFER_ID.into(),
+            sender: sender_address.to_owned(),
+            denom: RESTRICTED_DENOM.into(),
+            amount: Uint128::new(1),
+            recipient: recipient_address.to_owned(),
+            status: TransferStatus::Cancelled,
+            rejection_reason: None,
+            require_acceptance: false,
+            approvals: vec![],
+            required_approvers: vec![],
+            legs: vec![],
+            additional_coins: vec![],
+            vesting: None,
+            claimed: Uint128::zero(),
+            memo: None,
+            created: None,
+            updated_at: None,
+            sequence: None,
+            tags: vec![],
+            position: None,
+            priority: false,
+            expires: None,
+        };
+        store_test_transfer(&mut deps.storage, &stored_transfer);
+
+        let approve_transfer_msg = ExecuteMsg::ApproveTransfer {
+            id: TRANSFER_ID.into(),
+        };
+
+        // execute approve transfer
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(transfer_address.as_str(), &[]),
+            approve_transfer_msg,
+        );
+
+        match transfer_response {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::InvalidStatusTransition { from, to } => {
+                    assert_eq!(TransferStatus::Cancelled, from);
+                    assert_eq!(TransferStatus::Approved, to);
+                }
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        assert_eq!(
+            stored_transfer,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap()
+        );
+    }
+
+    #[test]
+    fn claim_vesting_transfer_releases_vested_portion() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer_address = Addr::unchecked("transfer_address");
+        let sender_address = Addr::unchecked("sender_address");
+        let recipient_address = Addr::unchecked("transfer_to");
+
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let mut transfer = pending_test_transfer(&sender_address, &recipient_address, 10);
+        transfer.vesting = Some(Vesting {
+            duration: 100,
+            start: None,
+        });
+        store_test_transfer(&mut deps.storage, &transfer);
+
+        let mut env = mock_env();
+        let approve_response = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(transfer_address.as_str(), &[]),
+            ExecuteMsg::ApproveTransfer {
+                id: TRANSFER_ID.into(),
+            },
+        )
+        .unwrap();
+
+        // nothing leaves escrow on approval
+        assert!(approve_response.messages.is_empty());
+
+        let claim_msg = ExecuteMsg::Claim {
+            id: TRANSFER_ID.into(),
+        };
+
+        match execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(recipient_address.as_str(), &[]),
+            claim_msg.clone(),
+        ) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(ContractError::NothingToClaim) => {}
+            Err(error) => panic!("unexpected error: {:?}", error),
+        }
+
+        env.block.time = env.block.time.plus_seconds(40);
+        let claim_response = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(recipient_address.as_str(), &[]),
+            claim_msg.clone(),
+        )
+        .unwrap();
+
+        assert_eq!(claim_response.attributes[3], attr("amount", "4"));
+        assert_eq!(claim_response.attributes[4], attr("claimed", "4"));
+
+        let expected_message: Binary = MsgTransferRequest {
+            amount: Some(Coin {
+                denom: RESTRICTED_DENOM.to_owned(),
+                amount: "4".into(),
+            }),
+            from_address: MOCK_CONTRACT_ADDR.to_owned(),
+            to_address: recipient_address.to_string(),
+            administrator: MOCK_CONTRACT_ADDR.to_owned(),
+        }
+        .into();
+        match &claim_response.messages[0].msg {
+            CosmosMsg::Stargate { type_url, value } => {
+                assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
+                assert_eq!(value, &expected_message);
+            }
+            _ => panic!("unexpected cosmos message"),
+        }
+
+        env.block.time = env.block.time.plus_seconds(100);
+        let claim_response = execute(
+            deps.as_mut(),
+            env,
+            mock_info(recipient_address.as_str(), &[]),
+            claim_msg,
+        )
+        .unwrap();
+
+        assert_eq!(claim_response.attributes[3], attr("amount", "6"));
+
+        let stored_transfer = load_transfer(&deps.storage, TRANSFER_ID).unwrap();
+        assert_eq!(TransferStatus::Settled, stored_transfer.status);
+        assert_eq!(Uint128::new(10), stored_transfer.claimed);
+    }
+
+    #[test]
+    fn decline_vesting_transfer_refunds_unclaimed_amount() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let sender_address = Addr::unchecked("sender_address");
+        let recipient_address = Addr::unchecked("transfer_to");
+
+        let mut transfer = pending_test_transfer(&sender_address, &recipient_address, 10);
+        transfer.status = TransferStatus::Approved;
+        transfer.vesting = Some(Vesting {
+            duration: 100,
+            start: Some(mock_env().block.time),
+        });
+        transfer.claimed = Uint128::new(4);
+        store_test_transfer(&mut deps.storage, &transfer);
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(recipient_address.as_str(), &[]),
+            ExecuteMsg::DeclineTransfer {
+                id: TRANSFER_ID.into(),
+            },
+        )
+        .unwrap();
+
+        let expected_message: Binary = MsgTransferRequest {
+            amount: Some(Coin {
+                denom: RESTRICTED_DENOM.to_owned(),
+                amount: "6".into(),
+            }),
+            from_address: MOCK_CONTRACT_ADDR.to_owned(),
+            to_address: sender_address.to_string(),
+            administrator: MOCK_CONTRACT_ADDR.to_owned(),
+        }
+        .into();
+        assert_eq!(response.messages.len(), 1);
+        match &response.messages[0].msg {
+            CosmosMsg::Stargate { type_url, value } => {
+                assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
+                assert_eq!(value, &expected_message);
+            }
+            _ => panic!("unexpected cosmos message"),
+        }
+    }
+
+    #[test]
+    fn accept_transfer_success() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let recipient_address = Addr::unchecked("transfer_to");
+        let mut stored_transfer =
+            pending_test_transfer(&Addr::unchecked("sender_address"), &recipient_address, 3);
+        stored_transfer.require_acceptance = true;
+        stored_transfer.status = TransferStatus::Approved;
+        store_test_transfer(&mut deps.storage, &stored_transfer);
+
+        let accept_transfer_msg = ExecuteMsg::AcceptTransfer {
+            id: TRANSFER_ID.into(),
+        };
+
+        // execute accept transfer
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(recipient_address.as_str(), &[]),
+            accept_transfer_msg,
+        )
+        .unwrap();
+
+        assert_eq!(response.attributes.len(), 6);
+        assert_eq!(
+            response.attributes[0],
+            attr("action", Action::Accept.to_string())
+        );
+
+        assert_eq!(response.messages.len(), 1);
+
+        let expected_message: Binary = MsgTransferRequest {
+            amount: Some(Coin {
+                denom: RESTRICTED_DENOM.to_owned(),
+                amount: "3".into(),
+            }),
+            from_address: MOCK_CONTRACT_ADDR.to_owned(),
+            to_address: recipient_address.to_string(),
+            administrator: MOCK_CONTRACT_ADDR.to_owned(),
+        }
+        .into();
+
+        match &response.messages[0].msg {
+            CosmosMsg::Stargate { type_url, value } => {
+                assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
+                assert_eq!(value, &expected_message);
+            }
+            _ => panic!("unexpected cosmos message"),
+        }
+
+        assert_eq!(
+            TransferStatus::Settled,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap().status
+        );
+    }
+
+    #[test]
+    fn accept_transfer_before_approval_returns_error() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let recipient_address = Addr::unchecked("transfer_to");
+        let mut stored_transfer =
+            pending_test_transfer(&Addr::unchecked("sender_address"), &recipient_address, 3);
+        stored_transfer.require_acceptance = true;
+        store_test_transfer(&mut deps.storage, &stored_transfer);
+
+        let accept_transfer_msg = ExecuteMsg::AcceptTransfer {
+            id: TRANSFER_ID.into(),
+        };
+
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(recipient_address.as_str(), &[]),
+            accept_transfer_msg,
+        );
+
+        match transfer_response {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::InvalidStatusTransition { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        assert_eq!(
+            stored_transfer,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap()
+        );
+    }
+
+    #[test]
+    fn accept_transfer_unauthorized() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let sender_address = Addr::unchecked("sender_address");
+        let mut stored_transfer =
+            pending_test_transfer(&sender_address, &Addr::unchecked("transfer_to"), 3);
+        stored_transfer.require_acceptance = true;
+        stored_transfer.status = TransferStatus::Approved;
+        store_test_transfer(&mut deps.storage, &stored_transfer);
+
+        let accept_transfer_msg = ExecuteMsg::AcceptTransfer {
+            id: TRANSFER_ID.into(),
+        };
+
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(sender_address.as_str(), &[]),
+            accept_transfer_msg,
+        );
+
+        match transfer_response {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::Unauthorized { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+    }
+
+    #[test]
+    fn set_approval_quorum_success() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let marker_admin = Addr::unchecked("marker_admin");
+        let mut test_marker: MarkerAccount = setup_restricted_marker();
+        test_marker.access_control.push(AccessGrant {
+            address: marker_admin.to_string(),
+            permissions: vec![Access::Admin.into()],
+        });
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let set_quorum_msg = ExecuteMsg::SetApprovalQuorum {
+            denom: RESTRICTED_DENOM.into(),
+            quorum: 3,
+        };
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(marker_admin.as_str(), &[]),
+            set_quorum_msg,
+        )
+        .unwrap();
+
+        assert_eq!(
+            response.attributes[0],
+            attr("action", Action::SetApprovalQuorum.to_string())
+        );
+        assert_eq!(response.attributes[2], attr("quorum", "3"));
+
+        let quorum: u32 = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetApprovalQuorum {
+                    denom: RESTRICTED_DENOM.into(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(3, quorum);
+    }
+
+    #[test]
+    fn set_approval_quorum_unauthorized() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        // transfer permission alone does not allow changing the quorum
+        let transfer_address = Addr::unchecked("transfer_address");
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let set_quorum_msg = ExecuteMsg::SetApprovalQuorum {
+            denom: RESTRICTED_DENOM.into(),
+            quorum: 3,
+        };
+
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(transfer_address.as_str(), &[]),
+            set_quorum_msg,
+        ) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::Unauthorized { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        assert_eq!(
+            None,
+            APPROVAL_QUORUM
+                .may_load(&deps.storage, RESTRICTED_DENOM)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn veto_transfer_success() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let veto_address = Addr::unchecked("compliance");
+        DENOM_VETO
+            .save(&mut deps.storage, RESTRICTED_DENOM, &veto_address)
+            .unwrap();
+
+        let sender_address = Addr::unchecked("sender_address");
+        let mut stored_transfer =
+            pending_test_transfer(&sender_address, &Addr::unchecked("transfer_to"), 3);
+        stored_transfer.require_acceptance = true;
+        stored_transfer.status = TransferStatus::Approved;
+        store_test_transfer(&mut deps.storage, &stored_transfer);
+
+        let veto_transfer_msg = ExecuteMsg::VetoTransfer {
+            id: TRANSFER_ID.into(),
+            reason: Some("sanctions screening".into()),
+        };
+
+        // execute veto transfer
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(veto_address.as_str(), &[]),
+            veto_transfer_msg,
+        )
+        .unwrap();
+
+        assert_eq!(response.attributes.len(), 8);
+        assert_eq!(
+            response.attributes[0],
+            attr("action", Action::Veto.to_string())
+        );
+        assert_eq!(response.attributes[6], attr("veto_address", "compliance"));
+        assert_eq!(
+            response.attributes[7],
+            attr("reason", "sanctions screening")
+        );
+
+        assert_eq!(response.messages.len(), 1);
+
+        let expected_message: Binary = MsgTransferRequest {
+            amount: Some(Coin {
+                denom: RESTRICTED_DENOM.to_owned(),
+                amount: "3".into(),
+            }),
+            to_address: sender_address.to_string(),
+            from_address: MOCK_CONTRACT_ADDR.to_owned(),
+            administrator: MOCK_CONTRACT_ADDR.to_owned(),
+        }
+        .into();
+
+        match &response.messages[0].msg {
+            CosmosMsg::Stargate { type_url, value } => {
+                assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
+                assert_eq!(value, &expected_message);
+            }
+            _ => panic!("unexpected cosmos message"),
+        }
+
+        let stored_transfer = load_transfer(&deps.storage, TRANSFER_ID).unwrap();
+        assert_eq!(TransferStatus::Rejected, stored_transfer.status);
+        assert_eq!(
+            Some("sanctions screening".to_string()),
+            stored_transfer.rejection_reason
+        );
+    }
+
+    #[test]
+    fn veto_transfer_unauthorized() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        GLOBAL_VETO
+            .save(&mut deps.storage, &Addr::unchecked("compliance"))
+            .unwrap();
+
+        let stored_transfer = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            3,
+        );
+        store_test_transfer(&mut deps.storage, &stored_transfer);
+
+        let veto_transfer_msg = ExecuteMsg::VetoTransfer {
+            id: TRANSFER_ID.into(),
+            reason: None,
+        };
+
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other_address", &[]),
+            veto_transfer_msg,
+        ) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::Unauthorized { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        assert_eq!(
+            stored_transfer,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap()
+        );
+    }
+
+    #[test]
+    fn set_global_veto_address() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let set_veto_msg = ExecuteMsg::SetVetoAddress {
+            denom: None,
+            address: Some("compliance".into()),
+        };
+
+        // only the contract admin can set the global veto
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other_address", &[]),
+            set_veto_msg.clone(),
+        ) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::Unauthorized { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            set_veto_msg,
+        )
+        .unwrap();
+
+        assert_eq!(
+            response.attributes[0],
+            attr("action", Action::SetVetoAddress.to_string())
+        );
+        assert_eq!(
+            Addr::unchecked("compliance"),
+            GLOBAL_VETO.load(&deps.storage).unwrap()
+        );
+    }
+
+    #[test]
+    fn transfers_use_cached_marker_type_until_refreshed() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+        mock_query_marker_response(&setup_restricted_marker(), &mut deps.querier);
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![coin(2, RESTRICTED_DENOM)]);
+
+        let transfer_msg = |id: &str| ExecuteMsg::Transfer {
+            id: id.into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount: Uint128::new(1),
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            transfer_msg(TRANSFER_ID),
+        )
+        .unwrap();
+        assert_eq!(
+            CachedMarker {
+                marker_type: MarkerType::Restricted,
+            },
+            MARKER_CACHE.load(&deps.storage, RESTRICTED_DENOM).unwrap()
+        );
+
+        // the marker no longer being restricted goes unnoticed until the cache is refreshed
+        let coin_marker = MarkerAccount {
+            marker_type: MarkerType::Coin.into(),
+            ..setup_restricted_marker()
+        };
+        mock_query_marker_response(&coin_marker, &mut deps.querier);
+
+        let refresh_msg = ExecuteMsg::RefreshMarkerCache {
+            denom: RESTRICTED_DENOM.into(),
+        };
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other_address", &[]),
+            refresh_msg.clone(),
+        ) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::Unauthorized { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            refresh_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes[0],
+            attr("action", Action::RefreshMarkerCache.to_string())
+        );
+        assert_eq!(
+            response.attributes[2],
+            attr("marker_type", "MARKER_TYPE_COIN")
+        );
+
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            transfer_msg("0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61"),
+        ) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::UnsupportedMarkerType => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+    }
+
+    #[test]
+    fn migrate_transfers_moves_legacy_keys_in_batches() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let pending = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            5,
+        );
+        let settled = Transfer {
+            id: "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61".into(),
+            status: TransferStatus::Settled,
+            ..pending.clone()
+        };
+        // stored under the bytes of their id, as transfers were before they were keyed by uuid
+        for transfer in [&pending, &settled] {
+            TRANSFER_STORAGE
+                .save(
+                    &mut deps.storage,
+                    transfer.id.as_bytes(),
+                    &StoredTransfer(transfer.clone()),
+                )
+                .unwrap();
+        }
+
+        // legacy transfers are read from their old key until they are moved
+        assert_eq!(pending, load_transfer(&deps.storage, TRANSFER_ID).unwrap());
+
+        // only the contract admin can migrate transfers
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other_address", &[]),
+            ExecuteMsg::MigrateTransfers { limit: None },
+        ) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::Unauthorized { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        let mut migrate = || -> Vec<Attribute> {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("contract_admin", &[]),
+                ExecuteMsg::MigrateTransfers { limit: Some(1) },
+            )
+            .unwrap()
+            .attributes[1..3]
+                .to_vec()
+        };
+        // each batch looks at a single transfer, continuing after the one before it
+        let batch = |migrated: &str, complete: &str| {
+            vec![attr("migrated", migrated), attr("complete", complete)]
+        };
+        assert_eq!(batch("1", "false"), migrate());
+        assert_eq!(batch("1", "false"), migrate());
+        // the pending transfer's new key sorts after its legacy key
+        assert_eq!(batch("0", "false"), migrate());
+        assert_eq!(batch("0", "true"), migrate());
+        assert!(LEGACY_MIGRATION_CURSOR
+            .may_load(&deps.storage)
+            .unwrap()
+            .is_none());
+
+        let pending_key = transfer_key(&pending.id).unwrap();
+        let settled_key = transfer_key(&settled.id).unwrap();
+        assert!(!TRANSFER_STORAGE.has(&deps.storage, pending.id.as_bytes()));
+        assert!(!TRANSFER_STORAGE.has(&deps.storage, settled.id.as_bytes()));
+        assert!(TRANSFER_STORAGE.has(&deps.storage, &pending_key));
+        // completed transfers are archived as they are moved
+        assert!(TRANSFER_HISTORY.has(&deps.storage, &settled_key));
+        assert_eq!(pending, load_transfer(&deps.storage, TRANSFER_ID).unwrap());
+    }
+
+    #[test]
+    fn prune_history_removes_old_completed_transfers() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let pending = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            5,
+        );
+        store_test_transfer(&mut deps.storage, &pending);
+        let settled = Transfer {
+            id: "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61".into(),
+            status: TransferStatus::Settled,
+            updated_at: Some(Timestamp::from_seconds(100)),
+            sequence: None,
+            ..pending.clone()
+        };
+        store_test_transfer(&mut deps.storage, &settled);
+        // a rejected transfer whose refund failed
+        let rejected = Transfer {
+            id: "9d3c5e7f-1a2b-4c6d-8e0f-a1b2c3d4e5f6".into(),
+            status: TransferStatus::Rejected,
+            updated_at: Some(Timestamp::from_seconds(50)),
+            sequence: None,
+            ..pending.clone()
+        };
+        store_test_transfer(&mut deps.storage, &rejected);
+        FAILED_SETTLEMENTS
+            .save(
+                &mut deps.storage,
+                (&transfer_key(&rejected.id).unwrap(), 2),
+                &FailedSettlement {
+                    transfer_id: rejected.id.to_owned(),
+                    error: "insufficient funds".into(),
+                    msg: transfer_marker_coins(
+                        Uint128::new(5),
+                        RESTRICTED_DENOM,
+                        &rejected.sender,
+                        &Addr::unchecked(MOCK_CONTRACT_ADDR),
+                        &Addr::unchecked(MOCK_CONTRACT_ADDR),
+                    ),
+                },
+            )
+            .unwrap();
+
+        // completed transfers leave the open transfers but can still be loaded
+        let settled_key = transfer_key(&settled.id).unwrap();
+        assert!(!TRANSFER_STORAGE.has(&deps.storage, &settled_key));
+        assert!(TRANSFER_HISTORY.has(&deps.storage, &settled_key));
+        assert_eq!(settled, load_transfer(&deps.storage, &settled.id).unwrap());
+
+        // only the contract admin can prune
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other_address", &[]),
+            ExecuteMsg::PruneHistory {
+                older_than: Timestamp::from_seconds(200),
+            },
+        ) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::Unauthorized { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        let prune = |deps: &mut OwnedDeps<_, _, _, _>, older_than: u64| -> Response {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("contract_admin", &[]),
+                ExecuteMsg::PruneHistory {
+                    older_than: Timestamp::from_seconds(older_than),
+                },
+            )
+            .unwrap()
+        };
+
+        let response = prune(&mut deps, 100);
+        assert_eq!(response.attributes[2], attr("pruned", "0"));
+
+        let response = prune(&mut deps, 200);
+        assert_eq!(
+            response.attributes[0],
+            attr("action", Action::PruneHistory.to_string())
+        );
+        assert_eq!(response.attributes[2], attr("pruned", "1"));
+
+        assert!(may_load_transfer(&deps.storage, &settled.id)
+            .unwrap()
+            .is_none());
+        // the failed refund keeps the rejected transfer until it is retried
+        assert_eq!(
+            rejected,
+            load_transfer(&deps.storage, &rejected.id).unwrap()
+        );
+        assert_eq!(pending, load_transfer(&deps.storage, TRANSFER_ID).unwrap());
+    }
+
+    #[test]
+    fn purge_settled_removes_transfers_past_retention() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let settled = Transfer {
+            status: TransferStatus::Settled,
+            updated_at: Some(Timestamp::from_seconds(100)),
+            sequence: None,
+            ..pending_test_transfer(
+                &Addr::unchecked("sender_address"),
+                &Addr::unchecked("transfer_to"),
+                5,
+            )
+        };
+        store_test_transfer(&mut deps.storage, &settled);
+
+        let purge = |deps: &mut OwnedDeps<_, _, _, _>, before: Option<u64>| {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("anyone", &[]),
+                ExecuteMsg::PurgeSettled {
+                    before: before.map(Timestamp::from_seconds),
+                    limit: None,
+                },
+            )
+        };
+
+        // nothing can be purged until the contract admin sets a retention period
+        match purge(&mut deps, None) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::RetentionPeriodNotSet => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        let set_retention_period = |deps: &mut OwnedDeps<_, _, _, _>, sender: &str, seconds| {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(sender, &[]),
+                ExecuteMsg::SetRetentionPeriod {
+                    seconds: Some(seconds),
+                },
+            )
+        };
+        match set_retention_period(&mut deps, "other_address", 0) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::Unauthorized { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        // the transfer completed more recently than the retention period ago
+        let seconds = mock_env().block.time.seconds() - 50;
+        set_retention_period(&mut deps, "contract_admin", seconds).unwrap();
+        let response = purge(&mut deps, None).unwrap();
+        assert_eq!(
+            response.attributes[0],
+            attr("action", Action::PurgeSettled.to_string())
+        );
+        assert_eq!(response.attributes[2], attr("purged", "0"));
+
+        set_retention_period(&mut deps, "contract_admin", 0).unwrap();
+        let response = purge(&mut deps, Some(100)).unwrap();
+        assert_eq!(response.attributes[2], attr("purged", "0"));
+        let response = purge(&mut deps, None).unwrap();
+        assert_eq!(response.attributes[2], attr("purged", "1"));
+
+        assert!(may_load_transfer(&deps.storage, &settled.id)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn creation_rate_limit_caps_transfers_per_window() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let set_limit_msg = ExecuteMsg::SetCreationRateLimit {
+            limit: Some(CreationRateLimit {
+                max_creations: 2,
+                blocks: 10,
+            }),
+        };
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other_address", &[]),
+            set_limit_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            set_limit_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![
+                attr("action", Action::SetCreationRateLimit.to_string()),
+                attr("max_creations", "2"),
+                attr("blocks", "10"),
+                attr("admin", "contract_admin"),
+            ]
+        );
+
+        let transfer_msg = |id: &str| ExecuteMsg::Transfer {
+            id: id.into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount: Uint128::new(1),
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+        let ids = [
+            "3e8a6b1c-2d4f-4a5b-8c9d-0e1f2a3b4c01",
+            "3e8a6b1c-2d4f-4a5b-8c9d-0e1f2a3b4c02",
+            "3e8a6b1c-2d4f-4a5b-8c9d-0e1f2a3b4c03",
+        ];
+
+        for id in &ids[..2] {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("sender", &[]),
+                transfer_msg(id),
+            )
+            .unwrap();
+        }
+
+        let mut later_env = mock_env();
+        later_env.block.height += 9;
+        let error = execute(
+            deps.as_mut(),
+            later_env.clone(),
+            mock_info("sender", &[]),
+            transfer_msg(ids[2]),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ContractError::CreationRateExceeded {
+                max_creations: 2,
+                blocks: 10
+            }
+        ));
+
+        // other senders have their own window
+        execute(
+            deps.as_mut(),
+            later_env.clone(),
+            mock_info("other_sender", &[]),
+            transfer_msg(ids[2]),
+        )
+        .unwrap();
+
+        // once the window has passed the creations no longer count
+        later_env.block.height += 1;
+        execute(
+            deps.as_mut(),
+            later_env.clone(),
+            mock_info("sender", &[]),
+            transfer_msg("3e8a6b1c-2d4f-4a5b-8c9d-0e1f2a3b4c04"),
+        )
+        .unwrap();
+        assert_eq!(
+            vec![(later_env.block.height, 1)],
+            SENDER_CREATIONS
+                .prefix(&Addr::unchecked("sender"))
+                .range(&deps.storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn set_pending_limit_caps_pending_transfers() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        store_test_transfer(
+            &mut deps.storage,
+            &pending_test_transfer(
+                &Addr::unchecked("sender"),
+                &Addr::unchecked("transfer_to"),
+                1,
+            ),
+        );
+
+        let set_pending_limit_msg = ExecuteMsg::SetPendingLimit { limit: Some(1) };
+
+        // only the contract admin can set the pending limit
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other_address", &[]),
+            set_pending_limit_msg.clone(),
+        ) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::Unauthorized { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            set_pending_limit_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes[0],
+            attr("action", Action::SetPendingLimit.to_string())
+        );
+        assert_eq!(response.attributes[1], attr("limit", "1"));
+
+        let transfer_msg = |id: &str| ExecuteMsg::Transfer {
+            id: id.into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount: Uint128::new(1),
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+        let other_id = "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61";
+
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            transfer_msg(other_id),
+        ) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::TooManyPendingTransfers { limit } => assert_eq!(1, limit),
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        // other senders have their own allowance
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other_sender", &[]),
+            transfer_msg(other_id),
+        )
+        .unwrap();
+
+        // removing the limit lets the sender queue more transfers again
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            ExecuteMsg::SetPendingLimit { limit: None },
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            transfer_msg("1f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61"),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn set_page_limit_caps_pages() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let transfer = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            5,
+        );
+        let ids = [
+            "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61",
+            "1f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61",
+            "2f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61",
+        ];
+        for id in ids {
+            store_test_transfer(
+                &mut deps.storage,
+                &Transfer {
+                    id: id.into(),
+                    ..transfer.clone()
+                },
+            );
+        }
+
+        let set_page_limit_msg = ExecuteMsg::SetPageLimit { limit: Some(2) };
+
+        // only the contract admin can set the page limit
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other_address", &[]),
+            set_page_limit_msg.clone(),
+        ) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::Unauthorized { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            set_page_limit_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes[0],
+            attr("action", Action::SetPageLimit.to_string())
+        );
+
+        let page = |start_after: Option<&str>| -> Vec<String> {
+            let response = query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetAllTransfers {
+                    start_after: start_after.map(String::from),
+                    limit: Some(10),
+                    order: Some(SortOrder::Descending),
+                },
+            )
+            .unwrap();
+            from_binary::<TransfersResponse>(&response)
+                .unwrap()
+                .transfers
+                .into_iter()
+                .map(|transfer| transfer.id)
+                .collect()
+        };
+
+        assert_eq!(vec![ids[2], ids[1]], page(None));
+        assert_eq!(vec![ids[0]], page(Some(ids[1])));
+    }
+
+    #[test]
+    fn has_marker_access_transfer_success() {
+        let transfer_address = Addr::unchecked("transfer_address");
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        assert!(has_marker_access_transfer(
+            transfer_address.to_owned(),
+            test_marker
+        ))
+    }
+
+    #[test]
+    fn has_marker_access_transfer_returns_false_with_no_permission() {
+        let transfer_address = Addr::unchecked("transfer_address");
+        let other_address = Addr::unchecked("other_address");
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        assert!(!has_marker_access_transfer(
+            other_address.to_owned(),
+            test_marker
+        ))
+    }
+
+    #[test]
+    fn has_marker_access_transfer_returns_false_without_transfer_permission() {
+        let non_transfer_address = Addr::unchecked("some_address_without_transfer");
+        let test_marker: MarkerAccount = MarkerAccount {
+            base_account: Some(BaseAccount {
+                address: "tp1l330sxue4suxz9dhc40e2pns0ymrytf8uz4squ".to_string(),
+                pub_key: None,
+                account_number: 10,
+                sequence: 0,
+            }),
+            manager: "tp13pnzut8zdjaqht7aqe7kk4ww5zfq04jzlytnmu".to_string(),
+            access_control: vec![AccessGrant {
+                address: "some_address_without_transfer".to_string(),
+                permissions: vec![(Access::Admin).into()],
+            }],
+            status: 0,
+            denom: "restricted_1".to_string(),
+            supply: "1000".to_string(),
+            marker_type: 0,
+            supply_fixed: false,
+            allow_governance_control: true,
+            allow_forced_transfer: false,
+            required_attributes: vec![],
+        };
+
+        assert!(!has_marker_access_transfer(
+            non_transfer_address.to_owned(),
+            test_marker
+        ))
+    }
+
+    #[test]
+    fn update_transfer_success() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let sender_address = Addr::unchecked("sender_address");
+        store_test_transfer(
+            &mut deps.storage,
+            &pending_test_transfer(&sender_address, &Addr::unchecked("transfer_to"), 3),
+        );
+        deps.querier
+            .mock_querier
+            .update_balance(sender_address.to_owned(), vec![coin(2, RESTRICTED_DENOM)]);
+
+        let update_transfer_msg = ExecuteMsg::UpdateTransfer {
+            id: TRANSFER_ID.into(),
+            new_recipient: Some("new_recipient".into()),
+            new_amount: Some(Uint128::new(5)),
+        };
+
+        // execute update transfer
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(sender_address.as_str(), &[]),
+            update_transfer_msg,
+        )
+        .unwrap();
+
+        assert_eq!(response.attributes.len(), 6);
+        assert_eq!(
+            response.attributes[0],
+            attr("action", Action::Update.to_string())
+        );
+        assert_eq!(response.attributes[3], attr("amount", "5"));
+        assert_eq!(response.attributes[5], attr("recipient", "new_recipient"));
+
+        // the increase is escrowed from the sender
+        assert_eq!(response.messages.len(), 1);
+
+        let expected_message: Binary = MsgTransferRequest {
+            amount: Some(Coin {
+                denom: RESTRICTED_DENOM.to_owned(),
+                amount: "2".into(),
+            }),
+            from_address: sender_address.to_string(),
+            to_address: MOCK_CONTRACT_ADDR.to_owned(),
+            administrator: MOCK_CONTRACT_ADDR.to_owned(),
+        }
+        .into();
+
+        match &response.messages[0].msg {
+            CosmosMsg::Stargate { type_url, value } => {
+                assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
+                assert_eq!(value, &expected_message);
+            }
+            _ => panic!("unexpected cosmos message"),
+        }
+
+        let stored_transfer = load_transfer(&deps.storage, TRANSFER_ID).unwrap();
+        assert_eq!(Uint128::new(5), stored_transfer.amount);
+        assert_eq!(Addr::unchecked("new_recipient"), stored_transfer.recipient);
+    }
+
+    #[test]
+    fn reduce_transfer_success() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let sender_address = Addr::unchecked("sender_address");
+        let transfer = pending_test_transfer(&sender_address, &Addr::unchecked("transfer_to"), 5);
+        store_test_transfer(&mut deps.storage, &transfer);
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(sender_address.as_str(), &[]),
+            ExecuteMsg::ReduceTransfer {
+                id: TRANSFER_ID.into(),
+                new_amount: Uint128::new(3),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(response.attributes[0], attr("action", "reduce"));
+        assert_eq!(response.attributes[3], attr("amount", "3"));
+        assert_eq!(response.attributes[4], attr("refund_amount", "2"));
+
+        let expected_refund: Binary = MsgTransferRequest {
+            amount: Some(Coin {
+                denom: RESTRICTED_DENOM.to_owned(),
+                amount: "2".into(),
+            }),
+            from_address: MOCK_CONTRACT_ADDR.to_owned(),
+            to_address: sender_address.to_string(),
+            administrator: MOCK_CONTRACT_ADDR.to_owned(),
+        }
+        .into();
+        assert_eq!(response.messages.len(), 1);
+        match &response.messages[0].msg {
+            CosmosMsg::Stargate { type_url, value } => {
+                assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
+                assert_eq!(value, &expected_refund);
+            }
+            _ => panic!("unexpected cosmos message"),
+        }
+
+        let stored_transfer = load_transfer(&deps.storage, TRANSFER_ID).unwrap();
+        assert_eq!(Uint128::new(3), stored_transfer.amount);
+    }
+
+    #[test]
+    fn reduce_transfer_to_larger_amount_returns_error() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let sender_address = Addr::unchecked("sender_address");
+        let transfer = pending_test_transfer(&sender_address, &Addr::unchecked("transfer_to"), 5);
+        store_test_transfer(&mut deps.storage, &transfer);
+
+        let reduce_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(sender_address.as_str(), &[]),
+            ExecuteMsg::ReduceTransfer {
+                id: TRANSFER_ID.into(),
+                new_amount: Uint128::new(5),
+            },
+        );
+
+        match reduce_response {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(ContractError::InvalidFields { fields }) => {
+                assert!(fields.contains(&"new_amount".into()));
+            }
+            Err(error) => panic!("unexpected error: {:?}", error),
+        }
+
+        assert_eq!(transfer, load_transfer(&deps.storage, TRANSFER_ID).unwrap());
+    }
+
+    #[test]
+    fn cancel_all_by_sender_cancels_matching_pending_transfers() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let sender_address = Addr::unchecked("sender_address");
+        let transfer = pending_test_transfer(&sender_address, &Addr::unchecked("transfer_to"), 5);
+        store_test_transfer(&mut deps.storage, &transfer);
+
+        // neither of these may be touched
+        let other_denom_id = "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61";
+        store_test_transfer(
+            &mut deps.storage,
+            &Transfer {
+                id: other_denom_id.into(),
+                denom: OTHER_RESTRICTED_DENOM.into(),
+                ..transfer.clone()
+            },
+        );
+        let other_sender_id = "1f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61";
+        store_test_transfer(
+            &mut deps.storage,
+            &Transfer {
+                id: other_sender_id.into(),
+                sender: Addr::unchecked("other_sender"),
+                ..transfer.clone()
+            },
+        );
+        let frozen_id = "9d3c5e7f-1a2b-4c6d-8e0f-a1b2c3d4e5f6";
+        store_test_transfer(
+            &mut deps.storage,
+            &Transfer {
+                id: frozen_id.into(),
+                ..transfer
+            },
+        );
+        TRANSFER_FREEZES
+            .save(
+                &mut deps.storage,
+                &transfer_key(frozen_id).unwrap(),
+                &TransferFreeze {
+                    reason: "under investigation".into(),
+                    frozen_by: Addr::unchecked("officer"),
+                    frozen_at: mock_env().block.time,
+                },
+            )
+            .unwrap();
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(sender_address.as_str(), &[]),
+            ExecuteMsg::CancelAllBySender {
+                denom: Some(RESTRICTED_DENOM.into()),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            response.attributes[0],
+            attr("action", Action::CancelAllBySender.to_string())
+        );
+        assert_eq!(response.attributes[1], attr("succeeded", "1"));
+        assert_eq!(response.attributes[2], attr("failed_count", "0"));
+        assert_eq!(response.messages.len(), 1);
+
+        let status = |id: &str| load_transfer(&deps.storage, id).unwrap().status;
+        assert_eq!(TransferStatus::Cancelled, status(TRANSFER_ID));
+        assert_eq!(TransferStatus::Pending, status(other_denom_id));
+        assert_eq!(TransferStatus::Pending, status(other_sender_id));
+        assert_eq!(TransferStatus::Pending, status(frozen_id));
+    }
+
+    #[test]
+    fn update_transfer_unauthorized() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let stored_transfer = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            3,
+        );
+        store_test_transfer(&mut deps.storage, &stored_transfer);
+
+        let update_transfer_msg = ExecuteMsg::UpdateTransfer {
+            id: TRANSFER_ID.into(),
+            new_recipient: Some("other_address".into()),
+            new_amount: None,
+        };
+
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other_address", &[]),
+            update_transfer_msg,
+        );
+
+        match transfer_response {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::Unauthorized { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        assert_eq!(
+            stored_transfer,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap()
+        );
+    }
+
+    #[test]
+    fn cancel_transfer_success() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let sender_address = Addr::unchecked("sender_address");
+        let recipient_address = Addr::unchecked("transfer_to");
+
+        let amount = Uint128::new(3);
+        let sender_info = mock_info(sender_address.as_str(), &[]);
+
+        store_test_transfer(
+            &mut deps.storage,
+            &Transfer {
+                id: TRANSFER_ID.into(),
+                sender: sender_address.to_owned(),
+                denom: RESTRICTED_DENOM.into(),
+                amount,
+                recipient: recipient_address.to_owned(),
+                status: TransferStatus::Pending,
+                rejection_reason: None,
+                require_acceptance: false,
+                approvals: vec![],
+                required_approvers: vec![],
+                legs: vec![],
+                additional_coins: vec![],
+                vesting: None,
+                claimed: Uint128::zero(),
+                memo: None,
+                created: None,
+                updated_at: None,
+                sequence: None,
+                tags: vec![],
+                position: None,
+                priority: false,
+                expires: None,
+            },
+        );
+
+        let cancel_transfer_msg = ExecuteMsg::CancelTransfer {
+            id: TRANSFER_ID.into(),
+        };
+
+        // execute cancel transfer
+        let cancel_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender_info.clone(),
+            cancel_transfer_msg.clone(),
+        );
+
+        let expected_coin = Coin {
+            denom: RESTRICTED_DENOM.to_owned(),
+            amount: amount.into(),
+        };
+
+        // verify approve transfer response
+        match cancel_response {
+            Ok(response) => {
+                assert_eq!(response.attributes.len(), 5);
+                assert_eq!(
+                    response.attributes[0],
+                    attr("action", Action::Cancel.to_string())
+                );
+                assert_eq!(response.attributes[1], attr("id", TRANSFER_ID));
+                assert_eq!(response.attributes[2], attr("denom", RESTRICTED_DENOM));
+                assert_eq!(response.attributes[3], attr("amount", amount.to_string()));
+                assert_eq!(
+                    response.attributes[4],
+                    attr("sender", sender_address.to_owned())
+                );
+
+                assert_eq!(response.messages.len(), 1);
+
+                let expected_message: Binary = MsgTransferRequest {
+                    amount: Some(expected_coin),
+                    from_address: MOCK_CONTRACT_ADDR.to_owned(),
+                    to_address: sender_info.clone().sender.to_string(),
+                    administrator: MOCK_CONTRACT_ADDR.to_owned(),
+                }
+                .into();
+
+                match &response.messages[0].msg {
+                    CosmosMsg::Stargate { type_url, value } => {
+                        assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
+                        assert_eq!(value, &expected_message);
+                    }
+                    _ => panic!("unexpected cosmos message"),
+                }
+            }
+            Err(error) => {
+                panic!("failed to cancel transfer: {:?}", error)
+            }
+        }
+
+        assert_eq!(
+            TransferStatus::Cancelled,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap().status
+        );
+    }
+
+    #[test]
+    fn cancel_transfer_sent_funds_returns_error() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let sender_address = Addr::unchecked("sender_address");
+        let recipient_address = Addr::unchecked("transfer_to");
+
+        let amount = Uint128::new(3);
+        let sender_info = mock_info(sender_address.as_str(), &[coin(1, RESTRICTED_DENOM)]);
+
+        let stored_transfer = Transfer {
+            id: TRANSFER_ID.into(),
+            sender: sender_address.to_owned(),
+            denom: RESTRICTED_DENOM.into(),
+            amount,
+            recipient: recipient_address.to_owned(),
+            status: TransferStatus::Pending,
+            rejection_reason: None,
+            require_acceptance: false,
+            approvals: vec![],
+            required_approvers: vec![],
+            legs: vec![],
+            additional_coins: vec![],
+            vesting: None,
+            claimed: Uint128::zero(),
+            memo: None,
+            created: None,
+            updated_at: None,
+            sequence: None,
+            tags: vec![],
+            position: None,
+            priority: false,
+            expires: None,
+        };
+        store_test_transfer(&mut deps.storage, &stored_transfer);
+
+        let cancel_transfer_msg = ExecuteMsg::CancelTransfer {
+            id: TRANSFER_ID.into(),
+        };
+
+        // execute cancel transfer
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender_info.clone(),
+            cancel_transfer_msg.clone(),
+        );
+
+        // verify cancel transfer response
+        assert_sent_funds_unsupported_error(transfer_response);
+
+        assert_eq!(
+            stored_transfer,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap()
+        );
+    }
+
+    #[test]
+    fn cancel_transfer_unauthorized() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let sender_address = Addr::unchecked("sender_address");
+        let recipient_address = Addr::unchecked("transfer_to");
+
+        let amount = Uint128::new(3);
+        let sender_info = mock_info("other_address", &[]);
+
+        let stored_transfer = Transfer {
+            id: TRANSFER_ID.into(),
+            sender: sender_address.to_owned(),
+            denom: RESTRICTED_DENOM.into(),
+            amount,
+            recipient: recipient_address.to_owned(),
+            status: TransferStatus::Pending,
+            rejection_reason: None,
+            require_acceptance: false,
+            approvals: vec![],
+            required_approvers: vec![],
+            legs: vec![],
+            additional_coins: vec![],
+            vesting: None,
+            claimed: Uint128::zero(),
+            memo: None,
+            created: None,
+            updated_at: None,
+            sequence: None,
+            tags: vec![],
+            position: None,
+            priority: false,
+            expires: None,
+        };
+        store_test_transfer(&mut deps.storage, &stored_transfer);
+
+        let cancel_transfer_msg = ExecuteMsg::CancelTransfer {
+            id: TRANSFER_ID.into(),
+        };
+
+        // execute cancel transfer
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender_info.clone(),
+            cancel_transfer_msg.clone(),
+        );
+
+        // verify cancel transfer response
+        match transfer_response {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::Unauthorized { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        assert_eq!(
+            stored_transfer,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap()
+        );
+    }
+
+    #[test]
+    fn cancel_transfer_unknown_transfer() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let sender_address = Addr::unchecked("sender_address");
+        let sender_info = mock_info(sender_address.as_str(), &[]);
+
+        let reject_transfer_msg = ExecuteMsg::CancelTransfer {
+            id: TRANSFER_ID.into(),
+        };
+
+        // execute cancel transfer
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender_info.clone(),
+            reject_transfer_msg.clone(),
+        );
+
+        assert_load_transfer_error(transfer_response);
+    }
+
+    #[test]
+    fn cancel_transfer_not_pending_returns_error() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let sender_address = Addr::unchecked("sender_address");
+        let recipient_address = Addr::unchecked("transfer_to");
+
+        let stored_transfer = Transfer {
+            id: TRANSFER_ID.into(),
+            sender: sender_address.to_owned(),
+            denom: RESTRICTED_DENOM.into(),
+            amount: Uint128::new(3),
+            recipient: recipient_address.to_owned(),
+            status: TransferStatus::Settled,
+            rejection_reason: None,
+            require_acceptance: false,
+            approvals: vec![],
+            required_approvers: vec![],
+            legs: vec![],
+            additional_coins: vec![],
+            vesting: None,
+            claimed: Uint128::zero(),
+            memo: None,
+            created: None,
+            updated_at: None,
+            sequence: None,
+            tags: vec![],
+            position: None,
+            priority: false,
+            expires: None,
+        };
+        store_test_transfer(&mut deps.storage, &stored_transfer);
+
+        let cancel_transfer_msg = ExecuteMsg::CancelTransfer {
+            id: TRANSFER_ID.into(),
+        };
+
+        // execute cancel transfer
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(sender_address.as_str(), &[]),
+            cancel_transfer_msg,
+        );
+
+        match transfer_response {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::InvalidStatusTransition { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        assert_eq!(
+            stored_transfer,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap()
+        );
+    }
+
+    #[test]
+    fn decline_transfer_success() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let sender_address = Addr::unchecked("sender_address");
+        let recipient_address = Addr::unchecked("transfer_to");
+
+        store_test_transfer(
+            &mut deps.storage,
+            &pending_test_transfer(&sender_address, &recipient_address, 3),
+        );
+
+        let decline_transfer_msg = ExecuteMsg::DeclineTransfer {
+            id: TRANSFER_ID.into(),
+        };
+
+        // execute decline transfer
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(recipient_address.as_str(), &[]),
+            decline_transfer_msg,
+        )
+        .unwrap();
+
+        assert_eq!(response.attributes.len(), 6);
+        assert_eq!(
+            response.attributes[0],
+            attr("action", Action::Decline.to_string())
+        );
+        assert_eq!(response.attributes[5], attr("recipient", "transfer_to"));
+
+        assert_eq!(response.messages.len(), 1);
+
+        let expected_message: Binary = MsgTransferRequest {
+            amount: Some(Coin {
+                denom: RESTRICTED_DENOM.to_owned(),
+                amount: "3".into(),
+            }),
+            to_address: sender_address.to_string(),
+            from_address: MOCK_CONTRACT_ADDR.to_owned(),
+            administrator: MOCK_CONTRACT_ADDR.to_owned(),
+        }
+        .into();
+
+        match &response.messages[0].msg {
+            CosmosMsg::Stargate { type_url, value } => {
+                assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
+                assert_eq!(value, &expected_message);
+            }
+            _ => panic!("unexpected cosmos message"),
+        }
+
+        assert_eq!(
+            TransferStatus::Declined,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap().status
+        );
+    }
+
+    #[test]
+    fn decline_transfer_unauthorized() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let sender_address = Addr::unchecked("sender_address");
+        let stored_transfer =
+            pending_test_transfer(&sender_address, &Addr::unchecked("transfer_to"), 3);
+        store_test_transfer(&mut deps.storage, &stored_transfer);
+
+        let decline_transfer_msg = ExecuteMsg::DeclineTransfer {
+            id: TRANSFER_ID.into(),
+        };
+
+        // the sender cannot decline on behalf of the recipient
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(sender_address.as_str(), &[]),
+            decline_transfer_msg,
+        );
+
+        match transfer_response {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::Unauthorized { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        assert_eq!(
+            stored_transfer,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap()
+        );
+    }
+
+    #[test]
+    fn force_cancel_transfer_success() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let sender_address = Addr::unchecked("sender_address");
+        let mut stored_transfer =
+            pending_test_transfer(&sender_address, &Addr::unchecked("transfer_to"), 3);
+        stored_transfer.status = TransferStatus::Approved;
+        store_test_transfer(&mut deps.storage, &stored_transfer);
+
+        let force_cancel_msg = ExecuteMsg::ForceCancelTransfer {
+            id: TRANSFER_ID.into(),
+        };
+
+        // execute force cancel transfer
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            force_cancel_msg,
+        )
+        .unwrap();
+
+        assert_eq!(response.attributes.len(), 6);
+        assert_eq!(
+            response.attributes[0],
+            attr("action", Action::ForceCancel.to_string())
+        );
+        assert_eq!(response.attributes[5], attr("admin", "contract_admin"));
+
+        assert_eq!(response.messages.len(), 1);
+
+        let expected_message: Binary = MsgTransferRequest {
+            amount: Some(Coin {
+                denom: RESTRICTED_DENOM.to_owned(),
+                amount: "3".into(),
+            }),
+            to_address: sender_address.to_string(),
+            from_address: MOCK_CONTRACT_ADDR.to_owned(),
+            administrator: MOCK_CONTRACT_ADDR.to_owned(),
+        }
+        .into();
+
+        match &response.messages[0].msg {
+            CosmosMsg::Stargate { type_url, value } => {
+                assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
+                assert_eq!(value, &expected_message);
+            }
+            _ => panic!("unexpected cosmos message"),
+        }
+
+        assert_eq!(
+            TransferStatus::Cancelled,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap().status
+        );
+    }
+
+    #[test]
+    fn cancel_approved_transfer_returns_error() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let sender_address = Addr::unchecked("sender_address");
+        let mut stored_transfer =
+            pending_test_transfer(&sender_address, &Addr::unchecked("transfer_to"), 3);
+        stored_transfer.status = TransferStatus::Approved;
+        store_test_transfer(&mut deps.storage, &stored_transfer);
+
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(sender_address.as_str(), &[]),
+            ExecuteMsg::CancelTransfer {
+                id: TRANSFER_ID.into(),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ContractError::InvalidStatusTransition {
+                from: TransferStatus::Approved,
+                to: TransferStatus::Cancelled,
+            }
+        ));
+        assert_eq!(
+            TransferStatus::Approved,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap().status
+        );
+    }
+
+    #[test]
+    fn force_cancel_transfer_unauthorized() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let sender_address = Addr::unchecked("sender_address");
+        let stored_transfer =
+            pending_test_transfer(&sender_address, &Addr::unchecked("transfer_to"), 3);
+        store_test_transfer(&mut deps.storage, &stored_transfer);
+
+        let force_cancel_msg = ExecuteMsg::ForceCancelTransfer {
+            id: TRANSFER_ID.into(),
+        };
+
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(sender_address.as_str(), &[]),
+            force_cancel_msg,
+        );
+
+        match transfer_response {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::Unauthorized { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        assert_eq!(
+            stored_transfer,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap()
+        );
+    }
+
+    #[test]
+    fn force_cancel_settled_transfer_returns_error() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let mut stored_transfer = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            3,
+        );
+        stored_transfer.status = TransferStatus::Settled;
+        store_test_transfer(&mut deps.storage, &stored_transfer);
+
+        let force_cancel_msg = ExecuteMsg::ForceCancelTransfer {
+            id: TRANSFER_ID.into(),
+        };
+
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            force_cancel_msg,
+        );
+
+        match transfer_response {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::InvalidStatusTransition { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+    }
+
+    #[test]
+    fn reject_transfer_success() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let sender_address = Addr::unchecked("sender_address");
+        let transfer_address = Addr::unchecked("transfer_address");
+        let recipient_address = Addr::unchecked("transfer_to");
+
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let amount = Uint128::new(3);
+        let sender_info = mock_info(transfer_address.as_str(), &[]);
+
+        store_test_transfer(
+            &mut deps.storage,
+            &Transfer {
+                id: TRANSFER_ID.into(),
+                sender: sender_address.to_owned(),
+                denom: RESTRICTED_DENOM.into(),
+                amount,
+                recipient: recipient_address.to_owned(),
+                status: TransferStatus::Pending,
+                rejection_reason: None,
+                require_acceptance: false,
+                approvals: vec![],
+                required_approvers: vec![],
+                legs: vec![],
+                additional_coins: vec![],
+                vesting: None,
+                claimed: Uint128::zero(),
+                memo: None,
+                created: None,
+                updated_at: None,
+                sequence: None,
+                tags: vec![],
+                position: None,
+                priority: false,
+                expires: None,
+            },
+        );
+
+        let reject_transfer_msg = ExecuteMsg::RejectTransfer {
+            id: TRANSFER_ID.into(),
+            reason: None,
+        };
+
+        // execute reject transfer
+        let reject_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender_info.clone(),
+            reject_transfer_msg.clone(),
+        );
+
+        let expected_coin = Coin {
+            denom: RESTRICTED_DENOM.to_owned(),
+            amount: amount.into(),
+        };
+
+        // verify approve transfer response
+        match reject_response {
+            Ok(response) => {
+                assert_eq!(response.attributes.len(), 7);
+                assert_eq!(
+                    response.attributes[0],
+                    attr("action", Action::Reject.to_string())
+                );
+                assert_eq!(response.attributes[1], attr("id", TRANSFER_ID));
+                assert_eq!(response.attributes[2], attr("denom", RESTRICTED_DENOM));
+                assert_eq!(response.attributes[3], attr("amount", amount.to_string()));
+                assert_eq!(
+                    response.attributes[4],
+                    attr("sender", sender_address.to_owned())
+                );
+                assert_eq!(
+                    response.attributes[5],
+                    attr("recipient", recipient_address.to_owned())
+                );
+                assert_eq!(
+                    response.attributes[6],
+                    attr("admin", transfer_address.to_owned())
+                );
+
+                assert_eq!(response.messages.len(), 1);
+
+                let expected_message: Binary = MsgTransferRequest {
+                    amount: Some(expected_coin),
+                    to_address: sender_address.to_string(),
+                    from_address: MOCK_CONTRACT_ADDR.to_owned(),
+                    administrator: MOCK_CONTRACT_ADDR.to_owned(),
+                }
+                .into();
+
+                match &response.messages[0].msg {
+                    CosmosMsg::Stargate { type_url, value } => {
+                        assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
+                        assert_eq!(value, &expected_message);
+                    }
+                    _ => panic!("unexpected cosmos message"),
+                }
+            }
+            Err(error) => {
+                panic!("failed to reject transfer: {:?}", error)
+            }
+        }
+
+        assert_eq!(
+            TransferStatus::Rejected,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap().status
+        );
+    }
+
+    #[test]
+    fn reject_transfer_with_reason() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer_address = Addr::unchecked("transfer_address");
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        store_test_transfer(
+            &mut deps.storage,
+            &pending_test_transfer(
+                &Addr::unchecked("sender_address"),
+                &Addr::unchecked("transfer_to"),
+                3,
+            ),
+        );
+
+        let reject_transfer_msg = ExecuteMsg::RejectTransfer {
+            id: TRANSFER_ID.into(),
+            reason: Some("recipient failed kyc".into()),
+        };
+
+        // execute reject transfer
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(transfer_address.as_str(), &[]),
+            reject_transfer_msg,
+        )
+        .unwrap();
+
+        assert_eq!(response.attributes.len(), 8);
+        assert_eq!(
+            response.attributes[7],
+            attr("reason", "recipient failed kyc")
+        );
+
+        let stored_transfer = load_transfer(&deps.storage, TRANSFER_ID).unwrap();
+        assert_eq!(TransferStatus::Rejected, stored_transfer.status);
+        assert_eq!(
+            Some("recipient failed kyc".to_string()),
+            stored_transfer.rejection_reason
+        );
+    }
+
+    #[test]
+    fn reject_transfer_sent_funds_returns_error() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let sender_address = Addr::unchecked("sender_address");
+        let transfer_address = Addr::unchecked("transfer_address");
+        let recipient_address = Addr::unchecked("transfer_to");
+
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let amount = Uint128::new(3);
+        let sender_info = mock_info(transfer_address.as_str(), &[coin(1, RESTRICTED_DENOM)]);
+
+        let stored_transfer = Transfer {
+            id: TRANSFER_ID.into(),
+            sender: sender_address.to_owned(),
+            denom: RESTRICTED_DENOM.into(),
+            amount,
+            recipient: recipient_address.to_owned(),
+            status: TransferStatus::Pending,
+            rejection_reason: None,
+            require_acceptance: false,
+            approvals: vec![],
+            required_approvers: vec![],
+            legs: vec![],
+            additional_coins: vec![],
+            vesting: None,
+            claimed: Uint128::zero(),
+            memo: None,
+            created: None,
+            updated_at: None,
+            sequence: None,
+            tags: vec![],
+            position: None,
+            priority: false,
+            expires: None,
+        };
+        store_test_transfer(&mut deps.storage, &stored_transfer);
+
+        let reject_transfer_msg = ExecuteMsg::RejectTransfer {
+            id: TRANSFER_ID.into(),
+            reason: None,
+        };
+
+        // execute reject transfer
+        let reject_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender_info.clone(),
+            reject_transfer_msg.clone(),
+        );
+
+        assert_sent_funds_unsupported_error(reject_response);
+
+        assert_eq!(
+            stored_transfer,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap()
+        );
+    }
+
+    #[test]
+    fn reject_transfer_unauthorized() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer_address = Addr::unchecked("transfer_address");
+        let sender_address = Addr::unchecked("sender_address");
+        let recipient_address = Addr::unchecked("transfer_to");
+
+        let test_marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let amount = Uint128::new(3);
+        let sender_info = mock_info(sender_address.as_str(), &[]);
+
+        let stored_transfer = Transfer {
+            id: TRANSFER_ID.into(),
+            sender: sender_address.to_owned(),
+            denom: RESTRICTED_DENOM.into(),
+            amount,
+            recipient: recipient_address.to_owned(),
+            status: TransferStatus::Pending,
+            rejection_reason: None,
+            require_acceptance: false,
+            approvals: vec![],
+            required_approvers: vec![],
+            legs: vec![],
+            additional_coins: vec![],
+            vesting: None,
+            claimed: Uint128::zero(),
+            memo: None,
+            created: None,
+            updated_at: None,
+            sequence: None,
+            tags: vec![],
+            position: None,
+            priority: false,
+            expires: None,
+        };
+        store_test_transfer(&mut deps.storage, &stored_transfer);
+
+        let reject_transfer_msg = ExecuteMsg::RejectTransfer {
+            id: TRANSFER_ID.into(),
+            reason: None,
+        };
+
+        // execute reject transfer
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender_info.clone(),
+            reject_transfer_msg.clone(),
+        );
+
+        // verify reject transfer response
+        match transfer_response {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::Unauthorized { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        assert_eq!(
+            stored_transfer,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap()
+        );
+    }
+
+    #[test]
+    fn reject_transfer_unknown_transfer() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let sender_address = Addr::unchecked("sender_address");
+        let sender_info = mock_info(sender_address.as_str(), &[]);
+
+        let reject_transfer_msg = ExecuteMsg::RejectTransfer {
+            id: TRANSFER_ID.into(),
+            reason: None,
+        };
+
+        // execute reject transfer
+        let transfer_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender_info.clone(),
+            reject_transfer_msg.clone(),
+        );
+
+        assert_load_transfer_error(transfer_response);
+    }
+
+    #[test]
+    fn query_transfer_by_id_test() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let sender_address = Addr::unchecked("sender_address");
+        let recipient_address = Addr::unchecked("transfer_to");
+
+        let amount = Uint128::new(3);
+
+        let transfer = &Transfer {
+            id: TRANSFER_ID.into(),
+            sender: sender_address.to_owned(),
+            denom: RESTRICTED_DENOM.into(),
+            amount,
+            recipient: recipient_address.to_owned(),
+            status: TransferStatus::Pending,
+            rejection_reason: None,
+            require_acceptance: false,
+            approvals: vec![],
+            required_approvers: vec![],
+            legs: vec![],
+            additional_coins: vec![],
+            vesting: None,
+            claimed: Uint128::zero(),
+            memo: None,
+            created: None,
+            updated_at: None,
+            sequence: None,
+            tags: vec![],
+            position: None,
+            priority: false,
+            expires: None,
+        };
+        store_test_transfer(&mut deps.storage, transfer);
+
+        let query_transfer_response = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetTransfer {
+                id: TRANSFER_ID.into(),
+            },
+        );
+
+        assert_eq!(
+            to_binary(&TransferResponse {
+                transfer: transfer.to_owned()
+            }),
+            query_transfer_response
+        );
+    }
+
+    #[test]
+    fn query_contract_info() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let query_contract_info_response =
+            query(deps.as_ref(), mock_env(), QueryMsg::GetContractInfo {});
+
+        match query_contract_info_response {
+            Ok(contract_info) => {
+                assert_eq!(
+                    contract_info,
+                    to_binary(&crate::msg::ContractInfoResponse {
+                        name: "contract_name".into()
+                    })
+                    .unwrap()
+                )
+            }
+            Err(error) => panic!("unexpected error: {:?}", error),
+        }
+    }
+
+    #[test]
+    fn query_version_info() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let result = cw2::set_contract_version(deps.as_mut().storage, CRATE_NAME, PACKAGE_VERSION);
+        match result {
+            Ok(..) => {}
+            Err(error) => panic!("unexpected error: {:?}", error),
+        }
+
+        let query_version_info_response =
+            query(deps.as_ref(), mock_env(), QueryMsg::GetVersionInfo {});
+
+        match query_version_info_response {
+            Ok(version_info) => {
+                assert_eq!(
+                    version_info,
+                    to_binary(&VersionInfoResponse {
+                        contract: CRATE_NAME.into(),
+                        version: PACKAGE_VERSION.into(),
+                    })
+                    .unwrap()
+                )
+            }
+            Err(error) => panic!("unexpected error: {:?}", error),
+        }
+    }
+
+    #[test]
+    fn query_all_transfers() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let amount = Uint128::new(1);
+        let transfer_msg = ExecuteMsg::Transfer {
+            id: TRANSFER_ID.into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount,
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+
+        let sender_info = mock_info("sender", &[]);
+
+        let sender_balance = coin(1, RESTRICTED_DENOM);
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![sender_balance]);
+
+        // execute create transfer
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            sender_info.clone(),
+            transfer_msg.clone(),
+        )
+        .unwrap();
+
+        // verify transfer response
+        let query_all_transfers_response = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetAllTransfers {
+                start_after: None,
+                limit: None,
+                order: None,
+            },
+        )
+        .unwrap();
+        let all_transfers = from_binary::<TransfersResponse>(&query_all_transfers_response)
+            .unwrap()
+            .transfers;
+        assert_eq!(1, all_transfers.len());
+        assert_eq!(TRANSFER_ID.to_string(), all_transfers[0].id);
+        assert_eq!(RESTRICTED_DENOM.to_string(), all_transfers[0].denom);
+        assert_eq!(amount, all_transfers[0].amount);
+        assert_eq!("transfer_to".to_string(), all_transfers[0].recipient);
+    }
+
+    #[test]
+    fn query_all_transfers_empty() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let sender_balance = coin(1, RESTRICTED_DENOM);
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![sender_balance]);
+
+        // verify transfer response
+        let query_all_transfers_response = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetAllTransfers {
+                start_after: None,
+                limit: None,
+                order: None,
+            },
+        )
+        .unwrap();
+        let all_transfers = from_binary::<TransfersResponse>(&query_all_transfers_response)
+            .unwrap()
+            .transfers;
+        assert_eq!(0, all_transfers.len());
+    }
+
+    #[test]
+    fn query_all_transfers_paged() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            5,
+        );
+        let ids = [
+            "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61",
+            "1f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61",
+            "2f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61",
+        ];
+        for id in ids {
+            store_test_transfer(
+                &mut deps.storage,
+                &Transfer {
+                    id: id.into(),
+                    ..transfer.clone()
+                },
+            );
+        }
+
+        let page = |start_after: Option<&str>| -> Vec<String> {
+            let response = query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetAllTransfers {
+                    start_after: start_after.map(String::from),
+                    limit: Some(2),
+                    order: None,
+                },
+            )
+            .unwrap();
+            from_binary::<TransfersResponse>(&response)
+                .unwrap()
+                .transfers
+                .into_iter()
+                .map(|transfer| transfer.id)
+                .collect()
+        };
+
+        assert_eq!(vec![ids[0], ids[1]], page(None));
+        assert_eq!(vec![ids[2]], page(Some(ids[1])));
+        assert!(page(Some(ids[2])).is_empty());
+    }
+
+    #[test]
+    fn query_transfers_by_sender() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let sender_address = Addr::unchecked("sender_address");
+        let transfer = pending_test_transfer(&sender_address, &Addr::unchecked("transfer_to"), 5);
+        let ids = [
+            "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61",
+            "1f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61",
+            "2f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61",
+        ];
+        for id in ids {
+            store_test_transfer(
+                &mut deps.storage,
+                &Transfer {
+                    id: id.into(),
+                    ..transfer.clone()
+                },
+            );
+        }
+        // sorts between the sender's transfers but must not be returned
+        store_test_transfer(
+            &mut deps.storage,
+            &Transfer {
+                id: "1a1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61".into(),
+                sender: Addr::unchecked("other_sender"),
+                ..transfer
+            },
+        );
+
+        let page = |start_after: Option<&str>| -> Vec<String> {
+            let response = query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetTransfersBySender {
+                    sender: sender_address.to_string(),
+                    start_after: start_after.map(String::from),
+                    limit: Some(2),
+                    order: None,
+                },
+            )
+            .unwrap();
+            from_binary::<TransfersResponse>(&response)
+                .unwrap()
+                .transfers
+                .into_iter()
+                .map(|transfer| transfer.id)
+                .collect()
+        };
+
+        assert_eq!(vec![ids[0], ids[1]], page(None));
+        assert_eq!(vec![ids[2]], page(Some(ids[1])));
+    }
+
+    #[test]
+    fn query_pending_approvals() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let approver_address = Addr::unchecked("approver");
+        let marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), approver_address.to_owned());
+        mock_query_marker_response(&marker, &mut deps.querier);
+
+        let transfer = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            5,
+        );
+        store_test_transfer(&mut deps.storage, &transfer);
+
+        // none of these may be returned to the approver
+        store_test_transfer(
+            &mut deps.storage,
+            &Transfer {
+                id: "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61".into(),
+                status: TransferStatus::Rejected,
+                ..transfer.clone()
+            },
+        );
+        store_test_transfer(
+            &mut deps.storage,
+            &Transfer {
+                id: "1f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61".into(),
+                required_approvers: vec![Addr::unchecked("other_approver")],
+                ..transfer.clone()
+            },
+        );
+        store_test_transfer(
+            &mut deps.storage,
+            &Transfer {
+                id: "2f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61".into(),
+                approvals: vec![Approval {
+                    approver: approver_address.to_owned(),
+                    height: 12345,
+                    time: mock_env().block.time,
+                    amount: Some(Uint128::new(5)),
+                }],
+                ..transfer.clone()
+            },
+        );
+
+        let pending_approvals = |approver: &str| -> Vec<Transfer> {
+            let response = query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetPendingApprovals {
+                    approver: approver.into(),
+                    start_after: None,
+                    limit: None,
+                    order: None,
+                },
+            )
+            .unwrap();
+            from_binary::<TransfersResponse>(&response)
+                .unwrap()
+                .transfers
+        };
+
+        assert_eq!(vec![transfer], pending_approvals("approver"));
+        assert!(pending_approvals("no_access").is_empty());
+    }
+
+    #[test]
+    fn pending_queries_follow_the_queue_order() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        mock_query_marker_response(&marker, &mut deps.querier);
+
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![coin(5, RESTRICTED_DENOM)]);
+
+        // created in the reverse of their id order
+        let ids = [
+            "ff1e5a3c-5f0e-4b8e-9b4a-1d5b0c6f7a01",
+            "af1e5a3c-5f0e-4b8e-9b4a-1d5b0c6f7a02",
+            "0f1e5a3c-5f0e-4b8e-9b4a-1d5b0c6f7a03",
+        ];
+        for id in ids {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("sender", &[]),
+                ExecuteMsg::Transfer {
+                    id: id.into(),
+                    denom: RESTRICTED_DENOM.into(),
+                    amount: Uint128::new(1),
+                    recipient: "transfer_to".into(),
+                    require_acceptance: None,
+                    approvers: None,
+                    recipients: None,
+                    additional_coins: None,
+                    vesting_duration: None,
+                    memo: None,
+                    tags: None,
+                },
+            )
+            .unwrap();
+        }
+
+        let queued = |deps: Deps, msg: QueryMsg| -> Vec<String> {
+            let response = query(deps, mock_env(), msg).unwrap();
+            from_binary::<TransfersResponse>(&response)
+                .unwrap()
+                .transfers
+                .into_iter()
+                .map(|transfer| transfer.id)
+                .collect()
+        };
+        let by_denom =
+            |start_after: Option<&str>, limit: Option<u32>| QueryMsg::GetPendingByDenom {
+                denom: RESTRICTED_DENOM.into(),
+                start_after: start_after.map(String::from),
+                limit,
+                order: None,
+            };
+        assert_eq!(ids.to_vec(), queued(deps.as_ref(), by_denom(None, None)));
+
+        let set_priority = |priority: bool| ExecuteMsg::SetTransferPriority {
+            id: ids[2].into(),
+            priority,
+        };
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            set_priority(true),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            set_priority(true),
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                attr("action", Action::SetTransferPriority.to_string()),
+                attr("id", ids[2]),
+                attr("priority", "true"),
+                attr("admin", "contract_admin"),
+            ],
+            response.attributes
+        );
+
+        // the prioritised transfer moves to the front, the rest stay first in first out
+        assert_eq!(
+            vec![ids[2], ids[0], ids[1]],
+            queued(
+                deps.as_ref(),
+                QueryMsg::GetPendingApprovals {
+                    approver: "approver".into(),
+                    start_after: None,
+                    limit: None,
+                    order: None,
+                }
+            )
+        );
+        assert_eq!(
+            vec![ids[0]],
+            queued(deps.as_ref(), by_denom(Some(ids[2]), Some(1)))
+        );
+        assert!(queued(
+            deps.as_ref(),
+            QueryMsg::GetPendingByDenom {
+                denom: "other_denom".into(),
+                start_after: None,
+                limit: None,
+                order: None,
+            }
+        )
+        .is_empty());
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            set_priority(false),
+        )
+        .unwrap();
+        assert_eq!(ids.to_vec(), queued(deps.as_ref(), by_denom(None, None)));
+    }
+
+    #[test]
+    fn archive_transfers_moves_completed_transfers_out_of_the_history() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let pending = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            5,
+        );
+        store_test_transfer(&mut deps.storage, &pending);
+        let settled = Transfer {
+            id: "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61".into(),
+            status: TransferStatus::Settled,
+            ..pending.clone()
+        };
+        store_test_transfer(&mut deps.storage, &settled);
+
+        let archive_msg = ExecuteMsg::ArchiveTransfers {
+            ids: vec![settled.id.to_owned(), pending.id.to_owned()],
+        };
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender_address", &[]),
+            archive_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+
+        // the open transfer is reported and left where it is
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            archive_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                attr("action", Action::ArchiveTransfers.to_string()),
+                attr("succeeded", "1"),
+                attr("failed_count", "1"),
+                attr(
+                    "failed",
+                    format!(
+                        "{}: {}",
+                        pending.id,
+                        ContractError::NotInHistory {
+                            id: pending.id.to_owned()
+                        }
+                    )
+                ),
+                attr("admin", "contract_admin"),
+            ],
+            response.attributes
+        );
+
+        let transfers = |deps: Deps, msg: QueryMsg| -> Vec<Transfer> {
+            let response = query(deps, mock_env(), msg).unwrap();
+            from_binary::<TransfersResponse>(&response)
+                .unwrap()
+                .transfers
+        };
+        assert!(transfers(
+            deps.as_ref(),
+            QueryMsg::GetTransferHistory {
+                start_after: None,
+                limit: None,
+                order: None,
+                filter: None,
+            }
+        )
+        .is_empty());
+        assert_eq!(
+            vec![pending.clone()],
+            transfers(
+                deps.as_ref(),
+                QueryMsg::GetAllTransfers {
+                    start_after: None,
+                    limit: None,
+                    order: None,
+                }
+            )
+        );
+        assert_eq!(
+            vec![settled.clone()],
+            transfers(
+                deps.as_ref(),
+                QueryMsg::GetArchivedTransfers {
+                    start_after: None,
+                    limit: None,
+                    order: None,
+                }
+            )
+        );
+        // archived transfers can still be looked up by id
+        assert_eq!(settled, load_transfer(&deps.storage, &settled.id).unwrap());
+    }
+
+    #[test]
+    fn query_pending_actions() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        mock_query_marker_response(&marker, &mut deps.querier);
+
+        let pending = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            5,
+        );
+        store_test_transfer(&mut deps.storage, &pending);
+        let awaiting_acceptance = Transfer {
+            id: "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61".into(),
+            status: TransferStatus::Approved,
+            require_acceptance: true,
+            ..pending.clone()
+        };
+        store_test_transfer(&mut deps.storage, &awaiting_acceptance);
+
+        let pending_actions = |address: &str| -> Vec<PendingAction> {
+            let response = query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetPendingActions {
+                    address: address.into(),
+                    start_after: None,
+                    limit: None,
+                    order: None,
+                },
+            )
+            .unwrap();
+            from_binary::<PendingActionsResponse>(&response)
+                .unwrap()
+                .pending_actions
+        };
+
+        assert_eq!(
+            vec![PendingAction {
+                transfer: pending.clone(),
+                actions: vec![AvailableAction::Cancel],
+            }],
+            pending_actions("sender_address")
+        );
+        assert_eq!(
+            vec![PendingAction {
+                transfer: pending,
+                actions: vec![AvailableAction::Approve],
+            }],
+            pending_actions("approver")
+        );
+        assert_eq!(
+            vec![PendingAction {
+                transfer: awaiting_acceptance,
+                actions: vec![AvailableAction::Accept],
+            }],
+            pending_actions("transfer_to")
+        );
+        assert!(pending_actions("bystander").is_empty());
+    }
+
+    #[test]
+    fn query_stats() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let mut transfer = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            5,
+        );
+        store_test_transfer(&mut deps.storage, &transfer);
+        store_test_transfer(
+            &mut deps.storage,
+            &Transfer {
+                id: "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61".into(),
+                ..transfer.clone()
+            },
+        );
+        transfer.status = TransferStatus::Rejected;
+        store_test_transfer(&mut deps.storage, &transfer);
+
+        let response = query(deps.as_ref(), mock_env(), QueryMsg::GetStats {}).unwrap();
+        assert_eq!(
+            TransferStats {
+                pending: 1,
+                rejected: 1,
+                ..TransferStats::default()
+            },
+            from_binary::<TransferStats>(&response).unwrap()
+        );
+    }
+
+    #[test]
+    fn query_totals() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let mut transfer = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            5,
+        );
+        store_test_transfer(&mut deps.storage, &transfer);
+        let mut other = Transfer {
+            id: "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61".into(),
+            ..transfer.clone()
+        };
+        store_test_transfer(&mut deps.storage, &other);
+
+        transfer.status = TransferStatus::Approved;
+        store_test_transfer(&mut deps.storage, &transfer);
+        transfer.status = TransferStatus::Settled;
+        store_test_transfer(&mut deps.storage, &transfer);
+        // approving without acceptance settles in the same save
+        other.status = TransferStatus::Settled;
+        store_test_transfer(&mut deps.storage, &other);
+
+        let response = query(deps.as_ref(), mock_env(), QueryMsg::GetTotals {}).unwrap();
+        assert_eq!(
+            TransferTotals {
+                created: 2,
+                approved: 2,
+                settled: 2,
+                ..TransferTotals::default()
+            },
+            from_binary::<TransferTotals>(&response).unwrap()
+        );
+    }
+
+    #[test]
+    fn query_denom_volume() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let denom_volume = |deps: Deps| -> DenomVolume {
+            let response = query(
+                deps,
+                mock_env(),
+                QueryMsg::GetDenomVolume {
+                    denom: RESTRICTED_DENOM.into(),
+                },
+            )
+            .unwrap();
+            from_binary(&response).unwrap()
+        };
+
+        let mut transfer = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            5,
+        );
+        store_test_transfer(&mut deps.storage, &transfer);
+        store_test_transfer(
+            &mut deps.storage,
+            &Transfer {
+                id: "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61".into(),
+                amount: Uint128::new(3),
+                ..transfer.clone()
+            },
+        );
+        assert_eq!(
+            DenomVolume {
+                escrowed: Uint128::new(8),
+                settled: Uint128::zero(),
+            },
+            denom_volume(deps.as_ref())
+        );
+
+        transfer.status = TransferStatus::Settled;
+        store_test_transfer(&mut deps.storage, &transfer);
+        assert_eq!(
+            DenomVolume {
+                escrowed: Uint128::new(3),
+                settled: Uint128::new(5),
+            },
+            denom_volume(deps.as_ref())
+        );
+    }
+
+    #[test]
+    fn query_denom_counters() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let denom_counters = |deps: Deps, height: Option<u64>| -> DenomCounters {
+            let response = query(
+                deps,
+                mock_env(),
+                QueryMsg::GetDenomCounters {
+                    denom: RESTRICTED_DENOM.into(),
+                    height,
+                },
+            )
+            .unwrap();
+            from_binary(&response).unwrap()
+        };
+
+        let transfer = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            5,
+        );
+        store_test_transfer(&mut deps.storage, &transfer);
+
+        let height = mock_env().block.height;
+        assert_eq!(
+            DenomCounters {
+                pending: 1,
+                escrowed: Uint128::new(5),
+            },
+            denom_counters(deps.as_ref(), None)
+        );
+        assert_eq!(
+            denom_counters(deps.as_ref(), None),
+            denom_counters(deps.as_ref(), Some(height + 1))
+        );
+        // the transfer was stored during the block, so it is missing from its start
+        assert_eq!(
+            DenomCounters::default(),
+            denom_counters(deps.as_ref(), Some(height))
+        );
+    }
+
+    #[test]
+    fn query_transfers_by_ids() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            5,
+        );
+        store_test_transfer(&mut deps.storage, &transfer);
+
+        let missing_id = "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61";
+        let response = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetTransfers {
+                ids: vec![missing_id.into(), TRANSFER_ID.into()],
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            TransferLookupsResponse {
+                results: vec![
+                    TransferLookup {
+                        id: missing_id.into(),
+                        transfer: None,
+                    },
+                    TransferLookup {
+                        id: TRANSFER_ID.into(),
+                        transfer: Some(transfer),
+                    },
+                ],
+            },
+            from_binary(&response).unwrap()
+        );
+    }
+
+    #[test]
+    fn query_contract_settings() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        APPROVAL_QUORUM
+            .save(&mut deps.storage, OTHER_RESTRICTED_DENOM, &2)
+            .unwrap();
+        DENOM_VETO
+            .save(
+                &mut deps.storage,
+                RESTRICTED_DENOM,
+                &Addr::unchecked("denom_veto"),
+            )
+            .unwrap();
+        GLOBAL_VETO
+            .save(&mut deps.storage, &Addr::unchecked("global_veto"))
+            .unwrap();
+
+        let response = query(deps.as_ref(), mock_env(), QueryMsg::GetContractSettings {}).unwrap();
+
+        assert_eq!(
+            ContractSettingsResponse {
+                name: "contract_name".into(),
+                admin: Some("contract_admin".into()),
+                pending_admin: None,
+                paused: false,
+                veto_address: Some(Addr::unchecked("global_veto")),
+                page_limit: MAX_PAGE_LIMIT,
+                pending_limit: None,
+                creation_rate_limit: None,
+                default_expiration: None,
+                allowed_denoms: None,
+                max_transfer_amount: None,
+                retention_period: None,
+                pending_recovery: None,
+                settlement_fee: None,
+                msg_fees: None,
+                creation_deposit: None,
+                fee_denom: DEFAULT_FEE_DENOM.into(),
+                denoms: vec![
+                    DenomSettings {
+                        denom: RESTRICTED_DENOM.into(),
+                        approval_quorum: 1,
+                        veto_address: Some(Addr::unchecked("denom_veto")),
+                        min_amount: None,
+                        max_amount: None,
+                        default_expiration: None,
+                        velocity_limit: None,
+                        auto_approve_threshold: None,
+                        frozen: false,
+                        fee_schedule: None,
+                    },
+                    DenomSettings {
+                        denom: OTHER_RESTRICTED_DENOM.into(),
+                        approval_quorum: 2,
+                        veto_address: None,
+                        min_amount: None,
+                        max_amount: None,
+                        default_expiration: None,
+                        velocity_limit: None,
+                        auto_approve_threshold: None,
+                        frozen: false,
+                        fee_schedule: None,
+                    },
+                ],
+            },
+            from_binary(&response).unwrap()
+        );
+    }
+
+    #[test]
+    fn query_list_denom_configs() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        APPROVAL_QUORUM
+            .save(&mut deps.storage, OTHER_RESTRICTED_DENOM, &2)
+            .unwrap();
+        DENOM_VETO
+            .save(
+                &mut deps.storage,
+                RESTRICTED_DENOM,
+                &Addr::unchecked("denom_veto"),
+            )
+            .unwrap();
+
+        let list = |start_after: Option<&str>, limit: Option<u32>| -> Vec<DenomSettings> {
+            let response = query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::ListDenomConfigs {
+                    start_after: start_after.map(String::from),
+                    limit,
+                },
+            )
+            .unwrap();
+            from_binary::<DenomConfigsResponse>(&response)
+                .unwrap()
+                .denoms
+        };
+
+        let restricted = DenomSettings {
+            denom: RESTRICTED_DENOM.into(),
+            approval_quorum: 1,
+            veto_address: Some(Addr::unchecked("denom_veto")),
+            min_amount: None,
+            max_amount: None,
+            default_expiration: None,
+            velocity_limit: None,
+            auto_approve_threshold: None,
+            frozen: false,
+            fee_schedule: None,
+        };
+        let other_restricted = DenomSettings {
+            denom: OTHER_RESTRICTED_DENOM.into(),
+            approval_quorum: 2,
+            veto_address: None,
+            min_amount: None,
+            max_amount: None,
+            default_expiration: None,
+            velocity_limit: None,
+            auto_approve_threshold: None,
+            frozen: false,
+            fee_schedule: None,
+        };
+        assert_eq!(
+            vec![restricted.clone(), other_restricted.clone()],
+            list(None, None)
+        );
+        assert_eq!(vec![restricted], list(None, Some(1)));
+        assert_eq!(
+            vec![other_restricted],
+            list(Some(RESTRICTED_DENOM), Some(1))
+        );
+    }
+
+    #[test]
+    fn query_export_state() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            5,
+        );
+        store_test_transfer(&mut deps.storage, &transfer);
+
+        let export = |start_after: Option<String>| -> ExportStateResponse {
+            let response = query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::ExportState {
+                    start_after,
+                    limit: None,
+                    order: None,
+                },
+            )
+            .unwrap();
+            from_binary(&response).unwrap()
+        };
+
+        assert_eq!(
+            ExportStateResponse {
+                format_version: EXPORT_FORMAT_VERSION,
+                config: State {
+                    name: "contract_name".into(),
+                    admin: None,
+                    default_expiration: None,
+                    allowed_denoms: None,
+                },
+                transfers: vec![transfer],
+            },
+            export(None)
+        );
+        assert!(export(Some(TRANSFER_ID.into())).transfers.is_empty());
+    }
+
+    #[test]
+    fn query_escrow_reconciliation() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            5,
+        );
+        store_test_transfer(&mut deps.storage, &transfer);
+        // escrows 5 of the initiator's denom
+        store_test_swap(&mut deps.storage);
+
+        let reconciliation = |deps: Deps| -> EscrowReconciliationResponse {
+            let response = query(
+                deps,
+                mock_env(),
+                QueryMsg::GetEscrowReconciliation {
+                    denom: RESTRICTED_DENOM.into(),
+                },
+            )
+            .unwrap();
+            from_binary(&response).unwrap()
+        };
+
+        // nothing has reached the contract account
+        let response = reconciliation(deps.as_ref());
+        assert_eq!(Uint128::new(5), response.transfer_escrow);
+        assert_eq!(Uint128::new(5), response.swap_escrow);
+        assert_eq!(Uint128::zero(), response.balance);
+        assert!(response.discrepancy);
+
+        deps.querier.mock_querier.update_balance(
+            mock_env().contract.address,
+            vec![coin(10, RESTRICTED_DENOM)],
+        );
+        assert!(!reconciliation(deps.as_ref()).discrepancy);
+    }
+
+    #[test]
+    fn sweep_orphaned_funds_returns_excess_to_marker_admin() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let mut test_marker: MarkerAccount = setup_restricted_marker();
+        test_marker.access_control.push(AccessGrant {
+            address: "marker_admin".into(),
+            permissions: vec![Access::Admin.into()],
+        });
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        store_test_transfer(
+            &mut deps.storage,
+            &pending_test_transfer(
+                &Addr::unchecked("sender_address"),
+                &Addr::unchecked("transfer_to"),
+                5,
+            ),
+        );
+        deps.querier
+            .mock_querier
+            .update_balance(mock_env().contract.address, vec![coin(8, RESTRICTED_DENOM)]);
+
+        let sweep_msg = ExecuteMsg::SweepOrphanedFunds {
+            denom: RESTRICTED_DENOM.into(),
+        };
+
+        // only the contract admin can sweep orphaned funds
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other_address", &[]),
+            sweep_msg.clone(),
+        ) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::Unauthorized { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            sweep_msg.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes[0],
+            attr("action", Action::SweepOrphanedFunds.to_string())
+        );
+        assert_eq!(response.attributes[2], attr("amount", "3"));
+        assert_eq!(response.attributes[3], attr("recipient", "marker_admin"));
+
+        let expected_message: Binary = MsgTransferRequest {
+            amount: Some(Coin {
+                denom: RESTRICTED_DENOM.into(),
+                amount: "3".into(),
+            }),
+            from_address: MOCK_CONTRACT_ADDR.to_owned(),
+            to_address: "marker_admin".into(),
+            administrator: MOCK_CONTRACT_ADDR.to_owned(),
+        }
+        .into();
+        assert_eq!(1, response.messages.len());
+        match &response.messages[0].msg {
+            CosmosMsg::Stargate { type_url, value } => {
+                assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
+                assert_eq!(value, &expected_message);
+            }
+            _ => panic!("unexpected cosmos message"),
+        }
+
+        // the escrowed coins are never swept
+        deps.querier
+            .mock_querier
+            .update_balance(mock_env().contract.address, vec![coin(5, RESTRICTED_DENOM)]);
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            sweep_msg,
+        ) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::NoOrphanedFunds { denom } => assert_eq!(RESTRICTED_DENOM, denom),
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+    }
+
+    #[test]
+    fn query_find_pending_transfers() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            5,
+        );
+        store_test_transfer(&mut deps.storage, &transfer);
+
+        // each differs from the transfer in one way and must not match
+        store_test_transfer(
+            &mut deps.storage,
+            &Transfer {
+                id: "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61".into(),
+                status: TransferStatus::Cancelled,
+                ..transfer.clone()
+            },
+        );
+        store_test_transfer(
+            &mut deps.storage,
+            &Transfer {
+                id: "1f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61".into(),
+                recipient: Addr::unchecked("other_recipient"),
+                ..transfer.clone()
+            },
+        );
+        store_test_transfer(
+            &mut deps.storage,
+            &Transfer {
+                id: "2f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61".into(),
+                denom: OTHER_RESTRICTED_DENOM.into(),
+                ..transfer.clone()
+            },
+        );
+
+        let response = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::FindPendingTransfers {
+                sender: "sender_address".into(),
+                recipient: "transfer_to".into(),
+                denom: RESTRICTED_DENOM.into(),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![transfer],
+            from_binary::<TransfersResponse>(&response)
+                .unwrap()
+                .transfers
+        );
+    }
+
+    #[test]
+    fn query_transfer_history() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            5,
+        );
+        store_test_transfer(&mut deps.storage, &transfer);
+        let settled = Transfer {
+            id: "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61".into(),
+            status: TransferStatus::Settled,
+            ..transfer.clone()
+        };
+        store_test_transfer(&mut deps.storage, &settled);
+        let rejected = Transfer {
+            id: "1f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61".into(),
+            status: TransferStatus::Rejected,
+            rejection_reason: Some("not allowed".into()),
+            ..transfer
+        };
+        store_test_transfer(&mut deps.storage, &rejected);
+
+        let history = |filter: Option<TransferStatus>| -> Vec<Transfer> {
+            let response = query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetTransferHistory {
+                    start_after: None,
+                    limit: None,
+                    order: None,
+                    filter,
+                },
+            )
+            .unwrap();
+            from_binary::<TransfersResponse>(&response)
+                .unwrap()
+                .transfers
+        };
+
+        assert_eq!(vec![settled, rejected.clone()], history(None));
+        assert_eq!(vec![rejected], history(Some(TransferStatus::Rejected)));
+
+        match query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetTransferHistory {
+                start_after: None,
+                limit: None,
+                order: None,
+                filter: Some(TransferStatus::Pending),
+            },
+        ) {
+            Ok(..) => panic!("expected error but was ok"),
+            Err(error) => assert!(error.to_string().contains("filter")),
+        }
+    }
+
+    #[test]
+    fn query_transfer_ignores_id_case() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            5,
+        );
+        store_test_transfer(&mut deps.storage, &transfer);
+
+        let response = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetTransfer {
+                id: TRANSFER_ID.to_uppercase(),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            TransferResponse { transfer },
+            from_binary(&response).unwrap()
+        );
+    }
+
+    #[test]
+    fn query_approvals() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        APPROVAL_QUORUM
+            .save(&mut deps.storage, RESTRICTED_DENOM, &2)
+            .unwrap();
+
+        let approval = Approval {
+            approver: Addr::unchecked("approver"),
+            height: 12345,
+            time: mock_env().block.time,
+            amount: Some(Uint128::new(5)),
+        };
+        store_test_transfer(
+            &mut deps.storage,
+            &Transfer {
+                approvals: vec![approval.clone()],
+                ..pending_test_transfer(
+                    &Addr::unchecked("sender_address"),
+                    &Addr::unchecked("transfer_to"),
+                    5,
+                )
+            },
+        );
+
+        let response = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetApprovals {
+                id: TRANSFER_ID.into(),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            ApprovalsResponse {
+                approvals: vec![approval],
+                quorum: 2,
+            },
+            from_binary(&response).unwrap()
+        );
+    }
+
+    #[test]
+    fn query_transfers_created() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let transfer = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            5,
+        );
+        // created at heights 100, 200 and 300, ten seconds apart, ids in reverse order
+        let ids = [
+            "2f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61",
+            "1f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61",
+            "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61",
+        ];
+        for (i, id) in ids.iter().enumerate() {
+            store_test_transfer(
+                &mut deps.storage,
+                &Transfer {
+                    id: id.to_string(),
+                    created: Some(Creation {
+                        height: 100 * (i as u64 + 1),
+                        time: Timestamp::from_seconds(10 * (i as u64 + 1)),
+                    }),
+                    ..transfer.clone()
+                },
+            );
+        }
+        // created before creation blocks were recorded, never returned
+        store_test_transfer(&mut deps.storage, &transfer);
+
+        let created = |range: CreationRange, start_after: Option<&str>| -> Vec<String> {
+            let response = query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetTransfersCreated {
+                    range,
+                    start_after: start_after.map(String::from),
+                    limit: Some(1),
+                    order: None,
+                },
+            )
+            .unwrap();
+            from_binary::<TransfersResponse>(&response)
+                .unwrap()
+                .transfers
+                .into_iter()
+                .map(|transfer| transfer.id)
+                .collect()
+        };
+
+        let heights = CreationRange::Height { from: 200, to: 301 };
+        assert_eq!(vec![ids[1]], created(heights, None));
+        assert_eq!(vec![ids[2]], created(heights, Some(ids[1])));
+        assert!(created(heights, Some(ids[2])).is_empty());
+
+        let times = CreationRange::Time {
+            from: Timestamp::from_seconds(0),
+            to: Timestamp::from_seconds(20),
+        };
+        assert_eq!(vec![ids[0]], created(times, None));
+        assert!(created(times, Some(ids[0])).is_empty());
+    }
+    #[test]
+    fn query_transfer_detailed() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let marker = MarkerAccount {
+            required_attributes: vec!["kyc.passport.pb".into()],
+            ..setup_restricted_marker()
+        };
+        mock_query_marker_response(&marker, &mut deps.querier);
+
+        let transfer = pending_test_transfer(
+            &Addr::unchecked("sender_address"),
+            &Addr::unchecked("transfer_to"),
+            5,
+        );
+        store_test_transfer(&mut deps.storage, &transfer);
+
+        let response = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetTransferDetailed {
+                id: TRANSFER_ID.into(),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            TransferDetailedResponse {
+                transfer,
+                markers: vec![MarkerDetails {
+                    denom: RESTRICTED_DENOM.into(),
+                    marker_type: MarkerType::Restricted,
+                    status: MarkerStatus::Active,
+                    required_attributes: vec!["kyc.passport.pb".into()],
+                    supply: "1000".into(),
+                }],
+            },
+            from_binary(&response).unwrap()
+        );
+    }
+
+    #[test]
+    fn audit_log_records_every_action() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let transfer_address = Addr::unchecked("transfer_address");
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            ExecuteMsg::Transfer {
+                id: TRANSFER_ID.into(),
+                denom: RESTRICTED_DENOM.into(),
+                amount: Uint128::new(1),
+                recipient: "transfer_to".into(),
+                require_acceptance: None,
+                approvers: None,
+                recipients: None,
+                additional_coins: None,
+                vesting_duration: None,
+                memo: None,
+                tags: None,
+            },
+        )
+        .unwrap();
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(transfer_address.as_str(), &[]),
+            ExecuteMsg::ApproveTransfer {
+                id: TRANSFER_ID.into(),
+            },
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            ExecuteMsg::SetPendingLimit { limit: Some(2) },
+        )
+        .unwrap();
+
+        // failed actions leave nothing behind
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other_address", &[]),
+            ExecuteMsg::SetPendingLimit { limit: Some(3) },
+        )
+        .unwrap_err();
+
+        // settlement replies are recorded as the contract's own actions
+        reply(
+            deps.as_mut(),
+            mock_env(),
+            Reply {
+                id: response.messages[0].id,
+                result: SubMsgResult::Err("marker transfer failed".into()),
+            },
+        )
+        .unwrap();
+
+        let audit_log = |deps: Deps, start_after: Option<u64>| -> Vec<AuditEntry> {
+            let response = query(
+                deps,
+                mock_env(),
+                QueryMsg::GetAuditLog {
+                    start_after,
+                    limit: None,
+                },
+            )
+            .unwrap();
+            from_binary::<AuditLogResponse>(&response).unwrap().entries
+        };
+
+        let entries = audit_log(deps.as_ref(), None);
+        assert_eq!(
+            vec![
+                (1, "create_transfer", "sender", Some(TRANSFER_ID)),
+                (2, "approve", "transfer_address", Some(TRANSFER_ID)),
+                (3, "set_pending_limit", "contract_admin", None),
+                (
+                    4,
+                    "settlement_failed",
+                    MOCK_CONTRACT_ADDR,
+                    Some(TRANSFER_ID)
+                ),
+            ],
+            entries
+                .iter()
+                .map(|entry| (
+                    entry.sequence,
+                    entry.action.as_str(),
+                    entry.actor.as_str(),
+                    entry.id.as_deref()
+                ))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(mock_env().block.height, entries[0].height);
+        assert_eq!(mock_env().block.time, entries[0].time);
+
+        // pages follow the sequence of the last entry read
+        assert_eq!(
+            vec![3, 4],
+            audit_log(deps.as_ref(), Some(2))
+                .iter()
+                .map(|entry| entry.sequence)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn update_admin() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let update_admin_msg = ExecuteMsg::UpdateAdmin {
+            new_admin: "new_admin".into(),
+        };
+
+        // only the contract admin can hand over the role
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other", &[]),
+            update_admin_msg.clone(),
+        ) {
+            Err(ContractError::Unauthorized { .. }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        // without a recorded admin the wasm admin is the contract admin
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            update_admin_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![
+                attr("action", "update_admin"),
+                attr("new_admin", "new_admin"),
+                attr("admin", "contract_admin"),
+            ]
+        );
+
+        // the role only changes hands once the proposed admin accepts it
+        assert_eq!(None, CONFIG.load(&deps.storage).unwrap().admin);
+        let settings: ContractSettingsResponse = from_binary(
+            &query(deps.as_ref(), mock_env(), QueryMsg::GetContractSettings {}).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(Some("contract_admin".to_string()), settings.admin);
+        assert_eq!(Some(Addr::unchecked("new_admin")), settings.pending_admin);
+
+        for sender in ["contract_admin", "other"].iter() {
+            match execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(sender, &[]),
+                ExecuteMsg::AcceptAdmin {},
+            ) {
+                Err(ContractError::Unauthorized { .. }) => {}
+                result => panic!("unexpected result: {:?}", result),
+            }
+        }
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("new_admin", &[]),
+            ExecuteMsg::AcceptAdmin {},
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![attr("action", "accept_admin"), attr("admin", "new_admin")]
+        );
+        assert_eq!(
+            Some(Addr::unchecked("new_admin")),
+            CONFIG.load(&deps.storage).unwrap().admin
+        );
+        assert_eq!(None, PENDING_ADMIN.may_load(&deps.storage).unwrap());
+
+        // the recorded admin replaces the wasm admin
+        let set_page_limit_msg = ExecuteMsg::SetPageLimit { limit: Some(2) };
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            set_page_limit_msg.clone(),
+        ) {
+            Err(ContractError::Unauthorized { .. }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("new_admin", &[]),
+            set_page_limit_msg,
+        )
+        .unwrap();
+
+        let settings: ContractSettingsResponse = from_binary(
+            &query(deps.as_ref(), mock_env(), QueryMsg::GetContractSettings {}).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(Some("new_admin".to_string()), settings.admin);
+        assert_eq!(None, settings.pending_admin);
+    }
+
+    #[test]
+    fn paused_contract_only_allows_refunds() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let sender = Addr::unchecked("sender");
+        let recipient = Addr::unchecked("recipient");
+        store_test_transfer(
+            &mut deps.storage,
+            &pending_test_transfer(&sender, &recipient, 3),
+        );
+
+        // only the contract admin can pause the contract
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other", &[]),
+            ExecuteMsg::Pause {},
+        ) {
+            Err(ContractError::Unauthorized { .. }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            ExecuteMsg::Pause {},
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![attr("action", "pause"), attr("admin", "contract_admin")]
+        );
+
+        let settings: ContractSettingsResponse = from_binary(
+            &query(deps.as_ref(), mock_env(), QueryMsg::GetContractSettings {}).unwrap(),
+        )
+        .unwrap();
+        assert!(settings.paused);
+
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            ExecuteMsg::ApproveTransfer {
+                id: TRANSFER_ID.into(),
+            },
+        ) {
+            Err(ContractError::Paused) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        // the sender can still take their coins back
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(sender.as_str(), &[]),
+            ExecuteMsg::CancelTransfer {
+                id: TRANSFER_ID.into(),
+            },
+        )
+        .unwrap();
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            ExecuteMsg::Unpause {},
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![attr("action", "unpause"), attr("admin", "contract_admin")]
+        );
+        assert!(!PAUSED.load(&deps.storage).unwrap());
+    }
+
+    #[test]
+    fn denom_config_limits_new_transfers() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        mock_query_marker_response(&marker, &mut deps.querier);
+
+        let set_denom_config_msg = ExecuteMsg::SetDenomConfig {
+            denom: RESTRICTED_DENOM.into(),
+            min_amount: Some(Uint128::new(2)),
+            max_amount: Some(Uint128::new(5)),
+            default_expiration: Some(100),
+        };
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other", &[]),
+            set_denom_config_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            set_denom_config_msg,
+        )
+        .unwrap();
+
+        let transfer_msg = |amount: u128| ExecuteMsg::Transfer {
+            id: TRANSFER_ID.into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount: Uint128::new(amount),
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+        let sender_info = mock_info("sender", &[]);
+
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender_info.clone(),
+            transfer_msg(1),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ContractError::AmountBelowMinimum { min, .. } if min == Uint128::new(2)
+        ));
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            sender_info.clone(),
+            transfer_msg(6),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ContractError::AmountAboveMaximum { max, .. } if max == Uint128::new(5)
+        ));
+
+        execute(deps.as_mut(), mock_env(), sender_info, transfer_msg(5)).unwrap();
+        let expires = mock_env().block.time.plus_seconds(100);
+        assert_eq!(
+            Some(expires),
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap().expires
+        );
+
+        // an expired transfer can no longer be approved
+        let mut expired_env = mock_env();
+        expired_env.block.time = expires;
+        let error = execute(
+            deps.as_mut(),
+            expired_env,
+            mock_info("approver", &[]),
+            ExecuteMsg::ApproveTransfer {
+                id: TRANSFER_ID.into(),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::TransferExpired { .. }));
+
+        let configs: DenomConfigsResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::ListDenomConfigs {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            vec![DenomSettings {
+                denom: RESTRICTED_DENOM.into(),
+                approval_quorum: 1,
+                veto_address: None,
+                min_amount: Some(Uint128::new(2)),
+                max_amount: Some(Uint128::new(5)),
+                default_expiration: Some(100),
+                velocity_limit: None,
+                auto_approve_threshold: None,
+                frozen: false,
+                fee_schedule: None,
+            }],
+            configs.denoms
+        );
+    }
+
+    #[test]
+    fn max_transfer_amount_caps_every_denom() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        let set_cap_msg = ExecuteMsg::SetMaxTransferAmount {
+            amount: Some(Uint128::new(4)),
+        };
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other", &[]),
+            set_cap_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            set_cap_msg,
+        )
+        .unwrap();
+
+        let transfer_msg = |amount: u128| ExecuteMsg::Transfer {
+            id: TRANSFER_ID.into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount: Uint128::new(amount),
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            transfer_msg(5),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ContractError::AmountExceedsCap { cap } if cap == Uint128::new(4)
+        ));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            transfer_msg(4),
+        )
+        .unwrap();
+
+        let settings: ContractSettingsResponse = from_binary(
+            &query(deps.as_ref(), mock_env(), QueryMsg::GetContractSettings {}).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(Some(Uint128::new(4)), settings.max_transfer_amount);
+    }
+
+    #[test]
+    fn velocity_limit_rolls_with_the_window() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            ExecuteMsg::SetVelocityLimit {
+                denom: RESTRICTED_DENOM.into(),
+                limit: Some(VelocityLimit {
+                    amount: Uint128::new(5),
+                    window: 100,
+                }),
+            },
+        )
+        .unwrap();
+
+        let transfer_msg = |id: &str, amount: u128| ExecuteMsg::Transfer {
+            id: id.into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount: Uint128::new(amount),
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+        let ids = [
+            "1b2e5a3c-5f0e-4b8e-9b4a-1d5b0c6f7a01",
+            "1b2e5a3c-5f0e-4b8e-9b4a-1d5b0c6f7a02",
+            "1b2e5a3c-5f0e-4b8e-9b4a-1d5b0c6f7a03",
+        ];
+        let at = |seconds: u64| {
+            let mut env = mock_env();
+            env.block.time = mock_env().block.time.plus_seconds(seconds);
+            env
+        };
+
+        execute(
+            deps.as_mut(),
+            at(0),
+            mock_info("sender", &[]),
+            transfer_msg(ids[0], 3),
+        )
+        .unwrap();
+
+        let error = execute(
+            deps.as_mut(),
+            at(50),
+            mock_info("sender", &[]),
+            transfer_msg(ids[1], 3),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ContractError::VelocityLimitExceeded { remaining, .. } if remaining == Uint128::new(2)
+        ));
+
+        // other senders have limits of their own
+        execute(
+            deps.as_mut(),
+            at(50),
+            mock_info("other_sender", &[]),
+            transfer_msg(ids[1], 3),
+        )
+        .unwrap();
+
+        // the first transfer falls out of the window
+        execute(
+            deps.as_mut(),
+            at(100),
+            mock_info("sender", &[]),
+            transfer_msg(ids[2], 5),
+        )
+        .unwrap();
+
+        let velocity: VelocityResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                at(100),
+                QueryMsg::GetVelocity {
+                    sender: "sender".into(),
+                    denom: RESTRICTED_DENOM.into(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(Uint128::new(5), velocity.used);
+        assert_eq!(
+            1,
+            SENDER_VELOCITY
+                .prefix((&Addr::unchecked("sender"), RESTRICTED_DENOM))
+                .keys(&deps.storage, None, None, Order::Ascending)
+                .count()
+        );
+    }
+
+    #[test]
+    fn allowlist_limits_recipients() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let mut marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        marker.access_control.push(AccessGrant {
+            address: "marker_admin".into(),
+            permissions: vec![Access::Admin.into()],
+        });
+        mock_query_marker_response(&marker, &mut deps.querier);
+
+        let enable_msg = ExecuteMsg::SetAllowlistEnabled {
+            denom: RESTRICTED_DENOM.into(),
+            enabled: true,
+        };
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            enable_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("marker_admin", &[]),
+            enable_msg,
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("marker_admin", &[]),
+            ExecuteMsg::UpdateAllowlist {
+                denom: RESTRICTED_DENOM.into(),
+                add: vec!["transfer_to".into()],
+                remove: vec![],
+            },
+        )
+        .unwrap();
+
+        let transfer_msg = |recipient: &str| ExecuteMsg::Transfer {
+            id: TRANSFER_ID.into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount: Uint128::new(3),
+            recipient: recipient.into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            transfer_msg("other"),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ContractError::RecipientNotAllowed { recipient, .. } if recipient == "other"
+        ));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            transfer_msg("transfer_to"),
+        )
+        .unwrap();
+
+        // a recipient removed after the transfer was created can no longer be paid
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("marker_admin", &[]),
+            ExecuteMsg::UpdateAllowlist {
+                denom: RESTRICTED_DENOM.into(),
+                add: vec![],
+                remove: vec!["transfer_to".into()],
+            },
+        )
+        .unwrap();
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            ExecuteMsg::ApproveTransfer {
+                id: TRANSFER_ID.into(),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::RecipientNotAllowed { .. }));
+
+        let allowlist: AllowlistResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetAllowlist {
+                    denom: RESTRICTED_DENOM.into(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(allowlist.enabled);
+        assert!(allowlist.addresses.is_empty());
+    }
+
+    #[test]
+    fn blocked_addresses_are_refused() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        mock_query_marker_response(&marker, &mut deps.querier);
+
+        let sender = Addr::unchecked("sender");
+        let recipient = Addr::unchecked("recipient");
+        store_test_transfer(
+            &mut deps.storage,
+            &pending_test_transfer(&sender, &recipient, 3),
+        );
+
+        let block_msg = ExecuteMsg::UpdateBlocklist {
+            add: vec!["recipient".into()],
+            remove: vec![],
+        };
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other", &[]),
+            block_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            block_msg,
+        )
+        .unwrap();
+
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(sender.as_str(), &[]),
+            ExecuteMsg::Transfer {
+                id: "2c3e5a3c-5f0e-4b8e-9b4a-1d5b0c6f7a01".into(),
+                denom: RESTRICTED_DENOM.into(),
+                amount: Uint128::new(3),
+                recipient: recipient.to_string(),
+                require_acceptance: None,
+                approvers: None,
+                recipients: None,
+                additional_coins: None,
+                vesting_duration: None,
+                memo: None,
+                tags: None,
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ContractError::AddressBlocked { address } if address == "recipient"
+        ));
+
+        // the transfer created before the recipient was blocked is refunded instead of settled
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            ExecuteMsg::ApproveTransfer {
+                id: TRANSFER_ID.into(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            vec![Event::new("blocked_party_refused")
+                .add_attribute("id", TRANSFER_ID)
+                .add_attribute("address", "recipient")],
+            response.events
+        );
+        assert_eq!(1, response.messages.len());
+        let transfer = load_transfer(&deps.storage, TRANSFER_ID).unwrap();
+        assert_eq!(TransferStatus::Rejected, transfer.status);
+        assert!(transfer.approvals.is_empty());
+
+        let blocklist: BlocklistResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetBlocklist {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(vec![recipient], blocklist.addresses);
+    }
+
+    #[test]
+    fn sudo_lets_governance_intervene() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let sender = Addr::unchecked("sender");
+        store_test_transfer(
+            &mut deps.storage,
+            &pending_test_transfer(&sender, &Addr::unchecked("recipient"), 3),
+        );
+
+        let response = sudo(
+            deps.as_mut(),
+            mock_env(),
+            SudoMsg::ForceRejectTransfer {
+                id: TRANSFER_ID.into(),
+                reason: Some("court order".into()),
+            },
+        )
+        .unwrap();
+        assert!(response
+            .attributes
+            .contains(&attr("action", "force_reject")));
+        assert!(response
+            .attributes
+            .contains(&attr("authority", GOVERNANCE_ACTOR)));
+        assert_eq!(1, response.messages.len());
+        let transfer = load_transfer(&deps.storage, TRANSFER_ID).unwrap();
+        assert_eq!(TransferStatus::Rejected, transfer.status);
+        assert_eq!(Some("court order".to_string()), transfer.rejection_reason);
+
+        sudo(deps.as_mut(), mock_env(), SudoMsg::Pause {}).unwrap();
+        assert!(PAUSED.load(&deps.storage).unwrap());
+
+        sudo(
+            deps.as_mut(),
+            mock_env(),
+            SudoMsg::UpdateAdmin {
+                new_admin: "new_admin".into(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            Some(Addr::unchecked("new_admin")),
+            CONFIG.load(&deps.storage).unwrap().admin
+        );
+
+        let audit_log: AuditLogResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetAuditLog {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            vec!["force_reject", "pause", "update_admin"],
+            audit_log
+                .entries
+                .iter()
+                .map(|entry| entry.action.as_str())
+                .collect::<Vec<&str>>()
+        );
+        assert!(audit_log
+            .entries
+            .iter()
+            .all(|entry| entry.actor == Addr::unchecked(GOVERNANCE_ACTOR)));
+    }
+
+    #[test]
+    fn roles_delegate_privileged_actions() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let assert_unauthorized = |result: Result<Response, ContractError>| match result {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(ContractError::Unauthorized { .. }) => {}
+            Err(error) => panic!("unexpected error: {:?}", error),
+        };
+        let grant_msg = ExecuteMsg::GrantRole {
+            role: Role::Operator,
+            address: "operator".into(),
+        };
+        let set_page_limit_msg = ExecuteMsg::SetPageLimit { limit: Some(2) };
+
+        // only the admin can hand out roles
+        assert_unauthorized(execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("operator", &[]),
+            grant_msg.clone(),
+        ));
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            grant_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![
+                attr("action", Action::GrantRole.to_string()),
+                attr("role", "operator"),
+                attr("address", "operator"),
+                attr("admin", "contract_admin"),
+            ]
+        );
+
+        let roles: RolesResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetRoles {
+                    address: "operator".into(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(vec![Role::Operator], roles.roles);
+
+        let members: RoleMembersResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetRoleMembers {
+                    role: Role::Operator,
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(vec![Addr::unchecked("operator")], members.addresses);
+
+        // the operator may do operational upkeep, but not compliance work
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("operator", &[]),
+            set_page_limit_msg.clone(),
+        )
+        .unwrap();
+        assert_unauthorized(execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("operator", &[]),
+            ExecuteMsg::UpdateBlocklist {
+                add: vec!["blocked".into()],
+                remove: vec![],
+            },
+        ));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            ExecuteMsg::RevokeRole {
+                role: Role::Operator,
+                address: "operator".into(),
+            },
+        )
+        .unwrap();
+        assert_unauthorized(execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("operator", &[]),
+            set_page_limit_msg,
+        ));
+    }
+
+    #[test]
+    fn update_config_changes_given_settings() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let update_msg = ExecuteMsg::UpdateConfig {
+            name: Some("renamed".into()),
+            page_limit: Some(10),
+            pending_limit: None,
+            max_transfer_amount: Some(Uint128::new(500)),
+        };
+
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other_address", &[]),
+            update_msg.clone(),
+        ) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::Unauthorized { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            update_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes[0],
+            attr("action", Action::UpdateConfig.to_string())
+        );
+        assert_eq!(
+            response.events,
+            vec![Event::new("config_changed").add_attributes(vec![
+                attr("previous_name", "contract_name"),
+                attr("name", "renamed"),
+                attr("page_limit", "10"),
+                attr("max_transfer_amount", "500"),
+            ])]
+        );
+
+        assert_eq!("renamed", CONFIG.load(&deps.storage).unwrap().name);
+        assert_eq!(10, PAGE_LIMIT.load(&deps.storage).unwrap());
+        assert!(PENDING_LIMIT.may_load(&deps.storage).unwrap().is_none());
+        assert_eq!(
+            Uint128::new(500),
+            MAX_TRANSFER_AMOUNT.load(&deps.storage).unwrap()
+        );
+    }
+
+    #[test]
+    fn registered_approvers_approve_without_marker_grants() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let mut marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        marker.access_control.push(AccessGrant {
+            address: "marker_admin".into(),
+            permissions: vec![Access::Admin.into()],
+        });
+        mock_query_marker_response(&marker, &mut deps.querier);
+
+        let transfer =
+            pending_test_transfer(&Addr::unchecked("sender"), &Addr::unchecked("recipient"), 3);
+        store_test_transfer(&mut deps.storage, &transfer);
+
+        let approve_msg = ExecuteMsg::ApproveTransfer {
+            id: transfer.id.clone(),
+        };
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("registered_approver", &[]),
+            approve_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+
+        let register_msg = ExecuteMsg::UpdateApprovers {
+            denom: RESTRICTED_DENOM.into(),
+            add: vec!["registered_approver".into()],
+            remove: vec![],
+        };
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            register_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("marker_admin", &[]),
+            register_msg,
+        )
+        .unwrap();
+
+        let approvers: ApproversResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetApprovers {
+                    denom: RESTRICTED_DENOM.into(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            vec![Addr::unchecked("registered_approver")],
+            approvers.addresses
+        );
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("registered_approver", &[]),
+            approve_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            TransferStatus::Settled,
+            load_transfer(&deps.storage, &transfer.id).unwrap().status
+        );
+    }
+
+    #[test]
+    fn delegates_approve_until_the_delegation_ends() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        mock_query_marker_response(&marker, &mut deps.querier);
+
+        let transfer =
+            pending_test_transfer(&Addr::unchecked("sender"), &Addr::unchecked("recipient"), 3);
+        let later_transfer = Transfer {
+            id: "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61".into(),
+            ..transfer.clone()
+        };
+        store_test_transfer(&mut deps.storage, &transfer);
+        store_test_transfer(&mut deps.storage, &later_transfer);
+
+        let expires_at = mock_env().block.time.plus_seconds(100);
+        let delegate_msg = ExecuteMsg::DelegateApproval {
+            denom: RESTRICTED_DENOM.into(),
+            delegate: "delegate".into(),
+            expires_at,
+        };
+
+        // only an approver of the denom can delegate
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other_address", &[]),
+            delegate_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            delegate_msg,
+        )
+        .unwrap();
+
+        let delegations = |deps: Deps, env: Env| -> Vec<ApprovalDelegation> {
+            from_binary::<DelegationsResponse>(
+                &query(
+                    deps,
+                    env,
+                    QueryMsg::GetDelegations {
+                        denom: RESTRICTED_DENOM.into(),
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap()
+            .delegations
+        };
+        assert_eq!(
+            vec![ApprovalDelegation {
+                denom: RESTRICTED_DENOM.into(),
+                delegator: Addr::unchecked("approver"),
+                delegate: Addr::unchecked("delegate"),
+                expires_at,
+            }],
+            delegations(deps.as_ref(), mock_env())
+        );
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("delegate", &[]),
+            ExecuteMsg::ApproveTransfer {
+                id: transfer.id.clone(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            TransferStatus::Settled,
+            load_transfer(&deps.storage, &transfer.id).unwrap().status
+        );
+
+        // the delegation lapses at `expires_at`
+        let mut expired_env = mock_env();
+        expired_env.block.time = expires_at;
+        assert!(delegations(deps.as_ref(), expired_env.clone()).is_empty());
+
+        let approve_later_msg = ExecuteMsg::ApproveTransfer {
+            id: later_transfer.id.clone(),
+        };
+        let error = execute(
+            deps.as_mut(),
+            expired_env,
+            mock_info("delegate", &[]),
+            approve_later_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            ExecuteMsg::RevokeDelegation {
+                denom: RESTRICTED_DENOM.into(),
+            },
+        )
+        .unwrap();
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("delegate", &[]),
+            approve_later_msg,
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+    }
+
+    #[test]
+    fn recovery_refunds_escrow_after_the_delay() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let transfer =
+            pending_test_transfer(&Addr::unchecked("sender"), &Addr::unchecked("recipient"), 3);
+        let other_transfer = Transfer {
+            id: "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61".into(),
+            denom: OTHER_RESTRICTED_DENOM.into(),
+            ..transfer.clone()
+        };
+        let approved_transfer = Transfer {
+            id: "9d3c5e7f-1a2b-4c6d-8e0f-a1b2c3d4e5f6".into(),
+            status: TransferStatus::Approved,
+            require_acceptance: true,
+            position: Some(2),
+            ..transfer.clone()
+        };
+        let frozen_transfer = Transfer {
+            id: "0e1f2a3b-4c5d-4e6f-8a9b-c0d1e2f3a4b5".into(),
+            position: Some(3),
+            ..transfer.clone()
+        };
+        store_test_transfer(&mut deps.storage, &transfer);
+        store_test_transfer(&mut deps.storage, &other_transfer);
+        store_test_transfer(&mut deps.storage, &approved_transfer);
+        store_test_transfer(&mut deps.storage, &frozen_transfer);
+        TRANSFER_FREEZES
+            .save(
+                &mut deps.storage,
+                &transfer_key(&frozen_transfer.id).unwrap(),
+                &TransferFreeze {
+                    reason: "under investigation".into(),
+                    frozen_by: Addr::unchecked("officer"),
+                    frozen_at: mock_env().block.time,
+                },
+            )
+            .unwrap();
+
+        let initiate_msg = ExecuteMsg::InitiateRecovery {
+            denom: RESTRICTED_DENOM.into(),
+        };
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            initiate_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            initiate_msg,
+        )
+        .unwrap();
+
+        let executable_at = mock_env().block.time.plus_seconds(RECOVERY_DELAY);
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            ExecuteMsg::ExecuteRecovery { limit: None },
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ContractError::RecoveryLocked { executable_at: at } if at == executable_at
+        ));
+
+        let mut env = mock_env();
+        env.block.time = executable_at;
+        let response = execute(
+            deps.as_mut(),
+            env,
+            mock_info("contract_admin", &[]),
+            ExecuteMsg::ExecuteRecovery { limit: None },
+        )
+        .unwrap();
+        assert_eq!(2, response.messages.len());
+        assert!(response.attributes.contains(&attr("complete", "true")));
+        assert!(response.attributes.contains(&attr("succeeded", "2")));
+
+        assert_eq!(
+            TransferStatus::Cancelled,
+            load_transfer(&deps.storage, &transfer.id).unwrap().status
+        );
+        assert_eq!(
+            TransferStatus::Rejected,
+            load_transfer(&deps.storage, &approved_transfer.id)
+                .unwrap()
+                .status
+        );
+        // the frozen transfer keeps its escrow
+        assert_eq!(
+            TransferStatus::Pending,
+            load_transfer(&deps.storage, &frozen_transfer.id)
+                .unwrap()
+                .status
+        );
+        assert_eq!(
+            TransferStatus::Pending,
+            load_transfer(&deps.storage, &other_transfer.id)
+                .unwrap()
+                .status
+        );
+        assert!(PENDING_RECOVERY.may_load(&deps.storage).unwrap().is_none());
+    }
+
+    #[test]
+    fn recovery_runs_in_batches_of_the_limit() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let transfer =
+            pending_test_transfer(&Addr::unchecked("sender"), &Addr::unchecked("recipient"), 3);
+        let ids = [
+            TRANSFER_ID,
+            "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61",
+            "9d3c5e7f-1a2b-4c6d-8e0f-a1b2c3d4e5f6",
+        ];
+        for (position, id) in ids.iter().enumerate() {
+            store_test_transfer(
+                &mut deps.storage,
+                &Transfer {
+                    id: id.to_string(),
+                    position: Some(position as u64 + 1),
+                    ..transfer.clone()
+                },
+            );
+        }
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            ExecuteMsg::InitiateRecovery {
+                denom: RESTRICTED_DENOM.into(),
+            },
+        )
+        .unwrap();
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(RECOVERY_DELAY);
+        let execute_msg = ExecuteMsg::ExecuteRecovery { limit: Some(2) };
+
+        // the first batch stops at the limit and leaves the recovery pending
+        let response = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("contract_admin", &[]),
+            execute_msg.clone(),
+        )
+        .unwrap();
+        assert!(response.attributes.contains(&attr("complete", "false")));
+        assert!(response.attributes.contains(&attr("succeeded", "2")));
+        assert!(PENDING_RECOVERY.may_load(&deps.storage).unwrap().is_some());
+        assert!(RECOVERY_CURSOR.may_load(&deps.storage).unwrap().is_some());
+
+        // the next continues after the cursor and completes the recovery
+        let response = execute(
+            deps.as_mut(),
+            env,
+            mock_info("contract_admin", &[]),
+            execute_msg,
+        )
+        .unwrap();
+        assert!(response.attributes.contains(&attr("complete", "true")));
+        assert!(response.attributes.contains(&attr("succeeded", "1")));
+        assert!(PENDING_RECOVERY.may_load(&deps.storage).unwrap().is_none());
+        assert!(RECOVERY_CURSOR.may_load(&deps.storage).unwrap().is_none());
+
+        for id in ids.iter() {
+            assert_eq!(
+                TransferStatus::Cancelled,
+                load_transfer(&deps.storage, id).unwrap().status
+            );
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::state::{State, CONFIG};
-    use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
-    use cosmwasm_std::{coin, from_binary, Addr, CosmosMsg, Storage};
-    use prost::Message;
-    use provwasm_mocks::{mock_provenance_dependencies, MockProvenanceQuerier};
-    use provwasm_std::shim::Any;
-    use provwasm_std::types::cosmos::auth::v1beta1::BaseAccount;
-    use provwasm_std::types::provenance::marker::v1::{
-        Access, AccessGrant, MarkerStatus, MarkerType, QueryMarkerRequest, QueryMarkerResponse,
-    };
-    use std::convert::TryInto;
+    #[test]
+    fn transfers_follow_instantiation_settings() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: Some(60),
+                allowed_denoms: Some(vec![RESTRICTED_DENOM.into()]),
+            },
+        );
+
+        let marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        mock_query_marker_response(&marker, &mut deps.querier);
+
+        let transfer_msg = |denom: &str| ExecuteMsg::Transfer {
+            id: TRANSFER_ID.into(),
+            denom: denom.into(),
+            amount: Uint128::new(3),
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            transfer_msg(OTHER_RESTRICTED_DENOM),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ContractError::DenomNotAllowed { denom } if denom == OTHER_RESTRICTED_DENOM
+        ));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            transfer_msg(RESTRICTED_DENOM),
+        )
+        .unwrap();
+
+        // the denom sets no default expiration, so the contract's applies
+        assert_eq!(
+            Some(mock_env().block.time.plus_seconds(60)),
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap().expires
+        );
+    }
+
+    #[test]
+    fn sudo_updates_risk_parameters() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let response = sudo(
+            deps.as_mut(),
+            mock_env(),
+            SudoMsg::SetPendingLimit { limit: Some(3) },
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                attr("action", Action::SetPendingLimit.to_string()),
+                attr("limit", "3"),
+                attr("authority", GOVERNANCE_ACTOR),
+            ],
+            response.attributes
+        );
+        assert_eq!(3, PENDING_LIMIT.load(&deps.storage).unwrap());
+
+        sudo(
+            deps.as_mut(),
+            mock_env(),
+            SudoMsg::SetDenomConfig {
+                denom: RESTRICTED_DENOM.into(),
+                min_amount: None,
+                max_amount: Some(Uint128::new(100)),
+                default_expiration: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            Some(Uint128::new(100)),
+            DENOM_CONFIG
+                .load(&deps.storage, RESTRICTED_DENOM)
+                .unwrap()
+                .max_amount
+        );
+
+        let limit = VelocityLimit {
+            amount: Uint128::new(10),
+            window: 3600,
+        };
+        sudo(
+            deps.as_mut(),
+            mock_env(),
+            SudoMsg::SetVelocityLimit {
+                denom: RESTRICTED_DENOM.into(),
+                limit: Some(limit.clone()),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            limit,
+            VELOCITY_LIMITS
+                .load(&deps.storage, RESTRICTED_DENOM)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn transfers_require_a_registered_denom() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        REGISTERED_DENOMS.remove(&mut deps.storage, RESTRICTED_DENOM);
+
+        let mut marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        marker.access_control.push(AccessGrant {
+            address: "marker_admin".into(),
+            permissions: vec![Access::Admin.into()],
+        });
+        mock_query_marker_response(&marker, &mut deps.querier);
+
+        let transfer_msg = ExecuteMsg::Transfer {
+            id: TRANSFER_ID.into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount: Uint128::new(3),
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            transfer_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ContractError::DenomNotRegistered { denom } if denom == RESTRICTED_DENOM
+        ));
+
+        // only the marker's admin can consent for the denom
+        let register_msg = ExecuteMsg::RegisterDenom {
+            denom: RESTRICTED_DENOM.into(),
+        };
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            register_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("marker_admin", &[]),
+            register_msg,
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+
+        let registered: RegisteredDenomsResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetRegisteredDenoms {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                RESTRICTED_DENOM.to_string(),
+                OTHER_RESTRICTED_DENOM.to_string()
+            ],
+            registered.denoms
+        );
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("marker_admin", &[]),
+            ExecuteMsg::DeregisterDenom {
+                denom: RESTRICTED_DENOM.into(),
+            },
+        )
+        .unwrap();
+        assert!(!REGISTERED_DENOMS.has(&deps.storage, RESTRICTED_DENOM));
+    }
+
+    #[test]
+    fn frozen_denoms_refuse_creation_and_approval() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let mut marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        marker.access_control.push(AccessGrant {
+            address: "marker_admin".into(),
+            permissions: vec![Access::Admin.into()],
+        });
+        mock_query_marker_response(&marker, &mut deps.querier);
+
+        let sender = Addr::unchecked("sender");
+        let recipient = Addr::unchecked("transfer_to");
+        let pending = pending_test_transfer(&sender, &recipient, 3);
+        store_test_transfer(&mut deps.storage, &pending);
+
+        let freeze_msg = ExecuteMsg::FreezeDenom {
+            denom: RESTRICTED_DENOM.into(),
+        };
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            freeze_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("marker_admin", &[]),
+            freeze_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![
+                attr("action", Action::FreezeDenom.to_string()),
+                attr("denom", RESTRICTED_DENOM),
+                attr("admin", "marker_admin"),
+            ]
+        );
+
+        let transfer_msg = ExecuteMsg::Transfer {
+            id: "5b9e2a71-3c4d-4e8f-a1b2-c3d4e5f60718".into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount: Uint128::new(3),
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            transfer_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ContractError::DenomFrozen { denom } if denom == RESTRICTED_DENOM
+        ));
+
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            ExecuteMsg::ApproveTransfer {
+                id: pending.id.to_owned(),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::DenomFrozen { .. }));
+
+        let query_msg = QueryMsg::ListDenomConfigs {
+            start_after: None,
+            limit: None,
+        };
+        let settings: DenomConfigsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert!(settings.denoms[0].frozen);
+
+        // the sender can still take their coins back
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            ExecuteMsg::CancelTransfer {
+                id: pending.id.to_owned(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            TransferStatus::Cancelled,
+            load_transfer(&deps.storage, &pending.id).unwrap().status
+        );
+
+        // the contract admin can lift the freeze as well as the marker admin
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            ExecuteMsg::UnfreezeDenom {
+                denom: RESTRICTED_DENOM.into(),
+            },
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn frozen_transfers_stay_in_escrow() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
 
-    use super::*;
+        let marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        mock_query_marker_response(&marker, &mut deps.querier);
 
-    const RESTRICTED_DENOM: &str = "restricted_1";
-    const TRANSFER_ID: &str = "56253028-12f5-4d2a-a691-ebdfd2a7b865";
+        let sender = Addr::unchecked("sender");
+        let recipient = Addr::unchecked("transfer_to");
+        let pending = pending_test_transfer(&sender, &recipient, 3);
+        store_test_transfer(&mut deps.storage, &pending);
+        ROLE_MEMBERS
+            .save(
+                &mut deps.storage,
+                (&Role::Compliance.to_string(), &Addr::unchecked("officer")),
+                &Empty {},
+            )
+            .unwrap();
+
+        let freeze_msg = ExecuteMsg::FreezeTransfer {
+            id: pending.id.to_owned(),
+            reason: "under investigation".into(),
+        };
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            freeze_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("officer", &[]),
+            freeze_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![
+                attr("action", Action::FreezeTransfer.to_string()),
+                attr("id", &pending.id),
+                attr("reason", "under investigation"),
+                attr("admin", "officer"),
+            ]
+        );
+
+        let freeze = |deps: Deps| {
+            let query_msg = QueryMsg::GetTransferFreeze {
+                id: pending.id.to_owned(),
+            };
+            from_binary::<TransferFreezeResponse>(&query(deps, mock_env(), query_msg).unwrap())
+                .unwrap()
+                .freeze
+        };
+        assert_eq!(
+            Some(TransferFreeze {
+                reason: "under investigation".into(),
+                frozen_by: Addr::unchecked("officer"),
+                frozen_at: mock_env().block.time,
+            }),
+            freeze(deps.as_ref())
+        );
+
+        // neither the approver, the recipient nor the sender can move the escrow
+        for (sender, msg) in [
+            (
+                "approver",
+                ExecuteMsg::ApproveTransfer {
+                    id: pending.id.to_owned(),
+                },
+            ),
+            (
+                "approver",
+                ExecuteMsg::RejectTransfer {
+                    id: pending.id.to_owned(),
+                    reason: None,
+                },
+            ),
+            (
+                "transfer_to",
+                ExecuteMsg::DeclineTransfer {
+                    id: pending.id.to_owned(),
+                },
+            ),
+            (
+                "sender",
+                ExecuteMsg::CancelTransfer {
+                    id: pending.id.to_owned(),
+                },
+            ),
+            (
+                "sender",
+                ExecuteMsg::UpdateTransfer {
+                    id: pending.id.to_owned(),
+                    new_recipient: Some("other_recipient".into()),
+                    new_amount: None,
+                },
+            ),
+            (
+                "sender",
+                ExecuteMsg::ReduceTransfer {
+                    id: pending.id.to_owned(),
+                    new_amount: Uint128::new(1),
+                },
+            ),
+        ] {
+            let error =
+                execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg).unwrap_err();
+            assert!(matches!(
+                error,
+                ContractError::TransferFrozen { reason } if reason == "under investigation"
+            ));
+        }
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("officer", &[]),
+            ExecuteMsg::UnfreezeTransfer {
+                id: pending.id.to_owned(),
+            },
+        )
+        .unwrap();
+        assert_eq!(None, freeze(deps.as_ref()));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            ExecuteMsg::ApproveTransfer {
+                id: pending.id.to_owned(),
+            },
+        )
+        .unwrap();
+
+        // only pending transfers can be frozen
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("officer", &[]),
+            ExecuteMsg::FreezeTransfer {
+                id: pending.id.to_owned(),
+                reason: "too late".into(),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::TransferNotPending { .. }));
+    }
 
     #[test]
-    fn create_transfer_success() {
+    fn settlement_fee_is_deducted_from_payouts() {
         let mut deps = mock_provenance_dependencies();
         setup_test_base(
             &mut deps.storage,
             &State {
                 name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
             },
         );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
 
-        let test_marker: MarkerAccount = setup_restricted_marker();
-        mock_query_marker_response(&test_marker, &mut deps.querier);
+        let marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        mock_query_marker_response(&marker, &mut deps.querier);
 
-        let amount = Uint128::new(1);
-        let transfer_msg = ExecuteMsg::Transfer {
-            id: TRANSFER_ID.into(),
-            denom: RESTRICTED_DENOM.into(),
-            amount: amount.into(),
-            recipient: "transfer_to".into(),
+        let set_fee_msg = ExecuteMsg::SetSettlementFee {
+            fee: Some(SettlementFee {
+                bps: 100,
+                destination: FeeDestination::Collector {
+                    address: Addr::unchecked("collector"),
+                },
+            }),
         };
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            set_fee_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
 
-        let sender_info = mock_info("sender", &[]);
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            set_fee_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![
+                attr("action", Action::SetSettlementFee.to_string()),
+                attr("bps", "100"),
+                attr("destination", "collector"),
+                attr("admin", "contract_admin"),
+            ]
+        );
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            ExecuteMsg::SetDenomFeeSchedule {
+                denom: RESTRICTED_DENOM.into(),
+                schedule: Some(FeeSchedule::Bps { bps: 250 }),
+            },
+        )
+        .unwrap();
 
-        let sender_balance = coin(1, RESTRICTED_DENOM);
-        deps.querier
-            .mock_querier
-            .update_balance(Addr::unchecked("sender"), vec![sender_balance]);
+        let sender = Addr::unchecked("sender");
+        let recipient = Addr::unchecked("transfer_to");
+        store_test_transfer(
+            &mut deps.storage,
+            &pending_test_transfer(&sender, &recipient, 1000),
+        );
 
-        let recipient = "transfer_to";
+        let approve = |deps: DepsMut, id: &str| {
+            execute(
+                deps,
+                mock_env(),
+                mock_info("approver", &[]),
+                ExecuteMsg::ApproveTransfer { id: id.into() },
+            )
+            .unwrap()
+        };
+        let payout = |amount: u128, to: &str| -> CosmosMsg {
+            transfer_marker_coins(
+                Uint128::new(amount),
+                RESTRICTED_DENOM,
+                &Addr::unchecked(to),
+                &Addr::unchecked(MOCK_CONTRACT_ADDR),
+                &Addr::unchecked(MOCK_CONTRACT_ADDR),
+            )
+            .into()
+        };
 
-        // execute create transfer
-        let transfer_response = execute(
+        // the denom's own rate applies instead of the settlement fee's
+        let response = approve(deps.as_mut(), TRANSFER_ID);
+        assert_eq!(
+            vec![payout(975, "transfer_to"), payout(25, "collector")],
+            response
+                .messages
+                .iter()
+                .map(|submessage| submessage.msg.clone())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![
+                Event::new(Action::SettlementFee.to_string()).add_attributes(vec![
+                    attr("id", TRANSFER_ID),
+                    attr("denom", RESTRICTED_DENOM),
+                    attr("amount", "25"),
+                    attr("collector", "collector"),
+                ])
+            ],
+            response.events
+        );
+
+        // split transfers share the fee in proportion to each leg
+        let split_id = "9d3c5e7f-1a2b-4c6d-8e0f-a1b2c3d4e5f6";
+        let mut split = pending_test_transfer(&sender, &recipient, 10);
+        split.id = split_id.into();
+        split.position = Some(2);
+        split.legs = vec![
+            TransferLeg {
+                address: Addr::unchecked("first"),
+                amount: Uint128::new(3),
+            },
+            TransferLeg {
+                address: Addr::unchecked("second"),
+                amount: Uint128::new(7),
+            },
+        ];
+        store_test_transfer(&mut deps.storage, &split);
+        execute(
             deps.as_mut(),
             mock_env(),
-            sender_info.clone(),
-            transfer_msg.clone(),
+            mock_info("contract_admin", &[]),
+            ExecuteMsg::SetDenomFeeSchedule {
+                denom: RESTRICTED_DENOM.into(),
+                schedule: Some(FeeSchedule::Bps { bps: 1000 }),
+            },
+        )
+        .unwrap();
+
+        let response = approve(deps.as_mut(), split_id);
+        assert_eq!(
+            vec![
+                payout(3, "first"),
+                payout(6, "second"),
+                payout(1, "collector")
+            ],
+            response
+                .messages
+                .iter()
+                .map(|submessage| submessage.msg.clone())
+                .collect::<Vec<_>>()
         );
+    }
 
-        let expected_coin = Coin {
-            denom: RESTRICTED_DENOM.to_owned(),
-            amount: amount.into(),
-        };
+    #[test]
+    fn settlement_fee_can_be_paid_to_the_marker_admin() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
 
-        // verify transfer response
-        match transfer_response {
-            Ok(response) => {
-                assert_eq!(response.attributes.len(), 6);
-                assert_eq!(
-                    response.attributes[0],
-                    attr("action", Action::Transfer.to_string())
-                );
-                assert_eq!(response.attributes[1], attr("id", TRANSFER_ID));
-                assert_eq!(response.attributes[2], attr("denom", RESTRICTED_DENOM));
-                assert_eq!(response.attributes[3], attr("amount", amount.to_string()));
-                assert_eq!(
-                    response.attributes[4],
-                    attr("sender", sender_info.clone().sender)
-                );
-                assert_eq!(response.attributes[5], attr("recipient", recipient));
+        let mut marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        marker.access_control.push(AccessGrant {
+            address: "marker_admin".into(),
+            permissions: vec![Access::Admin.into()],
+        });
+        mock_query_marker_response(&marker, &mut deps.querier);
 
-                assert_eq!(response.messages.len(), 1);
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            ExecuteMsg::SetSettlementFee {
+                fee: Some(SettlementFee {
+                    bps: 100,
+                    destination: FeeDestination::MarkerAdmin {},
+                }),
+            },
+        )
+        .unwrap();
+        assert!(response
+            .attributes
+            .contains(&attr("destination", "marker_admin")));
 
-                let expected_message: Binary = MsgTransferRequest {
-                    amount: Some(expected_coin),
-                    from_address: sender_info.clone().sender.to_string(),
-                    to_address: MOCK_CONTRACT_ADDR.to_owned(),
-                    administrator: MOCK_CONTRACT_ADDR.to_owned(),
-                }
-                .try_into()
-                .unwrap();
+        store_test_transfer(
+            &mut deps.storage,
+            &pending_test_transfer(
+                &Addr::unchecked("sender"),
+                &Addr::unchecked("transfer_to"),
+                1000,
+            ),
+        );
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            ExecuteMsg::ApproveTransfer {
+                id: TRANSFER_ID.into(),
+            },
+        )
+        .unwrap();
 
-                match &response.messages[0].msg {
-                    CosmosMsg::Stargate { type_url, value } => {
-                        assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
-                        assert_eq!(value, &expected_message);
-                    }
-                    _ => panic!("unexpected cosmos message"),
-                }
-            }
-            Err(error) => {
-                panic!("failed to create transfer: {:?}", error)
-            }
-        }
+        let fee: CosmosMsg = transfer_marker_coins(
+            Uint128::new(10),
+            RESTRICTED_DENOM,
+            &Addr::unchecked("marker_admin"),
+            &Addr::unchecked(MOCK_CONTRACT_ADDR),
+            &Addr::unchecked(MOCK_CONTRACT_ADDR),
+        )
+        .into();
+        assert_eq!(2, response.messages.len());
+        assert_eq!(fee, response.messages[1].msg);
+        assert!(response.events[0]
+            .attributes
+            .contains(&attr("collector", "marker_admin")));
+    }
 
-        // verify transfer stored
-        match TRANSFER_STORAGE.load(&deps.storage, TRANSFER_ID.as_bytes()) {
-            Ok(stored_transfer) => {
-                assert_eq!(
-                    stored_transfer,
-                    Transfer {
-                        id: TRANSFER_ID.into(),
-                        sender: sender_info.sender.to_owned(),
-                        denom: RESTRICTED_DENOM.into(),
-                        amount,
-                        recipient: Addr::unchecked(recipient)
-                    }
-                )
-            }
-            _ => {
-                panic!("transfer was not found in storage")
+    #[test]
+    fn msg_fees_are_assessed_on_creation_and_approval() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
+
+        let marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        mock_query_marker_response(&marker, &mut deps.querier);
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![coin(10, RESTRICTED_DENOM)]);
+
+        let set_fees_msg = ExecuteMsg::SetMsgFees {
+            fees: Some(MsgFees {
+                creation_fee: Some(Uint128::new(1_000)),
+                approval_fee: Some(Uint128::new(2_000)),
+                recipient: Some(Addr::unchecked("fee_recipient")),
+                recipient_bps: 5_000,
+            }),
+        };
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            set_fees_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            set_fees_msg,
+        )
+        .unwrap();
+
+        let assessment = |name: &str, amount: u128| -> CosmosMsg {
+            MsgAssessCustomMsgFeeRequest {
+                name: name.into(),
+                amount: Some(Coin {
+                    denom: DEFAULT_FEE_DENOM.into(),
+                    amount: amount.to_string(),
+                }),
+                recipient: "fee_recipient".into(),
+                from: MOCK_CONTRACT_ADDR.into(),
+                recipient_basis_points: "5000".into(),
             }
-        }
+            .into()
+        };
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            ExecuteMsg::Transfer {
+                id: TRANSFER_ID.into(),
+                denom: RESTRICTED_DENOM.into(),
+                amount: Uint128::new(3),
+                recipient: "transfer_to".into(),
+                require_acceptance: None,
+                approvers: None,
+                recipients: None,
+                additional_coins: None,
+                vesting_duration: None,
+                memo: None,
+                tags: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(2, response.messages.len());
+        assert_eq!(
+            assessment("transfer_creation", 1_000),
+            response.messages[0].msg
+        );
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            ExecuteMsg::ApproveTransfer {
+                id: TRANSFER_ID.into(),
+            },
+        )
+        .unwrap();
+        assert_eq!(2, response.messages.len());
+        assert_eq!(
+            assessment("transfer_approval", 2_000),
+            response.messages[0].msg
+        );
     }
 
     #[test]
-    fn create_transfer_with_funds_throws_error() {
+    fn msg_fees_are_assessed_on_scheduled_and_pulled_transfers() {
         let mut deps = mock_provenance_dependencies();
         setup_test_base(
             &mut deps.storage,
             &State {
                 name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
             },
         );
 
         let test_marker: MarkerAccount = setup_restricted_marker();
         mock_query_marker_response(&test_marker, &mut deps.querier);
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![coin(4, RESTRICTED_DENOM)]);
+        MSG_FEES
+            .save(
+                &mut deps.storage,
+                &MsgFees {
+                    creation_fee: Some(Uint128::new(1_000)),
+                    approval_fee: None,
+                    recipient: None,
+                    recipient_bps: 0,
+                },
+            )
+            .unwrap();
 
-        let amount = Uint128::new(1);
-        let transfer_msg = ExecuteMsg::Transfer {
-            id: "56253028-12f5-4d2a-a691-ebdfd2a7b865".into(),
-            denom: RESTRICTED_DENOM.into(),
-            amount: amount.into(),
-            recipient: "transfer_to".into(),
-        };
+        let assessment: CosmosMsg = MsgAssessCustomMsgFeeRequest {
+            name: "transfer_creation".into(),
+            amount: Some(Coin {
+                denom: DEFAULT_FEE_DENOM.into(),
+                amount: "1000".into(),
+            }),
+            recipient: "".into(),
+            from: MOCK_CONTRACT_ADDR.into(),
+            recipient_basis_points: "0".into(),
+        }
+        .into();
+        for result in execute_scheduled_and_pull(deps.as_mut()) {
+            let response = result.unwrap();
+            assert_eq!(2, response.messages.len());
+            assert_eq!(assessment, response.messages[0].msg);
+        }
+    }
 
-        let sender_info = mock_info("sender", &[coin(amount.u128(), RESTRICTED_DENOM)]);
+    #[test]
+    fn fee_exempt_addresses_are_not_charged() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
 
-        let sender_balance = coin(1, RESTRICTED_DENOM);
-        deps.querier
-            .mock_querier
-            .update_balance(Addr::unchecked("sender"), vec![sender_balance]);
+        let marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        mock_query_marker_response(&marker, &mut deps.querier);
 
-        // execute create transfer
-        let transfer_response = execute(
+        SETTLEMENT_FEE
+            .save(
+                &mut deps.storage,
+                &SettlementFee {
+                    bps: 100,
+                    destination: FeeDestination::Collector {
+                        address: Addr::unchecked("collector"),
+                    },
+                },
+            )
+            .unwrap();
+        MSG_FEES
+            .save(
+                &mut deps.storage,
+                &MsgFees {
+                    creation_fee: None,
+                    approval_fee: Some(Uint128::new(2_000)),
+                    recipient: None,
+                    recipient_bps: 0,
+                },
+            )
+            .unwrap();
+
+        let exempt_msg = ExecuteMsg::UpdateFeeExemptions {
+            add: vec!["sender".into(), "approver".into()],
+            remove: vec![],
+        };
+        let error = execute(
             deps.as_mut(),
             mock_env(),
-            sender_info.clone(),
-            transfer_msg.clone(),
+            mock_info("approver", &[]),
+            exempt_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            exempt_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![
+                attr("action", Action::UpdateFeeExemptions.to_string()),
+                attr("added", "sender,approver"),
+                attr("removed", ""),
+                attr("admin", "contract_admin"),
+            ]
         );
 
-        assert_sent_funds_unsupported_error(transfer_response);
+        let exemptions: FeeExemptionsResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetFeeExemptions {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            vec![Addr::unchecked("approver"), Addr::unchecked("sender")],
+            exemptions.addresses
+        );
+
+        let sender = Addr::unchecked("sender");
+        let recipient = Addr::unchecked("transfer_to");
+        store_test_transfer(
+            &mut deps.storage,
+            &pending_test_transfer(&sender, &recipient, 1000),
+        );
+
+        let approve = |deps: DepsMut, id: &str| {
+            execute(
+                deps,
+                mock_env(),
+                mock_info("approver", &[]),
+                ExecuteMsg::ApproveTransfer { id: id.into() },
+            )
+            .unwrap()
+        };
+        let payout = |amount: u128, to: &str| -> CosmosMsg {
+            transfer_marker_coins(
+                Uint128::new(amount),
+                RESTRICTED_DENOM,
+                &Addr::unchecked(to),
+                &Addr::unchecked(MOCK_CONTRACT_ADDR),
+                &Addr::unchecked(MOCK_CONTRACT_ADDR),
+            )
+            .into()
+        };
+        let messages = |response: Response| {
+            response
+                .messages
+                .into_iter()
+                .map(|submessage| submessage.msg)
+                .collect::<Vec<_>>()
+        };
+
+        // neither the approval nor the settlement is charged
+        let response = approve(deps.as_mut(), TRANSFER_ID);
+        assert_eq!(vec![payout(1000, "transfer_to")], messages(response));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            ExecuteMsg::UpdateFeeExemptions {
+                add: vec![],
+                remove: vec!["sender".into()],
+            },
+        )
+        .unwrap();
+
+        // the sender pays the settlement fee again, the approver still signs without msg fees
+        let second_id = "9d3c5e7f-1a2b-4c6d-8e0f-a1b2c3d4e5f6";
+        let mut second = pending_test_transfer(&sender, &recipient, 1000);
+        second.id = second_id.into();
+        second.position = Some(2);
+        store_test_transfer(&mut deps.storage, &second);
+
+        let response = approve(deps.as_mut(), second_id);
+        assert_eq!(
+            vec![payout(990, "transfer_to"), payout(10, "collector")],
+            messages(response)
+        );
     }
 
     #[test]
-    fn create_transfer_insufficient_funds_throws_error() {
+    fn fee_schedules_set_the_settlement_fee_of_a_denom() {
         let mut deps = mock_provenance_dependencies();
         setup_test_base(
             &mut deps.storage,
             &State {
                 name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
             },
         );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
 
-        let test_marker: MarkerAccount = setup_restricted_marker();
-        mock_query_marker_response(&test_marker, &mut deps.querier);
+        let mut marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        marker.access_control.push(AccessGrant {
+            address: "marker_admin".into(),
+            permissions: vec![Access::Admin.into()],
+        });
+        mock_query_marker_response(&marker, &mut deps.querier);
 
-        let amount = Uint128::new(2);
-        let transfer_msg = ExecuteMsg::Transfer {
-            id: TRANSFER_ID.into(),
-            denom: RESTRICTED_DENOM.into(),
-            amount: amount.into(),
-            recipient: "transfer_to".into(),
+        SETTLEMENT_FEE
+            .save(
+                &mut deps.storage,
+                &SettlementFee {
+                    bps: 100,
+                    destination: FeeDestination::Collector {
+                        address: Addr::unchecked("collector"),
+                    },
+                },
+            )
+            .unwrap();
+        MSG_FEES
+            .save(
+                &mut deps.storage,
+                &MsgFees {
+                    creation_fee: Some(Uint128::new(1_000)),
+                    approval_fee: None,
+                    recipient: None,
+                    recipient_bps: 0,
+                },
+            )
+            .unwrap();
+
+        let set_schedule = |deps: DepsMut, admin: &str, schedule: FeeSchedule| {
+            execute(
+                deps,
+                mock_env(),
+                mock_info(admin, &[]),
+                ExecuteMsg::SetDenomFeeSchedule {
+                    denom: RESTRICTED_DENOM.into(),
+                    schedule: Some(schedule),
+                },
+            )
+        };
+        let estimate = |deps: Deps, amount: u128| -> EstimateFeesResponse {
+            from_binary(
+                &query(
+                    deps,
+                    mock_env(),
+                    QueryMsg::EstimateFees {
+                        sender: "sender".into(),
+                        denom: RESTRICTED_DENOM.into(),
+                        amount: Uint128::new(amount),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap()
         };
 
-        let sender_info = mock_info("sender", &[]);
+        // without a schedule the settlement fee's rate applies
+        assert_eq!(
+            EstimateFeesResponse {
+                settlement_fee: Uint128::new(10),
+                collector: Some(Addr::unchecked("collector")),
+                creation_fee: Uint128::new(1_000),
+                approval_fee: Uint128::zero(),
+            },
+            estimate(deps.as_ref(), 1000)
+        );
 
-        let sender_balance = coin(1, RESTRICTED_DENOM);
-        deps.querier
-            .mock_querier
-            .update_balance(Addr::unchecked("sender"), vec![sender_balance]);
+        let error = set_schedule(
+            deps.as_mut(),
+            "approver",
+            FeeSchedule::Flat {
+                amount: Uint128::new(30),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
 
-        // execute create transfer
-        let transfer_response = execute(
+        let response = set_schedule(
             deps.as_mut(),
-            mock_env(),
-            sender_info.clone(),
-            transfer_msg.clone(),
+            "marker_admin",
+            FeeSchedule::Flat {
+                amount: Uint128::new(30),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![
+                attr("action", Action::SetDenomFeeSchedule.to_string()),
+                attr("denom", RESTRICTED_DENOM),
+                attr("schedule", "flat"),
+                attr("admin", "marker_admin"),
+            ]
+        );
+        assert_eq!(
+            Uint128::new(30),
+            estimate(deps.as_ref(), 1000).settlement_fee
         );
+        assert_eq!(Uint128::new(20), estimate(deps.as_ref(), 20).settlement_fee);
 
-        // verify transfer response
-        match transfer_response {
-            Ok(..) => {
-                panic!("expected error, but ok")
-            }
-            Err(error) => match error {
-                ContractError::InsufficientFunds => {}
-                error => panic!("unexpected error: {:?}", error),
+        set_schedule(
+            deps.as_mut(),
+            "contract_admin",
+            FeeSchedule::Tiered {
+                tiers: vec![
+                    FeeTier {
+                        min_amount: Uint128::new(100),
+                        bps: 100,
+                    },
+                    FeeTier {
+                        min_amount: Uint128::new(1000),
+                        bps: 50,
+                    },
+                ],
             },
-        }
+        )
+        .unwrap();
+        assert_eq!(Uint128::zero(), estimate(deps.as_ref(), 99).settlement_fee);
+        assert_eq!(Uint128::new(9), estimate(deps.as_ref(), 999).settlement_fee);
+        assert_eq!(
+            Uint128::new(5),
+            estimate(deps.as_ref(), 1000).settlement_fee
+        );
+
+        // settlement charges the fee the schedule estimated
+        let sender = Addr::unchecked("sender");
+        let recipient = Addr::unchecked("transfer_to");
+        store_test_transfer(
+            &mut deps.storage,
+            &pending_test_transfer(&sender, &recipient, 1000),
+        );
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            ExecuteMsg::ApproveTransfer {
+                id: TRANSFER_ID.into(),
+            },
+        )
+        .unwrap();
+        let payout = |amount: u128, to: &str| -> CosmosMsg {
+            transfer_marker_coins(
+                Uint128::new(amount),
+                RESTRICTED_DENOM,
+                &Addr::unchecked(to),
+                &Addr::unchecked(MOCK_CONTRACT_ADDR),
+                &Addr::unchecked(MOCK_CONTRACT_ADDR),
+            )
+            .into()
+        };
+        assert_eq!(
+            vec![payout(995, "transfer_to"), payout(5, "collector")],
+            response
+                .messages
+                .into_iter()
+                .map(|submessage| submessage.msg)
+                .collect::<Vec<_>>()
+        );
     }
 
     #[test]
-    fn create_transfer_invalid_data() {
+    fn creation_deposits_are_refunded_or_forfeited() {
         let mut deps = mock_provenance_dependencies();
         setup_test_base(
             &mut deps.storage,
             &State {
                 name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
             },
         );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
 
-        let test_marker: MarkerAccount = setup_restricted_marker();
-        mock_query_marker_response(&test_marker, &mut deps.querier);
+        let marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        mock_query_marker_response(&marker, &mut deps.querier);
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![coin(10, RESTRICTED_DENOM)]);
+
+        let deposit = CreationDeposit {
+            amount: coin(1_000, "nhash"),
+            collector: Addr::unchecked("collector"),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            ExecuteMsg::SetCreationDeposit {
+                deposit: Some(deposit.clone()),
+            },
+        )
+        .unwrap();
 
-        let amount = Uint128::new(1);
         let transfer_msg = ExecuteMsg::Transfer {
-            id: "".into(),
+            id: TRANSFER_ID.into(),
             denom: RESTRICTED_DENOM.into(),
-            amount: amount.into(),
+            amount: Uint128::new(3),
             recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
         };
+        for funds in [vec![], vec![coin(500, "nhash")]] {
+            let error = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("sender", &funds),
+                transfer_msg.clone(),
+            )
+            .unwrap_err();
+            assert!(
+                matches!(error, ContractError::DepositRequired { deposit } if deposit == "1000nhash")
+            );
+        }
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[coin(1_000, "nhash")]),
+            transfer_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            Some(deposit.clone()),
+            TRANSFER_DEPOSITS
+                .may_load(&deps.storage, &transfer_key(TRANSFER_ID).unwrap())
+                .unwrap()
+        );
 
-        let sender_info = mock_info("sender", &[]);
-
-        let sender_balance = coin(1, RESTRICTED_DENOM);
-        deps.querier
-            .mock_querier
-            .update_balance(Addr::unchecked("sender"), vec![sender_balance]);
+        let released = |response: &Response, to: &str| {
+            response.messages.iter().any(|submessage| {
+                submessage.msg
+                    == CosmosMsg::Bank(BankMsg::Send {
+                        to_address: to.into(),
+                        amount: vec![coin(1_000, "nhash")],
+                    })
+            })
+        };
 
-        // execute create transfer
-        let transfer_response = execute(
+        // cancelled by the sender, the deposit is returned
+        let response = execute(
             deps.as_mut(),
             mock_env(),
-            sender_info.clone(),
-            transfer_msg.clone(),
+            mock_info("sender", &[]),
+            ExecuteMsg::CancelTransfer {
+                id: TRANSFER_ID.into(),
+            },
+        )
+        .unwrap();
+        assert!(released(&response, "sender"));
+        assert_eq!(
+            None,
+            TRANSFER_DEPOSITS
+                .may_load(&deps.storage, &transfer_key(TRANSFER_ID).unwrap())
+                .unwrap()
         );
 
-        // verify transfer response
-        match transfer_response {
-            Ok(..) => panic!("expected error, but ok"),
-            Err(error) => match error {
-                ContractError::InvalidFields { fields } => {
-                    assert!(fields.contains(&"id".into()));
-                }
-                error => panic!("unexpected error: {:?}", error),
+        let sender = Addr::unchecked("sender");
+        let recipient = Addr::unchecked("transfer_to");
+        let held = |storage: &mut dyn Storage, id: &str, position: u64, expires| {
+            let mut transfer = pending_test_transfer(&sender, &recipient, 3);
+            transfer.id = id.into();
+            transfer.position = Some(position);
+            transfer.expires = expires;
+            store_test_transfer(storage, &transfer);
+            TRANSFER_DEPOSITS
+                .save(storage, &transfer_key(id).unwrap(), &deposit)
+                .unwrap();
+        };
+
+        // rejected, the deposit is forfeited to the collector
+        let rejected_id = "9d3c5e7f-1a2b-4c6d-8e0f-a1b2c3d4e5f6";
+        held(&mut deps.storage, rejected_id, 2, None);
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            ExecuteMsg::RejectTransfer {
+                id: rejected_id.into(),
+                reason: None,
             },
-        }
+        )
+        .unwrap();
+        assert!(released(&response, "collector"));
+
+        // left to expire, cancelling it forfeits the deposit as well
+        let expired_id = "0e1f2a3b-4c5d-4e6f-8a9b-c0d1e2f3a4b5";
+        held(
+            &mut deps.storage,
+            expired_id,
+            3,
+            Some(mock_env().block.time),
+        );
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            ExecuteMsg::CancelTransfer {
+                id: expired_id.into(),
+            },
+        )
+        .unwrap();
+        assert!(released(&response, "collector"));
     }
 
     #[test]
-    fn create_transfer_existing_id() {
+    fn accrued_fees_are_withdrawn_by_the_fee_manager() {
         let mut deps = mock_provenance_dependencies();
         setup_test_base(
             &mut deps.storage,
             &State {
                 name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
             },
         );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
 
-        let test_marker: MarkerAccount = setup_restricted_marker();
-        mock_query_marker_response(&test_marker, &mut deps.querier);
-
-        let amount = Uint128::new(1);
-        let sender_info = mock_info("sender", &[]);
+        let marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        mock_query_marker_response(&marker, &mut deps.querier);
 
-        store_test_transfer(
-            &mut deps.storage,
-            &Transfer {
-                id: TRANSFER_ID.into(),
-                sender: sender_info.sender.to_owned(),
-                denom: RESTRICTED_DENOM.into(),
-                amount,
-                recipient: Addr::unchecked("transfer_to"),
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            ExecuteMsg::SetSettlementFee {
+                fee: Some(SettlementFee {
+                    bps: 100,
+                    destination: FeeDestination::Accrue {},
+                }),
             },
-        );
+        )
+        .unwrap();
 
-        let transfer_msg = ExecuteMsg::Transfer {
-            id: TRANSFER_ID.into(),
-            denom: RESTRICTED_DENOM.into(),
-            amount: amount.into(),
-            recipient: "transfer_to".into(),
+        let payout = |amount: u128, to: &str| -> CosmosMsg {
+            transfer_marker_coins(
+                Uint128::new(amount),
+                RESTRICTED_DENOM,
+                &Addr::unchecked(to),
+                &Addr::unchecked(MOCK_CONTRACT_ADDR),
+                &Addr::unchecked(MOCK_CONTRACT_ADDR),
+            )
+            .into()
         };
 
-        let sender_balance = coin(1, RESTRICTED_DENOM);
-        deps.querier
-            .mock_querier
-            .update_balance(Addr::unchecked("sender"), vec![sender_balance]);
+        // the fees of both settlements stay with the contract
+        let sender = Addr::unchecked("sender");
+        let recipient = Addr::unchecked("transfer_to");
+        let second_id = "9d3c5e7f-1a2b-4c6d-8e0f-a1b2c3d4e5f6";
+        for (position, id) in [TRANSFER_ID, second_id].iter().enumerate() {
+            let mut transfer = pending_test_transfer(&sender, &recipient, 1000);
+            transfer.id = id.to_string();
+            transfer.position = Some(position as u64 + 1);
+            store_test_transfer(&mut deps.storage, &transfer);
 
-        // execute create transfer
-        let transfer_response = execute(
+            let response = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("approver", &[]),
+                ExecuteMsg::ApproveTransfer { id: id.to_string() },
+            )
+            .unwrap();
+            assert_eq!(
+                vec![payout(990, "transfer_to")],
+                response
+                    .messages
+                    .into_iter()
+                    .map(|submessage| submessage.msg)
+                    .collect::<Vec<_>>()
+            );
+        }
+
+        let accrued: AccruedFeesResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetAccruedFees {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(vec![coin(20, RESTRICTED_DENOM)], accrued.fees);
+
+        let withdraw_msg = ExecuteMsg::WithdrawFees {
+            denom: RESTRICTED_DENOM.into(),
+            to: "treasury".into(),
+        };
+        let error = execute(
             deps.as_mut(),
             mock_env(),
-            sender_info.clone(),
-            transfer_msg.clone(),
+            mock_info("approver", &[]),
+            withdraw_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            withdraw_msg.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![
+                attr("action", Action::WithdrawFees.to_string()),
+                attr("denom", RESTRICTED_DENOM),
+                attr("amount", "20"),
+                attr("to", "treasury"),
+                attr("admin", "contract_admin"),
+            ]
         );
+        assert_eq!(vec![SubMsg::new(payout(20, "treasury"))], response.messages);
 
-        // verify transfer response
-        match transfer_response {
-            Ok(..) => panic!("expected error, but ok"),
-            Err(error) => match error {
-                ContractError::InvalidFields { fields } => {
-                    assert!(fields.contains(&"id".into()));
-                }
-                error => panic!("unexpected error: {:?}", error),
-            },
-        }
+        // nothing is left to withdraw
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            withdraw_msg,
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::NoFeesAccrued { .. }));
     }
 
     #[test]
-    fn create_transfer_unrestricted_marker_throws_error() {
+    fn incentives_are_paid_to_the_settling_approver() {
         let mut deps = mock_provenance_dependencies();
         setup_test_base(
             &mut deps.storage,
             &State {
                 name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
             },
         );
 
-        let amount = Uint128::new(1);
-        let transfer_msg = ExecuteMsg::Transfer {
-            id: TRANSFER_ID.into(),
-            denom: "unrestricted-marker".into(),
-            amount: amount.into(),
+        let marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        mock_query_marker_response(&marker, &mut deps.querier);
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![coin(10, RESTRICTED_DENOM)]);
+
+        let transfer_msg = |id: &str| ExecuteMsg::Transfer {
+            id: id.into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount: Uint128::new(3),
             recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+        let incentive = |to: &str| -> CosmosMsg {
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: to.into(),
+                amount: vec![coin(500, DEFAULT_FEE_DENOM)],
+            })
         };
 
-        let sender_info = mock_info("sender", &[]);
+        // only nhash can be attached as an incentive
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[coin(500, "other")]),
+            transfer_msg(TRANSFER_ID),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::SentFundsUnsupported));
 
-        let sender_balance = coin(amount.u128(), "unrestricted-marker");
-        deps.querier
-            .mock_querier
-            .update_balance(Addr::unchecked("sender"), vec![sender_balance]);
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[coin(500, DEFAULT_FEE_DENOM)]),
+            transfer_msg(TRANSFER_ID),
+        )
+        .unwrap();
+        assert!(response.attributes.contains(&attr("incentive", "500nhash")));
 
-        // execute create transfer
-        let transfer_response = execute(
+        let response = execute(
             deps.as_mut(),
             mock_env(),
-            sender_info.clone(),
-            transfer_msg.clone(),
+            mock_info("approver", &[]),
+            ExecuteMsg::ApproveTransfer {
+                id: TRANSFER_ID.into(),
+            },
+        )
+        .unwrap();
+        assert!(response
+            .messages
+            .iter()
+            .any(|submessage| submessage.msg == incentive("approver")));
+        assert_eq!(
+            None,
+            TRANSFER_INCENTIVES
+                .may_load(&deps.storage, &transfer_key(TRANSFER_ID).unwrap())
+                .unwrap()
         );
 
-        // verify transfer response
-        match transfer_response {
-            Ok(..) => panic!("expected error, but ok"),
-            Err(error) => match error {
-                ContractError::UnsupportedMarkerType => {}
-                error => panic!("unexpected error: {:?}", error),
+        // cancelled before an approver settles it, the incentive is returned to the sender
+        let cancelled_id = "9d3c5e7f-1a2b-4c6d-8e0f-a1b2c3d4e5f6";
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[coin(500, DEFAULT_FEE_DENOM)]),
+            transfer_msg(cancelled_id),
+        )
+        .unwrap();
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            ExecuteMsg::CancelTransfer {
+                id: cancelled_id.into(),
             },
-        }
+        )
+        .unwrap();
+        assert!(response
+            .messages
+            .iter()
+            .any(|submessage| submessage.msg == incentive("sender")));
     }
 
     #[test]
-    fn approve_transfer_success() {
+    fn fees_are_collected_in_the_fee_denom() {
         let mut deps = mock_provenance_dependencies();
         setup_test_base(
             &mut deps.storage,
             &State {
                 name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
             },
         );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
 
-        let transfer_address = Addr::unchecked("transfer_address");
-        let sender_address = Addr::unchecked("sender_address");
-        let recipient_address = Addr::unchecked("transfer_to");
-
-        let test_marker: MarkerAccount =
-            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
-        mock_query_marker_response(&test_marker, &mut deps.querier);
+        let set_denom = |deps: DepsMut, admin: &str, denom: &str| {
+            execute(
+                deps,
+                mock_env(),
+                mock_info(admin, &[]),
+                ExecuteMsg::SetFeeDenom {
+                    denom: Some(denom.into()),
+                },
+            )
+        };
 
-        let amount = Uint128::new(1);
-        let sender_info = mock_info(transfer_address.as_str(), &[]);
+        // restricted markers cannot be forwarded by the contract
+        let marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        mock_query_marker_response(&marker, &mut deps.querier);
+        let error = set_denom(deps.as_mut(), "contract_admin", RESTRICTED_DENOM).unwrap_err();
+        assert!(matches!(error, ContractError::InvalidFeeDenom { .. }));
 
-        store_test_transfer(
-            &mut deps.storage,
-            &Transfer {
-                id: TRANSFER_ID.into(),
-                sender: sender_address.to_owned(),
-                denom: RESTRICTED_DENOM.into(),
-                amount,
-                recipient: recipient_address.to_owned(),
-            },
+        let stablecoin = MarkerAccount {
+            denom: "stablecoin".into(),
+            marker_type: MarkerType::Coin.into(),
+            ..setup_restricted_marker()
+        };
+        mock_query_marker_response(&stablecoin, &mut deps.querier);
+        let error = set_denom(deps.as_mut(), "approver", "stablecoin").unwrap_err();
+        assert!(matches!(error, ContractError::Unauthorized { .. }));
+        let response = set_denom(deps.as_mut(), "contract_admin", "stablecoin").unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![
+                attr("action", Action::SetFeeDenom.to_string()),
+                attr("denom", "stablecoin"),
+                attr("admin", "contract_admin"),
+            ]
         );
 
-        let approve_transfer_msg = ExecuteMsg::ApproveTransfer {
+        mock_query_marker_response(&marker, &mut deps.querier);
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![coin(10, RESTRICTED_DENOM)]);
+        MSG_FEES
+            .save(
+                &mut deps.storage,
+                &MsgFees {
+                    creation_fee: Some(Uint128::new(1_000)),
+                    approval_fee: None,
+                    recipient: None,
+                    recipient_bps: 0,
+                },
+            )
+            .unwrap();
+
+        let transfer_msg = ExecuteMsg::Transfer {
             id: TRANSFER_ID.into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount: Uint128::new(3),
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
         };
 
-        // execute approve transfer
-        let transfer_response = execute(
+        // incentives are still attached in nhash
+        let error = execute(
             deps.as_mut(),
             mock_env(),
-            sender_info.clone(),
-            approve_transfer_msg.clone(),
-        );
-
-        let expected_coin = Coin {
-            denom: RESTRICTED_DENOM.to_owned(),
-            amount: amount.into(),
-        };
-
-        // verify approve transfer response
-        match transfer_response {
-            Ok(response) => {
-                assert_eq!(response.attributes.len(), 7);
-                assert_eq!(
-                    response.attributes[0],
-                    attr("action", Action::Approve.to_string())
-                );
-                assert_eq!(response.attributes[1], attr("id", TRANSFER_ID));
-                assert_eq!(response.attributes[2], attr("denom", RESTRICTED_DENOM));
-                assert_eq!(response.attributes[3], attr("amount", amount.to_string()));
-                assert_eq!(response.attributes[4], attr("sender", sender_address));
-                assert_eq!(
-                    response.attributes[5],
-                    attr("recipient", recipient_address.to_owned())
-                );
-                assert_eq!(response.attributes[6], attr("admin", transfer_address));
-
-                assert_eq!(response.messages.len(), 1);
-
-                let expected_message: Binary = MsgTransferRequest {
-                    amount: Some(expected_coin),
-                    from_address: MOCK_CONTRACT_ADDR.to_owned(),
-                    to_address: recipient_address.to_string(),
-                    administrator: MOCK_CONTRACT_ADDR.to_owned(),
-                }
-                .try_into()
-                .unwrap();
-
-                match &response.messages[0].msg {
-                    CosmosMsg::Stargate { type_url, value } => {
-                        assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
-                        assert_eq!(value, &expected_message);
-                    }
-                    _ => panic!("unexpected cosmos message"),
-                }
-            }
-            Err(error) => {
-                panic!("failed to create transfer: {:?}", error)
-            }
-        }
+            mock_info("sender", &[coin(500, "stablecoin")]),
+            transfer_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::SentFundsUnsupported));
 
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[coin(500, DEFAULT_FEE_DENOM)]),
+            transfer_msg,
+        )
+        .unwrap();
+        assert!(response.attributes.contains(&attr("incentive", "500nhash")));
         assert_eq!(
-            None,
-            TRANSFER_STORAGE
-                .may_load(&deps.storage, TRANSFER_ID.as_bytes())
-                .unwrap()
+            CosmosMsg::from(MsgAssessCustomMsgFeeRequest {
+                name: "transfer_creation".into(),
+                amount: Some(Coin {
+                    denom: "stablecoin".into(),
+                    amount: "1000".into(),
+                }),
+                recipient: "".into(),
+                from: MOCK_CONTRACT_ADDR.into(),
+                recipient_basis_points: "0".into(),
+            }),
+            response.messages[0].msg
         );
     }
 
     #[test]
-    fn approve_transfer_sent_funds_returns_error() {
+    fn sweep_orphaned_funds_keeps_accrued_fees() {
         let mut deps = mock_provenance_dependencies();
         setup_test_base(
             &mut deps.storage,
             &State {
                 name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
             },
         );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
 
-        let transfer_address = Addr::unchecked("transfer_address");
-        let sender_address = Addr::unchecked("sender_address");
-        let recipient_address = Addr::unchecked("transfer_to");
+        let mut marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        marker.access_control.push(AccessGrant {
+            address: "marker_admin".into(),
+            permissions: vec![Access::Admin.into()],
+        });
+        mock_query_marker_response(&marker, &mut deps.querier);
 
-        let test_marker: MarkerAccount =
-            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
-        mock_query_marker_response(&test_marker, &mut deps.querier);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            ExecuteMsg::SetSettlementFee {
+                fee: Some(SettlementFee {
+                    bps: 100,
+                    destination: FeeDestination::Accrue {},
+                }),
+            },
+        )
+        .unwrap();
 
-        let amount = Uint128::new(1);
-        let sender_info = mock_info(transfer_address.as_str(), &[coin(1, RESTRICTED_DENOM)]);
+        // settling accrues a fee of 10 to the contract
+        store_test_transfer(
+            &mut deps.storage,
+            &pending_test_transfer(
+                &Addr::unchecked("sender_address"),
+                &Addr::unchecked("transfer_to"),
+                1000,
+            ),
+        );
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            ExecuteMsg::ApproveTransfer {
+                id: TRANSFER_ID.into(),
+            },
+        )
+        .unwrap();
 
-        let stored_transfer = Transfer {
-            id: TRANSFER_ID.into(),
-            sender: sender_address.to_owned(),
-            denom: RESTRICTED_DENOM.into(),
-            amount,
-            recipient: recipient_address.to_owned(),
-        };
-        store_test_transfer(&mut deps.storage, &stored_transfer);
+        deps.querier.mock_querier.update_balance(
+            mock_env().contract.address,
+            vec![coin(13, RESTRICTED_DENOM)],
+        );
+        let reconciliation: EscrowReconciliationResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetEscrowReconciliation {
+                    denom: RESTRICTED_DENOM.into(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(Uint128::zero(), reconciliation.transfer_escrow);
+        assert_eq!(Uint128::new(10), reconciliation.accrued_fees);
+        assert!(reconciliation.discrepancy);
 
-        let approve_transfer_msg = ExecuteMsg::ApproveTransfer {
-            id: TRANSFER_ID.into(),
+        let sweep_msg = ExecuteMsg::SweepOrphanedFunds {
+            denom: RESTRICTED_DENOM.into(),
         };
 
-        // execute approve transfer
-        let transfer_response = execute(
+        // only the balance beyond the accrued fees is swept
+        let response = execute(
             deps.as_mut(),
             mock_env(),
-            sender_info.clone(),
-            approve_transfer_msg.clone(),
-        );
-
-        // verify approve transfer response
-        assert_sent_funds_unsupported_error(transfer_response);
+            mock_info("contract_admin", &[]),
+            sweep_msg.clone(),
+        )
+        .unwrap();
+        assert_eq!(response.attributes[2], attr("amount", "3"));
 
-        assert_eq!(
-            stored_transfer,
-            TRANSFER_STORAGE
-                .load(&deps.storage, TRANSFER_ID.as_bytes())
-                .unwrap()
+        deps.querier.mock_querier.update_balance(
+            mock_env().contract.address,
+            vec![coin(10, RESTRICTED_DENOM)],
         );
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            sweep_msg,
+        ) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::NoOrphanedFunds { denom } => assert_eq!(RESTRICTED_DENOM, denom),
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
     }
 
     #[test]
-    fn approve_transfer_unauthorized() {
+    fn approve_transfers_forwards_settlement_events() {
         let mut deps = mock_provenance_dependencies();
         setup_test_base(
             &mut deps.storage,
             &State {
                 name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
             },
         );
+        mock_contract_admin(&mut deps.querier, "contract_admin");
 
-        let transfer_address = Addr::unchecked("transfer_address");
-        let approver_address = Addr::unchecked("approver_address");
-        let sender_address = Addr::unchecked("sender_address");
-        let recipient_address = Addr::unchecked("transfer_to");
-
-        let test_marker: MarkerAccount =
-            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
-        mock_query_marker_response(&test_marker, &mut deps.querier);
-
-        let amount = Uint128::new(1);
-        let sender_info = mock_info(approver_address.as_str(), &[]);
-
-        let stored_transfer = Transfer {
-            id: TRANSFER_ID.into(),
-            sender: sender_address.to_owned(),
-            denom: RESTRICTED_DENOM.into(),
-            amount,
-            recipient: recipient_address.to_owned(),
-        };
-        store_test_transfer(&mut deps.storage, &stored_transfer);
-
-        let approve_transfer_msg = ExecuteMsg::ApproveTransfer {
-            id: TRANSFER_ID.into(),
-        };
+        let marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        mock_query_marker_response(&marker, &mut deps.querier);
 
-        // execute approve transfer
-        let transfer_response = execute(
+        execute(
             deps.as_mut(),
             mock_env(),
-            sender_info.clone(),
-            approve_transfer_msg.clone(),
+            mock_info("contract_admin", &[]),
+            ExecuteMsg::SetSettlementFee {
+                fee: Some(SettlementFee {
+                    bps: 100,
+                    destination: FeeDestination::Accrue {},
+                }),
+            },
+        )
+        .unwrap();
+
+        store_test_transfer(
+            &mut deps.storage,
+            &pending_test_transfer(
+                &Addr::unchecked("sender_address"),
+                &Addr::unchecked("transfer_to"),
+                1000,
+            ),
         );
 
-        match transfer_response {
-            Ok(..) => {
-                panic!("expected error, but ok")
-            }
-            Err(error) => match error {
-                ContractError::Unauthorized { .. } => {}
-                error => panic!("unexpected error: {:?}", error),
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            ExecuteMsg::ApproveTransfers {
+                ids: vec![TRANSFER_ID.into()],
             },
-        }
+        )
+        .unwrap();
 
-        assert_eq!(
-            stored_transfer,
-            TRANSFER_STORAGE
-                .load(&deps.storage, TRANSFER_ID.as_bytes())
-                .unwrap()
-        );
+        // the approval is followed by the fee it charged
+        assert_eq!(response.events.len(), 2);
+        assert_eq!(response.events[0].ty, Action::ApproveTransfers.to_string());
+        assert_eq!(response.events[1].ty, Action::SettlementFee.to_string());
+        assert!(response.events[1]
+            .attributes
+            .contains(&attr("amount", "10")));
     }
 
     #[test]
-    fn approve_transfer_unknown_transfer() {
+    fn scheduled_and_pulled_transfers_require_a_registered_denom() {
         let mut deps = mock_provenance_dependencies();
         setup_test_base(
             &mut deps.storage,
             &State {
                 name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
             },
         );
 
-        let transfer_address = Addr::unchecked("transfer_address");
-        let sender_info = mock_info(transfer_address.as_str(), &[]);
-
-        let approve_transfer_msg = ExecuteMsg::ApproveTransfer {
-            id: TRANSFER_ID.into(),
-        };
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![coin(4, RESTRICTED_DENOM)]);
 
-        // execute approve transfer
-        let transfer_response = execute(
-            deps.as_mut(),
-            mock_env(),
-            sender_info.clone(),
-            approve_transfer_msg.clone(),
-        );
+        // the denom was registered when the schedule and allowance were set up
+        REGISTERED_DENOMS.remove(&mut deps.storage, RESTRICTED_DENOM);
 
-        assert_load_transfer_error(transfer_response);
+        for result in execute_scheduled_and_pull(deps.as_mut()) {
+            match result {
+                Ok(..) => panic!("expected error, but ok"),
+                Err(error) => match error {
+                    ContractError::DenomNotRegistered { denom } => {
+                        assert_eq!(RESTRICTED_DENOM, denom)
+                    }
+                    error => panic!("unexpected error: {:?}", error),
+                },
+            }
+        }
     }
 
     #[test]
-    fn has_marker_access_transfer_success() {
-        let transfer_address = Addr::unchecked("transfer_address");
-        let test_marker: MarkerAccount =
-            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
-        assert!(has_marker_access_transfer(
-            transfer_address.to_owned(),
-            test_marker.into()
-        ))
-    }
+    fn scheduled_and_pulled_transfers_refuse_a_frozen_denom() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
 
-    #[test]
-    fn has_marker_access_transfer_returns_false_with_no_permission() {
-        let transfer_address = Addr::unchecked("transfer_address");
-        let other_address = Addr::unchecked("other_address");
-        let test_marker: MarkerAccount =
-            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
-        assert_eq!(
-            false,
-            has_marker_access_transfer(other_address.to_owned(), test_marker.into())
-        )
-    }
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![coin(4, RESTRICTED_DENOM)]);
 
-    #[test]
-    fn has_marker_access_transfer_returns_false_without_transfer_permission() {
-        let non_transfer_address = Addr::unchecked("some_address_without_transfer");
-        let test_marker: MarkerAccount = MarkerAccount {
-            base_account: Some(BaseAccount {
-                address: "tp1l330sxue4suxz9dhc40e2pns0ymrytf8uz4squ".to_string(),
-                pub_key: None,
-                account_number: 10,
-                sequence: 0,
-            }),
-            manager: "tp13pnzut8zdjaqht7aqe7kk4ww5zfq04jzlytnmu".to_string(),
-            access_control: vec![AccessGrant {
-                address: "some_address_without_transfer".to_string(),
-                permissions: vec![(Access::Admin).into()],
-            }],
-            status: 0,
-            denom: "restricted_1".to_string(),
-            supply: "1000".to_string(),
-            marker_type: 0,
-            supply_fixed: false,
-            allow_governance_control: true,
-            allow_forced_transfer: false,
-            required_attributes: vec![],
-        };
+        FROZEN_DENOMS
+            .save(&mut deps.storage, RESTRICTED_DENOM, &Empty {})
+            .unwrap();
 
-        assert_eq!(
-            false,
-            has_marker_access_transfer(non_transfer_address.to_owned(), test_marker.into())
-        )
+        for result in execute_scheduled_and_pull(deps.as_mut()) {
+            match result {
+                Ok(..) => panic!("expected error, but ok"),
+                Err(error) => match error {
+                    ContractError::DenomFrozen { denom } => assert_eq!(RESTRICTED_DENOM, denom),
+                    error => panic!("unexpected error: {:?}", error),
+                },
+            }
+        }
     }
 
     #[test]
-    fn cancel_transfer_success() {
+    fn scheduled_and_pulled_transfers_are_held_to_creation_limits() {
         let mut deps = mock_provenance_dependencies();
         setup_test_base(
             &mut deps.storage,
             &State {
                 name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
             },
         );
 
-        let sender_address = Addr::unchecked("sender_address");
-        let recipient_address = Addr::unchecked("transfer_to");
-
-        let amount = Uint128::new(3);
-        let sender_info = mock_info(sender_address.as_str(), &[]);
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![coin(4, RESTRICTED_DENOM)]);
 
+        // the sender already has as many transfers pending as allowed
+        PENDING_LIMIT.save(&mut deps.storage, &1).unwrap();
         store_test_transfer(
             &mut deps.storage,
-            &Transfer {
-                id: TRANSFER_ID.into(),
-                sender: sender_address.to_owned(),
-                denom: RESTRICTED_DENOM.into(),
-                amount,
-                recipient: recipient_address.to_owned(),
-            },
-        );
-
-        let cancel_transfer_msg = ExecuteMsg::CancelTransfer {
-            id: TRANSFER_ID.into(),
-        };
-
-        // execute cancel transfer
-        let cancel_response = execute(
-            deps.as_mut(),
-            mock_env(),
-            sender_info.clone(),
-            cancel_transfer_msg.clone(),
+            &pending_test_transfer(
+                &Addr::unchecked("sender"),
+                &Addr::unchecked("transfer_to"),
+                1,
+            ),
         );
 
-        let expected_coin = Coin {
-            denom: RESTRICTED_DENOM.to_owned(),
-            amount: amount.into(),
-        };
-
-        // verify approve transfer response
-        match cancel_response {
-            Ok(response) => {
-                assert_eq!(response.attributes.len(), 5);
-                assert_eq!(
-                    response.attributes[0],
-                    attr("action", Action::Cancel.to_string())
-                );
-                assert_eq!(response.attributes[1], attr("id", TRANSFER_ID));
-                assert_eq!(response.attributes[2], attr("denom", RESTRICTED_DENOM));
-                assert_eq!(response.attributes[3], attr("amount", amount.to_string()));
-                assert_eq!(
-                    response.attributes[4],
-                    attr("sender", sender_address.to_owned())
-                );
-
-                assert_eq!(response.messages.len(), 1);
+        for result in execute_scheduled_and_pull(deps.as_mut()) {
+            match result {
+                Ok(..) => panic!("expected error, but ok"),
+                Err(error) => match error {
+                    ContractError::TooManyPendingTransfers { limit } => assert_eq!(1, limit),
+                    error => panic!("unexpected error: {:?}", error),
+                },
+            }
+        }
 
-                let expected_message: Binary = MsgTransferRequest {
-                    amount: Some(expected_coin),
-                    from_address: MOCK_CONTRACT_ADDR.to_owned(),
-                    to_address: sender_info.clone().sender.to_string(),
-                    administrator: MOCK_CONTRACT_ADDR.to_owned(),
-                }
-                .try_into()
-                .unwrap();
+        // without the limit the caller still owes the creation deposit
+        PENDING_LIMIT.remove(&mut deps.storage);
+        CREATION_DEPOSIT
+            .save(
+                &mut deps.storage,
+                &CreationDeposit {
+                    amount: coin(100, "nhash"),
+                    collector: Addr::unchecked("collector"),
+                },
+            )
+            .unwrap();
 
-                match &response.messages[0].msg {
-                    CosmosMsg::Stargate { type_url, value } => {
-                        assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
-                        assert_eq!(value, &expected_message);
-                    }
-                    _ => panic!("unexpected cosmos message"),
-                }
-            }
-            Err(error) => {
-                panic!("failed to cancel transfer: {:?}", error)
+        for result in execute_scheduled_and_pull(deps.as_mut()) {
+            match result {
+                Ok(..) => panic!("expected error, but ok"),
+                Err(error) => match error {
+                    ContractError::DepositRequired { deposit } => assert_eq!("100nhash", deposit),
+                    error => panic!("unexpected error: {:?}", error),
+                },
             }
         }
-
-        assert_eq!(
-            None,
-            TRANSFER_STORAGE
-                .may_load(&deps.storage, TRANSFER_ID.as_bytes())
-                .unwrap()
-        );
     }
 
     #[test]
-    fn cancel_transfer_sent_funds_returns_error() {
+    fn update_transfer_checks_the_new_terms() {
         let mut deps = mock_provenance_dependencies();
         setup_test_base(
             &mut deps.storage,
             &State {
                 name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
             },
         );
 
         let sender_address = Addr::unchecked("sender_address");
-        let recipient_address = Addr::unchecked("transfer_to");
-
-        let amount = Uint128::new(3);
-        let sender_info = mock_info(sender_address.as_str(), &[coin(1, RESTRICTED_DENOM)]);
+        store_test_transfer(
+            &mut deps.storage,
+            &pending_test_transfer(&sender_address, &Addr::unchecked("transfer_to"), 3),
+        );
+        deps.querier
+            .mock_querier
+            .update_balance(sender_address.to_owned(), vec![coin(10, RESTRICTED_DENOM)]);
 
-        let stored_transfer = Transfer {
-            id: TRANSFER_ID.into(),
-            sender: sender_address.to_owned(),
-            denom: RESTRICTED_DENOM.into(),
-            amount,
-            recipient: recipient_address.to_owned(),
+        let update = |deps: DepsMut, new_recipient: Option<&str>, new_amount: Option<u128>| {
+            execute(
+                deps,
+                mock_env(),
+                mock_info(sender_address.as_str(), &[]),
+                ExecuteMsg::UpdateTransfer {
+                    id: TRANSFER_ID.into(),
+                    new_recipient: new_recipient.map(String::from),
+                    new_amount: new_amount.map(Uint128::new),
+                },
+            )
         };
-        store_test_transfer(&mut deps.storage, &stored_transfer);
 
-        let cancel_transfer_msg = ExecuteMsg::CancelTransfer {
-            id: TRANSFER_ID.into(),
-        };
+        BLOCKLIST
+            .save(&mut deps.storage, &Addr::unchecked("blocked"), &Empty {})
+            .unwrap();
+        let error = update(deps.as_mut(), Some("blocked"), None).unwrap_err();
+        assert!(matches!(
+            error,
+            ContractError::AddressBlocked { address } if address == "blocked"
+        ));
 
-        // execute cancel transfer
-        let transfer_response = execute(
-            deps.as_mut(),
-            mock_env(),
-            sender_info.clone(),
-            cancel_transfer_msg.clone(),
-        );
+        FROZEN_DENOMS
+            .save(&mut deps.storage, RESTRICTED_DENOM, &Empty {})
+            .unwrap();
+        let error = update(deps.as_mut(), None, Some(4)).unwrap_err();
+        assert!(matches!(error, ContractError::DenomFrozen { .. }));
+        FROZEN_DENOMS.remove(&mut deps.storage, RESTRICTED_DENOM);
 
-        // verify cancel transfer response
-        assert_sent_funds_unsupported_error(transfer_response);
+        // only the increase counts towards the velocity limit
+        VELOCITY_LIMITS
+            .save(
+                &mut deps.storage,
+                RESTRICTED_DENOM,
+                &VelocityLimit {
+                    amount: Uint128::new(4),
+                    window: 86400,
+                },
+            )
+            .unwrap();
+        update(deps.as_mut(), None, Some(7)).unwrap();
+        let error = update(deps.as_mut(), None, Some(8)).unwrap_err();
+        assert!(matches!(
+            error,
+            ContractError::VelocityLimitExceeded { remaining, .. } if remaining.is_zero()
+        ));
+    }
 
-        assert_eq!(
-            stored_transfer,
-            TRANSFER_STORAGE
-                .load(&deps.storage, TRANSFER_ID.as_bytes())
-                .unwrap()
+    #[test]
+    fn escrow_is_confirmed_by_reply_without_a_balance_check() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+        store_test_transfer(
+            &mut deps.storage,
+            &pending_test_transfer(
+                &Addr::unchecked("sender_address"),
+                &Addr::unchecked("transfer_to"),
+                3,
+            ),
         );
+
+        // no balances are mocked, a failing escrow fails its reply and with it the transaction
+        let mut results = execute_scheduled_and_pull(deps.as_mut());
+        results.push(execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("initiator", &[]),
+            propose_test_swap_msg(),
+        ));
+        results.push(execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender_address", &[]),
+            ExecuteMsg::UpdateTransfer {
+                id: TRANSFER_ID.into(),
+                new_recipient: None,
+                new_amount: Some(Uint128::new(5)),
+            },
+        ));
+
+        for result in results {
+            let escrow = result.unwrap().messages.pop().unwrap();
+            assert_eq!(ReplyOn::Error, escrow.reply_on);
+            assert_eq!(ESCROW_REPLY_ID, escrow.id);
+        }
     }
 
     #[test]
-    fn cancel_transfer_unauthorized() {
+    fn batch_transfer_escrow_overflow_is_an_error() {
         let mut deps = mock_provenance_dependencies();
         setup_test_base(
             &mut deps.storage,
             &State {
                 name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
             },
         );
 
-        let sender_address = Addr::unchecked("sender_address");
-        let recipient_address = Addr::unchecked("transfer_to");
-
-        let amount = Uint128::new(3);
-        let sender_info = mock_info(&"other_address".to_string(), &[]);
+        let test_marker: MarkerAccount = setup_restricted_marker();
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+        deps.querier.mock_querier.update_balance(
+            Addr::unchecked("sender"),
+            vec![coin(u128::MAX, RESTRICTED_DENOM)],
+        );
 
-        let stored_transfer = Transfer {
-            id: TRANSFER_ID.into(),
-            sender: sender_address.to_owned(),
+        let input = |id: &str| TransferInput {
+            id: id.into(),
             denom: RESTRICTED_DENOM.into(),
-            amount,
-            recipient: recipient_address.to_owned(),
-        };
-        store_test_transfer(&mut deps.storage, &stored_transfer);
-
-        let cancel_transfer_msg = ExecuteMsg::CancelTransfer {
-            id: TRANSFER_ID.into(),
+            amount: Uint128::new(u128::MAX / 2 + 1),
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
         };
-
-        // execute cancel transfer
-        let transfer_response = execute(
+        let error = execute(
             deps.as_mut(),
             mock_env(),
-            sender_info.clone(),
-            cancel_transfer_msg.clone(),
+            mock_info("sender", &[]),
+            ExecuteMsg::BatchTransfer {
+                transfers: vec![
+                    input(TRANSFER_ID),
+                    input("9d3c5e7f-1a2b-4c6d-8e0f-a1b2c3d4e5f6"),
+                ],
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ContractError::Std(StdError::Overflow { .. })
+        ));
+    }
+
+    #[test]
+    fn creation_deposit_overflow_is_an_error() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
         );
+        CREATION_DEPOSIT
+            .save(
+                &mut deps.storage,
+                &CreationDeposit {
+                    amount: coin(u128::MAX, DEFAULT_FEE_DENOM),
+                    collector: Addr::unchecked("collector"),
+                },
+            )
+            .unwrap();
 
-        // verify cancel transfer response
-        match transfer_response {
-            Ok(..) => panic!("expected error, but ok"),
-            Err(error) => match error {
-                ContractError::Unauthorized { .. } => {}
-                error => panic!("unexpected error: {:?}", error),
+        let input = |id: &str| TransferInput {
+            id: id.into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount: Uint128::new(1),
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[coin(u128::MAX, DEFAULT_FEE_DENOM)]),
+            ExecuteMsg::BatchTransfer {
+                transfers: vec![
+                    input(TRANSFER_ID),
+                    input("9d3c5e7f-1a2b-4c6d-8e0f-a1b2c3d4e5f6"),
+                ],
             },
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ContractError::Std(StdError::Overflow { .. })
+        ));
+    }
+
+    #[test]
+    fn reply_fails_escrow_errors_and_unknown_ids() {
+        let mut deps = mock_provenance_dependencies();
+        let mut reply_to = |id, result| reply(deps.as_mut(), mock_env(), Reply { id, result });
+
+        // a failed escrow fails the transaction that created the transfer
+        match reply_to(
+            ESCROW_REPLY_ID,
+            SubMsgResult::Err("insufficient funds".into()),
+        ) {
+            Err(ContractError::EscrowFailed { error }) => assert_eq!("insufficient funds", error),
+            result => panic!("unexpected result: {:?}", result),
         }
 
-        assert_eq!(
-            stored_transfer,
-            TRANSFER_STORAGE
-                .load(&deps.storage, TRANSFER_ID.as_bytes())
-                .unwrap()
-        );
+        // escrow submessages only reply on error
+        match reply_to(
+            ESCROW_REPLY_ID,
+            SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        ) {
+            Err(ContractError::UnknownReplyId { id }) => assert_eq!(ESCROW_REPLY_ID, id),
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        // and no settlement awaits any other id
+        match reply_to(42, SubMsgResult::Err("marker transfer failed".into())) {
+            Err(ContractError::UnknownReplyId { id }) => assert_eq!(42, id),
+            result => panic!("unexpected result: {:?}", result),
+        }
     }
 
     #[test]
-    fn cancel_transfer_unknown_transfer() {
+    fn sudo_update_admin_drops_the_proposed_admin() {
         let mut deps = mock_provenance_dependencies();
         setup_test_base(
             &mut deps.storage,
             &State {
                 name: "contract_name".into(),
+                admin: Some(Addr::unchecked("contract_admin")),
+                default_expiration: None,
+                allowed_denoms: None,
             },
         );
 
-        let sender_address = Addr::unchecked("sender_address");
-        let sender_info = mock_info(sender_address.as_str(), &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_admin", &[]),
+            ExecuteMsg::UpdateAdmin {
+                new_admin: "proposed_admin".into(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            Some(Addr::unchecked("proposed_admin")),
+            PENDING_ADMIN.may_load(&deps.storage).unwrap()
+        );
 
-        let reject_transfer_msg = ExecuteMsg::CancelTransfer {
-            id: TRANSFER_ID.into(),
-        };
+        sudo(
+            deps.as_mut(),
+            mock_env(),
+            SudoMsg::UpdateAdmin {
+                new_admin: "governance_admin".into(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            Some(Addr::unchecked("governance_admin")),
+            CONFIG.load(&deps.storage).unwrap().admin
+        );
+        assert_eq!(None, PENDING_ADMIN.may_load(&deps.storage).unwrap());
 
-        // execute cancel transfer
-        let transfer_response = execute(
+        // the earlier proposal can no longer be accepted
+        match execute(
             deps.as_mut(),
             mock_env(),
-            sender_info.clone(),
-            reject_transfer_msg.clone(),
+            mock_info("proposed_admin", &[]),
+            ExecuteMsg::AcceptAdmin {},
+        ) {
+            Err(ContractError::Unauthorized { .. }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert_eq!(
+            Some(Addr::unchecked("governance_admin")),
+            CONFIG.load(&deps.storage).unwrap().admin
         );
+    }
 
-        assert_load_transfer_error(transfer_response);
+    #[test]
+    fn execute_batch_discards_the_writes_of_a_failing_id() {
+        let mut deps = mock_provenance_dependencies();
+        let ids = vec![TRANSFER_ID.to_string(), "fail".to_string()];
+
+        let response = execute_batch(deps.as_mut(), ids, Action::ApproveTransfers, |deps, id| {
+            ACCRUED_FEES.save(deps.storage, &id, &Uint128::new(5))?;
+            match id.as_str() {
+                "fail" => Err(ContractError::ScheduleNotDue),
+                _ => Ok(Response::new()),
+            }
+        })
+        .unwrap();
+
+        assert!(response.attributes.contains(&attr("succeeded", "1")));
+        assert!(response.attributes.contains(&attr("failed_count", "1")));
+        assert_eq!(
+            Some(Uint128::new(5)),
+            ACCRUED_FEES.may_load(&deps.storage, TRANSFER_ID).unwrap()
+        );
+        // the failing id's fee never reached storage
+        assert_eq!(None, ACCRUED_FEES.may_load(&deps.storage, "fail").unwrap());
     }
 
     #[test]
-    fn reject_transfer_success() {
+    fn standing_approval_settles_matching_batch_transfers() {
         let mut deps = mock_provenance_dependencies();
         setup_test_base(
             &mut deps.storage,
             &State {
                 name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
             },
         );
 
-        let sender_address = Addr::unchecked("sender_address");
-        let transfer_address = Addr::unchecked("transfer_address");
-        let recipient_address = Addr::unchecked("transfer_to");
-
-        let test_marker: MarkerAccount =
-            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
-        mock_query_marker_response(&test_marker, &mut deps.querier);
+        let marker =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), Addr::unchecked("approver"));
+        mock_query_marker_response(&marker, &mut deps.querier);
 
-        let amount = Uint128::new(3);
-        let sender_info = mock_info(transfer_address.as_str(), &[]);
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![coin(10, RESTRICTED_DENOM)]);
 
-        store_test_transfer(
-            &mut deps.storage,
-            &Transfer {
-                id: TRANSFER_ID.into(),
-                sender: sender_address.to_owned(),
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            ExecuteMsg::SetStandingApproval {
+                sender: "sender".into(),
+                recipient: "transfer_to".into(),
                 denom: RESTRICTED_DENOM.into(),
-                amount,
-                recipient: recipient_address.to_owned(),
+                max_amount: Uint128::new(5),
+                expires: mock_env().block.time.plus_seconds(100),
             },
-        );
+        )
+        .unwrap();
 
-        let reject_transfer_msg = ExecuteMsg::RejectTransfer {
-            id: TRANSFER_ID.into(),
+        let other_transfer_id = "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61";
+        let transfer_input = |id: &str, recipient: &str, amount: u128| TransferInput {
+            id: id.into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount: Uint128::new(amount),
+            recipient: recipient.into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
         };
 
-        // execute reject transfer
-        let reject_response = execute(
+        let response = execute(
             deps.as_mut(),
             mock_env(),
-            sender_info.clone(),
-            reject_transfer_msg.clone(),
-        );
-
-        let expected_coin = Coin {
-            denom: RESTRICTED_DENOM.to_owned(),
-            amount: amount.into(),
-        };
-
-        // verify approve transfer response
-        match reject_response {
-            Ok(response) => {
-                assert_eq!(response.attributes.len(), 6);
-                assert_eq!(
-                    response.attributes[0],
-                    attr("action", Action::Reject.to_string())
-                );
-                assert_eq!(response.attributes[1], attr("id", TRANSFER_ID));
-                assert_eq!(response.attributes[2], attr("denom", RESTRICTED_DENOM));
-                assert_eq!(response.attributes[3], attr("amount", amount.to_string()));
-                assert_eq!(
-                    response.attributes[4],
-                    attr("sender", sender_address.to_owned())
-                );
-                assert_eq!(
-                    response.attributes[5],
-                    attr("admin", transfer_address.to_owned())
-                );
-
-                assert_eq!(response.messages.len(), 1);
-
-                let expected_message: Binary = MsgTransferRequest {
-                    amount: Some(expected_coin),
-                    to_address: sender_address.to_string(),
-                    from_address: MOCK_CONTRACT_ADDR.to_owned(),
-                    administrator: MOCK_CONTRACT_ADDR.to_owned(),
-                }
-                .try_into()
-                .unwrap();
+            mock_info("sender", &[]),
+            ExecuteMsg::BatchTransfer {
+                transfers: vec![
+                    transfer_input(TRANSFER_ID, "transfer_to", 3),
+                    transfer_input(other_transfer_id, "holder_2", 2),
+                ],
+            },
+        )
+        .unwrap();
+        assert_eq!(response.attributes[1], attr("count", "2"));
+        assert!(response.events[0]
+            .attributes
+            .contains(&attr("standing_approver", "approver")));
+        assert!(!response.events[1]
+            .attributes
+            .iter()
+            .any(|attribute| attribute.key == "standing_approver"));
 
-                match &response.messages[0].msg {
-                    CosmosMsg::Stargate { type_url, value } => {
-                        assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
-                        assert_eq!(value, &expected_message);
-                    }
-                    _ => panic!("unexpected cosmos message"),
-                }
-            }
-            Err(error) => {
-                panic!("failed to reject transfer: {:?}", error)
-            }
-        }
+        // the single escrow comes first and the settlement of the covered transfer after it
+        assert_eq!(2, response.messages.len());
+        assert_eq!(ReplyOn::Error, response.messages[0].reply_on);
+        assert_eq!(ReplyOn::Always, response.messages[1].reply_on);
 
+        let transfer = load_transfer(&deps.storage, TRANSFER_ID).unwrap();
+        assert_eq!(TransferStatus::Settled, transfer.status);
+        assert_eq!(Addr::unchecked("approver"), transfer.approvals[0].approver);
         assert_eq!(
-            None,
-            TRANSFER_STORAGE
-                .may_load(&deps.storage, TRANSFER_ID.as_bytes())
+            TransferStatus::Pending,
+            load_transfer(&deps.storage, other_transfer_id)
                 .unwrap()
+                .status
         );
     }
 
     #[test]
-    fn reject_transfer_sent_funds_returns_error() {
+    fn auto_approve_threshold_settles_batch_and_pulled_transfers() {
         let mut deps = mock_provenance_dependencies();
         setup_test_base(
             &mut deps.storage,
             &State {
                 name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
             },
         );
 
-        let sender_address = Addr::unchecked("sender_address");
-        let transfer_address = Addr::unchecked("transfer_address");
-        let recipient_address = Addr::unchecked("transfer_to");
-
-        let test_marker: MarkerAccount =
-            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        let test_marker: MarkerAccount = setup_restricted_marker();
         mock_query_marker_response(&test_marker, &mut deps.querier);
 
-        let amount = Uint128::new(3);
-        let sender_info = mock_info(transfer_address.as_str(), &[coin(1, RESTRICTED_DENOM)]);
+        deps.querier
+            .mock_querier
+            .update_balance(Addr::unchecked("sender"), vec![coin(20, RESTRICTED_DENOM)]);
+        AUTO_APPROVE_THRESHOLDS
+            .save(&mut deps.storage, RESTRICTED_DENOM, &Uint128::new(5))
+            .unwrap();
 
-        let stored_transfer = Transfer {
-            id: TRANSFER_ID.into(),
-            sender: sender_address.to_owned(),
+        let other_transfer_id = "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61";
+        let transfer_input = |id: &str, amount: u128| TransferInput {
+            id: id.into(),
             denom: RESTRICTED_DENOM.into(),
-            amount,
-            recipient: recipient_address.to_owned(),
-        };
-        store_test_transfer(&mut deps.storage, &stored_transfer);
-
-        let reject_transfer_msg = ExecuteMsg::RejectTransfer {
-            id: TRANSFER_ID.into(),
+            amount: Uint128::new(amount),
+            recipient: "transfer_to".into(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
         };
-
-        // execute reject transfer
-        let reject_response = execute(
+        let response = execute(
             deps.as_mut(),
             mock_env(),
-            sender_info.clone(),
-            reject_transfer_msg.clone(),
+            mock_info("sender", &[]),
+            ExecuteMsg::BatchTransfer {
+                transfers: vec![
+                    transfer_input(TRANSFER_ID, 4),
+                    transfer_input(other_transfer_id, 5),
+                ],
+            },
+        )
+        .unwrap();
+        assert!(response.events[0]
+            .attributes
+            .contains(&attr("auto_approve_threshold", "5")));
+        assert_eq!(2, response.messages.len());
+        assert_eq!(ReplyOn::Always, response.messages[1].reply_on);
+        assert_eq!(
+            TransferStatus::Settled,
+            load_transfer(&deps.storage, TRANSFER_ID).unwrap().status
         );
-
-        assert_sent_funds_unsupported_error(reject_response);
-
         assert_eq!(
-            stored_transfer,
-            TRANSFER_STORAGE
-                .load(&deps.storage, TRANSFER_ID.as_bytes())
+            TransferStatus::Pending,
+            load_transfer(&deps.storage, other_transfer_id)
                 .unwrap()
+                .status
         );
+
+        let allowance_id = "0a1e5a3c-5f0e-4b8e-9b4a-1d5b0c6f7a01";
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender", &[]),
+            ExecuteMsg::GrantAllowance {
+                id: allowance_id.into(),
+                recipient: "transfer_to".into(),
+                denom: RESTRICTED_DENOM.into(),
+                amount: Uint128::new(5),
+            },
+        )
+        .unwrap();
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("transfer_to", &[]),
+            ExecuteMsg::PullTransfer {
+                allowance_id: allowance_id.into(),
+                amount: Uint128::new(3),
+            },
+        )
+        .unwrap();
+        assert!(response
+            .attributes
+            .contains(&attr("auto_approve_threshold", "5")));
+        assert_eq!(2, response.messages.len());
+        let transfer_id = derived_transfer_id("allowance", &transfer_key(allowance_id).unwrap(), 1);
+        let transfer = load_transfer(&deps.storage, &transfer_id).unwrap();
+        assert_eq!(TransferStatus::Settled, transfer.status);
+        assert_eq!(mock_env().contract.address, transfer.approvals[0].approver);
+    }
+
+    fn assert_load_transfer_error(response: Result<Response, ContractError>) {
+        match response {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::LoadTransferFailed { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+    }
+
+    fn assert_sent_funds_unsupported_error(response: Result<Response, ContractError>) {
+        match response {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(error) => match error {
+                ContractError::SentFundsUnsupported => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+    }
+
+    fn setup_test_base(storage: &mut dyn Storage, contract_info: &State) {
+        if let Err(error) = CONFIG.save(storage, contract_info) {
+            panic!("unexpected error: {:?}", error)
+        }
+        for denom in [RESTRICTED_DENOM, OTHER_RESTRICTED_DENOM] {
+            REGISTERED_DENOMS.save(storage, denom, &Empty {}).unwrap();
+        }
     }
 
     #[test]
-    fn reject_transfer_unauthorized() {
+    fn propose_swap_success() {
         let mut deps = mock_provenance_dependencies();
         setup_test_base(
             &mut deps.storage,
             &State {
                 name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
             },
         );
 
-        let transfer_address = Addr::unchecked("transfer_address");
-        let sender_address = Addr::unchecked("sender_address");
-        let recipient_address = Addr::unchecked("transfer_to");
-
-        let test_marker =
-            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        let test_marker: MarkerAccount = setup_restricted_marker();
         mock_query_marker_response(&test_marker, &mut deps.querier);
 
-        let amount = Uint128::new(3);
-        let sender_info = mock_info(sender_address.as_str(), &[]);
+        deps.querier.mock_querier.update_balance(
+            Addr::unchecked("initiator"),
+            vec![coin(5, RESTRICTED_DENOM)],
+        );
 
-        let stored_transfer = Transfer {
-            id: TRANSFER_ID.into(),
-            sender: sender_address.to_owned(),
-            denom: RESTRICTED_DENOM.into(),
-            amount,
-            recipient: recipient_address.to_owned(),
-        };
-        store_test_transfer(&mut deps.storage, &stored_transfer);
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("initiator", &[]),
+            propose_test_swap_msg(),
+        )
+        .unwrap();
 
-        let reject_transfer_msg = ExecuteMsg::RejectTransfer {
-            id: TRANSFER_ID.into(),
-        };
+        assert_eq!(response.attributes.len(), 8);
+        assert_eq!(
+            response.attributes[0],
+            attr("action", Action::ProposeSwap.to_string())
+        );
+        assert_eq!(response.attributes[7], attr("counterparty", "counterparty"));
+
+        assert_eq!(response.messages.len(), 1);
+        let expected_message: Binary = MsgTransferRequest {
+            amount: Some(Coin {
+                denom: RESTRICTED_DENOM.to_owned(),
+                amount: "5".into(),
+            }),
+            from_address: "initiator".to_owned(),
+            to_address: MOCK_CONTRACT_ADDR.to_owned(),
+            administrator: MOCK_CONTRACT_ADDR.to_owned(),
+        }
+        .into();
+        match &response.messages[0].msg {
+            CosmosMsg::Stargate { type_url, value } => {
+                assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
+                assert_eq!(value, &expected_message);
+            }
+            _ => panic!("unexpected cosmos message"),
+        }
+
+        let stored_swap = SWAP_STORAGE
+            .load(&deps.storage, TRANSFER_ID.as_bytes())
+            .unwrap();
+        assert_eq!(SwapStatus::Proposed, stored_swap.status);
+        assert_eq!(
+            Addr::unchecked("counterparty"),
+            stored_swap.counterparty.owner
+        );
+    }
+
+    #[test]
+    fn fund_swap_unauthorized() {
+        let mut deps = mock_provenance_dependencies();
+        setup_test_base(
+            &mut deps.storage,
+            &State {
+                name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
+            },
+        );
+        store_test_swap(&mut deps.storage);
 
-        // execute reject transfer
-        let transfer_response = execute(
+        let fund_response = execute(
             deps.as_mut(),
             mock_env(),
-            sender_info.clone(),
-            reject_transfer_msg.clone(),
+            mock_info("initiator", &[]),
+            ExecuteMsg::FundSwap {
+                id: TRANSFER_ID.into(),
+            },
         );
 
-        // verify reject transfer response
-        match transfer_response {
+        match fund_response {
             Ok(..) => panic!("expected error, but ok"),
-            Err(error) => match error {
-                ContractError::Unauthorized { .. } => {}
-                error => panic!("unexpected error: {:?}", error),
-            },
+            Err(ContractError::Unauthorized { error }) => {
+                assert_eq!(error, "Only the counterparty can fund a swap")
+            }
+            Err(error) => panic!("unexpected error: {:?}", error),
         }
-
-        assert_eq!(
-            stored_transfer,
-            TRANSFER_STORAGE
-                .load(&deps.storage, TRANSFER_ID.as_bytes())
-                .unwrap()
-        );
     }
 
     #[test]
-    fn reject_transfer_unknown_transfer() {
+    fn swap_settles_once_funded_and_approved() {
         let mut deps = mock_provenance_dependencies();
         setup_test_base(
             &mut deps.storage,
             &State {
                 name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
             },
         );
 
-        let sender_address = Addr::unchecked("sender_address");
-        let sender_info = mock_info(sender_address.as_str(), &[]);
-
-        let reject_transfer_msg = ExecuteMsg::RejectTransfer {
-            id: TRANSFER_ID.into(),
-        };
+        let transfer_address = Addr::unchecked("transfer_address");
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+        store_test_swap(&mut deps.storage);
 
-        // execute reject transfer
-        let transfer_response = execute(
+        // the approver holds transfer permission for both markers and approves both legs at once
+        let approve_response = execute(
             deps.as_mut(),
             mock_env(),
-            sender_info.clone(),
-            reject_transfer_msg.clone(),
+            mock_info(transfer_address.as_str(), &[]),
+            ExecuteMsg::ApproveSwap {
+                id: TRANSFER_ID.into(),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            approve_response.attributes[2],
+            attr(
+                "approved_denoms",
+                format!("{},{}", RESTRICTED_DENOM, OTHER_RESTRICTED_DENOM)
+            )
         );
+        assert!(approve_response.messages.is_empty());
 
-        assert_load_transfer_error(transfer_response);
+        deps.querier.mock_querier.update_balance(
+            Addr::unchecked("counterparty"),
+            vec![coin(2, OTHER_RESTRICTED_DENOM)],
+        );
+
+        let fund_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("counterparty", &[]),
+            ExecuteMsg::FundSwap {
+                id: TRANSFER_ID.into(),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(fund_response.attributes[5], attr("settled", "true"));
+        assert_eq!(fund_response.messages.len(), 3);
+
+        for (message, (denom, amount, from, to)) in fund_response.messages.iter().zip([
+            (
+                OTHER_RESTRICTED_DENOM,
+                "2",
+                "counterparty",
+                MOCK_CONTRACT_ADDR,
+            ),
+            (RESTRICTED_DENOM, "5", MOCK_CONTRACT_ADDR, "counterparty"),
+            (OTHER_RESTRICTED_DENOM, "2", MOCK_CONTRACT_ADDR, "initiator"),
+        ]) {
+            let expected_message: Binary = MsgTransferRequest {
+                amount: Some(Coin {
+                    denom: denom.to_owned(),
+                    amount: amount.into(),
+                }),
+                from_address: from.to_owned(),
+                to_address: to.to_owned(),
+                administrator: MOCK_CONTRACT_ADDR.to_owned(),
+            }
+            .into();
+            match &message.msg {
+                CosmosMsg::Stargate { type_url, value } => {
+                    assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
+                    assert_eq!(value, &expected_message);
+                }
+                _ => panic!("unexpected cosmos message"),
+            }
+        }
+
+        let stored_swap = SWAP_STORAGE
+            .load(&deps.storage, TRANSFER_ID.as_bytes())
+            .unwrap();
+        assert_eq!(SwapStatus::Settled, stored_swap.status);
     }
 
     #[test]
-    fn query_transfer_by_id_test() {
+    fn blocked_swap_party_is_refused() {
         let mut deps = mock_provenance_dependencies();
         setup_test_base(
             &mut deps.storage,
             &State {
                 name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
             },
         );
 
-        let sender_address = Addr::unchecked("sender_address");
-        let recipient_address = Addr::unchecked("transfer_to");
-
-        let amount = Uint128::new(3);
+        let transfer_address = Addr::unchecked("transfer_address");
+        let test_marker: MarkerAccount =
+            setup_restricted_marker_transfer(RESTRICTED_DENOM.into(), transfer_address.to_owned());
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+        deps.querier.mock_querier.update_balance(
+            Addr::unchecked("counterparty"),
+            vec![coin(2, OTHER_RESTRICTED_DENOM)],
+        );
 
-        let transfer = &Transfer {
+        let counterparty = Addr::unchecked("counterparty");
+        let fund_msg = ExecuteMsg::FundSwap {
             id: TRANSFER_ID.into(),
-            sender: sender_address.to_owned(),
-            denom: RESTRICTED_DENOM.into(),
-            amount,
-            recipient: recipient_address.to_owned(),
         };
-        store_test_transfer(&mut deps.storage, transfer);
+        BLOCKLIST
+            .save(&mut deps.storage, &counterparty, &Empty {})
+            .unwrap();
 
-        let query_transfer_response = query(
-            deps.as_ref(),
+        let error = execute(
+            deps.as_mut(),
             mock_env(),
-            QueryMsg::GetTransfer {
+            mock_info("initiator", &[]),
+            propose_test_swap_msg(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ContractError::AddressBlocked { address } if address == "counterparty"
+        ));
+
+        store_test_swap(&mut deps.storage);
+        let error = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("counterparty", &[]),
+            fund_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ContractError::AddressBlocked { address } if address == "counterparty"
+        ));
+
+        // a party blocked once both legs are escrowed has the swap refunded instead of settled
+        BLOCKLIST.remove(&mut deps.storage, &counterparty);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("counterparty", &[]),
+            fund_msg,
+        )
+        .unwrap();
+        BLOCKLIST
+            .save(&mut deps.storage, &counterparty, &Empty {})
+            .unwrap();
+
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(transfer_address.as_str(), &[]),
+            ExecuteMsg::ApproveSwap {
                 id: TRANSFER_ID.into(),
             },
+        )
+        .unwrap();
+        assert_eq!(
+            vec![Event::new("blocked_party_refused")
+                .add_attribute("id", TRANSFER_ID)
+                .add_attribute("address", "counterparty")],
+            response.events
         );
+        assert!(!response.attributes.contains(&attr("settled", "true")));
+
+        for (message, (denom, amount, to)) in response.messages.iter().zip([
+            (RESTRICTED_DENOM, "5", "initiator"),
+            (OTHER_RESTRICTED_DENOM, "2", "counterparty"),
+        ]) {
+            let expected_message: Binary = MsgTransferRequest {
+                amount: Some(Coin {
+                    denom: denom.to_owned(),
+                    amount: amount.into(),
+                }),
+                from_address: MOCK_CONTRACT_ADDR.to_owned(),
+                to_address: to.to_owned(),
+                administrator: MOCK_CONTRACT_ADDR.to_owned(),
+            }
+            .into();
+            match &message.msg {
+                CosmosMsg::Stargate { value, .. } => assert_eq!(value, &expected_message),
+                _ => panic!("unexpected cosmos message"),
+            }
+        }
+        assert_eq!(2, response.messages.len());
 
-        assert_eq!(to_binary(transfer), query_transfer_response);
+        let stored_swap = SWAP_STORAGE
+            .load(&deps.storage, TRANSFER_ID.as_bytes())
+            .unwrap();
+        assert_eq!(SwapStatus::Rejected, stored_swap.status);
+        assert_eq!(
+            Some(String::from("counterparty is blocked")),
+            stored_swap.rejection_reason
+        );
     }
 
     #[test]
-    fn query_contract_info() {
+    fn approve_swap_unauthorized() {
         let mut deps = mock_provenance_dependencies();
         setup_test_base(
             &mut deps.storage,
             &State {
                 name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
             },
         );
 
-        let query_contract_info_response =
-            query(deps.as_ref(), mock_env(), QueryMsg::GetContractInfo {});
+        let test_marker: MarkerAccount = setup_restricted_marker_transfer(
+            RESTRICTED_DENOM.into(),
+            Addr::unchecked("transfer_address"),
+        );
+        mock_query_marker_response(&test_marker, &mut deps.querier);
+        store_test_swap(&mut deps.storage);
 
-        match query_contract_info_response {
-            Ok(contract_info) => {
-                assert_eq!(
-                    contract_info,
-                    to_binary(&CONFIG.load(&deps.storage).unwrap()).unwrap()
-                )
-            }
+        let approve_response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("initiator", &[]),
+            ExecuteMsg::ApproveSwap {
+                id: TRANSFER_ID.into(),
+            },
+        );
+
+        match approve_response {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(ContractError::Unauthorized { .. }) => {}
             Err(error) => panic!("unexpected error: {:?}", error),
         }
     }
 
     #[test]
-    fn query_version_info() {
+    fn cancel_funded_swap_refunds_both_legs() {
         let mut deps = mock_provenance_dependencies();
         setup_test_base(
             &mut deps.storage,
             &State {
                 name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
             },
         );
+        store_test_swap(&mut deps.storage);
 
-        let result = cw2::set_contract_version(deps.as_mut().storage, CRATE_NAME, PACKAGE_VERSION);
-        match result {
-            Ok(..) => {}
-            Err(error) => panic!("unexpected error: {:?}", error),
-        }
+        let mut swap = SWAP_STORAGE
+            .load(&deps.storage, TRANSFER_ID.as_bytes())
+            .unwrap();
+        swap.status = SwapStatus::Funded;
+        SWAP_STORAGE
+            .save(&mut deps.storage, TRANSFER_ID.as_bytes(), &swap)
+            .unwrap();
 
-        let query_version_info_response =
-            query(deps.as_ref(), mock_env(), QueryMsg::GetVersionInfo {});
+        let response = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("counterparty", &[]),
+            ExecuteMsg::CancelSwap {
+                id: TRANSFER_ID.into(),
+            },
+        )
+        .unwrap();
 
-        match query_version_info_response {
-            Ok(version_info) => {
-                assert_eq!(
-                    version_info,
-                    to_binary(&cw2::get_contract_version(&deps.storage).unwrap()).unwrap()
-                )
+        assert_eq!(response.messages.len(), 2);
+
+        for (message, (denom, amount, to)) in response.messages.iter().zip([
+            (RESTRICTED_DENOM, "5", "initiator"),
+            (OTHER_RESTRICTED_DENOM, "2", "counterparty"),
+        ]) {
+            let expected_message: Binary = MsgTransferRequest {
+                amount: Some(Coin {
+                    denom: denom.to_owned(),
+                    amount: amount.into(),
+                }),
+                from_address: MOCK_CONTRACT_ADDR.to_owned(),
+                to_address: to.to_owned(),
+                administrator: MOCK_CONTRACT_ADDR.to_owned(),
+            }
+            .into();
+            match &message.msg {
+                CosmosMsg::Stargate { type_url, value } => {
+                    assert_eq!(type_url, "/provenance.marker.v1.MsgTransferRequest");
+                    assert_eq!(value, &expected_message);
+                }
+                _ => panic!("unexpected cosmos message"),
             }
-            Err(error) => panic!("unexpected error: {:?}", error),
         }
+
+        let stored_swap = SWAP_STORAGE
+            .load(&deps.storage, TRANSFER_ID.as_bytes())
+            .unwrap();
+        assert_eq!(SwapStatus::Cancelled, stored_swap.status);
     }
 
     #[test]
-    fn query_all_transfers() {
+    fn create_recurring_transfer_success() {
         let mut deps = mock_provenance_dependencies();
         setup_test_base(
             &mut deps.storage,
             &State {
                 name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
             },
         );
 
         let test_marker: MarkerAccount = setup_restricted_marker();
         mock_query_marker_response(&test_marker, &mut deps.querier);
 
-        let amount = Uint128::new(1);
-        let transfer_msg = ExecuteMsg::Transfer {
-            id: TRANSFER_ID.into(),
-            denom: RESTRICTED_DENOM.into(),
-            amount: amount.into(),
-            recipient: "transfer_to".into(),
-        };
-
-        let sender_info = mock_info("sender", &[]);
-
-        let sender_balance = coin(1, RESTRICTED_DENOM);
-        deps.querier
-            .mock_querier
-            .update_balance(Addr::unchecked("sender"), vec![sender_balance]);
-
-        // execute create transfer
-        execute(
+        let env = mock_env();
+        let response = execute(
             deps.as_mut(),
-            mock_env(),
-            sender_info.clone(),
-            transfer_msg.clone(),
+            env.clone(),
+            mock_info("sender", &[]),
+            ExecuteMsg::CreateRecurringTransfer {
+                id: TRANSFER_ID.into(),
+                denom: RESTRICTED_DENOM.into(),
+                amount: Uint128::new(2),
+                recipient: "transfer_to".into(),
+                interval: ScheduleInterval::Blocks(100),
+                occurrences: 3,
+            },
         )
         .unwrap();
 
-        // verify transfer response
-        let query_all_transfers_response =
-            query(deps.as_ref(), mock_env(), QueryMsg::GetAllTransfers {}).unwrap();
-        let all_transfers: Vec<Transfer> = from_binary(&query_all_transfers_response).unwrap();
-        assert_eq!(1, all_transfers.len());
-        assert_eq!(TRANSFER_ID.to_string(), all_transfers[0].id);
-        assert_eq!(RESTRICTED_DENOM.to_string(), all_transfers[0].denom);
-        assert_eq!(amount, all_transfers[0].amount);
-        assert_eq!("transfer_to".to_string(), all_transfers[0].recipient);
+        assert_eq!(
+            response.attributes[0],
+            attr("action", Action::CreateRecurringTransfer.to_string())
+        );
+        // nothing is escrowed until an occurrence is created
+        assert!(response.messages.is_empty());
+
+        let stored_recurring_transfer = RECURRING_TRANSFER_STORAGE
+            .load(&deps.storage, &transfer_key(TRANSFER_ID).unwrap())
+            .unwrap();
+        assert_eq!(
+            RecurringTransfer {
+                id: TRANSFER_ID.into(),
+                sender: Addr::unchecked("sender"),
+                denom: RESTRICTED_DENOM.into(),
+                amount: Uint128::new(2),
+                recipient: Addr::unchecked("transfer_to"),
+                interval: ScheduleInterval::Blocks(100),
+                occurrences: 3,
+                executed: 0,
+                start_height: env.block.height,
+                start_time: env.block.time,
+            },
+            stored_recurring_transfer
+        );
     }
 
     #[test]
-    fn query_all_transfers_empty() {
+    fn execute_scheduled_creates_each_occurrence_when_due() {
         let mut deps = mock_provenance_dependencies();
         setup_test_base(
             &mut deps.storage,
             &State {
                 name: "contract_name".into(),
+                admin: None,
+                default_expiration: None,
+                allowed_denoms: None,
             },
         );
 
         let test_marker: MarkerAccount = setup_restricted_marker();
         mock_query_marker_response(&test_marker, &mut deps.querier);
 
-        let sender_balance = coin(1, RESTRICTED_DENOM);
+        let mut env = mock_env();
+        RECURRING_TRANSFER_STORAGE
+            .save(
+                &mut deps.storage,
+                &transfer_key(TRANSFER_ID).unwrap(),
+                &RecurringTransfer {
+                    id: TRANSFER_ID.into(),
+                    sender: Addr::unchecked("sender"),
+                    denom: RESTRICTED_DENOM.into(),
+                    amount: Uint128::new(2),
+                    recipient: Addr::unchecked("transfer_to"),
+                    interval: ScheduleInterval::Blocks(100),
+                    occurrences: 2,
+                    executed: 0,
+                    start_height: env.block.height,
+                    start_time: env.block.time,
+                },
+            )
+            .unwrap();
+
         deps.querier
             .mock_querier
-            .update_balance(Addr::unchecked("sender"), vec![sender_balance]);
+            .update_balance(Addr::unchecked("sender"), vec![coin(4, RESTRICTED_DENOM)]);
 
-        // verify transfer response
-        let query_all_transfers_response =
-            query(deps.as_ref(), mock_env(), QueryMsg::GetAllTransfers {}).unwrap();
-        let all_transfers: Vec<Transfer> = from_binary(&query_all_transfers_response).unwrap();
-        assert_eq!(0, all_transfers.len());
-    }
+        let execute_scheduled_msg = ExecuteMsg::ExecuteScheduled {
+            id: TRANSFER_ID.into(),
+        };
 
-    fn assert_load_transfer_error(response: Result<Response, ContractError>) {
-        match response {
+        // the first occurrence is due immediately
+        let response = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            execute_scheduled_msg.clone(),
+        )
+        .unwrap();
+
+        let first_transfer_id =
+            derived_transfer_id("recurring_transfer", &transfer_key(TRANSFER_ID).unwrap(), 1);
+        assert_eq!(
+            response.attributes[2],
+            attr("transfer_id", &first_transfer_id)
+        );
+        assert_eq!(response.messages.len(), 1);
+
+        let stored_transfer = load_transfer(&deps.storage, &first_transfer_id).unwrap();
+        assert_eq!(Addr::unchecked("transfer_to"), stored_transfer.recipient);
+        assert_eq!(Uint128::new(2), stored_transfer.amount);
+        assert_eq!(TransferStatus::Pending, stored_transfer.status);
+
+        // the second occurrence waits for the interval to pass
+        match execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            execute_scheduled_msg.clone(),
+        ) {
             Ok(..) => panic!("expected error, but ok"),
-            Err(error) => match error {
-                ContractError::LoadTransferFailed { .. } => {}
-                error => panic!("unexpected error: {:?}", error),
+            Err(ContractError::ScheduleNotDue) => {}
+            Err(error) => panic!("unexpected error: {:?}", error),
+        }
+
+        // the schedule is found whatever case its id is written in
+        env.block.height += 100;
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::ExecuteScheduled {
+                id: TRANSFER_ID.to_uppercase(),
             },
+        )
+        .unwrap();
+
+        // every occurrence has been created
+        env.block.height += 100;
+        match execute(
+            deps.as_mut(),
+            env,
+            mock_info("anyone", &[]),
+            execute_scheduled_msg,
+        ) {
+            Ok(..) => panic!("expected error, but ok"),
+            Err(ContractError::ScheduleNotDue) => {}
+            Err(error) => panic!("unexpected error: {:?}", error),
         }
+
+        let stored_recurring_transfer = RECURRING_TRANSFER_STORAGE
+            .load(&deps.storage, &transfer_key(TRANSFER_ID).unwrap())
+            .unwrap();
+        assert_eq!(2, stored_recurring_transfer.executed);
     }
 
-    fn assert_sent_funds_unsupported_error(response: Result<Response, ContractError>) {
-        match response {
-            Ok(..) => panic!("expected error, but ok"),
-            Err(error) => match error {
-                ContractError::SentFundsUnsupported => {}
-                error => panic!("unexpected error: {:?}", error),
-            },
+    fn propose_test_swap_msg() -> ExecuteMsg {
+        ExecuteMsg::ProposeSwap {
+            id: TRANSFER_ID.into(),
+            denom: RESTRICTED_DENOM.into(),
+            amount: Uint128::new(5),
+            counterparty: "counterparty".into(),
+            counterparty_denom: OTHER_RESTRICTED_DENOM.into(),
+            counterparty_amount: Uint128::new(2),
         }
     }
 
-    fn setup_test_base(storage: &mut dyn Storage, contract_info: &State) {
-        if let Err(error) = CONFIG.save(storage, &contract_info) {
+    fn store_test_swap(storage: &mut dyn Storage) {
+        let swap = Swap {
+            id: TRANSFER_ID.into(),
+            initiator: SwapLeg {
+                owner: Addr::unchecked("initiator"),
+                denom: RESTRICTED_DENOM.into(),
+                amount: Uint128::new(5),
+                approval: None,
+            },
+            counterparty: SwapLeg {
+                owner: Addr::unchecked("counterparty"),
+                denom: OTHER_RESTRICTED_DENOM.into(),
+                amount: Uint128::new(2),
+                approval: None,
+            },
+            status: SwapStatus::Proposed,
+            rejection_reason: None,
+        };
+        if let Err(error) = SWAP_STORAGE.save(storage, swap.id.as_bytes(), &swap) {
             panic!("unexpected error: {:?}", error)
+        };
+    }
+
+    /// stores a due recurring transfer and an allowance, both of 2 from sender to transfer_to,
+    /// and returns the results of creating a transfer from each
+    fn execute_scheduled_and_pull(mut deps: DepsMut) -> Vec<Result<Response, ContractError>> {
+        let env = mock_env();
+        let allowance_id = "9d3c5e7f-1a2b-4c6d-8e0f-a1b2c3d4e5f6";
+        RECURRING_TRANSFER_STORAGE
+            .save(
+                deps.storage,
+                &transfer_key(TRANSFER_ID).unwrap(),
+                &RecurringTransfer {
+                    id: TRANSFER_ID.into(),
+                    sender: Addr::unchecked("sender"),
+                    denom: RESTRICTED_DENOM.into(),
+                    amount: Uint128::new(2),
+                    recipient: Addr::unchecked("transfer_to"),
+                    interval: ScheduleInterval::Blocks(100),
+                    occurrences: 1,
+                    executed: 0,
+                    start_height: env.block.height,
+                    start_time: env.block.time,
+                },
+            )
+            .unwrap();
+        ALLOWANCE_STORAGE
+            .save(
+                deps.storage,
+                &transfer_key(allowance_id).unwrap(),
+                &Allowance {
+                    id: allowance_id.into(),
+                    sender: Addr::unchecked("sender"),
+                    recipient: Addr::unchecked("transfer_to"),
+                    denom: RESTRICTED_DENOM.into(),
+                    amount: Uint128::new(2),
+                    pulled: Uint128::zero(),
+                    pulls: 0,
+                },
+            )
+            .unwrap();
+
+        vec![
+            execute(
+                deps.branch(),
+                env.clone(),
+                mock_info("anyone", &[]),
+                ExecuteMsg::ExecuteScheduled {
+                    id: TRANSFER_ID.into(),
+                },
+            ),
+            execute(
+                deps,
+                env,
+                mock_info("transfer_to", &[]),
+                ExecuteMsg::PullTransfer {
+                    allowance_id: allowance_id.into(),
+                    amount: Uint128::new(2),
+                },
+            ),
+        ]
+    }
+
+    fn pending_test_transfer(sender: &Addr, recipient: &Addr, amount: u128) -> Transfer {
+        Transfer {
+            id: TRANSFER_ID.into(),
+            sender: sender.to_owned(),
+            denom: RESTRICTED_DENOM.into(),
+            amount: Uint128::new(amount),
+            recipient: recipient.to_owned(),
+            status: TransferStatus::Pending,
+            rejection_reason: None,
+            require_acceptance: false,
+            approvals: vec![],
+            required_approvers: vec![],
+            legs: vec![],
+            additional_coins: vec![],
+            vesting: None,
+            claimed: Uint128::zero(),
+            memo: None,
+            created: None,
+            updated_at: None,
+            sequence: None,
+            tags: vec![],
+            position: Some(1),
+            priority: false,
+            expires: None,
         }
     }
 
     fn store_test_transfer(storage: &mut dyn Storage, transfer: &Transfer) {
-        if let Err(error) = TRANSFER_STORAGE.save(storage, transfer.id.as_bytes(), transfer) {
+        if let Err(error) = save_transfer(storage, mock_env().block.height, transfer) {
             panic!("unexpected error: {:?}", error)
         };
     }
 
     fn setup_restricted_marker() -> MarkerAccount {
-        return MarkerAccount {
+        MarkerAccount {
             base_account: Some(BaseAccount {
                 address: "tp1l330sxue4suxz9dhc40e2pns0ymrytf8uz4squ".to_string(),
                 pub_key: None,
@@ -1665,11 +18164,11 @@ mod tests {
             allow_governance_control: true,
             allow_forced_transfer: false,
             required_attributes: vec![],
-        };
+        }
     }
 
     fn setup_restricted_marker_transfer(denom: String, admin: Addr) -> MarkerAccount {
-        return MarkerAccount {
+        MarkerAccount {
             base_account: Some(BaseAccount {
                 address: "tp1l330sxue4suxz9dhc40e2pns0ymrytf8uz4squ".to_string(),
                 pub_key: None,
@@ -1689,14 +18188,28 @@ mod tests {
                 ],
             }],
             status: MarkerStatus::Active.into(),
-            denom: denom,
+            denom,
             supply: "1000".to_string(),
             marker_type: MarkerType::Restricted.into(),
             supply_fixed: false,
             allow_governance_control: false,
             allow_forced_transfer: false,
             required_attributes: vec![],
-        };
+        }
+    }
+
+    fn mock_contract_admin(querier: &mut MockProvenanceQuerier, admin: &str) {
+        let admin = admin.to_string();
+        querier.mock_querier.update_wasm(move |query| match query {
+            WasmQuery::ContractInfo { .. } => {
+                let mut contract_info = ContractInfoResponse::default();
+                contract_info.admin = Some(admin.to_owned());
+                SystemResult::Ok(ContractResult::Ok(to_binary(&contract_info).unwrap()))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "wasm".into(),
+            }),
+        });
     }
 
     fn mock_query_marker_response(
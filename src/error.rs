@@ -1,10 +1,42 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Timestamp, Uint128};
 use thiserror::Error;
 
+use crate::state::{SwapStatus, TransferStatus};
+
 #[derive(Error, Debug)]
 pub enum ContractError {
-    #[error("Insufficient funds to complete the transfer")]
-    InsufficientFunds,
+    #[error("{address} may not send or receive transfers")]
+    AddressBlocked { address: String },
+
+    #[error("Senders may create at most {max_creations} transfers every {blocks} blocks")]
+    CreationRateExceeded { max_creations: u32, blocks: u64 },
+
+    #[error("Transfers of {denom} may be at most {max}")]
+    AmountAboveMaximum { denom: String, max: Uint128 },
+
+    #[error("Transfers of {denom} must be at least {min}")]
+    AmountBelowMinimum { denom: String, min: Uint128 },
+
+    #[error("Transfers of any denom may be at most {cap}")]
+    AmountExceedsCap { cap: Uint128 },
+
+    #[error("Only {remaining} of the allowance remains to be pulled")]
+    AllowanceExceeded { remaining: Uint128 },
+
+    #[error("Transfer was already approved by {approver}")]
+    DuplicateApproval { approver: String },
+
+    #[error("{denom} is not one of the denoms the contract accepts")]
+    DenomNotAllowed { denom: String },
+
+    #[error("Transfers of {denom} are frozen")]
+    DenomFrozen { denom: String },
+
+    #[error("The marker admin of {denom} has not registered it with the contract")]
+    DenomNotRegistered { denom: String },
+
+    #[error("Escrowing the transfer failed: {error}")]
+    EscrowFailed { error: String },
 
     #[error("Can only upgrade from same type")]
     InvalidContractType,
@@ -12,18 +44,98 @@ pub enum ContractError {
     #[error("Invalid fields: {fields:?}")]
     InvalidFields { fields: Vec<String> },
 
+    #[error("Transfer cannot move from {from:?} to {to:?}")]
+    InvalidStatusTransition {
+        from: TransferStatus,
+        to: TransferStatus,
+    },
+
+    #[error("Swap cannot move from {from:?} to {to:?}")]
+    InvalidSwapStatusTransition { from: SwapStatus, to: SwapStatus },
+
+    #[error("Failed to load swap: {error:?}")]
+    LoadSwapFailed { error: StdError },
+
     #[error("Failed to load transfer: {error:?}")]
     LoadTransferFailed { error: StdError },
 
+    #[error("Failed to load allowance: {error:?}")]
+    LoadAllowanceFailed { error: StdError },
+
+    #[error("Failed to load recurring transfer: {error:?}")]
+    LoadRecurringTransferFailed { error: StdError },
+
+    #[error("Failed to load transfer template: {error:?}")]
+    LoadTemplateFailed { error: StdError },
+
+    #[error("No occurrence of the recurring transfer is due")]
+    ScheduleNotDue,
+
+    #[error("No balance of {denom} is held beyond its escrow")]
+    NoOrphanedFunds { denom: String },
+
+    #[error("No escrow recovery has been initiated")]
+    NoRecoveryPending,
+
+    #[error("No failed settlements of transfer {id}")]
+    NoFailedSettlements { id: String },
+
+    #[error("Nothing has vested for the recipient to claim")]
+    NothingToClaim,
+
+    #[error("The contract is paused")]
+    Paused,
+
+    #[error("{recipient} is not on the recipient allowlist of {denom}")]
+    RecipientNotAllowed { recipient: String, denom: String },
+
+    #[error("The escrow recovery cannot be executed before {executable_at}")]
+    RecoveryLocked { executable_at: Timestamp },
+
+    #[error("No retention period is set for completed transfers")]
+    RetentionPeriodNotSet,
+
     #[error("{0}")]
     Std(#[from] StdError),
 
     #[error("bank sends are not allowed in restricted marker transfers")]
     SentFundsUnsupported,
 
+    #[error("A deposit of {deposit} must be sent to create transfers")]
+    DepositRequired { deposit: String },
+
+    #[error("No fees of {denom} have accrued")]
+    NoFeesAccrued { denom: String },
+
+    #[error("{denom} is not an active unrestricted coin the contract can collect fees in")]
+    InvalidFeeDenom { denom: String },
+
+    #[error("Senders may have at most {limit} transfers pending")]
+    TooManyPendingTransfers { limit: u32 },
+
+    #[error("Transfer {id} is not in the history")]
+    NotInHistory { id: String },
+
+    #[error("Transfer expired at {expires}")]
+    TransferExpired { expires: Timestamp },
+
+    #[error("Transfer is frozen: {reason}")]
+    TransferFrozen { reason: String },
+
+    #[error("Transfer is no longer pending: {status:?}")]
+    TransferNotPending { status: TransferStatus },
+
+    #[error(
+        "Only {remaining} more {denom} may be transferred by the sender in the current window"
+    )]
+    VelocityLimitExceeded { denom: String, remaining: Uint128 },
+
     #[error("Unauthorized: {error:?}")]
     Unauthorized { error: String },
 
+    #[error("Unknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
+
     #[error("Unsupported upgrade: {source_version:?} => {target_version:?}")]
     UnsupportedUpgrade {
         source_version: String,
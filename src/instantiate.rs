@@ -1,7 +1,11 @@
 use crate::contract::{CRATE_NAME, PACKAGE_VERSION};
 use crate::error::contract_err;
+use crate::migrate::LATEST_SCHEMA_VERSION;
 use crate::msg::{InstantiateMsg, Validate};
-use crate::state::{State, CONFIG};
+use crate::state::{
+    FeeDestination, State, CONFIG, CREATION_DEPOSIT, MSG_FEES, PENDING_LIMIT, SCHEMA_VERSION,
+    SETTLEMENT_FEE,
+};
 use crate::ContractError;
 use cosmwasm_std::{attr, entry_point, DepsMut, Env, MessageInfo, Response};
 use cw2::set_contract_version;
@@ -20,8 +24,38 @@ pub fn instantiate(
         return Err(contract_err("no funds should be sent during instantiate"));
     }
     // Create and store config state.
-    let contract_info = State { name: msg.name };
+    let admin = match msg.admin {
+        Some(admin) => deps.api.addr_validate(&admin)?,
+        None => info.sender,
+    };
+    let contract_info = State {
+        name: msg.name,
+        admin: Some(admin),
+        default_expiration: msg.default_expiration,
+        allowed_denoms: msg.allowed_denoms,
+    };
     CONFIG.save(deps.storage, &contract_info)?;
+    if let Some(limit) = msg.pending_limit {
+        PENDING_LIMIT.save(deps.storage, &limit)?;
+    }
+    if let Some(fee) = msg.settlement_fee {
+        if let FeeDestination::Collector { address } = &fee.destination {
+            deps.api.addr_validate(address.as_str())?;
+        }
+        SETTLEMENT_FEE.save(deps.storage, &fee)?;
+    }
+    if let Some(fees) = msg.msg_fees {
+        if let Some(recipient) = &fees.recipient {
+            deps.api.addr_validate(recipient.as_str())?;
+        }
+        MSG_FEES.save(deps.storage, &fees)?;
+    }
+    if let Some(deposit) = msg.creation_deposit {
+        deps.api.addr_validate(deposit.collector.as_str())?;
+        CREATION_DEPOSIT.save(deps.storage, &deposit)?;
+    }
+    // a new contract starts out with the latest storage layout
+    SCHEMA_VERSION.save(deps.storage, &LATEST_SCHEMA_VERSION)?;
 
     set_contract_version(deps.storage, CRATE_NAME, PACKAGE_VERSION)?;
 
@@ -35,7 +69,9 @@ pub fn instantiate(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::state::{CreationDeposit, MsgFees, SettlementFee};
     use cosmwasm_std::testing::{mock_env, mock_info};
+    use cosmwasm_std::{Addr, Coin, Uint128};
     use provwasm_mocks::mock_provenance_dependencies;
 
     #[test]
@@ -47,6 +83,13 @@ mod tests {
 
         let init_msg = InstantiateMsg {
             name: contract_name.into(),
+            admin: None,
+            default_expiration: None,
+            pending_limit: None,
+            allowed_denoms: None,
+            settlement_fee: None,
+            msg_fees: None,
+            creation_deposit: None,
         };
 
         let init_response = instantiate(deps.as_mut(), mock_env(), info, init_msg.clone());
@@ -60,6 +103,9 @@ mod tests {
 
                 let expected_state = State {
                     name: contract_name.into(),
+                    admin: Some(Addr::unchecked("contract_admin")),
+                    default_expiration: None,
+                    allowed_denoms: None,
                 };
 
                 assert_eq!(
@@ -72,8 +118,66 @@ mod tests {
 
                 assert_eq!(PACKAGE_VERSION, version_info.version);
                 assert_eq!(CRATE_NAME, version_info.contract);
+                assert_eq!(
+                    LATEST_SCHEMA_VERSION,
+                    SCHEMA_VERSION.load(&deps.storage).unwrap()
+                );
             }
             error => panic!("failed to initialize: {:?}", error),
         }
     }
+
+    #[test]
+    fn initialization_with_settings() {
+        let mut deps = mock_provenance_dependencies();
+
+        let init_msg = InstantiateMsg {
+            name: "please transfer me".into(),
+            admin: Some("contract_admin".into()),
+            default_expiration: Some(3600),
+            pending_limit: Some(5),
+            allowed_denoms: Some(vec!["restricted_1".into()]),
+            settlement_fee: Some(SettlementFee {
+                bps: 25,
+                destination: FeeDestination::Accrue {},
+            }),
+            msg_fees: Some(MsgFees {
+                creation_fee: Some(Uint128::new(100)),
+                approval_fee: None,
+                recipient: Some(Addr::unchecked("fee_recipient")),
+                recipient_bps: 5_000,
+            }),
+            creation_deposit: Some(CreationDeposit {
+                amount: Coin::new(1_000, "nhash"),
+                collector: Addr::unchecked("collector"),
+            }),
+        };
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("deployer", &[]),
+            init_msg,
+        )
+        .unwrap();
+
+        assert_eq!(
+            State {
+                name: "please transfer me".into(),
+                admin: Some(Addr::unchecked("contract_admin")),
+                default_expiration: Some(3600),
+                allowed_denoms: Some(vec!["restricted_1".into()]),
+            },
+            CONFIG.load(&deps.storage).unwrap()
+        );
+        assert_eq!(5, PENDING_LIMIT.load(&deps.storage).unwrap());
+        assert_eq!(25, SETTLEMENT_FEE.load(&deps.storage).unwrap().bps);
+        assert_eq!(
+            Some(Uint128::new(100)),
+            MSG_FEES.load(&deps.storage).unwrap().creation_fee
+        );
+        assert_eq!(
+            Addr::unchecked("collector"),
+            CREATION_DEPOSIT.load(&deps.storage).unwrap().collector
+        );
+    }
 }
@@ -1,7 +1,7 @@
 pub mod contract;
 mod error;
-mod instantiate;
-mod migrate;
+pub mod instantiate;
+pub mod migrate;
 pub mod msg;
 pub mod state;
 
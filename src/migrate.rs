@@ -1,16 +1,53 @@
-use cosmwasm_std::{entry_point, DepsMut, Env, Response};
+use std::collections::BTreeMap;
+
+use cosmwasm_std::{
+    attr, entry_point, Addr, DepsMut, Empty, Env, Order, Response, StdResult, Storage,
+};
 use cw2::set_contract_version;
-use cw_storage_plus::Item;
-use semver::{Version, VersionReq};
+use cw_storage_plus::{Item, Map};
+use semver::Version;
 
 use crate::contract::{CRATE_NAME, PACKAGE_VERSION};
 use crate::error::ContractError;
-use crate::msg::MigrateMsg;
-use crate::state::{State, CONFIG};
-use crate::ContractError::{InvalidContractType, UnsupportedUpgrade};
+use crate::msg::{MigrateMsg, MAX_BATCH_SIZE};
+use crate::state::{
+    archive_transfer, is_legacy_key, migrate_legacy_transfers, next_position, save_transfer,
+    transfer_key, update_denom_counters, update_denom_volumes, FeeSchedule, State, StoredTransfer,
+    TransferStats, TransferStatus, TransferTotals, ALLOWANCE_STORAGE, CONFIG, DENOM_COUNTERS,
+    DENOM_FEE_SCHEDULES, DENOM_VOLUME, PENDING_BY_SENDER, PENDING_QUEUE, PENDING_QUEUE_BY_DENOM,
+    RECURRING_TRANSFER_STORAGE, REGISTERED_DENOMS, SCHEMA_VERSION, TRANSFER_HISTORY,
+    TRANSFER_STATS, TRANSFER_STORAGE, TRANSFER_TOTALS,
+};
+use crate::ContractError::{InvalidContractType, Unauthorized, UnsupportedUpgrade};
+
+/// A migration step, bringing storage from the schema version at its position to the next one
+type MigrationStep = fn(&mut dyn Storage, &Env) -> Result<(), ContractError>;
+
+/// Every migration step in the order they run. New steps are appended, never reordered, as the
+/// position of a step is the schema version it migrates from
+const MIGRATION_STEPS: &[MigrationStep] = &[
+    migrate_legacy_config,
+    backfill_transfer_stats,
+    backfill_denom_volumes,
+    backfill_transfer_indexes,
+    rekey_transfers_by_uuid,
+    archive_completed_transfers,
+    backfill_denom_counters,
+    backfill_transfer_totals,
+    compact_transfer_encoding,
+    backfill_pending_queue,
+    register_denoms_in_use,
+    convert_denom_fee_rates,
+    backfill_pending_by_sender,
+    rekey_schedules_and_allowances,
+    backfill_pending_queue_by_denom,
+];
+
+/// The schema version storage is in once every migration step has run
+pub const LATEST_SCHEMA_VERSION: u32 = MIGRATION_STEPS.len() as u32;
 
 #[entry_point]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
     let stored_contract_version = cw2::get_contract_version(deps.storage)?;
 
     // ensure we are migrating from an allowed contract
@@ -27,29 +64,299 @@ pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, C
         });
     }
 
-    let config_migration_req = VersionReq::parse("<0.3.0").unwrap();
-
-    if config_migration_req.matches(&current_version) {
-        if CONFIG.may_load(deps.storage)?.is_none() {
-            // when migrating from cosmwasm-storage::Singleton to Item, cosmwasm_std::storage_keys::to_length_prefixed
-            // was used for the key. Hardcoding this value to copy the legacy storage
-            const LEGACY_CONFIG: Item<State> = Item::new("\0\u{6}config");
-            let state = LEGACY_CONFIG.load(deps.storage).unwrap();
-            CONFIG.save(deps.storage, &state)?;
-            LEGACY_CONFIG.remove(deps.storage)
+    // only the wasm admin can submit a migration, so it has to be the admin the contract records.
+    // Without a wasm admin the migration came through a governance proposal, and contracts that
+    // record no admin defer to the wasm admin already
+    if let Some(admin) = CONFIG.may_load(deps.storage)?.and_then(|state| state.admin) {
+        let contract_info = deps
+            .querier
+            .query_wasm_contract_info(env.contract.address.to_owned())?;
+        if contract_info
+            .admin
+            .is_some_and(|wasm_admin| wasm_admin != admin.as_str())
+        {
+            return Err(Unauthorized {
+                error: String::from("Only the contract admin can migrate the contract"),
+            });
         }
     }
 
+    // contracts stored before the schema version existed run every step, each of which leaves
+    // storage it has already migrated untouched
+    let schema_version = SCHEMA_VERSION.may_load(deps.storage)?.unwrap_or_default();
+    for step in MIGRATION_STEPS.iter().skip(schema_version as usize) {
+        step(deps.storage, &env)?;
+    }
+    SCHEMA_VERSION.save(deps.storage, &LATEST_SCHEMA_VERSION)?;
+
     set_contract_version(deps.storage, CRATE_NAME, PACKAGE_VERSION)?;
-    Ok(Response::default())
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "migrate"),
+        attr("from_version", current_version.to_string()),
+        attr("to_version", new_version.to_string()),
+        attr("schema_version", LATEST_SCHEMA_VERSION.to_string()),
+    ]))
+}
+
+fn migrate_legacy_config(storage: &mut dyn Storage, _env: &Env) -> Result<(), ContractError> {
+    // when migrating from cosmwasm-storage::Singleton to Item, cosmwasm_std::storage_keys::to_length_prefixed
+    // was used for the key. Hardcoding this value to copy the legacy storage
+    const LEGACY_CONFIG: Item<State> = Item::new("\0\u{6}config");
+    if CONFIG.may_load(storage)?.is_none() {
+        if let Some(state) = LEGACY_CONFIG.may_load(storage)? {
+            CONFIG.save(storage, &state)?;
+            LEGACY_CONFIG.remove(storage)
+        }
+    }
+    Ok(())
+}
+
+fn backfill_transfer_stats(storage: &mut dyn Storage, _env: &Env) -> Result<(), ContractError> {
+    // status counts are only kept up to date from the first save after they were introduced
+    if TRANSFER_STATS.may_load(storage)?.is_none() {
+        let mut stats = TransferStats::default();
+        for item in TRANSFER_STORAGE.range(storage, None, None, Order::Ascending) {
+            let (_, StoredTransfer(transfer)) = item?;
+            stats.record(transfer.status);
+        }
+        TRANSFER_STATS.save(storage, &stats)?;
+    }
+    Ok(())
+}
+
+fn backfill_denom_volumes(storage: &mut dyn Storage, _env: &Env) -> Result<(), ContractError> {
+    // the same goes for the escrowed and settled volume of each denom
+    if DENOM_VOLUME
+        .keys(storage, None, None, Order::Ascending)
+        .next()
+        .is_none()
+    {
+        let transfers = TRANSFER_STORAGE
+            .range(storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, StoredTransfer(transfer))| transfer))
+            .collect::<StdResult<Vec<_>>>()?;
+        for transfer in transfers {
+            update_denom_volumes(storage, None, Some(&transfer))?;
+        }
+    }
+    Ok(())
+}
+
+fn backfill_transfer_indexes(storage: &mut dyn Storage, _env: &Env) -> Result<(), ContractError> {
+    // transfers stored before the secondary indexes existed are missing from them, saving
+    // each one again adds it
+    if TRANSFER_STORAGE
+        .idx
+        .sender
+        .keys_raw(storage, None, None, Order::Ascending)
+        .next()
+        .is_none()
+    {
+        let transfers = TRANSFER_STORAGE
+            .range(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (id, transfer) in transfers {
+            TRANSFER_STORAGE.save(storage, &id, &transfer)?;
+        }
+    }
+    Ok(())
+}
+
+fn rekey_transfers_by_uuid(storage: &mut dyn Storage, _env: &Env) -> Result<(), ContractError> {
+    // transfers were once keyed by the bytes of their id string. Only a batch is moved to the
+    // 16 byte uuid here, the rest are moved by `MigrateTransfers` or when they next change and
+    // are read from their legacy key until then
+    migrate_legacy_transfers(storage, MAX_BATCH_SIZE)?;
+    Ok(())
+}
+
+fn archive_completed_transfers(storage: &mut dyn Storage, _env: &Env) -> Result<(), ContractError> {
+    // completed transfers were once kept with the open ones, those still under their legacy
+    // key are archived when they are moved
+    let completed_transfers = TRANSFER_STORAGE
+        .range(storage, None, None, Order::Ascending)
+        .filter(|item| {
+            item.as_ref()
+                .map_or(true, |(key, StoredTransfer(transfer))| {
+                    !is_legacy_key(key) && !transfer.status.is_open()
+                })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    for (key, StoredTransfer(transfer)) in completed_transfers {
+        archive_transfer(storage, &key, &transfer)?;
+    }
+    Ok(())
+}
+
+fn backfill_denom_counters(storage: &mut dyn Storage, env: &Env) -> Result<(), ContractError> {
+    // the counters of open transfers start at the block of the migration that added them,
+    // earlier heights read as empty
+    if DENOM_COUNTERS
+        .keys(storage, None, None, Order::Ascending)
+        .next()
+        .is_none()
+    {
+        let transfers = TRANSFER_STORAGE
+            .range(storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, StoredTransfer(transfer))| transfer))
+            .collect::<StdResult<Vec<_>>>()?;
+        for transfer in transfers {
+            update_denom_counters(storage, env.block.height, None, Some(&transfer))?;
+        }
+    }
+    Ok(())
+}
+
+fn backfill_transfer_totals(storage: &mut dyn Storage, _env: &Env) -> Result<(), ContractError> {
+    // the totals of stored transfers are counted as though each was created in its current
+    // status, those already pruned from the history are missing
+    if TRANSFER_TOTALS.may_load(storage)?.is_none() {
+        let mut totals = TransferTotals::default();
+        for map in [&TRANSFER_STORAGE, &TRANSFER_HISTORY] {
+            for item in map.range(storage, None, None, Order::Ascending) {
+                let (_, StoredTransfer(transfer)) = item?;
+                totals.record(None, transfer.status);
+            }
+        }
+        TRANSFER_TOTALS.save(storage, &totals)?;
+    }
+    Ok(())
+}
+
+fn compact_transfer_encoding(_storage: &mut dyn Storage, _env: &Env) -> Result<(), ContractError> {
+    // transfers stored as JSON objects still load and are written in the compact encoding the
+    // next time they are saved, so they are no longer all rewritten at once. The step only holds
+    // the position of the steps after it
+    Ok(())
+}
+
+fn backfill_pending_queue(storage: &mut dyn Storage, env: &Env) -> Result<(), ContractError> {
+    // pending transfers created before positions were assigned join the queue oldest first,
+    // those without a creation block ahead of the rest in id order
+    let mut transfers = TRANSFER_STORAGE
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, StoredTransfer(transfer))| transfer))
+        .filter(|item| {
+            item.as_ref().map_or(true, |transfer| {
+                transfer.status == TransferStatus::Pending && transfer.position.is_none()
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    transfers.sort_by_key(|transfer| {
+        transfer
+            .created
+            .as_ref()
+            .map(|created| (created.height, created.time))
+    });
+    for mut transfer in transfers {
+        transfer.position = Some(next_position(storage)?);
+        save_transfer(storage, env.block.height, &transfer)?;
+    }
+    Ok(())
+}
+
+fn register_denoms_in_use(storage: &mut dyn Storage, _env: &Env) -> Result<(), ContractError> {
+    // denoms had to be registered by their marker admins once the contract started asking for
+    // consent. Those it already moved are registered so their transfers carry on
+    let denoms = DENOM_VOLUME
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<String>>>()?;
+    for denom in denoms {
+        REGISTERED_DENOMS.save(storage, &denom, &Empty {})?;
+    }
+    Ok(())
+}
+
+fn convert_denom_fee_rates(storage: &mut dyn Storage, _env: &Env) -> Result<(), ContractError> {
+    // denoms were charged a rate of their own before they could have a fee schedule
+    const DENOM_FEE_BPS: Map<&str, u16> = Map::new("denom_fee_bps");
+
+    let rates = DENOM_FEE_BPS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(String, u16)>>>()?;
+    for (denom, bps) in rates {
+        DENOM_FEE_SCHEDULES.save(storage, &denom, &FeeSchedule::Bps { bps })?;
+        DENOM_FEE_BPS.remove(storage, &denom);
+    }
+    Ok(())
+}
+
+fn backfill_pending_by_sender(storage: &mut dyn Storage, _env: &Env) -> Result<(), ContractError> {
+    // pending transfers were counted from the sender's transfers before each sender kept a count
+    let mut counts: BTreeMap<Addr, u32> = BTreeMap::new();
+    for item in TRANSFER_STORAGE.range(storage, None, None, Order::Ascending) {
+        let (_, StoredTransfer(transfer)) = item?;
+        if transfer.status == TransferStatus::Pending {
+            *counts.entry(transfer.sender).or_default() += 1;
+        }
+    }
+    for (sender, count) in counts {
+        PENDING_BY_SENDER.save(storage, &sender, &count)?;
+    }
+    Ok(())
+}
+
+fn rekey_schedules_and_allowances(
+    storage: &mut dyn Storage,
+    _env: &Env,
+) -> Result<(), ContractError> {
+    // recurring transfers and allowances were once keyed by the bytes of their id string, so
+    // the same uuid written in another case made a second one
+    let schedules = RECURRING_TRANSFER_STORAGE
+        .range(storage, None, None, Order::Ascending)
+        .filter(|item| item.as_ref().map_or(true, |(key, _)| is_legacy_key(key)))
+        .collect::<StdResult<Vec<_>>>()?;
+    for (legacy_key, schedule) in schedules {
+        RECURRING_TRANSFER_STORAGE.remove(storage, &legacy_key);
+        RECURRING_TRANSFER_STORAGE.save(storage, &transfer_key(&schedule.id)?, &schedule)?;
+    }
+
+    let allowances = ALLOWANCE_STORAGE
+        .range(storage, None, None, Order::Ascending)
+        .filter(|item| item.as_ref().map_or(true, |(key, _)| is_legacy_key(key)))
+        .collect::<StdResult<Vec<_>>>()?;
+    for (legacy_key, allowance) in allowances {
+        ALLOWANCE_STORAGE.remove(storage, &legacy_key);
+        ALLOWANCE_STORAGE.save(storage, &transfer_key(&allowance.id)?, &allowance)?;
+    }
+    Ok(())
+}
+
+fn backfill_pending_queue_by_denom(
+    storage: &mut dyn Storage,
+    _env: &Env,
+) -> Result<(), ContractError> {
+    // the pending transfers of a denom were once found by walking the whole queue
+    let entries = PENDING_QUEUE
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (rank, position, key) in entries {
+        let StoredTransfer(transfer) = TRANSFER_STORAGE.load(storage, &key)?;
+        PENDING_QUEUE_BY_DENOM.save(
+            storage,
+            (&transfer.denom, (rank, position, &key)),
+            &Empty {},
+        )?;
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use cosmwasm_std::testing::mock_env;
-    use provwasm_mocks::mock_provenance_dependencies;
+    use cosmwasm_std::{
+        to_binary, Addr, ContractInfoResponse, ContractResult, SystemError, SystemResult,
+        Timestamp, Uint128, WasmQuery,
+    };
+    use provwasm_mocks::{mock_provenance_dependencies, MockProvenanceQuerier};
 
     use super::*;
+    use crate::state::{
+        load_transfer, Allowance, Creation, DenomCounters, DenomVolume, RecurringTransfer,
+        ScheduleInterval, Transfer, STORAGE_TRANSFER_KEY, TRANSFER_CREATED_HEIGHT,
+        TRANSFER_CREATED_TIME,
+    };
+    use cw_storage_plus::Map;
 
     #[test]
     fn migrate_test() {
@@ -71,16 +378,40 @@ mod tests {
 
                 assert_eq!(PACKAGE_VERSION, version_info.version);
                 assert_eq!(CRATE_NAME, version_info.contract);
+                assert_eq!(
+                    LATEST_SCHEMA_VERSION,
+                    SCHEMA_VERSION.load(&deps.storage).unwrap()
+                );
             }
             error => panic!("failed to initialize: {:?}", error),
         }
     }
 
+    #[test]
+    fn test_migrate_skips_steps_already_run() {
+        let mut deps = mock_provenance_dependencies();
+
+        set_contract_version(deps.as_mut().storage, CRATE_NAME, "2.0.0").unwrap();
+        SCHEMA_VERSION
+            .save(&mut deps.storage, &LATEST_SCHEMA_VERSION)
+            .unwrap();
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+        // the status count backfill is not run again
+        assert_eq!(None, TRANSFER_STATS.may_load(&deps.storage).unwrap());
+    }
+
     #[test]
     fn test_migrate_legacy_config() {
         let mut deps = mock_provenance_dependencies();
 
-        let contract_info = State { name: "rmt".into() };
+        let contract_info = State {
+            name: "rmt".into(),
+            admin: None,
+            default_expiration: None,
+            allowed_denoms: None,
+        };
 
         // store legacy config state.
         const LEGACY_CONFIG: Item<State> = Item::new("\0\u{6}config");
@@ -100,6 +431,389 @@ mod tests {
         assert_eq!(contract_info, CONFIG.load(&deps.storage).unwrap())
     }
 
+    #[test]
+    fn test_migrate_counts_stored_transfers() {
+        let mut deps = mock_provenance_dependencies();
+
+        let transfer = Transfer {
+            id: "56253028-12f5-4d2a-a691-ebdfd2a7b865".into(),
+            sender: Addr::unchecked("sender"),
+            denom: "restricted_1".into(),
+            amount: Uint128::new(1),
+            recipient: Addr::unchecked("recipient"),
+            status: TransferStatus::Pending,
+            rejection_reason: None,
+            require_acceptance: false,
+            approvals: vec![],
+            required_approvers: vec![],
+            legs: vec![],
+            additional_coins: vec![],
+            vesting: None,
+            claimed: Uint128::zero(),
+            memo: None,
+            created: None,
+            updated_at: None,
+            sequence: None,
+            tags: vec![],
+            position: None,
+            priority: false,
+            expires: None,
+        };
+        // stored directly as a JSON object, as transfers were before their statuses were
+        // counted and indexed
+        Map::<&[u8], Transfer>::new(STORAGE_TRANSFER_KEY)
+            .save(&mut deps.storage, transfer.id.as_bytes(), &transfer)
+            .unwrap();
+
+        set_contract_version(deps.as_mut().storage, CRATE_NAME, "2.0.0").unwrap();
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+        assert_eq!(
+            TransferStats {
+                pending: 1,
+                ..TransferStats::default()
+            },
+            TRANSFER_STATS.load(&deps.storage).unwrap()
+        );
+        assert_eq!(
+            DenomVolume {
+                escrowed: Uint128::new(1),
+                settled: Uint128::zero(),
+            },
+            DENOM_VOLUME.load(&deps.storage, "restricted_1").unwrap()
+        );
+        assert_eq!(
+            DenomCounters {
+                pending: 1,
+                escrowed: Uint128::new(1),
+            },
+            DENOM_COUNTERS.load(&deps.storage, "restricted_1").unwrap()
+        );
+        // the denom already in use is registered for its transfers to carry on
+        assert!(REGISTERED_DENOMS.has(&deps.storage, "restricted_1"));
+        assert_eq!(
+            TransferTotals {
+                created: 1,
+                ..TransferTotals::default()
+            },
+            TRANSFER_TOTALS.load(&deps.storage).unwrap()
+        );
+        // the pending transfer is given the first position in the queue
+        let transfer = Transfer {
+            position: Some(1),
+            ..transfer
+        };
+        assert_eq!(
+            transfer,
+            load_transfer(&deps.storage, &transfer.id).unwrap()
+        );
+        let key = transfer_key(&transfer.id).unwrap();
+        assert!(PENDING_QUEUE.has(&deps.storage, (1, 1, &key)));
+        assert!(PENDING_QUEUE_BY_DENOM.has(&deps.storage, (&transfer.denom, (1, 1, &key))));
+        assert!(!TRANSFER_STORAGE.has(&deps.storage, transfer.id.as_bytes()));
+        // and written again as the array of its fields
+        let stored = deps
+            .storage
+            .get(&TRANSFER_STORAGE.key(&transfer_key(&transfer.id).unwrap()))
+            .unwrap();
+        assert_eq!(b'[', stored[0]);
+        assert_eq!(
+            vec![StoredTransfer(transfer)],
+            TRANSFER_STORAGE
+                .idx
+                .sender
+                .prefix(Addr::unchecked("sender"))
+                .range(&deps.storage, None, None, Order::Ascending)
+                .map(|item| item.unwrap().1)
+                .collect::<Vec<_>>()
+        );
+        // and counted against its sender's pending limit
+        assert_eq!(
+            1,
+            PENDING_BY_SENDER
+                .load(&deps.storage, &Addr::unchecked("sender"))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_migrate_keeps_the_creation_blocks_of_legacy_transfers() {
+        let mut deps = mock_provenance_dependencies();
+
+        let untracked = Transfer {
+            id: "56253028-12f5-4d2a-a691-ebdfd2a7b865".into(),
+            sender: Addr::unchecked("sender"),
+            denom: "restricted_1".into(),
+            amount: Uint128::new(1),
+            recipient: Addr::unchecked("recipient"),
+            status: TransferStatus::Pending,
+            rejection_reason: None,
+            require_acceptance: false,
+            approvals: vec![],
+            required_approvers: vec![],
+            legs: vec![],
+            additional_coins: vec![],
+            vesting: None,
+            claimed: Uint128::zero(),
+            memo: None,
+            created: None,
+            updated_at: None,
+            sequence: None,
+            tags: vec![],
+            position: None,
+            priority: false,
+            expires: None,
+        };
+        let created = Creation {
+            height: 7,
+            time: Timestamp::from_seconds(70),
+        };
+        let tracked = Transfer {
+            id: "66253028-12f5-4d2a-a691-ebdfd2a7b865".into(),
+            created: Some(created.to_owned()),
+            ..untracked.to_owned()
+        };
+
+        // stored under their legacy keys without the fields added since, one from before
+        // creation blocks were recorded and one indexed by its creation block
+        let legacy_storage = Map::<&[u8], serde_json::Value>::new(STORAGE_TRANSFER_KEY);
+        for transfer in [&untracked, &tracked] {
+            let mut stored = serde_json::to_value(transfer).unwrap();
+            let fields = stored.as_object_mut().unwrap();
+            for field in [
+                "updated_at",
+                "sequence",
+                "tags",
+                "position",
+                "priority",
+                "expires",
+            ] {
+                fields.remove(field);
+            }
+            if transfer.created.is_none() {
+                fields.remove("created");
+            }
+            legacy_storage
+                .save(&mut deps.storage, transfer.id.as_bytes(), &stored)
+                .unwrap();
+        }
+        let legacy_key = tracked.id.as_bytes();
+        TRANSFER_CREATED_HEIGHT
+            .save(&mut deps.storage, (created.height, legacy_key), &Empty {})
+            .unwrap();
+        TRANSFER_CREATED_TIME
+            .save(
+                &mut deps.storage,
+                (created.time.nanos(), legacy_key),
+                &Empty {},
+            )
+            .unwrap();
+
+        set_contract_version(deps.as_mut().storage, CRATE_NAME, "2.0.0").unwrap();
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+        // neither transfer has changed since it was stored
+        let untracked = load_transfer(&deps.storage, &untracked.id).unwrap();
+        assert_eq!(None, untracked.created);
+        assert_eq!(None, untracked.updated_at);
+        let tracked = load_transfer(&deps.storage, &tracked.id).unwrap();
+        assert_eq!(Some(created.to_owned()), tracked.created);
+        assert_eq!(None, tracked.updated_at);
+
+        // and the creation index entries moved to the uuid key
+        let key = transfer_key(&tracked.id).unwrap();
+        assert!(TRANSFER_CREATED_HEIGHT.has(&deps.storage, (created.height, &key)));
+        assert!(TRANSFER_CREATED_TIME.has(&deps.storage, (created.time.nanos(), &key)));
+        assert!(!TRANSFER_CREATED_HEIGHT.has(&deps.storage, (created.height, legacy_key)));
+        assert!(!TRANSFER_CREATED_TIME.has(&deps.storage, (created.time.nanos(), legacy_key)));
+        assert_eq!(
+            1,
+            TRANSFER_CREATED_HEIGHT
+                .keys_raw(&deps.storage, None, None, Order::Ascending)
+                .count()
+        );
+    }
+
+    #[test]
+    fn test_migrate_backfills_pending_queue_by_denom() {
+        let mut deps = mock_provenance_dependencies();
+
+        let transfer = Transfer {
+            id: "56253028-12f5-4d2a-a691-ebdfd2a7b865".into(),
+            sender: Addr::unchecked("sender"),
+            denom: "restricted_1".into(),
+            amount: Uint128::new(1),
+            recipient: Addr::unchecked("recipient"),
+            status: TransferStatus::Pending,
+            rejection_reason: None,
+            require_acceptance: false,
+            approvals: vec![],
+            required_approvers: vec![],
+            legs: vec![],
+            additional_coins: vec![],
+            vesting: None,
+            claimed: Uint128::zero(),
+            memo: None,
+            created: None,
+            updated_at: None,
+            sequence: None,
+            tags: vec![],
+            position: Some(4),
+            priority: true,
+            expires: None,
+        };
+        // queued only in the pending queue of every denom, as transfers were before
+        let key = transfer_key(&transfer.id).unwrap();
+        TRANSFER_STORAGE
+            .save(&mut deps.storage, &key, &StoredTransfer(transfer.clone()))
+            .unwrap();
+        PENDING_QUEUE
+            .save(&mut deps.storage, (0, 4, &key), &Empty {})
+            .unwrap();
+
+        set_contract_version(deps.as_mut().storage, CRATE_NAME, "2.0.0").unwrap();
+        SCHEMA_VERSION
+            .save(&mut deps.storage, &(LATEST_SCHEMA_VERSION - 1))
+            .unwrap();
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+        assert!(PENDING_QUEUE_BY_DENOM.has(&deps.storage, ("restricted_1", (0, 4, &key))));
+    }
+
+    #[test]
+    fn test_migrate_rekeys_schedules_and_allowances() {
+        let mut deps = mock_provenance_dependencies();
+
+        let id = "56253028-12f5-4d2a-a691-ebdfd2a7b865";
+        let schedule = RecurringTransfer {
+            id: id.into(),
+            sender: Addr::unchecked("sender"),
+            denom: "restricted_1".into(),
+            amount: Uint128::new(2),
+            recipient: Addr::unchecked("recipient"),
+            interval: ScheduleInterval::Blocks(100),
+            occurrences: 2,
+            executed: 1,
+            start_height: 1,
+            start_time: Timestamp::from_seconds(1),
+        };
+        let allowance = Allowance {
+            id: id.into(),
+            sender: Addr::unchecked("sender"),
+            recipient: Addr::unchecked("recipient"),
+            denom: "restricted_1".into(),
+            amount: Uint128::new(10),
+            pulled: Uint128::new(3),
+            pulls: 1,
+        };
+        // stored under the bytes of their id string, as they were before
+        RECURRING_TRANSFER_STORAGE
+            .save(&mut deps.storage, id.as_bytes(), &schedule)
+            .unwrap();
+        ALLOWANCE_STORAGE
+            .save(&mut deps.storage, id.as_bytes(), &allowance)
+            .unwrap();
+
+        set_contract_version(deps.as_mut().storage, CRATE_NAME, "2.0.0").unwrap();
+        SCHEMA_VERSION
+            .save(&mut deps.storage, &(LATEST_SCHEMA_VERSION - 2))
+            .unwrap();
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+        let key = transfer_key(id).unwrap();
+        assert_eq!(
+            schedule,
+            RECURRING_TRANSFER_STORAGE
+                .load(&deps.storage, &key)
+                .unwrap()
+        );
+        assert_eq!(
+            allowance,
+            ALLOWANCE_STORAGE.load(&deps.storage, &key).unwrap()
+        );
+        assert!(!RECURRING_TRANSFER_STORAGE.has(&deps.storage, id.as_bytes()));
+        assert!(!ALLOWANCE_STORAGE.has(&deps.storage, id.as_bytes()));
+    }
+
+    #[test]
+    fn test_migrate_converts_denom_fee_rates() {
+        let mut deps = mock_provenance_dependencies();
+
+        // store a fee rate from before fee schedules
+        const DENOM_FEE_BPS: Map<&str, u16> = Map::new("denom_fee_bps");
+        DENOM_FEE_BPS
+            .save(&mut deps.storage, "restricted_1", &250)
+            .unwrap();
+
+        set_contract_version(deps.as_mut().storage, CRATE_NAME, "2.0.0").unwrap();
+        SCHEMA_VERSION
+            .save(&mut deps.storage, &(LATEST_SCHEMA_VERSION - 4))
+            .unwrap();
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+        assert_eq!(
+            FeeSchedule::Bps { bps: 250 },
+            DENOM_FEE_SCHEDULES
+                .load(&deps.storage, "restricted_1")
+                .unwrap()
+        );
+        assert_eq!(
+            None,
+            DENOM_FEE_BPS
+                .may_load(&deps.storage, "restricted_1")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_migrate_requires_the_contract_admin() {
+        let mut deps = mock_provenance_dependencies();
+
+        set_contract_version(deps.as_mut().storage, CRATE_NAME, "2.0.0").unwrap();
+        CONFIG
+            .save(
+                &mut deps.storage,
+                &State {
+                    name: "rmt".into(),
+                    admin: Some(Addr::unchecked("contract_admin")),
+                    default_expiration: None,
+                    allowed_denoms: None,
+                },
+            )
+            .unwrap();
+
+        // a wasm admin the contract does not record cannot migrate it
+        mock_wasm_admin(&mut deps.querier, Some("other_address"));
+        match migrate(deps.as_mut(), mock_env(), MigrateMsg {}) {
+            Ok(..) => panic!("migration should fail when the wasm admin is not the contract admin"),
+            Err(error) => match error {
+                Unauthorized { .. } => {}
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        mock_wasm_admin(&mut deps.querier, Some("contract_admin"));
+        let response = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!(
+            vec![
+                attr("action", "migrate"),
+                attr("from_version", "2.0.0"),
+                attr("to_version", PACKAGE_VERSION),
+                attr("schema_version", LATEST_SCHEMA_VERSION.to_string()),
+            ],
+            response.attributes
+        );
+
+        // a governance migration of a contract without a wasm admin is allowed
+        mock_wasm_admin(&mut deps.querier, None);
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    }
+
     #[test]
     fn test_migrate_invalid_contract_type() {
         let mut deps = mock_provenance_dependencies();
@@ -123,7 +837,7 @@ mod tests {
         let current_version: String = "999.0.0".into();
         let new_version: String = String::from(PACKAGE_VERSION);
 
-        set_contract_version(deps.as_mut().storage, CRATE_NAME, current_version).unwrap();
+        set_contract_version(deps.as_mut().storage, CRATE_NAME, &current_version).unwrap();
 
         let migrate_response = migrate(deps.as_mut(), mock_env(), MigrateMsg {});
 
@@ -131,11 +845,28 @@ mod tests {
             Ok(..) => panic!("migration should fail when the version is decreasing"),
             Err(error) => match error {
                 UnsupportedUpgrade {
-                    source_version: current_version,
-                    target_version: new_version,
-                } => {}
+                    source_version,
+                    target_version,
+                } => {
+                    assert_eq!(current_version, source_version);
+                    assert_eq!(new_version, target_version);
+                }
                 error => panic!("unexpected error: {:?}", error),
             },
         }
     }
+
+    fn mock_wasm_admin(querier: &mut MockProvenanceQuerier, admin: Option<&str>) {
+        let admin = admin.map(String::from);
+        querier.mock_querier.update_wasm(move |query| match query {
+            WasmQuery::ContractInfo { .. } => {
+                let mut contract_info = ContractInfoResponse::default();
+                contract_info.admin = admin.to_owned();
+                SystemResult::Ok(ContractResult::Ok(to_binary(&contract_info).unwrap()))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "wasm".into(),
+            }),
+        });
+    }
 }
@@ -1,12 +1,64 @@
 use crate::error::ContractError;
-use cosmwasm_std::Uint128;
+use crate::state::{
+    Allowance, Approval, ApprovalDelegation, AuditEntry, CreationDeposit, CreationRange,
+    CreationRateLimit, DenomCounters, DenomVolume, FailedSettlement, FeeDestination, FeeSchedule,
+    MsgFees, Recovery, RecurringTransfer, Role, ScheduleInterval, SettlementFee, SortOrder,
+    StandingApproval, State, Swap, Transfer, TransferFreeze, TransferStats, TransferStatus,
+    TransferTemplate, TransferTotals, VelocityLimit, DERIVED_TRANSFER_ID_VERSION, MAX_FEE_BPS,
+};
+use cosmwasm_schema::QueryResponses;
+use cosmwasm_std::{Addr, Coin, Timestamp, Uint128};
+use provwasm_std::types::provenance::marker::v1::{MarkerStatus, MarkerType};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+/// Maximum number of characters allowed in a rejection reason
+pub const MAX_REASON_LENGTH: usize = 256;
+
+/// Most transfers that can be handled by a single batch message
+pub const MAX_BATCH_SIZE: usize = 100;
+
+/// Longest memo, in characters, that can travel with a transfer
+pub const MAX_MEMO_LENGTH: usize = 256;
+
+/// Most tags a transfer can carry
+pub const MAX_TAGS: usize = 8;
+
+/// Longest tag, in characters
+pub const MAX_TAG_LENGTH: usize = 64;
+
+/// Longest transfer template id, in characters
+pub const MAX_TEMPLATE_ID_LENGTH: usize = 64;
+
+/// Version of the `ExportState` response shape, raised whenever a field is changed or removed
+pub const EXPORT_FORMAT_VERSION: u32 = 1;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub name: String,
+    // the contract admin, the instantiating address when unset
+    #[serde(default)]
+    pub admin: Option<String>,
+    // seconds after creation a pending transfer can no longer be approved, unless its denom
+    // sets a default expiration of its own
+    #[serde(default)]
+    pub default_expiration: Option<u64>,
+    // most transfers a sender may have pending at once, unlimited when unset
+    #[serde(default)]
+    pub pending_limit: Option<u32>,
+    // the only denoms transfers may move, any restricted marker when unset
+    #[serde(default)]
+    pub allowed_denoms: Option<Vec<String>>,
+    // fee deducted from every transfer as it settles, none when unset
+    #[serde(default)]
+    pub settlement_fee: Option<SettlementFee>,
+    // fees charged for creating and approving transfers, none when unset
+    #[serde(default)]
+    pub msg_fees: Option<MsgFees>,
+    // deposit held with each transfer created, none when unset
+    #[serde(default)]
+    pub creation_deposit: Option<CreationDeposit>,
 }
 
 /// Simple validation of InstantiateMsg data
@@ -28,6 +80,44 @@ impl Validate for InstantiateMsg {
             invalid_fields.push("name");
         }
 
+        if matches!(&self.admin, Some(admin) if admin.is_empty()) {
+            invalid_fields.push("admin");
+        }
+
+        if self.default_expiration == Some(0) {
+            invalid_fields.push("default_expiration");
+        }
+
+        if self.pending_limit == Some(0) {
+            invalid_fields.push("pending_limit");
+        }
+
+        if let Some(denoms) = &self.allowed_denoms {
+            let mut unique = denoms.to_owned();
+            unique.sort();
+            unique.dedup();
+            if denoms.is_empty()
+                || unique.len() != denoms.len()
+                || denoms.iter().any(|denom| denom.is_empty())
+            {
+                invalid_fields.push("allowed_denoms");
+            }
+        }
+
+        if matches!(&self.settlement_fee, Some(fee) if !invalid_settlement_fee_fields(fee).is_empty())
+        {
+            invalid_fields.push("settlement_fee");
+        }
+
+        if matches!(&self.msg_fees, Some(fees) if !invalid_msg_fees_fields(fees).is_empty()) {
+            invalid_fields.push("msg_fees");
+        }
+
+        if matches!(&self.creation_deposit, Some(deposit) if !invalid_creation_deposit_fields(deposit).is_empty())
+        {
+            invalid_fields.push("creation_deposit");
+        }
+
         match invalid_fields.len() {
             0 => Ok(()),
             _ => Err(ContractError::InvalidFields {
@@ -41,23 +131,611 @@ impl Validate for InstantiateMsg {
 #[serde(rename_all = "snake_case")]
 pub struct MigrateMsg {}
 
+/// Actions chain governance can take through the sudo entry point, without the admin's keys
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SudoMsg {
+    // rejects a transfer still holding escrow and refunds its sender
+    ForceRejectTransfer {
+        id: String,
+        reason: Option<String>,
+    },
+    Pause {},
+    // replaces the amount limits and default expiration of the denom, whoever manages them
+    SetDenomConfig {
+        denom: String,
+        min_amount: Option<Uint128>,
+        max_amount: Option<Uint128>,
+        default_expiration: Option<u64>,
+    },
+    // without an amount only the limits of each denom apply
+    SetMaxTransferAmount {
+        amount: Option<Uint128>,
+    },
+    // without a limit senders may have any number of transfers pending
+    SetPendingLimit {
+        limit: Option<u32>,
+    },
+    // without a limit senders may transfer any amount of the denom
+    SetVelocityLimit {
+        denom: String,
+        limit: Option<VelocityLimit>,
+    },
+    Unpause {},
+    // makes `new_admin` the contract admin at once, dropping a proposed admin
+    UpdateAdmin {
+        new_admin: String,
+    },
+}
+
+impl Validate for SudoMsg {
+    fn validate(&self) -> Result<(), ContractError> {
+        let mut invalid_fields: Vec<&str> = vec![];
+
+        match self {
+            SudoMsg::ForceRejectTransfer { id, .. } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+            }
+            SudoMsg::Pause {} | SudoMsg::Unpause {} => {}
+            SudoMsg::SetDenomConfig {
+                denom,
+                min_amount,
+                max_amount,
+                default_expiration,
+            } => invalid_fields.extend(invalid_denom_config_fields(
+                denom,
+                min_amount,
+                max_amount,
+                default_expiration,
+            )),
+            SudoMsg::SetPendingLimit { limit } => {
+                if *limit == Some(0) {
+                    invalid_fields.push("limit");
+                }
+            }
+            SudoMsg::SetVelocityLimit { denom, limit } => {
+                invalid_fields.extend(invalid_velocity_limit_fields(denom, limit))
+            }
+            SudoMsg::SetMaxTransferAmount { amount } => {
+                if matches!(amount, Some(amount) if amount.is_zero()) {
+                    invalid_fields.push("amount");
+                }
+            }
+            SudoMsg::UpdateAdmin { new_admin } => {
+                if new_admin.is_empty() {
+                    invalid_fields.push("new_admin");
+                }
+            }
+        }
+
+        match invalid_fields.len() {
+            0 => Ok(()),
+            _ => Err(ContractError::InvalidFields {
+                fields: invalid_fields.into_iter().map(|item| item.into()).collect(),
+            }),
+        }
+    }
+}
+
+/// A share of a transfer paid to one recipient on settlement
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Recipient {
+    pub address: String,
+    pub amount: Uint128,
+}
+
+/// The terms of a single transfer, as given to `Transfer` or as an entry of `BatchTransfer`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransferInput {
+    pub id: String,
+    pub denom: String,
+    pub amount: Uint128,
+    pub recipient: String,
+    pub require_acceptance: Option<bool>,
+    pub approvers: Option<Vec<String>>,
+    pub recipients: Option<Vec<Recipient>>,
+    pub additional_coins: Option<Vec<Coin>>,
+    pub vesting_duration: Option<u64>,
+    pub memo: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+impl TransferInput {
+    /// returns the names of the fields that fail validation
+    fn invalid_fields(&self) -> Vec<&'static str> {
+        let mut invalid_fields: Vec<&str> = vec![];
+        let TransferInput {
+            id,
+            denom,
+            amount,
+            recipient,
+            require_acceptance,
+            approvers,
+            recipients,
+            additional_coins,
+            vesting_duration,
+            memo,
+            tags,
+        } = self;
+
+        if !is_valid_transfer_id(id) {
+            invalid_fields.push("id");
+        }
+
+        if amount.lt(&Uint128::new(1)) {
+            invalid_fields.push("amount");
+        }
+        if denom.is_empty() {
+            invalid_fields.push("denom");
+        }
+        if recipient.is_empty() {
+            invalid_fields.push("recipient");
+        }
+        if let Some(approvers) = approvers {
+            let mut unique_approvers = approvers.clone();
+            unique_approvers.sort();
+            unique_approvers.dedup();
+            if approvers.is_empty()
+                || unique_approvers.len() != approvers.len()
+                || approvers.iter().any(|approver| approver.is_empty())
+            {
+                invalid_fields.push("approvers");
+            }
+        }
+        if let Some(recipients) = recipients {
+            let total = recipients
+                .iter()
+                .try_fold(Uint128::zero(), |total, leg| total.checked_add(leg.amount));
+            if recipients.is_empty()
+                || total.ok() != Some(*amount)
+                || !recipients.iter().any(|leg| leg.address.eq(recipient))
+                || recipients
+                    .iter()
+                    .any(|leg| leg.address.is_empty() || leg.amount.is_zero())
+            {
+                invalid_fields.push("recipients");
+            }
+        }
+        if let Some(additional_coins) = additional_coins {
+            let mut denoms = vec![denom];
+            denoms.extend(additional_coins.iter().map(|coin| &coin.denom));
+            // split legs are only defined for the primary denom
+            if additional_coins.is_empty()
+                || recipients.is_some()
+                || (1..denoms.len()).any(|i| denoms[..i].contains(&denoms[i]))
+                || additional_coins
+                    .iter()
+                    .any(|coin| coin.denom.is_empty() || coin.amount.is_zero())
+            {
+                invalid_fields.push("additional_coins");
+            }
+        }
+        // vested coins are claimed by the single recipient in the primary denom
+        if let Some(vesting_duration) = vesting_duration {
+            if *vesting_duration < 1
+                || *require_acceptance == Some(true)
+                || recipients.is_some()
+                || additional_coins.is_some()
+            {
+                invalid_fields.push("vesting_duration");
+            }
+        }
+        if let Some(memo) = memo {
+            if memo.is_empty() || memo.chars().count() > MAX_MEMO_LENGTH {
+                invalid_fields.push("memo");
+            }
+        }
+        if let Some(tags) = tags {
+            if tags.is_empty()
+                || tags.len() > MAX_TAGS
+                || (1..tags.len()).any(|i| tags[..i].contains(&tags[i]))
+                || tags
+                    .iter()
+                    .any(|tag| tag.is_empty() || tag.chars().count() > MAX_TAG_LENGTH)
+            {
+                invalid_fields.push("tags");
+            }
+        }
+
+        invalid_fields
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
+    // makes the proposed admin the contract admin, callable by the proposed admin
+    AcceptAdmin {},
+    AcceptTransfer {
+        id: String,
+    },
+    // moves each completed transfer out of the history, reporting the ids that could not be moved
+    ArchiveTransfers {
+        ids: Vec<String>,
+    },
+    ApproveSwap {
+        id: String,
+    },
     ApproveTransfer {
         id: String,
     },
+    ApproveTransferPartial {
+        id: String,
+        amount: Uint128,
+    },
+    // approves each transfer in turn, reporting the ids that could not be approved
+    ApproveTransfers {
+        ids: Vec<String>,
+    },
+    // creates every transfer in one transaction, escrowing the total of each denom at once
+    BatchTransfer {
+        transfers: Vec<TransferInput>,
+    },
+    // cancels the caller's pending transfers, optionally only those of one denom
+    CancelAllBySender {
+        denom: Option<String>,
+    },
+    // drops the pending escrow recovery before it is executed
+    CancelRecovery {},
+    CancelSwap {
+        id: String,
+    },
+    // pays the recipient whatever has vested of an approved transfer since their last claim
+    Claim {
+        id: String,
+    },
     CancelTransfer {
         id: String,
     },
+    // creates a transfer on the terms of one of the sender's templates, `amount` replacing the
+    // template's default amount
+    CreateFromTemplate {
+        template_id: String,
+        id: String,
+        amount: Option<Uint128>,
+    },
+    // creates a pending transfer of `amount` once per interval, `occurrences` times in total
+    CreateRecurringTransfer {
+        id: String,
+        denom: String,
+        amount: Uint128,
+        recipient: String,
+        interval: ScheduleInterval,
+        occurrences: u32,
+    },
+    DeclineTransfer {
+        id: String,
+    },
+    DeleteTemplate {
+        template_id: String,
+    },
+    // withdraws the marker admin's consent, refusing new transfers of the denom. Transfers
+    // already created can still be settled or refunded
+    DeregisterDenom {
+        denom: String,
+    },
+    // once the delay of the pending escrow recovery has passed, looks at the next `limit` open
+    // transfers, a batch by default, and refunds the senders of those of its denom. Runs again
+    // until it reports `complete`
+    ExecuteRecovery {
+        limit: Option<u32>,
+    },
+    // creates the next transfer of a recurring transfer once it is due, callable by anyone
+    ExecuteScheduled {
+        id: String,
+    },
+    ForceCancelTransfer {
+        id: String,
+    },
+    // holds a pending transfer in escrow, refusing its approval, rejection and cancellation until
+    // it is unfrozen
+    FreezeTransfer {
+        id: String,
+        reason: String,
+    },
+    // refuses new transfers and swaps of the denom and their approvals until it is unfrozen.
+    // Transfers and swaps already created can still be cancelled
+    FreezeDenom {
+        denom: String,
+    },
+    // escrows the counterparty leg of a proposed swap
+    FundSwap {
+        id: String,
+    },
+    // lets the recipient pull transfers of the denom from the sender, up to `amount` in total
+    GrantAllowance {
+        id: String,
+        recipient: String,
+        denom: String,
+        amount: Uint128,
+    },
+    // starts the delay before the escrow of every open transfer of the denom may be returned to
+    // its sender, replacing an earlier recovery
+    InitiateRecovery {
+        denom: String,
+    },
+    // stops transfers being created, approved or settled until `Unpause`. Pending transfers can
+    // still be cancelled or rejected, refunding their senders
+    Pause {},
+    // escrows `amount` of `denom` in exchange for `counterparty_amount` of `counterparty_denom`
+    ProposeSwap {
+        id: String,
+        denom: String,
+        amount: Uint128,
+        counterparty: String,
+        counterparty_denom: String,
+        counterparty_amount: Uint128,
+    },
+    // moves up to `limit` transfers, a batch by default, off the key they were stored under
+    // before transfers were keyed by uuid
+    MigrateTransfers {
+        limit: Option<u32>,
+    },
+    // removes up to a batch of transfers completed before `older_than` from the history
+    PruneHistory {
+        older_than: Timestamp,
+    },
+    // removes up to `limit` transfers, a batch by default, completed before `before` and
+    // longer ago than the retention period. Anyone may purge them.
+    PurgeSettled {
+        before: Option<Timestamp>,
+        limit: Option<u32>,
+    },
+    // creates a pending transfer of `amount` from the sender of the allowance to its recipient,
+    // callable by the recipient
+    PullTransfer {
+        allowance_id: String,
+        amount: Uint128,
+    },
+    // rereads the marker of the denom into the marker cache
+    RefreshMarkerCache {
+        denom: String,
+    },
+    // shrinks a pending transfer, refunding the difference to the sender
+    ReduceTransfer {
+        id: String,
+        new_amount: Uint128,
+    },
+    RejectSwap {
+        id: String,
+        reason: Option<String>,
+    },
     RejectTransfer {
         id: String,
+        reason: Option<String>,
+    },
+    // rejects and refunds each transfer in turn, reporting the ids that could not be rejected
+    RejectTransfers {
+        ids: Vec<String>,
+        reason: Option<String>,
+    },
+    // resends the failed settlements of the transfer
+    RetryFailed {
+        id: String,
+    },
+    // lends the sender's approval rights for the denom to `delegate` until `expires_at`,
+    // replacing an earlier delegation of the sender
+    DelegateApproval {
+        denom: String,
+        delegate: String,
+        expires_at: Timestamp,
+    },
+    // grants the role to the address
+    GrantRole {
+        role: Role,
+        address: String,
+    },
+    // ends an allowance, transfers already pulled are unaffected
+    RevokeAllowance {
+        id: String,
+    },
+    // ends the sender's delegation of approval rights for the denom
+    RevokeDelegation {
+        denom: String,
+    },
+    // takes the role away from the address
+    RevokeRole {
+        role: Role,
+        address: String,
+    },
+    // removes the standing approval of the route
+    RevokeStandingApproval {
+        sender: String,
+        recipient: String,
+        denom: String,
+    },
+    // stores terms the sender can create transfers from, replacing their template of the same id
+    SaveTemplate {
+        template_id: String,
+        denom: String,
+        recipient: String,
+        amount: Option<Uint128>,
+        memo: Option<String>,
+    },
+    // records the marker admin's consent to transfers of the denom going through the contract
+    RegisterDenom {
+        denom: String,
+    },
+    // refuses transfers of the denom paying addresses not on its allowlist while enabled
+    SetAllowlistEnabled {
+        denom: String,
+        enabled: bool,
+    },
+    SetApprovalQuorum {
+        denom: String,
+        quorum: u32,
+    },
+    // approves new transfers of the denom below `threshold` as they are created. Without a
+    // threshold every transfer waits for approval again
+    SetAutoApproveThreshold {
+        denom: String,
+        threshold: Option<Uint128>,
+    },
+    // limits how many transfers each sender may create within `limit.blocks` blocks. Without a
+    // limit senders may create any number
+    SetCreationRateLimit {
+        limit: Option<CreationRateLimit>,
+    },
+    // limits new transfers of the denom to between `min_amount` and `max_amount`, and lets them
+    // wait `default_expiration` seconds for approval. Without any setting the limits are removed
+    SetDenomConfig {
+        denom: String,
+        min_amount: Option<Uint128>,
+        max_amount: Option<Uint128>,
+        default_expiration: Option<u64>,
+    },
+    // requires senders to attach the deposit to each transfer they create. Without a deposit
+    // transfers are created without one
+    SetCreationDeposit {
+        deposit: Option<CreationDeposit>,
+    },
+    // collects msg fees in the denom, an active unrestricted coin. Without a denom they are
+    // collected in nhash again
+    SetFeeDenom {
+        denom: Option<String>,
+    },
+    // levies `fees.creation_fee` on each transfer created and `fees.approval_fee` on each approval
+    // through the msg-fees module, added to the fees of the transaction. Without fees neither is
+    // levied
+    SetMsgFees {
+        fees: Option<MsgFees>,
+    },
+    // pays the fees of the denom accrued by the contract out to `to`
+    WithdrawFees {
+        denom: String,
+        to: String,
+    },
+    // charges transfers of the denom by the schedule instead of the settlement fee's rate. Without
+    // a schedule the settlement fee's rate applies again
+    SetDenomFeeSchedule {
+        denom: String,
+        schedule: Option<FeeSchedule>,
+    },
+    // without a limit the default maximum page size applies again
+    // without an amount only the limits of each denom apply
+    SetMaxTransferAmount {
+        amount: Option<Uint128>,
+    },
+    SetPageLimit {
+        limit: Option<u32>,
+    },
+    // without a limit senders may have any number of transfers pending
+    SetPendingLimit {
+        limit: Option<u32>,
+    },
+    // limits how much of the denom each sender may transfer within a rolling window of
+    // `limit.window` seconds. Without a limit senders may transfer any amount
+    SetVelocityLimit {
+        denom: String,
+        limit: Option<VelocityLimit>,
+    },
+    // without a retention period completed transfers are only removed by `PruneHistory`
+    SetRetentionPeriod {
+        seconds: Option<u64>,
+    },
+    // deducts `fee.bps` basis points of every settled amount and pays them to `fee.destination`,
+    // a collector address or the marker admin of each denom. Without a fee transfers settle in
+    // full
+    SetSettlementFee {
+        fee: Option<SettlementFee>,
+    },
+    // approves transfers of the denom from the sender to the recipient as they are created,
+    // until they add up to more than `max_amount` or `expires` passes. Replaces the standing
+    // approval of the route, starting its count again
+    SetStandingApproval {
+        sender: String,
+        recipient: String,
+        denom: String,
+        max_amount: Uint128,
+        expires: Timestamp,
+    },
+    // queues a pending transfer ahead of those not prioritised, or back in creation order
+    SetTransferPriority {
+        id: String,
+        priority: bool,
+    },
+    // without a denom the veto applies to every denom, without an address the veto is removed
+    SetVetoAddress {
+        denom: Option<String>,
+        address: Option<String>,
+    },
+    // returns the balance of the denom held beyond what is escrowed to the marker admin
+    SweepOrphanedFunds {
+        denom: String,
     },
     Transfer {
         id: String,
         denom: String,
         amount: Uint128,
         recipient: String,
+        // when set, approved coins are held until the recipient accepts the transfer
+        require_acceptance: Option<bool>,
+        // addresses with marker transfer permission that must all approve the transfer
+        approvers: Option<Vec<String>>,
+        // splits the amount across several recipients, `recipient` must be one of them
+        recipients: Option<Vec<Recipient>>,
+        // coins of other restricted denoms escrowed and settled atomically with this transfer
+        additional_coins: Option<Vec<Coin>>,
+        // releases the approved amount linearly to the recipient over this many seconds
+        vesting_duration: Option<u64>,
+        // settlement instructions or reference text stored with the transfer
+        memo: Option<String>,
+        // labels grouping related transfers, each can be listed with `GetTransfersByTag`
+        tags: Option<Vec<String>>,
+    },
+    Unpause {},
+    UnfreezeDenom {
+        denom: String,
+    },
+    UnfreezeTransfer {
+        id: String,
+    },
+    // adds and removes addresses blocked from sending or receiving transfers
+    UpdateBlocklist {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    // adds and removes addresses exempt from the contract's fees
+    UpdateFeeExemptions {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    // adds and removes addresses on the recipient allowlist of the denom
+    UpdateAllowlist {
+        denom: String,
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    // registers and unregisters approvers of the denom, who need no transfer grant on its marker
+    UpdateApprovers {
+        denom: String,
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    // proposes `new_admin` as the contract admin, replacing an earlier proposal. The current admin
+    // keeps the role until `new_admin` runs `AcceptAdmin`
+    UpdateAdmin {
+        new_admin: String,
+    },
+    // changes the settings given and keeps the rest: the contract name, the most transfers a
+    // paged query returns, the most transfers a sender may have pending and the largest amount a
+    // single transfer may move
+    UpdateConfig {
+        name: Option<String>,
+        page_limit: Option<u32>,
+        pending_limit: Option<u32>,
+        max_transfer_amount: Option<Uint128>,
+    },
+    UpdateTransfer {
+        id: String,
+        new_recipient: Option<String>,
+        new_amount: Option<Uint128>,
+    },
+    VetoTransfer {
+        id: String,
+        reason: Option<String>,
     },
 }
 
@@ -78,27 +756,23 @@ impl Validate for ExecuteMsg {
         let mut invalid_fields: Vec<&str> = vec![];
 
         match self {
-            ExecuteMsg::ApproveTransfer { id } => {
+            ExecuteMsg::AcceptAdmin {} => {}
+            ExecuteMsg::AcceptTransfer { id } => {
                 if Uuid::parse_str(id).is_err() {
                     invalid_fields.push("id");
                 }
             }
-            ExecuteMsg::CancelTransfer { id } => {
+            ExecuteMsg::ApproveSwap { id } => {
                 if Uuid::parse_str(id).is_err() {
                     invalid_fields.push("id");
                 }
             }
-            ExecuteMsg::RejectTransfer { id } => {
+            ExecuteMsg::ApproveTransfer { id } => {
                 if Uuid::parse_str(id).is_err() {
                     invalid_fields.push("id");
                 }
             }
-            ExecuteMsg::Transfer {
-                id,
-                denom,
-                amount,
-                recipient,
-            } => {
+            ExecuteMsg::ApproveTransferPartial { id, amount } => {
                 if Uuid::parse_str(id).is_err() {
                     invalid_fields.push("id");
                 }
@@ -106,12 +780,1398 @@ impl Validate for ExecuteMsg {
                 if amount.lt(&Uint128::new(1)) {
                     invalid_fields.push("amount");
                 }
-                if denom.is_empty() {
+            }
+            ExecuteMsg::Claim { id } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+            }
+            ExecuteMsg::ApproveTransfers { ids } | ExecuteMsg::ArchiveTransfers { ids } => {
+                if !is_valid_batch(ids) {
+                    invalid_fields.push("ids");
+                }
+            }
+            ExecuteMsg::BatchTransfer { transfers } => {
+                let ids: Vec<String> = transfers.iter().map(|input| input.id.clone()).collect();
+                if !is_valid_batch(&ids)
+                    || transfers
+                        .iter()
+                        .any(|input| !input.invalid_fields().is_empty())
+                {
+                    invalid_fields.push("transfers");
+                }
+            }
+            ExecuteMsg::CancelAllBySender { denom } => {
+                if matches!(denom, Some(denom) if denom.is_empty()) {
                     invalid_fields.push("denom");
                 }
-                if recipient.is_empty() {
-                    invalid_fields.push("recipient");
+            }
+            ExecuteMsg::CancelSwap { id } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+            }
+            ExecuteMsg::CancelTransfer { id } | ExecuteMsg::UnfreezeTransfer { id } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+            }
+            ExecuteMsg::CreateFromTemplate {
+                template_id,
+                id,
+                amount,
+            } => {
+                if !is_valid_template_id(template_id) {
+                    invalid_fields.push("template_id");
+                }
+
+                if !is_valid_transfer_id(id) {
+                    invalid_fields.push("id");
+                }
+
+                if *amount == Some(Uint128::zero()) {
+                    invalid_fields.push("amount");
+                }
+            }
+            ExecuteMsg::CreateRecurringTransfer {
+                id,
+                denom,
+                amount,
+                recipient,
+                interval,
+                occurrences,
+            } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+
+                if amount.lt(&Uint128::new(1)) {
+                    invalid_fields.push("amount");
+                }
+
+                if recipient.is_empty() {
+                    invalid_fields.push("recipient");
+                }
+
+                if matches!(
+                    interval,
+                    ScheduleInterval::Blocks(0) | ScheduleInterval::Seconds(0)
+                ) {
+                    invalid_fields.push("interval");
+                }
+
+                if *occurrences < 1 {
+                    invalid_fields.push("occurrences");
+                }
+            }
+            ExecuteMsg::DeclineTransfer { id } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+            }
+            ExecuteMsg::DeleteTemplate { template_id } => {
+                if !is_valid_template_id(template_id) {
+                    invalid_fields.push("template_id");
+                }
+            }
+            ExecuteMsg::ExecuteScheduled { id } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+            }
+            ExecuteMsg::ForceCancelTransfer { id } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+            }
+            ExecuteMsg::FundSwap { id } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+            }
+            ExecuteMsg::GrantAllowance {
+                id,
+                recipient,
+                denom,
+                amount,
+            } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+
+                if recipient.is_empty() {
+                    invalid_fields.push("recipient");
+                }
+
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+
+                if amount.is_zero() {
+                    invalid_fields.push("amount");
+                }
+            }
+            ExecuteMsg::Pause {} | ExecuteMsg::Unpause {} => {}
+            ExecuteMsg::CancelRecovery {} => {}
+            ExecuteMsg::ExecuteRecovery { limit } => {
+                if *limit == Some(0) {
+                    invalid_fields.push("limit");
+                }
+            }
+            ExecuteMsg::InitiateRecovery { denom } => {
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+            }
+            ExecuteMsg::ProposeSwap {
+                id,
+                denom,
+                amount,
+                counterparty,
+                counterparty_denom,
+                counterparty_amount,
+            } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+
+                if amount.lt(&Uint128::new(1)) {
+                    invalid_fields.push("amount");
+                }
+
+                if counterparty.is_empty() {
+                    invalid_fields.push("counterparty");
+                }
+
+                if counterparty_denom.is_empty() || counterparty_denom.eq(denom) {
+                    invalid_fields.push("counterparty_denom");
+                }
+
+                if counterparty_amount.lt(&Uint128::new(1)) {
+                    invalid_fields.push("counterparty_amount");
+                }
+            }
+            ExecuteMsg::MigrateTransfers { limit } => {
+                if *limit == Some(0) {
+                    invalid_fields.push("limit");
+                }
+            }
+            ExecuteMsg::PruneHistory { .. } => {}
+            ExecuteMsg::PurgeSettled { limit, .. } => {
+                if *limit == Some(0) {
+                    invalid_fields.push("limit");
+                }
+            }
+            ExecuteMsg::PullTransfer {
+                allowance_id,
+                amount,
+            } => {
+                if Uuid::parse_str(allowance_id).is_err() {
+                    invalid_fields.push("allowance_id");
+                }
+
+                if amount.is_zero() {
+                    invalid_fields.push("amount");
+                }
+            }
+            ExecuteMsg::RefreshMarkerCache { denom } => {
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+            }
+            ExecuteMsg::ReduceTransfer { id, new_amount } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+
+                if new_amount.lt(&Uint128::new(1)) {
+                    invalid_fields.push("new_amount");
+                }
+            }
+            ExecuteMsg::RejectSwap { id, reason } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+
+                if let Some(reason) = reason {
+                    if reason.is_empty() || reason.chars().count() > MAX_REASON_LENGTH {
+                        invalid_fields.push("reason");
+                    }
+                }
+            }
+            ExecuteMsg::FreezeTransfer { id, reason } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+
+                if reason.is_empty() || reason.chars().count() > MAX_REASON_LENGTH {
+                    invalid_fields.push("reason");
+                }
+            }
+            ExecuteMsg::RejectTransfer { id, reason } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+
+                if let Some(reason) = reason {
+                    if reason.is_empty() || reason.chars().count() > MAX_REASON_LENGTH {
+                        invalid_fields.push("reason");
+                    }
+                }
+            }
+            ExecuteMsg::RejectTransfers { ids, reason } => {
+                if !is_valid_batch(ids) {
+                    invalid_fields.push("ids");
+                }
+
+                if let Some(reason) = reason {
+                    if reason.is_empty() || reason.chars().count() > MAX_REASON_LENGTH {
+                        invalid_fields.push("reason");
+                    }
+                }
+            }
+            ExecuteMsg::RetryFailed { id } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+            }
+            ExecuteMsg::RevokeAllowance { id } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+            }
+            ExecuteMsg::DelegateApproval {
+                denom, delegate, ..
+            } => {
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+                if delegate.is_empty() {
+                    invalid_fields.push("delegate");
+                }
+            }
+            ExecuteMsg::RevokeDelegation { denom } => {
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+            }
+            ExecuteMsg::GrantRole { address, .. } | ExecuteMsg::RevokeRole { address, .. } => {
+                if address.is_empty() {
+                    invalid_fields.push("address");
+                }
+            }
+            ExecuteMsg::RevokeStandingApproval {
+                sender,
+                recipient,
+                denom,
+            } => {
+                invalid_fields.extend(invalid_route_fields(sender, recipient, denom));
+            }
+            ExecuteMsg::SaveTemplate {
+                template_id,
+                denom,
+                recipient,
+                amount,
+                memo,
+            } => {
+                if !is_valid_template_id(template_id) {
+                    invalid_fields.push("template_id");
+                }
+
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+
+                if recipient.is_empty() {
+                    invalid_fields.push("recipient");
+                }
+
+                if *amount == Some(Uint128::zero()) {
+                    invalid_fields.push("amount");
+                }
+
+                if matches!(memo, Some(memo) if memo.is_empty() || memo.chars().count() > MAX_MEMO_LENGTH)
+                {
+                    invalid_fields.push("memo");
+                }
+            }
+            ExecuteMsg::RegisterDenom { denom }
+            | ExecuteMsg::DeregisterDenom { denom }
+            | ExecuteMsg::FreezeDenom { denom }
+            | ExecuteMsg::UnfreezeDenom { denom } => {
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+            }
+            ExecuteMsg::SetAllowlistEnabled { denom, .. } => {
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+            }
+            ExecuteMsg::SetApprovalQuorum { denom, quorum } => {
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+
+                if *quorum < 1 {
+                    invalid_fields.push("quorum");
+                }
+            }
+            ExecuteMsg::SetAutoApproveThreshold { denom, threshold } => {
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+
+                if threshold.is_some_and(|threshold| threshold.is_zero()) {
+                    invalid_fields.push("threshold");
+                }
+            }
+            ExecuteMsg::SetCreationRateLimit { limit } => {
+                if let Some(limit) = limit {
+                    if limit.max_creations < 1 {
+                        invalid_fields.push("max_creations");
+                    }
+
+                    if limit.blocks < 1 {
+                        invalid_fields.push("blocks");
+                    }
+                }
+            }
+            ExecuteMsg::SetDenomConfig {
+                denom,
+                min_amount,
+                max_amount,
+                default_expiration,
+            } => invalid_fields.extend(invalid_denom_config_fields(
+                denom,
+                min_amount,
+                max_amount,
+                default_expiration,
+            )),
+            ExecuteMsg::SetMaxTransferAmount { amount } => {
+                if matches!(amount, Some(amount) if amount.is_zero()) {
+                    invalid_fields.push("amount");
+                }
+            }
+            ExecuteMsg::SetCreationDeposit { deposit } => {
+                if let Some(deposit) = deposit {
+                    invalid_fields.extend(invalid_creation_deposit_fields(deposit));
+                }
+            }
+            ExecuteMsg::SetFeeDenom { denom } => {
+                if denom.as_ref().is_some_and(|denom| denom.is_empty()) {
+                    invalid_fields.push("denom");
+                }
+            }
+            ExecuteMsg::SetMsgFees { fees } => {
+                if let Some(fees) = fees {
+                    invalid_fields.extend(invalid_msg_fees_fields(fees));
+                }
+            }
+            ExecuteMsg::WithdrawFees { denom, to } => {
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+
+                if to.is_empty() {
+                    invalid_fields.push("to");
+                }
+            }
+            ExecuteMsg::SetDenomFeeSchedule { denom, schedule } => {
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+
+                match schedule {
+                    Some(FeeSchedule::Flat { amount }) if amount.is_zero() => {
+                        invalid_fields.push("amount");
+                    }
+                    Some(FeeSchedule::Bps { bps }) if *bps > MAX_FEE_BPS => {
+                        invalid_fields.push("bps");
+                    }
+                    Some(FeeSchedule::Tiered { tiers })
+                        if tiers.is_empty()
+                            || tiers.iter().any(|tier| tier.bps > MAX_FEE_BPS)
+                            || tiers
+                                .windows(2)
+                                .any(|pair| pair[0].min_amount >= pair[1].min_amount) =>
+                    {
+                        invalid_fields.push("tiers");
+                    }
+                    _ => {}
+                }
+            }
+            ExecuteMsg::SetPageLimit { limit } | ExecuteMsg::SetPendingLimit { limit } => {
+                if *limit == Some(0) {
+                    invalid_fields.push("limit");
+                }
+            }
+            ExecuteMsg::SetRetentionPeriod { .. } => {}
+            ExecuteMsg::SetVelocityLimit { denom, limit } => {
+                invalid_fields.extend(invalid_velocity_limit_fields(denom, limit))
+            }
+            ExecuteMsg::SetSettlementFee { fee } => {
+                if let Some(fee) = fee {
+                    invalid_fields.extend(invalid_settlement_fee_fields(fee));
+                }
+            }
+            ExecuteMsg::SetStandingApproval {
+                sender,
+                recipient,
+                denom,
+                max_amount,
+                ..
+            } => {
+                invalid_fields.extend(invalid_route_fields(sender, recipient, denom));
+
+                if max_amount.is_zero() {
+                    invalid_fields.push("max_amount");
+                }
+            }
+            ExecuteMsg::SetTransferPriority { id, .. } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+            }
+            ExecuteMsg::SweepOrphanedFunds { denom } => {
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+            }
+            ExecuteMsg::SetVetoAddress { denom, address } => {
+                if matches!(denom, Some(denom) if denom.is_empty()) {
+                    invalid_fields.push("denom");
+                }
+                if matches!(address, Some(address) if address.is_empty()) {
+                    invalid_fields.push("address");
+                }
+            }
+            ExecuteMsg::Transfer {
+                id,
+                denom,
+                amount,
+                recipient,
+                require_acceptance,
+                approvers,
+                recipients,
+                additional_coins,
+                vesting_duration,
+                memo,
+                tags,
+            } => {
+                let input = TransferInput {
+                    id: id.to_owned(),
+                    denom: denom.to_owned(),
+                    amount: *amount,
+                    recipient: recipient.to_owned(),
+                    require_acceptance: *require_acceptance,
+                    approvers: approvers.to_owned(),
+                    recipients: recipients.to_owned(),
+                    additional_coins: additional_coins.to_owned(),
+                    vesting_duration: *vesting_duration,
+                    memo: memo.to_owned(),
+                    tags: tags.to_owned(),
+                };
+                invalid_fields.extend(input.invalid_fields());
+            }
+            ExecuteMsg::UpdateAllowlist { denom, add, remove }
+            | ExecuteMsg::UpdateApprovers { denom, add, remove } => {
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+                if add.iter().any(|address| address.is_empty()) {
+                    invalid_fields.push("add");
+                }
+                if remove.iter().any(|address| address.is_empty()) {
+                    invalid_fields.push("remove");
+                }
+                if add.is_empty() && remove.is_empty() {
+                    invalid_fields.push("add");
+                    invalid_fields.push("remove");
+                }
+            }
+            ExecuteMsg::UpdateBlocklist { add, remove }
+            | ExecuteMsg::UpdateFeeExemptions { add, remove } => {
+                if add.iter().any(|address| address.is_empty()) {
+                    invalid_fields.push("add");
+                }
+                if remove.iter().any(|address| address.is_empty()) {
+                    invalid_fields.push("remove");
+                }
+                if add.is_empty() && remove.is_empty() {
+                    invalid_fields.push("add");
+                    invalid_fields.push("remove");
+                }
+            }
+            ExecuteMsg::UpdateConfig {
+                name,
+                page_limit,
+                pending_limit,
+                max_transfer_amount,
+            } => {
+                if matches!(name, Some(name) if name.is_empty()) {
+                    invalid_fields.push("name");
+                }
+                if *page_limit == Some(0) {
+                    invalid_fields.push("page_limit");
+                }
+                if *pending_limit == Some(0) {
+                    invalid_fields.push("pending_limit");
+                }
+                if max_transfer_amount.is_some_and(|amount| amount.is_zero()) {
+                    invalid_fields.push("max_transfer_amount");
+                }
+                if name.is_none()
+                    && page_limit.is_none()
+                    && pending_limit.is_none()
+                    && max_transfer_amount.is_none()
+                {
+                    invalid_fields.push("name");
+                    invalid_fields.push("page_limit");
+                    invalid_fields.push("pending_limit");
+                    invalid_fields.push("max_transfer_amount");
+                }
+            }
+            ExecuteMsg::UpdateAdmin { new_admin } => {
+                if new_admin.is_empty() {
+                    invalid_fields.push("new_admin");
+                }
+            }
+            ExecuteMsg::UpdateTransfer {
+                id,
+                new_recipient,
+                new_amount,
+            } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+
+                if new_recipient.is_none() && new_amount.is_none() {
+                    invalid_fields.push("new_recipient");
+                    invalid_fields.push("new_amount");
+                }
+                if matches!(new_recipient, Some(recipient) if recipient.is_empty()) {
+                    invalid_fields.push("new_recipient");
+                }
+                if matches!(new_amount, Some(amount) if amount.lt(&Uint128::new(1))) {
+                    invalid_fields.push("new_amount");
+                }
+            }
+            ExecuteMsg::VetoTransfer { id, reason } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+
+                if let Some(reason) = reason {
+                    if reason.is_empty() || reason.chars().count() > MAX_REASON_LENGTH {
+                        invalid_fields.push("reason");
+                    }
+                }
+            }
+        }
+
+        match invalid_fields.len() {
+            0 => Ok(()),
+            _ => Err(ContractError::InvalidFields {
+                fields: invalid_fields.into_iter().map(|item| item.into()).collect(),
+            }),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, QueryResponses)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    #[returns(TransferResponse)]
+    GetTransfer { id: String },
+    #[returns(ApprovalsResponse)]
+    GetApprovals { id: String },
+    #[returns(TransferDetailedResponse)]
+    GetTransferDetailed { id: String },
+    #[returns(FailedSettlementsResponse)]
+    GetFailedSettlements { id: String },
+    #[returns(TransferFreezeResponse)]
+    GetTransferFreeze { id: String },
+    #[returns(ContractInfoResponse)]
+    GetContractInfo {},
+    #[returns(VersionInfoResponse)]
+    GetVersionInfo {},
+    #[returns(TransfersResponse)]
+    GetAllTransfers {
+        start_after: Option<String>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+    #[returns(u32)]
+    GetApprovalQuorum { denom: String },
+    #[returns(Swap)]
+    GetSwap { id: String },
+    #[returns(RecurringTransfer)]
+    GetRecurringTransfer { id: String },
+    #[returns(Allowance)]
+    GetAllowance { id: String },
+    // addresses in address order, following the `start_after` address
+    // blocked addresses in address order, following the `start_after` address
+    #[returns(RegisteredDenomsResponse)]
+    GetRegisteredDenoms {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(BlocklistResponse)]
+    GetBlocklist {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(FeeExemptionsResponse)]
+    GetFeeExemptions {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(AccruedFeesResponse)]
+    GetAccruedFees {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(RolesResponse)]
+    GetRoles { address: String },
+    #[returns(RoleMembersResponse)]
+    GetRoleMembers {
+        role: Role,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(ApproversResponse)]
+    GetApprovers {
+        denom: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(DelegationsResponse)]
+    GetDelegations {
+        denom: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(AllowlistResponse)]
+    GetAllowlist {
+        denom: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(StandingApproval)]
+    GetStandingApproval {
+        sender: String,
+        recipient: String,
+        denom: String,
+    },
+    // templates in id order, following the `start_after` template id
+    #[returns(TemplatesResponse)]
+    GetTemplates {
+        sender: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(TransfersResponse)]
+    GetTransfersBySender {
+        sender: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+    #[returns(TransfersResponse)]
+    GetTransfersByTag {
+        tag: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+    // pending transfers in queue order: prioritised transfers first, then oldest first
+    #[returns(TransfersResponse)]
+    GetPendingApprovals {
+        approver: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+    // pending transfers of the denom in the same queue order as `GetPendingApprovals`
+    #[returns(TransfersResponse)]
+    GetPendingByDenom {
+        denom: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+    #[returns(PendingActionsResponse)]
+    GetPendingActions {
+        address: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+    #[returns(TransferStats)]
+    GetStats {},
+    // the sequence number last assigned to a transfer of the sender, 0 before their first
+    #[returns(SequenceResponse)]
+    GetSequence { sender: String },
+    // how much of the denom the sender transferred within the window of the denom's velocity limit
+    #[returns(VelocityResponse)]
+    GetVelocity { sender: String, denom: String },
+    // counts only grow, unlike those of `GetStats`
+    #[returns(TransferTotals)]
+    GetTotals {},
+    #[returns(DenomVolume)]
+    GetDenomVolume { denom: String },
+    // counters as they stood at the start of the block at `height`, the latest when unset
+    #[returns(DenomCounters)]
+    GetDenomCounters { denom: String, height: Option<u64> },
+    #[returns(TransferLookupsResponse)]
+    GetTransfers { ids: Vec<String> },
+    #[returns(ContractSettingsResponse)]
+    GetContractSettings {},
+    // entries oldest first, following the `start_after` sequence number
+    #[returns(AuditLogResponse)]
+    GetAuditLog {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(EstimateFeesResponse)]
+    EstimateFees {
+        sender: String,
+        denom: String,
+        amount: Uint128,
+    },
+    #[returns(DenomConfigsResponse)]
+    ListDenomConfigs {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(ExportStateResponse)]
+    ExportState {
+        start_after: Option<String>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+    #[returns(EscrowReconciliationResponse)]
+    GetEscrowReconciliation { denom: String },
+    // transfers moved out of the history by `ArchiveTransfers`, in id order
+    #[returns(TransfersResponse)]
+    GetArchivedTransfers {
+        start_after: Option<String>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+    #[returns(TransfersResponse)]
+    GetTransferHistory {
+        start_after: Option<String>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+        // one of the terminal statuses: rejected, cancelled, declined or settled
+        filter: Option<TransferStatus>,
+    },
+    #[returns(TransfersResponse)]
+    GetTransfersCreated {
+        range: CreationRange,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+    #[returns(TransfersResponse)]
+    FindPendingTransfers {
+        sender: String,
+        recipient: String,
+        denom: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransferResponse {
+    pub transfer: Transfer,
+}
+
+/// A page of the transfers an address can act on, in id order
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingActionsResponse {
+    pub pending_actions: Vec<PendingAction>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingAction {
+    pub transfer: Transfer,
+    pub actions: Vec<AvailableAction>,
+}
+
+/// What an address can currently do with a transfer
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AvailableAction {
+    /// the address sent the still pending transfer
+    Cancel,
+    /// the address holds transfer permission on every marker and has yet to approve
+    Approve,
+    /// the address is the recipient of an approved transfer awaiting acceptance
+    Accept,
+}
+
+/// A transfer together with the current state of the markers for each of its denoms
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransferDetailedResponse {
+    pub transfer: Transfer,
+    pub markers: Vec<MarkerDetails>,
+}
+
+/// The parts of a marker account clients need when showing a transfer
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarkerDetails {
+    pub denom: String,
+    pub marker_type: MarkerType,
+    pub status: MarkerStatus,
+    pub required_attributes: Vec<String>,
+    pub supply: String,
+}
+
+/// The approvals collected for a transfer and how many it needs
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ApprovalsResponse {
+    pub approvals: Vec<Approval>,
+    pub quorum: u32,
+}
+
+/// The sequence number last assigned to a transfer of the sender
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SequenceResponse {
+    pub sender: Addr,
+    pub sequence: u64,
+}
+
+/// What a sender transferred of a denom within the window of its velocity limit, zero while the
+/// denom has no limit
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VelocityResponse {
+    pub sender: Addr,
+    pub denom: String,
+    pub limit: Option<VelocityLimit>,
+    pub used: Uint128,
+}
+
+/// A page of the audit log. Pass the sequence of the last entry as `start_after` to get the next.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuditLogResponse {
+    pub entries: Vec<AuditEntry>,
+}
+
+/// The settlements of a transfer that failed and can be retried
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FailedSettlementsResponse {
+    pub failed_settlements: Vec<FailedSettlement>,
+}
+
+/// The freeze holding a transfer in escrow, if it is frozen
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransferFreezeResponse {
+    pub freeze: Option<TransferFreeze>,
+}
+
+/// A page of the denoms registered by their marker admins. Pass the last denom as `start_after`
+/// to get the next.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RegisteredDenomsResponse {
+    pub denoms: Vec<String>,
+}
+
+/// A page of the registered approvers of a denom. Pass the last address as `start_after` to get
+/// the next.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ApproversResponse {
+    pub denom: String,
+    pub addresses: Vec<Addr>,
+}
+
+/// A page of the active approval delegations of a denom. Pass the last delegator as `start_after`
+/// to get the next.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DelegationsResponse {
+    pub delegations: Vec<ApprovalDelegation>,
+}
+
+/// A page of the recipient allowlist of a denom. Pass the last address as `start_after` to get
+/// the next.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AllowlistResponse {
+    pub denom: String,
+    // transfers of the denom may pay other addresses while the allowlist is disabled
+    pub enabled: bool,
+    pub addresses: Vec<Addr>,
+}
+
+/// A page of the blocked addresses. Pass the last address as `start_after` to get the next.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BlocklistResponse {
+    pub addresses: Vec<Addr>,
+}
+
+/// A page of the addresses exempt from fees. Pass the last address as `start_after` to get the
+/// next.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeeExemptionsResponse {
+    pub addresses: Vec<Addr>,
+}
+
+/// A page of the fees accrued by the contract in denom order. Pass the last denom as `start_after`
+/// to get the next.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AccruedFeesResponse {
+    pub fees: Vec<Coin>,
+}
+
+/// The fees a transfer of the amount by the sender would be charged
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EstimateFeesResponse {
+    // deducted from the payouts in the transfer's denom as it settles, and the address it is paid to
+    pub settlement_fee: Uint128,
+    pub collector: Option<Addr>,
+    // levied in nhash on the transaction creating the transfer
+    pub creation_fee: Uint128,
+    // levied in nhash on each approval, unless the approver is exempt from fees
+    pub approval_fee: Uint128,
+}
+
+/// The roles granted to the address
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RolesResponse {
+    pub address: Addr,
+    pub roles: Vec<Role>,
+}
+
+/// A page of the holders of a role. Pass the last one as `start_after` to get the next.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoleMembersResponse {
+    pub role: Role,
+    pub addresses: Vec<Addr>,
+}
+
+/// A page of a sender's templates. Pass the id of the last one as `start_after` to get the next.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TemplatesResponse {
+    pub templates: Vec<TransferTemplate>,
+}
+
+/// A page of transfers in id order. Pass the id of the last one as `start_after` to get the next.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransfersResponse {
+    pub transfers: Vec<Transfer>,
+}
+
+/// The transfer stored under the id, if there is one
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransferLookup {
+    pub id: String,
+    pub transfer: Option<Transfer>,
+}
+
+/// One lookup per requested id, in the order the ids were given
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransferLookupsResponse {
+    pub results: Vec<TransferLookup>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ContractInfoResponse {
+    pub name: String,
+}
+
+/// A page of stored transfers along with the config, for indexers and backups. Every page
+/// carries the format version and config so each can be read on its own.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportStateResponse {
+    pub format_version: u32,
+    pub config: State,
+    pub transfers: Vec<Transfer>,
+}
+
+/// What the contract should hold of a denom for open transfers and swaps next to what it does
+/// hold. A discrepancy points at escrow that was orphaned or released without a record.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EscrowReconciliationResponse {
+    pub denom: String,
+    pub transfer_escrow: Uint128,
+    pub swap_escrow: Uint128,
+    // held for settlements that failed and await a retry
+    pub failed_settlement_escrow: Uint128,
+    // settlement fees accrued to the contract and not yet withdrawn
+    pub accrued_fees: Uint128,
+    pub balance: Uint128,
+    pub discrepancy: bool,
+}
+
+/// Everything that changes how the contract treats transfers, as currently configured
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ContractSettingsResponse {
+    pub name: String,
+    // the contract admin, who may set quorums and veto addresses alongside marker admins
+    pub admin: Option<String>,
+    // proposed by the contract admin, becomes the admin once it runs `AcceptAdmin`
+    pub pending_admin: Option<Addr>,
+    pub paused: bool,
+    pub veto_address: Option<Addr>,
+    // most transfers a paged query returns
+    pub page_limit: u32,
+    // most transfers a sender may have pending at once
+    pub pending_limit: Option<u32>,
+    // most transfers a sender may create within a number of blocks
+    pub creation_rate_limit: Option<CreationRateLimit>,
+    // seconds pending transfers of denoms without their own default expiration stay approvable
+    pub default_expiration: Option<u64>,
+    // the only denoms transfers may move, any restricted marker when unset
+    pub allowed_denoms: Option<Vec<String>>,
+    // largest amount of any denom a single transfer may move
+    pub max_transfer_amount: Option<Uint128>,
+    // seconds completed transfers are kept before anyone may purge them
+    pub retention_period: Option<u64>,
+    pub pending_recovery: Option<Recovery>,
+    pub settlement_fee: Option<SettlementFee>,
+    pub msg_fees: Option<MsgFees>,
+    pub creation_deposit: Option<CreationDeposit>,
+    pub fee_denom: String,
+    // only denoms with at least one setting are listed
+    pub denoms: Vec<DenomSettings>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DenomSettings {
+    pub denom: String,
+    pub approval_quorum: u32,
+    pub veto_address: Option<Addr>,
+    pub min_amount: Option<Uint128>,
+    pub max_amount: Option<Uint128>,
+    pub default_expiration: Option<u64>,
+    pub velocity_limit: Option<VelocityLimit>,
+    pub auto_approve_threshold: Option<Uint128>,
+    pub frozen: bool,
+    // fee schedule charged instead of the settlement fee's rate
+    pub fee_schedule: Option<FeeSchedule>,
+}
+
+/// A page of configured denoms in denom order. Pass the last denom as `start_after` to get the next.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DenomConfigsResponse {
+    pub denoms: Vec<DenomSettings>,
+}
+
+/// The cw2 contract name and version stored at instantiation or the last migration
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VersionInfoResponse {
+    pub contract: String,
+    pub version: String,
+}
+
+impl Validate for QueryMsg {
+    /// Simple validation of QueryMsg data
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use restricted_marker_transfer::msg::{QueryMsg, Validate};
+    /// pub fn query(msg: QueryMsg){
+    ///
+    ///     let result = msg.validate();
+    /// }
+    /// ```
+    fn validate(&self) -> Result<(), ContractError> {
+        let mut invalid_fields: Vec<&str> = vec![];
+
+        match self {
+            QueryMsg::GetTransfer { id } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+            }
+            QueryMsg::GetContractInfo {} => {}
+            QueryMsg::GetVersionInfo {} => {}
+            QueryMsg::GetStats {} => {}
+            QueryMsg::GetSequence { sender } => {
+                if sender.is_empty() {
+                    invalid_fields.push("sender");
+                }
+            }
+            QueryMsg::GetVelocity { sender, denom } => {
+                if sender.is_empty() {
+                    invalid_fields.push("sender");
+                }
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+            }
+            QueryMsg::GetTotals {} => {}
+            QueryMsg::GetContractSettings {} => {}
+            QueryMsg::GetAuditLog { limit, .. } => {
+                if *limit == Some(0) {
+                    invalid_fields.push("limit");
+                }
+            }
+            QueryMsg::GetStandingApproval {
+                sender,
+                recipient,
+                denom,
+            } => {
+                invalid_fields.extend(invalid_route_fields(sender, recipient, denom));
+            }
+            QueryMsg::GetTemplates {
+                sender,
+                start_after,
+                limit,
+            } => {
+                if sender.is_empty() {
+                    invalid_fields.push("sender");
+                }
+                if start_after
+                    .as_ref()
+                    .is_some_and(|template_id| !is_valid_template_id(template_id))
+                {
+                    invalid_fields.push("start_after");
+                }
+                if *limit == Some(0) {
+                    invalid_fields.push("limit");
+                }
+            }
+            QueryMsg::ListDenomConfigs { start_after, limit } => {
+                if start_after.as_ref().is_some_and(|denom| denom.is_empty()) {
+                    invalid_fields.push("start_after");
+                }
+                if *limit == Some(0) {
+                    invalid_fields.push("limit");
+                }
+            }
+            QueryMsg::ExportState {
+                start_after, limit, ..
+            } => {
+                invalid_fields.extend(invalid_page_fields(start_after, *limit));
+            }
+            QueryMsg::GetTransfers { ids } => {
+                if !is_valid_batch(ids) {
+                    invalid_fields.push("ids");
+                }
+            }
+            QueryMsg::GetAllTransfers {
+                start_after, limit, ..
+            } => {
+                invalid_fields.extend(invalid_page_fields(start_after, *limit));
+            }
+            QueryMsg::GetApprovalQuorum { denom } => {
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+            }
+            QueryMsg::GetDenomVolume { denom } | QueryMsg::GetDenomCounters { denom, .. } => {
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+            }
+            QueryMsg::GetArchivedTransfers {
+                start_after, limit, ..
+            } => {
+                invalid_fields.extend(invalid_page_fields(start_after, *limit));
+            }
+            QueryMsg::GetTransferHistory {
+                start_after,
+                limit,
+                filter,
+                ..
+            } => {
+                invalid_fields.extend(invalid_page_fields(start_after, *limit));
+
+                if matches!(filter, Some(status) if status.is_open()) {
+                    invalid_fields.push("filter");
+                }
+            }
+            QueryMsg::GetTransfersCreated {
+                range,
+                start_after,
+                limit,
+                ..
+            } => {
+                if range.is_empty() {
+                    invalid_fields.push("range");
+                }
+
+                invalid_fields.extend(invalid_page_fields(start_after, *limit));
+            }
+            QueryMsg::FindPendingTransfers {
+                sender,
+                recipient,
+                denom,
+            } => {
+                if sender.is_empty() {
+                    invalid_fields.push("sender");
+                }
+
+                if recipient.is_empty() {
+                    invalid_fields.push("recipient");
+                }
+
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+            }
+            QueryMsg::GetEscrowReconciliation { denom } => {
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+            }
+            QueryMsg::GetApprovals { id }
+            | QueryMsg::GetTransferDetailed { id }
+            | QueryMsg::GetFailedSettlements { id }
+            | QueryMsg::GetTransferFreeze { id } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+            }
+            QueryMsg::GetAllowance { id } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+            }
+            QueryMsg::GetRoles { address } => {
+                if address.is_empty() {
+                    invalid_fields.push("address");
+                }
+            }
+            QueryMsg::EstimateFees {
+                sender,
+                denom,
+                amount,
+            } => {
+                if sender.is_empty() {
+                    invalid_fields.push("sender");
+                }
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+                if amount.is_zero() {
+                    invalid_fields.push("amount");
+                }
+            }
+            QueryMsg::GetBlocklist { start_after, limit }
+            | QueryMsg::GetFeeExemptions { start_after, limit }
+            | QueryMsg::GetAccruedFees { start_after, limit }
+            | QueryMsg::GetRegisteredDenoms { start_after, limit }
+            | QueryMsg::GetRoleMembers {
+                start_after, limit, ..
+            } => {
+                if start_after
+                    .as_ref()
+                    .is_some_and(|address| address.is_empty())
+                {
+                    invalid_fields.push("start_after");
+                }
+                if *limit == Some(0) {
+                    invalid_fields.push("limit");
+                }
+            }
+            QueryMsg::GetAllowlist {
+                denom,
+                start_after,
+                limit,
+            }
+            | QueryMsg::GetApprovers {
+                denom,
+                start_after,
+                limit,
+            }
+            | QueryMsg::GetDelegations {
+                denom,
+                start_after,
+                limit,
+            } => {
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+                if start_after
+                    .as_ref()
+                    .is_some_and(|address| address.is_empty())
+                {
+                    invalid_fields.push("start_after");
+                }
+                if *limit == Some(0) {
+                    invalid_fields.push("limit");
+                }
+            }
+            QueryMsg::GetRecurringTransfer { id } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+            }
+            QueryMsg::GetSwap { id } => {
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push("id");
+                }
+            }
+            QueryMsg::GetTransfersBySender {
+                sender,
+                start_after,
+                limit,
+                ..
+            } => {
+                if sender.is_empty() {
+                    invalid_fields.push("sender");
+                }
+
+                invalid_fields.extend(invalid_page_fields(start_after, *limit));
+            }
+            QueryMsg::GetTransfersByTag {
+                tag,
+                start_after,
+                limit,
+                ..
+            } => {
+                if tag.is_empty() {
+                    invalid_fields.push("tag");
+                }
+
+                invalid_fields.extend(invalid_page_fields(start_after, *limit));
+            }
+            QueryMsg::GetPendingApprovals {
+                approver,
+                start_after,
+                limit,
+                ..
+            } => {
+                if approver.is_empty() {
+                    invalid_fields.push("approver");
+                }
+
+                invalid_fields.extend(invalid_page_fields(start_after, *limit));
+            }
+            QueryMsg::GetPendingByDenom {
+                denom,
+                start_after,
+                limit,
+                ..
+            } => {
+                if denom.is_empty() {
+                    invalid_fields.push("denom");
+                }
+
+                invalid_fields.extend(invalid_page_fields(start_after, *limit));
+            }
+            QueryMsg::GetPendingActions {
+                address,
+                start_after,
+                limit,
+                ..
+            } => {
+                if address.is_empty() {
+                    invalid_fields.push("address");
                 }
+
+                invalid_fields.extend(invalid_page_fields(start_after, *limit));
             }
         }
 
@@ -124,48 +2184,126 @@ impl Validate for ExecuteMsg {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub enum QueryMsg {
-    GetTransfer { id: String },
-    GetContractInfo {},
-    GetVersionInfo {},
-    GetAllTransfers {},
+/// returns the paging fields that are invalid, a start must be a transfer id and a limit nonzero
+fn invalid_page_fields(start_after: &Option<String>, limit: Option<u32>) -> Vec<&'static str> {
+    let mut invalid_fields = vec![];
+
+    if matches!(start_after, Some(id) if Uuid::parse_str(id).is_err()) {
+        invalid_fields.push("start_after");
+    }
+
+    if limit == Some(0) {
+        invalid_fields.push("limit");
+    }
+
+    invalid_fields
 }
 
-impl Validate for QueryMsg {
-    /// Simple validation of QueryMsg data
-    ///
-    /// ### Example
-    ///
-    /// ```rust
-    /// use restricted_marker_transfer::msg::{QueryMsg, Validate};
-    /// pub fn query(msg: QueryMsg){
-    ///
-    ///     let result = msg.validate();
-    /// }
-    /// ```
-    fn validate(&self) -> Result<(), ContractError> {
-        let mut invalid_fields: Vec<&str> = vec![];
+/// returns the names of the empty fields of a standing approval route
+fn invalid_route_fields(sender: &str, recipient: &str, denom: &str) -> Vec<&'static str> {
+    [
+        ("sender", sender),
+        ("recipient", recipient),
+        ("denom", denom),
+    ]
+    .iter()
+    .filter(|(_, value)| value.is_empty())
+    .map(|(field, _)| *field)
+    .collect()
+}
 
-        match self {
-            QueryMsg::GetTransfer { id } => {
-                if Uuid::parse_str(id).is_err() {
-                    invalid_fields.push("id");
-                }
-            }
-            QueryMsg::GetContractInfo {} => {}
-            QueryMsg::GetVersionInfo {} => {}
-            QueryMsg::GetAllTransfers {} => {}
-        }
+/// returns the names of the invalid fields of a denom config
+fn invalid_denom_config_fields(
+    denom: &str,
+    min_amount: &Option<Uint128>,
+    max_amount: &Option<Uint128>,
+    default_expiration: &Option<u64>,
+) -> Vec<&'static str> {
+    let mut invalid_fields = vec![];
+    if denom.is_empty() {
+        invalid_fields.push("denom");
+    }
+    if matches!(max_amount, Some(max) if max.is_zero() || Some(*max) < *min_amount) {
+        invalid_fields.push("max_amount");
+    }
+    if *default_expiration == Some(0) {
+        invalid_fields.push("default_expiration");
+    }
+    invalid_fields
+}
 
-        match invalid_fields.len() {
-            0 => Ok(()),
-            _ => Err(ContractError::InvalidFields {
-                fields: invalid_fields.into_iter().map(|item| item.into()).collect(),
-            }),
-        }
+/// returns the names of the invalid fields of a velocity limit
+fn invalid_velocity_limit_fields(denom: &str, limit: &Option<VelocityLimit>) -> Vec<&'static str> {
+    let mut invalid_fields = vec![];
+    if denom.is_empty() {
+        invalid_fields.push("denom");
+    }
+    if matches!(limit, Some(limit) if limit.amount.is_zero() || limit.window == 0) {
+        invalid_fields.push("limit");
+    }
+    invalid_fields
+}
+
+/// returns the names of the invalid fields of a settlement fee
+fn invalid_settlement_fee_fields(fee: &SettlementFee) -> Vec<&'static str> {
+    let mut invalid_fields = vec![];
+    if fee.bps > MAX_FEE_BPS {
+        invalid_fields.push("bps");
+    }
+    if matches!(&fee.destination, FeeDestination::Collector { address } if address.as_str().is_empty())
+    {
+        invalid_fields.push("destination");
+    }
+    invalid_fields
+}
+
+/// returns the names of the invalid fields of a set of msg fees
+fn invalid_msg_fees_fields(fees: &MsgFees) -> Vec<&'static str> {
+    let mut invalid_fields = vec![];
+    if fees.creation_fee.is_some_and(|fee| fee.is_zero()) {
+        invalid_fields.push("creation_fee");
+    }
+    if fees.approval_fee.is_some_and(|fee| fee.is_zero()) {
+        invalid_fields.push("approval_fee");
+    }
+    if matches!(&fees.recipient, Some(recipient) if recipient.as_str().is_empty()) {
+        invalid_fields.push("recipient");
     }
+    if fees.recipient_bps > MAX_FEE_BPS {
+        invalid_fields.push("recipient_bps");
+    }
+    invalid_fields
+}
+
+/// returns the names of the invalid fields of a creation deposit
+fn invalid_creation_deposit_fields(deposit: &CreationDeposit) -> Vec<&'static str> {
+    let mut invalid_fields = vec![];
+    if deposit.amount.amount.is_zero() || deposit.amount.denom.is_empty() {
+        invalid_fields.push("amount");
+    }
+    if deposit.collector.as_str().is_empty() {
+        invalid_fields.push("collector");
+    }
+    invalid_fields
+}
+
+/// returns true if the id is a uuid a sender may give a new transfer, one not of the version
+/// the contract derives the ids of recurring and pulled transfers in
+fn is_valid_transfer_id(id: &str) -> bool {
+    Uuid::parse_str(id).is_ok_and(|uuid| uuid.get_version_num() != DERIVED_TRANSFER_ID_VERSION)
+}
+
+/// returns true if the template id is neither empty nor longer than the longest allowed
+fn is_valid_template_id(template_id: &str) -> bool {
+    !template_id.is_empty() && template_id.chars().count() <= MAX_TEMPLATE_ID_LENGTH
+}
+
+/// returns true if the ids are unique uuids and within the batch size limit
+fn is_valid_batch(ids: &[String]) -> bool {
+    !ids.is_empty()
+        && ids.len() <= MAX_BATCH_SIZE
+        && ids.iter().all(|id| Uuid::parse_str(id).is_ok())
+        && (1..ids.len()).all(|i| !ids[..i].contains(&ids[i]))
 }
 
 pub trait Validate {
@@ -175,7 +2313,11 @@ pub trait Validate {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::msg::ExecuteMsg::{ApproveTransfer, CancelTransfer, RejectTransfer, Transfer};
+    use crate::msg::ExecuteMsg::{
+        ApproveTransfer, ApproveTransferPartial, ApproveTransfers, CancelTransfer,
+        CreateRecurringTransfer, ProposeSwap, RejectTransfer, Transfer, UpdateTransfer,
+    };
+    use crate::state::derived_transfer_id;
 
     #[test]
     fn validate_transfer() {
@@ -184,6 +2326,13 @@ mod tests {
             denom: "".to_string(),
             amount: Uint128::new(0),
             recipient: "".to_string(),
+            require_acceptance: None,
+            approvers: Some(vec!["approver".to_string(), "approver".to_string()]),
+            recipients: Some(vec![]),
+            additional_coins: Some(vec![]),
+            vesting_duration: Some(0),
+            memo: Some("m".repeat(MAX_MEMO_LENGTH + 1)),
+            tags: Some(vec!["tranche-A".to_string(), "tranche-A".to_string()]),
         };
 
         let validate_response = invalid_transfer_msg.validate();
@@ -192,11 +2341,90 @@ mod tests {
             Ok(..) => panic!("expected error but was ok"),
             Err(error) => match error {
                 ContractError::InvalidFields { fields } => {
-                    assert_eq!(4, fields.len());
+                    assert_eq!(10, fields.len());
                     assert!(fields.contains(&"id".into()));
                     assert!(fields.contains(&"denom".into()));
                     assert!(fields.contains(&"amount".into()));
                     assert!(fields.contains(&"recipient".into()));
+                    assert!(fields.contains(&"approvers".into()));
+                    assert!(fields.contains(&"recipients".into()));
+                    assert!(fields.contains(&"additional_coins".into()));
+                    assert!(fields.contains(&"vesting_duration".into()));
+                    assert!(fields.contains(&"memo".into()));
+                    assert!(fields.contains(&"tags".into()));
+                }
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+    }
+
+    #[test]
+    fn validate_transfer_refuses_derived_ids() {
+        let derived_id = derived_transfer_id(
+            "recurring_transfer",
+            Uuid::parse_str("56253028-12f5-4d2a-a691-ebdfd2a7b865")
+                .unwrap()
+                .as_bytes(),
+            1,
+        );
+        let transfer_msg = |id: &str| Transfer {
+            id: id.to_string(),
+            denom: "restricted_1".to_string(),
+            amount: Uint128::new(1),
+            recipient: "recipient".to_string(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+
+        // ids of the version the contract derives are kept for recurring and pulled transfers
+        match transfer_msg(&derived_id).validate() {
+            Err(ContractError::InvalidFields { fields }) => assert_eq!(fields, vec!["id"]),
+            result => panic!("unexpected validation result: {:?}", result),
+        }
+        match (ExecuteMsg::CreateFromTemplate {
+            template_id: "payroll".into(),
+            id: derived_id,
+            amount: None,
+        })
+        .validate()
+        {
+            Err(ContractError::InvalidFields { fields }) => assert_eq!(fields, vec!["id"]),
+            result => panic!("unexpected validation result: {:?}", result),
+        }
+        assert!(transfer_msg("56253028-12f5-4d2a-a691-ebdfd2a7b865")
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn validate_transfer_additional_coins() {
+        let invalid_transfer_msg = Transfer {
+            id: "54c4f5d9-5253-43ac-9011-bbc52465581e".to_string(),
+            denom: "restricted_1".to_string(),
+            amount: Uint128::new(5),
+            recipient: "transfer_to".to_string(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: Some(vec![Coin::new(2, "restricted_1")]),
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+
+        let validate_response = invalid_transfer_msg.validate();
+
+        match validate_response {
+            Ok(..) => panic!("expected error but was ok"),
+            Err(error) => match error {
+                ContractError::InvalidFields { fields } => {
+                    assert_eq!(1, fields.len());
+                    assert!(fields.contains(&"additional_coins".into()));
                 }
                 error => panic!("unexpected error: {:?}", error),
             },
@@ -223,6 +2451,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn validate_approve_transfer_partial() {
+        let invalid_approve_msg = ApproveTransferPartial {
+            id: "not-a-real-uuid".to_string(),
+            amount: Uint128::new(0),
+        };
+
+        let validate_response = invalid_approve_msg.validate();
+
+        match validate_response {
+            Ok(..) => panic!("expected error but was ok"),
+            Err(error) => match error {
+                ContractError::InvalidFields { fields } => {
+                    assert_eq!(2, fields.len());
+                    assert!(fields.contains(&"id".into()));
+                    assert!(fields.contains(&"amount".into()));
+                }
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+    }
+
     #[test]
     fn validate_cancel_transfer() {
         let invalid_cancel_msg = CancelTransfer {
@@ -247,6 +2497,28 @@ mod tests {
     fn validate_reject_transfer() {
         let invalid_reject_msg = RejectTransfer {
             id: "not-a-real-uuid".to_string(),
+            reason: None,
+        };
+
+        let validate_response = invalid_reject_msg.validate();
+
+        match validate_response {
+            Ok(..) => panic!("expected error but was ok"),
+            Err(error) => match error {
+                ContractError::InvalidFields { fields } => {
+                    assert_eq!(1, fields.len());
+                    assert!(fields.contains(&"id".into()));
+                }
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+    }
+
+    #[test]
+    fn validate_reject_transfer_reason() {
+        let invalid_reject_msg = RejectTransfer {
+            id: "56253028-12f5-4d2a-a691-ebdfd2a7b865".to_string(),
+            reason: Some("x".repeat(MAX_REASON_LENGTH + 1)),
         };
 
         let validate_response = invalid_reject_msg.validate();
@@ -256,7 +2528,298 @@ mod tests {
             Err(error) => match error {
                 ContractError::InvalidFields { fields } => {
                     assert_eq!(1, fields.len());
+                    assert!(fields.contains(&"reason".into()));
+                }
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+    }
+
+    #[test]
+    fn validate_instantiate_settings() {
+        let init_msg = InstantiateMsg {
+            name: "contract_name".into(),
+            admin: None,
+            default_expiration: Some(0),
+            pending_limit: Some(0),
+            allowed_denoms: Some(vec!["restricted_1".into(), "restricted_1".into()]),
+            settlement_fee: Some(SettlementFee {
+                bps: MAX_FEE_BPS + 1,
+                destination: FeeDestination::Accrue {},
+            }),
+            msg_fees: Some(MsgFees {
+                creation_fee: Some(Uint128::zero()),
+                approval_fee: None,
+                recipient: None,
+                recipient_bps: 0,
+            }),
+            creation_deposit: Some(CreationDeposit {
+                amount: Coin::new(0, "nhash"),
+                collector: Addr::unchecked("collector"),
+            }),
+        };
+        match init_msg.validate() {
+            Err(ContractError::InvalidFields { fields }) => {
+                assert_eq!(
+                    fields,
+                    vec![
+                        "default_expiration",
+                        "pending_limit",
+                        "allowed_denoms",
+                        "settlement_fee",
+                        "msg_fees",
+                        "creation_deposit"
+                    ]
+                );
+            }
+            result => panic!("unexpected validation result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn validate_admin() {
+        let init_msg = InstantiateMsg {
+            name: "contract_name".into(),
+            admin: Some("".into()),
+            default_expiration: None,
+            pending_limit: None,
+            allowed_denoms: None,
+            settlement_fee: None,
+            msg_fees: None,
+            creation_deposit: None,
+        };
+        match init_msg.validate() {
+            Err(ContractError::InvalidFields { fields }) => {
+                assert_eq!(fields, vec!["admin"]);
+            }
+            result => panic!("unexpected validation result: {:?}", result),
+        }
+
+        let update_admin_msg = ExecuteMsg::UpdateAdmin {
+            new_admin: "".into(),
+        };
+        match update_admin_msg.validate() {
+            Err(ContractError::InvalidFields { fields }) => {
+                assert_eq!(fields, vec!["new_admin"]);
+            }
+            result => panic!("unexpected validation result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn validate_set_denom_config() {
+        let set_denom_config_msg = |min: u128, max: u128| ExecuteMsg::SetDenomConfig {
+            denom: "restricted_1".into(),
+            min_amount: Some(Uint128::new(min)),
+            max_amount: Some(Uint128::new(max)),
+            default_expiration: Some(0),
+        };
+
+        match set_denom_config_msg(5, 4).validate() {
+            Err(ContractError::InvalidFields { fields }) => {
+                assert_eq!(fields, vec!["max_amount", "default_expiration"]);
+            }
+            result => panic!("unexpected validation result: {:?}", result),
+        }
+
+        match set_denom_config_msg(0, 0).validate() {
+            Err(ContractError::InvalidFields { fields }) => {
+                assert_eq!(fields, vec!["max_amount", "default_expiration"]);
+            }
+            result => panic!("unexpected validation result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn validate_update_transfer() {
+        let invalid_update_msg = UpdateTransfer {
+            id: "not-a-real-uuid".to_string(),
+            new_recipient: None,
+            new_amount: None,
+        };
+
+        let validate_response = invalid_update_msg.validate();
+
+        match validate_response {
+            Ok(..) => panic!("expected error but was ok"),
+            Err(error) => match error {
+                ContractError::InvalidFields { fields } => {
+                    assert_eq!(3, fields.len());
+                    assert!(fields.contains(&"id".into()));
+                    assert!(fields.contains(&"new_recipient".into()));
+                    assert!(fields.contains(&"new_amount".into()));
+                }
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+    }
+
+    #[test]
+    fn validate_propose_swap() {
+        let invalid_propose_msg = ProposeSwap {
+            id: "fake-id".to_string(),
+            denom: "restricted_1".to_string(),
+            amount: Uint128::new(0),
+            counterparty: "".to_string(),
+            counterparty_denom: "restricted_1".to_string(),
+            counterparty_amount: Uint128::new(0),
+        };
+
+        let validate_response = invalid_propose_msg.validate();
+
+        match validate_response {
+            Ok(..) => panic!("expected error but was ok"),
+            Err(error) => match error {
+                ContractError::InvalidFields { fields } => {
+                    assert_eq!(5, fields.len());
+                    assert!(fields.contains(&"id".into()));
+                    assert!(fields.contains(&"amount".into()));
+                    assert!(fields.contains(&"counterparty".into()));
+                    assert!(fields.contains(&"counterparty_denom".into()));
+                    assert!(fields.contains(&"counterparty_amount".into()));
+                }
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+    }
+
+    #[test]
+    fn validate_create_recurring_transfer() {
+        let invalid_create_msg = CreateRecurringTransfer {
+            id: "fake-id".to_string(),
+            denom: "".to_string(),
+            amount: Uint128::new(0),
+            recipient: "".to_string(),
+            interval: ScheduleInterval::Blocks(0),
+            occurrences: 0,
+        };
+
+        let validate_response = invalid_create_msg.validate();
+
+        match validate_response {
+            Ok(..) => panic!("expected error but was ok"),
+            Err(error) => match error {
+                ContractError::InvalidFields { fields } => {
+                    assert_eq!(6, fields.len());
                     assert!(fields.contains(&"id".into()));
+                    assert!(fields.contains(&"denom".into()));
+                    assert!(fields.contains(&"amount".into()));
+                    assert!(fields.contains(&"recipient".into()));
+                    assert!(fields.contains(&"interval".into()));
+                    assert!(fields.contains(&"occurrences".into()));
+                }
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+    }
+
+    #[test]
+    fn validate_save_template() {
+        let invalid_save_msg = ExecuteMsg::SaveTemplate {
+            template_id: "t".repeat(MAX_TEMPLATE_ID_LENGTH + 1),
+            denom: "".to_string(),
+            recipient: "".to_string(),
+            amount: Some(Uint128::zero()),
+            memo: Some("".to_string()),
+        };
+
+        match invalid_save_msg.validate() {
+            Ok(..) => panic!("expected error but was ok"),
+            Err(error) => match error {
+                ContractError::InvalidFields { fields } => {
+                    assert_eq!(5, fields.len());
+                    assert!(fields.contains(&"template_id".into()));
+                    assert!(fields.contains(&"denom".into()));
+                    assert!(fields.contains(&"recipient".into()));
+                    assert!(fields.contains(&"amount".into()));
+                    assert!(fields.contains(&"memo".into()));
+                }
+                error => panic!("unexpected error: {:?}", error),
+            },
+        }
+
+        let valid_save_msg = ExecuteMsg::SaveTemplate {
+            template_id: "monthly-distribution".to_string(),
+            denom: "restricted_1".to_string(),
+            recipient: "recipient".to_string(),
+            amount: None,
+            memo: None,
+        };
+        assert!(valid_save_msg.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_approve_transfers() {
+        let id = "54c4f5d9-5253-43ac-9011-bbc52465581e".to_string();
+
+        for ids in [
+            vec![],
+            vec![id.clone(), id.clone()],
+            vec![id.clone(), "not-a-real-uuid".to_string()],
+            vec![id; MAX_BATCH_SIZE + 1],
+        ] {
+            match (ApproveTransfers { ids }).validate() {
+                Ok(..) => panic!("expected error but was ok"),
+                Err(error) => match error {
+                    ContractError::InvalidFields { fields } => {
+                        assert_eq!(vec![String::from("ids")], fields);
+                    }
+                    error => panic!("unexpected error: {:?}", error),
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn validate_batch_transfer() {
+        let transfer_input = TransferInput {
+            id: "54c4f5d9-5253-43ac-9011-bbc52465581e".to_string(),
+            denom: "restricted_1".to_string(),
+            amount: Uint128::new(5),
+            recipient: "transfer_to".to_string(),
+            require_acceptance: None,
+            approvers: None,
+            recipients: None,
+            additional_coins: None,
+            vesting_duration: None,
+            memo: None,
+            tags: None,
+        };
+
+        for transfers in [
+            vec![],
+            vec![transfer_input.clone(), transfer_input.clone()],
+            vec![TransferInput {
+                amount: Uint128::zero(),
+                ..transfer_input
+            }],
+        ] {
+            match (ExecuteMsg::BatchTransfer { transfers }).validate() {
+                Ok(..) => panic!("expected error but was ok"),
+                Err(error) => match error {
+                    ContractError::InvalidFields { fields } => {
+                        assert_eq!(vec![String::from("transfers")], fields);
+                    }
+                    error => panic!("unexpected error: {:?}", error),
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn validate_get_all_transfers() {
+        let msg = QueryMsg::GetAllTransfers {
+            start_after: Some("not-a-real-uuid".into()),
+            limit: Some(0),
+            order: None,
+        };
+
+        match msg.validate() {
+            Ok(..) => panic!("expected error but was ok"),
+            Err(error) => match error {
+                ContractError::InvalidFields { fields } => {
+                    assert!(fields.contains(&"start_after".into()));
+                    assert!(fields.contains(&"limit".into()));
                 }
                 error => panic!("unexpected error: {:?}", error),
             },
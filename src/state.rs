@@ -1,9 +1,26 @@
 use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{self, MapAccess, SeqAccess, Visitor};
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp;
+use std::collections::BTreeMap;
 use std::convert::Into;
+use std::fmt;
+use std::iter::Peekable;
+use std::ops;
 
-use cosmwasm_std::{Addr, Storage, Uint128};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{
+    Addr, BlockInfo, Coin, Empty, Order, Record, StdError, StdResult, Storage, Timestamp, Uint128,
+};
+use cw_storage_plus::{
+    Bound, Index, IndexList, IndexedMap, Item, Map, MultiIndex, PrimaryKey, SnapshotMap, Strategy,
+};
+use provwasm_std::types::provenance::marker::v1::{MarkerType, MsgTransferRequest};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::error::ContractError;
 
 pub const STORAGE_TRANSFER_KEY: &str = "transfer";
 
@@ -13,6 +30,149 @@ pub const STORAGE_TRANSFER_KEY: &str = "transfer";
 pub struct State {
     // The contract name
     pub name: String,
+    // The address allowed to run admin messages, the wasm admin of the contract when unset as
+    // it is for contracts instantiated before the admin was recorded
+    #[serde(default)]
+    pub admin: Option<Addr>,
+    // Seconds after creation a pending transfer can no longer be approved, for denoms without
+    // a default expiration of their own
+    #[serde(default)]
+    pub default_expiration: Option<u64>,
+    // The only denoms transfers may move, any restricted marker when unset
+    #[serde(default)]
+    pub allowed_denoms: Option<Vec<String>>,
+}
+
+/// Lifecycle of a transfer. Transfers are created as `Pending` and are retained in storage once
+/// they reach a terminal status. A settled transfer becomes `Failed` when a marker transfer
+/// paying it out fails, and settles again once the failed settlements are retried.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferStatus {
+    #[default]
+    Pending,
+    Approved,
+    Rejected,
+    Cancelled,
+    Declined,
+    Settled,
+    Failed,
+}
+
+impl TransferStatus {
+    /// returns true while the contract still holds the transfer's coins in escrow
+    pub fn holds_escrow(&self) -> bool {
+        matches!(self, TransferStatus::Pending | TransferStatus::Approved)
+    }
+
+    /// returns true while the transfer is kept with the open transfers. The coins of a failed
+    /// transfer are held by its failed settlements until they are retried.
+    pub fn is_open(&self) -> bool {
+        self.holds_escrow() || *self == TransferStatus::Failed
+    }
+
+    /// returns true if a transfer in this status may move to the next status
+    pub fn can_transition_to(&self, next: TransferStatus) -> bool {
+        matches!(
+            (self, next),
+            (TransferStatus::Pending, TransferStatus::Approved)
+                | (TransferStatus::Pending, TransferStatus::Rejected)
+                | (TransferStatus::Pending, TransferStatus::Cancelled)
+                | (TransferStatus::Pending, TransferStatus::Declined)
+                | (TransferStatus::Approved, TransferStatus::Cancelled)
+                | (TransferStatus::Approved, TransferStatus::Declined)
+                | (TransferStatus::Approved, TransferStatus::Rejected)
+                | (TransferStatus::Approved, TransferStatus::Settled)
+                | (TransferStatus::Settled, TransferStatus::Failed)
+                | (TransferStatus::Failed, TransferStatus::Settled)
+        )
+    }
+}
+
+/// Who approved a transfer, when, and for how much
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Approval {
+    pub approver: Addr,
+    pub height: u64,
+    pub time: Timestamp,
+    // unset for approvals recorded before amounts were
+    #[serde(default)]
+    pub amount: Option<Uint128>,
+}
+
+/// Linear release of an approved transfer to its recipient over `duration` seconds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Vesting {
+    pub duration: u64,
+    // set when the transfer is approved
+    pub start: Option<Timestamp>,
+}
+
+impl Vesting {
+    /// returns how much of the amount has vested at the given time
+    pub fn vested(&self, amount: Uint128, now: Timestamp) -> Uint128 {
+        let elapsed = match self.start {
+            Some(start) => now.seconds().saturating_sub(start.seconds()),
+            None => return Uint128::zero(),
+        };
+
+        if elapsed >= self.duration {
+            amount
+        } else {
+            amount.multiply_ratio(elapsed, self.duration)
+        }
+    }
+}
+
+/// The block a transfer was created in
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Creation {
+    pub height: u64,
+    pub time: Timestamp,
+}
+
+/// Blocks to look for created transfers in, from the first up to but not including the last
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CreationRange {
+    Height { from: u64, to: u64 },
+    Time { from: Timestamp, to: Timestamp },
+}
+
+impl CreationRange {
+    /// returns true if no block falls in the range
+    pub fn is_empty(&self) -> bool {
+        let (from, to) = self.bounds();
+        from >= to
+    }
+
+    fn bounds(&self) -> (u64, u64) {
+        match self {
+            CreationRange::Height { from, to } => (*from, *to),
+            CreationRange::Time { from, to } => (from.nanos(), to.nanos()),
+        }
+    }
+
+    fn index(&self) -> &'static Map<'static, (u64, &'static [u8]), Empty> {
+        match self {
+            CreationRange::Height { .. } => &TRANSFER_CREATED_HEIGHT,
+            CreationRange::Time { .. } => &TRANSFER_CREATED_TIME,
+        }
+    }
+
+    fn key(&self, creation: &Creation) -> u64 {
+        match self {
+            CreationRange::Height { .. } => creation.height,
+            CreationRange::Time { .. } => creation.time.nanos(),
+        }
+    }
+}
+
+/// A share of a transfer paid to one recipient on settlement
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransferLeg {
+    pub address: Addr,
+    pub amount: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -22,15 +182,2503 @@ pub struct Transfer {
     pub denom: String,
     pub amount: Uint128,
     pub recipient: Addr,
+    // transfers stored before statuses were tracked are all pending
+    #[serde(default)]
+    pub status: TransferStatus,
+    #[serde(default)]
+    pub rejection_reason: Option<String>,
+    // approved coins stay in escrow until the recipient accepts
+    #[serde(default)]
+    pub require_acceptance: bool,
+    // approvals collected so far, settlement happens once the denom's quorum is met
+    #[serde(default)]
+    pub approvals: Vec<Approval>,
+    // when set, only these addresses may approve and all of them must
+    #[serde(default)]
+    pub required_approvers: Vec<Addr>,
+    // when set, the amount is split across these recipients instead of paid to `recipient`
+    #[serde(default)]
+    pub legs: Vec<TransferLeg>,
+    // coins of other restricted denoms escrowed and settled together with `amount` of `denom`
+    #[serde(default)]
+    pub additional_coins: Vec<Coin>,
+    // when set, approved coins are released to the recipient as they vest instead of at once
+    #[serde(default)]
+    pub vesting: Option<Vesting>,
+    // portion of `amount` the recipient has already claimed from vesting
+    #[serde(default)]
+    pub claimed: Uint128,
+    // settlement instructions or reference text supplied by the sender
+    #[serde(default)]
+    pub memo: Option<String>,
+    // unset for transfers created before creation blocks were recorded
+    #[serde(default)]
+    pub created: Option<Creation>,
+    // block time of the last change made to the transfer, unset for transfers not changed
+    // since updates were recorded
+    #[serde(default)]
+    pub updated_at: Option<Timestamp>,
+    // position of the transfer among those created by its sender, counting from 1. Unset for
+    // transfers created before sequences were assigned
+    #[serde(default)]
+    pub sequence: Option<u64>,
+    // labels grouping related transfers, indexed by `TRANSFER_TAGS`
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // position of the transfer among all transfers created, counting from 1, and its place in
+    // the pending queue. Unset for transfers created before positions were assigned
+    #[serde(default)]
+    pub position: Option<u64>,
+    // prioritised transfers are queued ahead of all others, set by an operator
+    #[serde(default)]
+    pub priority: bool,
+    // the transfer can no longer be approved from this time, set from the default expiration of
+    // its denoms when it is created
+    #[serde(default)]
+    pub expires: Option<Timestamp>,
+}
+
+impl Transfer {
+    /// Move the transfer to the next status, failing if the transition is not allowed
+    pub fn transition(&mut self, next: TransferStatus) -> Result<(), ContractError> {
+        if !self.status.can_transition_to(next) {
+            return Err(ContractError::InvalidStatusTransition {
+                from: self.status,
+                to: next,
+            });
+        }
+        self.status = next;
+        Ok(())
+    }
+
+    /// Every coin held in escrow for the transfer, starting with the unclaimed part of `amount`
+    pub fn coins(&self) -> Vec<Coin> {
+        let mut coins = vec![Coin::new((self.amount - self.claimed).u128(), &self.denom)];
+        coins.extend(self.additional_coins.iter().cloned());
+        coins
+    }
+
+    /// The addresses the transfer pays on settlement
+    pub fn payees(&self) -> Vec<&Addr> {
+        match self.legs.is_empty() {
+            true => vec![&self.recipient],
+            false => self.legs.iter().map(|leg| &leg.address).collect(),
+        }
+    }
+
+    /// Whether the transfer has passed its expiry
+    pub fn is_expired(&self, block: &BlockInfo) -> bool {
+        self.expires.is_some_and(|expires| block.time >= expires)
+    }
+
+    /// The key of the transfer in `PENDING_QUEUE`. Prioritised transfers rank ahead of the
+    /// rest, each group in creation order.
+    fn queue_entry(&self) -> Option<(u8, u64)> {
+        self.position
+            .map(|position| (u8::from(!self.priority), position))
+    }
+
+    /// The coins the contract holds for the transfer in its current status
+    fn escrowed_coins(&self) -> Vec<Coin> {
+        match self.status.holds_escrow() {
+            true => self.coins(),
+            false => vec![],
+        }
+    }
+
+    /// The coins paid out to the recipients so far
+    fn settled_coins(&self) -> Vec<Coin> {
+        if self.status == TransferStatus::Settled {
+            let mut coins = vec![Coin::new(self.amount.u128(), &self.denom)];
+            coins.extend(self.additional_coins.iter().cloned());
+            coins
+        } else {
+            vec![Coin::new(self.claimed.u128(), &self.denom)]
+        }
+    }
+}
+
+/// Storage encoding of a transfer. The fields are written as a JSON array in declaration order
+/// instead of an object, leaving their names out of every record. Records written as objects
+/// before the compact encoding still load. Fields added later go at the end, so a shorter
+/// array loads with their defaults.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StoredTransfer(pub Transfer);
+
+const TRANSFER_FIELDS: usize = 22;
+
+impl Serialize for StoredTransfer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let transfer = &self.0;
+        let mut fields = serializer.serialize_seq(Some(TRANSFER_FIELDS))?;
+        fields.serialize_element(&transfer.id)?;
+        fields.serialize_element(&transfer.sender)?;
+        fields.serialize_element(&transfer.denom)?;
+        fields.serialize_element(&transfer.amount)?;
+        fields.serialize_element(&transfer.recipient)?;
+        fields.serialize_element(&transfer.status)?;
+        fields.serialize_element(&transfer.rejection_reason)?;
+        fields.serialize_element(&transfer.require_acceptance)?;
+        fields.serialize_element(&transfer.approvals)?;
+        fields.serialize_element(&transfer.required_approvers)?;
+        fields.serialize_element(&transfer.legs)?;
+        fields.serialize_element(&transfer.additional_coins)?;
+        fields.serialize_element(&transfer.vesting)?;
+        fields.serialize_element(&transfer.claimed)?;
+        fields.serialize_element(&transfer.memo)?;
+        fields.serialize_element(&transfer.created)?;
+        fields.serialize_element(&transfer.updated_at)?;
+        fields.serialize_element(&transfer.sequence)?;
+        fields.serialize_element(&transfer.tags)?;
+        fields.serialize_element(&transfer.position)?;
+        fields.serialize_element(&transfer.priority)?;
+        fields.serialize_element(&transfer.expires)?;
+        fields.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for StoredTransfer {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(StoredTransferVisitor)
+    }
+}
+
+struct StoredTransferVisitor;
+
+impl<'de> Visitor<'de> for StoredTransferVisitor {
+    type Value = StoredTransfer;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a transfer as an array of its fields or as an object")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut fields: A) -> Result<Self::Value, A::Error> {
+        let missing = |index| de::Error::invalid_length(index, &self);
+        Ok(StoredTransfer(Transfer {
+            id: fields.next_element()?.ok_or_else(|| missing(0))?,
+            sender: fields.next_element()?.ok_or_else(|| missing(1))?,
+            denom: fields.next_element()?.ok_or_else(|| missing(2))?,
+            amount: fields.next_element()?.ok_or_else(|| missing(3))?,
+            recipient: fields.next_element()?.ok_or_else(|| missing(4))?,
+            status: fields.next_element()?.unwrap_or_default(),
+            rejection_reason: fields.next_element()?.unwrap_or_default(),
+            require_acceptance: fields.next_element()?.unwrap_or_default(),
+            approvals: fields.next_element()?.unwrap_or_default(),
+            required_approvers: fields.next_element()?.unwrap_or_default(),
+            legs: fields.next_element()?.unwrap_or_default(),
+            additional_coins: fields.next_element()?.unwrap_or_default(),
+            vesting: fields.next_element()?.unwrap_or_default(),
+            claimed: fields.next_element()?.unwrap_or_default(),
+            memo: fields.next_element()?.unwrap_or_default(),
+            created: fields.next_element()?.unwrap_or_default(),
+            updated_at: fields.next_element()?.unwrap_or_default(),
+            sequence: fields.next_element()?.unwrap_or_default(),
+            tags: fields.next_element()?.unwrap_or_default(),
+            position: fields.next_element()?.unwrap_or_default(),
+            priority: fields.next_element()?.unwrap_or_default(),
+            expires: fields.next_element()?.unwrap_or_default(),
+        }))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, fields: A) -> Result<Self::Value, A::Error> {
+        Transfer::deserialize(MapAccessDeserializer::new(fields)).map(StoredTransfer)
+    }
+}
+
+/// Permission from a sender for a recipient to pull transfers of a denom from them, up to
+/// `amount` in total
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Allowance {
+    pub id: String,
+    pub sender: Addr,
+    pub recipient: Addr,
+    pub denom: String,
+    pub amount: Uint128,
+    // total of the transfers pulled so far
+    pub pulled: Uint128,
+    // number of transfers pulled so far
+    pub pulls: u32,
+}
+
+/// Terms a sender stores once and creates transfers from with `CreateFromTemplate`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransferTemplate {
+    pub id: String,
+    pub sender: Addr,
+    pub denom: String,
+    pub recipient: Addr,
+    // used when a transfer is created from the template without an amount
+    pub amount: Option<Uint128>,
+    pub memo: Option<String>,
+}
+
+/// Pre-authorisation from a marker transfer permission holder for transfers of a denom from a
+/// sender to a recipient. Each transfer it approves is counted against `max_amount`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StandingApproval {
+    pub approver: Addr,
+    pub sender: Addr,
+    pub recipient: Addr,
+    pub denom: String,
+    // most the approved transfers may add up to
+    pub max_amount: Uint128,
+    // amount of the transfers approved so far
+    pub used: Uint128,
+    pub expires: Timestamp,
+}
+
+impl StandingApproval {
+    /// returns true if a transfer of `amount` may still be approved at the given block
+    pub fn covers(&self, block: &BlockInfo, amount: Uint128) -> bool {
+        block.time < self.expires
+            && self
+                .used
+                .checked_add(amount)
+                .is_ok_and(|total| total <= self.max_amount)
+    }
+}
+
+/// Approval rights an approver of a denom lends to another address until `expires_at`, for
+/// example to cover an absence. The delegate may act only while the delegator still holds them.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ApprovalDelegation {
+    pub denom: String,
+    pub delegator: Addr,
+    pub delegate: Addr,
+    pub expires_at: Timestamp,
+}
+
+impl ApprovalDelegation {
+    /// returns true if the delegate may still act at the given block
+    pub fn is_active(&self, block: &BlockInfo) -> bool {
+        block.time < self.expires_at
+    }
+}
+
+/// Lifecycle of a delivery-versus-payment swap. Swaps are created as `Proposed` once the
+/// initiator's leg is escrowed and become `Funded` when the counterparty escrows theirs.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SwapStatus {
+    #[default]
+    Proposed,
+    Funded,
+    Settled,
+    Cancelled,
+    Rejected,
+}
+
+impl SwapStatus {
+    /// returns true while the contract may still hold escrow for the swap
+    pub fn is_open(&self) -> bool {
+        matches!(self, SwapStatus::Proposed | SwapStatus::Funded)
+    }
+
+    /// returns true if a swap in this status may move to the next status
+    pub fn can_transition_to(&self, next: SwapStatus) -> bool {
+        matches!(
+            (self, next),
+            (SwapStatus::Proposed, SwapStatus::Funded)
+                | (SwapStatus::Proposed, SwapStatus::Cancelled)
+                | (SwapStatus::Proposed, SwapStatus::Rejected)
+                | (SwapStatus::Funded, SwapStatus::Settled)
+                | (SwapStatus::Funded, SwapStatus::Cancelled)
+                | (SwapStatus::Funded, SwapStatus::Rejected)
+        )
+    }
+}
+
+/// The coins one party of a swap delivers, and the approval for them to move
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SwapLeg {
+    pub owner: Addr,
+    pub denom: String,
+    pub amount: Uint128,
+    pub approval: Option<Approval>,
+}
+
+/// Two restricted marker legs exchanged atomically once both are escrowed and approved
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Swap {
+    pub id: String,
+    pub initiator: SwapLeg,
+    pub counterparty: SwapLeg,
+    pub status: SwapStatus,
+    pub rejection_reason: Option<String>,
+}
+
+impl Swap {
+    /// Move the swap to the next status, failing if the transition is not allowed
+    pub fn transition(&mut self, next: SwapStatus) -> Result<(), ContractError> {
+        if !self.status.can_transition_to(next) {
+            return Err(ContractError::InvalidSwapStatusTransition {
+                from: self.status,
+                to: next,
+            });
+        }
+        self.status = next;
+        Ok(())
+    }
+
+    /// returns true once both legs are escrowed and approved
+    pub fn is_ready_to_settle(&self) -> bool {
+        self.status == SwapStatus::Funded
+            && self.initiator.approval.is_some()
+            && self.counterparty.approval.is_some()
+    }
+}
+
+/// A set of privileged actions the admin can delegate to other addresses
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Every privileged action, including granting and revoking roles
+    Admin,
+    /// Operational upkeep: pausing, pagination, pruning, archiving and sweeping
+    Operator,
+    /// Denom limits, the blocklist, vetoes and force cancellation
+    Compliance,
+    /// Collecting the fees the contract charges
+    FeeManager,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [
+        Role::Admin,
+        Role::Operator,
+        Role::Compliance,
+        Role::FeeManager,
+    ];
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Role::Admin => write!(f, "admin"),
+            Role::Operator => write!(f, "operator"),
+            Role::Compliance => write!(f, "compliance"),
+            Role::FeeManager => write!(f, "fee_manager"),
+        }
+    }
+}
+
+/// Time between the occurrences of a recurring transfer
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleInterval {
+    Blocks(u64),
+    Seconds(u64),
+}
+
+/// A schedule that creates a pending transfer of the same terms once per interval
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RecurringTransfer {
+    pub id: String,
+    pub sender: Addr,
+    pub denom: String,
+    pub amount: Uint128,
+    pub recipient: Addr,
+    pub interval: ScheduleInterval,
+    // total number of transfers the schedule creates
+    pub occurrences: u32,
+    // number of transfers created so far
+    pub executed: u32,
+    pub start_height: u64,
+    pub start_time: Timestamp,
+}
+
+impl RecurringTransfer {
+    /// returns true if the next occurrence may be created at the given block. The first
+    /// occurrence is due as soon as the schedule is created.
+    pub fn is_due(&self, block: &BlockInfo) -> bool {
+        if self.executed >= self.occurrences {
+            return false;
+        }
+
+        let elapsed_intervals = u64::from(self.executed);
+        match self.interval {
+            ScheduleInterval::Blocks(blocks) => {
+                block.height
+                    >= self
+                        .start_height
+                        .saturating_add(blocks.saturating_mul(elapsed_intervals))
+            }
+            ScheduleInterval::Seconds(seconds) => {
+                block.time.seconds()
+                    >= self
+                        .start_time
+                        .seconds()
+                        .saturating_add(seconds.saturating_mul(elapsed_intervals))
+            }
+        }
+    }
+}
+
+/// Number of stored transfers in each status, kept up to date by `save_transfer`
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct TransferStats {
+    pub pending: u64,
+    pub approved: u64,
+    pub rejected: u64,
+    pub cancelled: u64,
+    pub declined: u64,
+    pub settled: u64,
+    #[serde(default)]
+    pub failed: u64,
+}
+
+impl TransferStats {
+    fn count_mut(&mut self, status: TransferStatus) -> &mut u64 {
+        match status {
+            TransferStatus::Pending => &mut self.pending,
+            TransferStatus::Approved => &mut self.approved,
+            TransferStatus::Rejected => &mut self.rejected,
+            TransferStatus::Cancelled => &mut self.cancelled,
+            TransferStatus::Declined => &mut self.declined,
+            TransferStatus::Settled => &mut self.settled,
+            TransferStatus::Failed => &mut self.failed,
+        }
+    }
+
+    /// Count one more transfer in the status
+    pub fn record(&mut self, status: TransferStatus) {
+        *self.count_mut(status) += 1;
+    }
+
+    /// Move one transfer from the previous status to the next
+    pub fn record_transition(&mut self, previous: TransferStatus, next: TransferStatus) {
+        let count = self.count_mut(previous);
+        *count = count.saturating_sub(1);
+        self.record(next);
+    }
+}
+
+/// Number of transfers ever created and ever moved into each outcome, kept up to date by
+/// `save_transfer`. Unlike the status counts these only grow, pruning history leaves them.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct TransferTotals {
+    pub created: u64,
+    pub approved: u64,
+    pub rejected: u64,
+    pub cancelled: u64,
+    pub declined: u64,
+    pub settled: u64,
+    #[serde(default)]
+    pub failed: u64,
+}
+
+impl TransferTotals {
+    /// Count a transfer moving from its previous status, if it was stored before, to the next
+    pub fn record(&mut self, previous: Option<TransferStatus>, next: TransferStatus) {
+        if previous.is_none() {
+            self.created += 1;
+        }
+        // a pending transfer is approved and settled in the same save when it needs no acceptance
+        let was_pending = matches!(previous, None | Some(TransferStatus::Pending));
+        match next {
+            TransferStatus::Pending => {}
+            TransferStatus::Approved => self.approved += 1,
+            TransferStatus::Rejected => self.rejected += 1,
+            TransferStatus::Cancelled => self.cancelled += 1,
+            TransferStatus::Declined => self.declined += 1,
+            TransferStatus::Settled => {
+                if was_pending {
+                    self.approved += 1;
+                }
+                // a failed transfer was already counted when it first settled
+                if previous != Some(TransferStatus::Failed) {
+                    self.settled += 1;
+                }
+            }
+            TransferStatus::Failed => self.failed += 1,
+        }
+    }
+}
+
+/// Amounts of a denom currently held in escrow and paid out by settled transfers
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct DenomVolume {
+    pub escrowed: Uint128,
+    pub settled: Uint128,
+}
+
+/// Transfers pending with coins of a denom and the amount of it they hold in escrow
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct DenomCounters {
+    pub pending: u64,
+    pub escrowed: Uint128,
+}
+
+/// A marker transfer moving a transfer's escrow out, awaiting its reply
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SettlementAttempt {
+    pub transfer_id: String,
+    pub msg: MsgTransferRequest,
+}
+
+/// A marker transfer moving a transfer's escrow out that failed, its coins are still held by
+/// the contract until it is retried
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FailedSettlement {
+    pub transfer_id: String,
+    pub error: String,
+    pub msg: MsgTransferRequest,
 }
 
 pub const CONFIG: Item<State> = Item::new("config");
 
-pub const TRANSFER_STORAGE: Map<&[u8], Transfer> = Map::new(STORAGE_TRANSFER_KEY);
+/// Address the contract admin proposed to hand the role to, waiting for it to accept
+pub const PENDING_ADMIN: Item<Addr> = Item::new("pending_admin");
+
+/// Set by an operator to stop transfers being created or settled while it is true
+pub const PAUSED: Item<bool> = Item::new("paused");
+
+/// Seconds between the admin initiating an escrow recovery and being able to execute it, so
+/// senders and marker admins can object before their escrow is returned
+pub const RECOVERY_DELAY: u64 = 7 * 24 * 60 * 60;
+
+/// An escrow recovery the admin initiated to return the escrow of every open transfer of a denom
+/// to its sender, for example before decommissioning the contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Recovery {
+    pub denom: String,
+    pub initiated_by: Addr,
+    pub initiated_at: Timestamp,
+    pub executable_at: Timestamp,
+}
+
+/// The escrow recovery waiting for its delay to pass, at most one at a time
+pub const PENDING_RECOVERY: Item<Recovery> = Item::new("pending_recovery");
+
+/// Key of the last open transfer the pending escrow recovery looked at, the next batch continues
+/// after it. Unset before the first batch and once the recovery is complete or dropped.
+pub const RECOVERY_CURSOR: Item<Vec<u8>> = Item::new("recovery_cursor");
+
+/// Version of the layout of everything in storage, advanced by the migration steps in
+/// [`crate::migrate`] and independent of the contract version
+pub const SCHEMA_VERSION: Item<u32> = Item::new("schema_version");
+
+/// Secondary indexes kept alongside every stored transfer. Only the primary `denom` and
+/// `recipient` are indexed, not those of additional coins or legs.
+pub struct TransferIndexes<'a> {
+    pub sender: MultiIndex<'a, Addr, StoredTransfer, &'a [u8]>,
+    pub recipient: MultiIndex<'a, Addr, StoredTransfer, &'a [u8]>,
+    pub denom: MultiIndex<'a, String, StoredTransfer, &'a [u8]>,
+}
+
+impl<'a> IndexList<StoredTransfer> for TransferIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<StoredTransfer>> + '_> {
+        let indexes: Vec<&dyn Index<StoredTransfer>> =
+            vec![&self.sender, &self.recipient, &self.denom];
+        Box::new(indexes.into_iter())
+    }
+}
+
+fn transfer_sender(_: &[u8], StoredTransfer(transfer): &StoredTransfer) -> Addr {
+    transfer.sender.to_owned()
+}
+
+fn transfer_recipient(_: &[u8], StoredTransfer(transfer): &StoredTransfer) -> Addr {
+    transfer.recipient.to_owned()
+}
+
+fn transfer_denom(_: &[u8], StoredTransfer(transfer): &StoredTransfer) -> String {
+    transfer.denom.to_owned()
+}
+
+pub const TRANSFER_STORAGE: IndexedMap<&[u8], StoredTransfer, TransferIndexes> = IndexedMap::new(
+    STORAGE_TRANSFER_KEY,
+    TransferIndexes {
+        sender: MultiIndex::new(transfer_sender, STORAGE_TRANSFER_KEY, "transfer__sender"),
+        recipient: MultiIndex::new(
+            transfer_recipient,
+            STORAGE_TRANSFER_KEY,
+            "transfer__recipient",
+        ),
+        denom: MultiIndex::new(transfer_denom, STORAGE_TRANSFER_KEY, "transfer__denom"),
+    },
+);
+
+/// Transfers that reached a terminal status, moved out of `TRANSFER_STORAGE` by `save_transfer`
+/// so scans of open transfers stay short
+pub const TRANSFER_HISTORY: IndexedMap<&[u8], StoredTransfer, TransferIndexes> = IndexedMap::new(
+    "transfer_history",
+    TransferIndexes {
+        sender: MultiIndex::new(
+            transfer_sender,
+            "transfer_history",
+            "transfer_history__sender",
+        ),
+        recipient: MultiIndex::new(
+            transfer_recipient,
+            "transfer_history",
+            "transfer_history__recipient",
+        ),
+        denom: MultiIndex::new(
+            transfer_denom,
+            "transfer_history",
+            "transfer_history__denom",
+        ),
+    },
+);
+
+/// Completed transfers moved out of the history by `ArchiveTransfers`. They have no index
+/// entries and are left out of every listing query except `GetArchivedTransfers`, but can
+/// still be loaded by id.
+pub const TRANSFER_ARCHIVE: Map<&[u8], StoredTransfer> = Map::new("transfer_archive");
+
+/// Key of the last open transfer `migrate_legacy_transfers` looked at, the next batch continues
+/// after it. Unset before the first batch and once a batch reaches the last open transfer.
+pub const LEGACY_MIGRATION_CURSOR: Item<Vec<u8>> = Item::new("legacy_migration_cursor");
+
+/// Ids of completed transfers by the block time in nanoseconds they were completed at, used to
+/// prune history
+pub const TRANSFER_COMPLETED_TIME: Map<(u64, &[u8]), Empty> = Map::new("transfer_completed_time");
+
+pub const SWAP_STORAGE: Map<&[u8], Swap> = Map::new("swap");
+
+/// Recurring transfers by the `transfer_key` of their id
+pub const RECURRING_TRANSFER_STORAGE: Map<&[u8], RecurringTransfer> =
+    Map::new("recurring_transfer");
+
+/// Allowances by the `transfer_key` of their id
+pub const ALLOWANCE_STORAGE: Map<&[u8], Allowance> = Map::new("allowance");
+
+/// Transfer templates by their sender and id, template ids are only unique per sender
+pub const TRANSFER_TEMPLATES: Map<(&Addr, &str), TransferTemplate> = Map::new("transfer_template");
+
+/// Standing approvals by the denom, sender and recipient of the route they approve
+pub const STANDING_APPROVALS: Map<(&str, &Addr, &Addr), StandingApproval> =
+    Map::new("standing_approval");
+
+pub const TRANSFER_STATS: Item<TransferStats> = Item::new("transfer_stats");
+
+pub const TRANSFER_TOTALS: Item<TransferTotals> = Item::new("transfer_totals");
+
+/// Transfer ids by creation height, written by `save_transfer` when a transfer is first stored
+pub const TRANSFER_CREATED_HEIGHT: Map<(u64, &[u8]), Empty> = Map::new("transfer_created_height");
+
+/// Transfer ids by creation time in nanoseconds, written alongside the height index
+pub const TRANSFER_CREATED_TIME: Map<(u64, &[u8]), Empty> = Map::new("transfer_created_time");
+
+/// Transfer ids by each of their tags, written by `save_transfer` when a transfer is first
+/// stored
+pub const TRANSFER_TAGS: Map<(&str, &[u8]), Empty> = Map::new("transfer_tag");
+
+/// The rank, position and transfer key of an entry in a pending queue
+pub type QueueKey<'a> = (u8, u64, &'a [u8]);
+
+/// Pending transfers in the order approvers should process them, prioritised transfers first and
+/// then by position. Kept up to date by `save_transfer`.
+pub const PENDING_QUEUE: Map<QueueKey, Empty> = Map::new("pending_queue");
+
+/// The pending queue of each denom, in the same order. Kept up to date by `save_transfer`.
+pub const PENDING_QUEUE_BY_DENOM: Map<(&str, QueueKey), Empty> = Map::new("pending_queue_by_denom");
+
+/// Escrowed and settled totals per denom, kept up to date by `save_transfer`
+pub const DENOM_VOLUME: Map<&str, DenomVolume> = Map::new("denom_volume");
+
+/// Pending counts and escrowed totals per denom, checkpointed every block they change so they
+/// can be read as they stood at an earlier height
+pub const DENOM_COUNTERS: SnapshotMap<&str, DenomCounters> = SnapshotMap::new(
+    "denom_counters",
+    "denom_counters__checkpoints",
+    "denom_counters__changelog",
+    Strategy::EveryBlock,
+);
+
+/// Marker details that do not change once the marker exists, kept so creating transfers of the
+/// denom does not query its marker every time
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CachedMarker {
+    pub marker_type: MarkerType,
+}
+
+/// Cached marker details by denom, filled on first use and refreshed by an operator
+pub const MARKER_CACHE: Map<&str, CachedMarker> = Map::new("marker_cache");
+
+/// Number of approvals required before a transfer of the denom settles, one when unset
+pub const APPROVAL_QUORUM: Map<&str, u32> = Map::new("approval_quorum");
+
+/// Amount of a denom below which new transfers are approved as they are created, set by the
+/// marker's admin
+pub const AUTO_APPROVE_THRESHOLDS: Map<&str, Uint128> = Map::new("auto_approve_thresholds");
+
+/// Limits on new transfers of a denom, set by the compliance role or the marker's admin
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct DenomConfig {
+    pub min_amount: Option<Uint128>,
+    pub max_amount: Option<Uint128>,
+    // seconds a new transfer may wait for approval before it expires
+    pub default_expiration: Option<u64>,
+}
+
+pub const DENOM_CONFIG: Map<&str, DenomConfig> = Map::new("denom_config");
+
+/// Addresses the compliance role blocked from sending or receiving transfers
+pub const BLOCKLIST: Map<&Addr, Empty> = Map::new("blocklist");
+
+/// Denoms whose marker admin consented to their transfers going through the contract. Transfers
+/// and swaps of any other denom are refused.
+pub const REGISTERED_DENOMS: Map<&str, Empty> = Map::new("registered_denoms");
+
+/// Denoms frozen by the admin role or the marker admin. Their transfers and swaps can be
+/// cancelled, but none are created or approved until the denom is unfrozen.
+pub const FROZEN_DENOMS: Map<&str, Empty> = Map::new("frozen_denoms");
+
+/// Denoms whose transfers may only pay the addresses on their `RECIPIENT_ALLOWLIST`, enabled by
+/// the marker's admin
+pub const ALLOWLIST_ENABLED: Map<&str, Empty> = Map::new("allowlist_enabled");
+
+/// Addresses the marker's admin allows transfers of the denom to pay
+pub const RECIPIENT_ALLOWLIST: Map<(&str, &Addr), Empty> = Map::new("recipient_allowlist");
+
+/// Returns up to `limit` addresses on the allowlist of the denom, in address order after
+/// `start_after`
+pub fn get_allowlist(
+    storage: &dyn Storage,
+    denom: &str,
+    start_after: Option<&Addr>,
+    limit: usize,
+) -> StdResult<Vec<Addr>> {
+    RECIPIENT_ALLOWLIST
+        .prefix(denom)
+        .keys(
+            storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect()
+}
+
+/// Holders of each role, keyed by the role's name
+pub const ROLE_MEMBERS: Map<(&str, &Addr), Empty> = Map::new("role_members");
+
+/// Whether the address was granted the role
+pub fn holds_role(storage: &dyn Storage, role: Role, address: &Addr) -> bool {
+    ROLE_MEMBERS.has(storage, (&role.to_string(), address))
+}
+
+/// Addresses the marker's admin registered as approvers of the denom. They may approve and reject
+/// its transfers without a transfer grant on the marker.
+pub const APPROVER_REGISTRY: Map<(&str, &Addr), Empty> = Map::new("approver_registry");
+
+/// Returns a page of the registered approvers of the denom in address order
+pub fn get_registered_approvers(
+    storage: &dyn Storage,
+    denom: &str,
+    start_after: Option<&Addr>,
+    limit: usize,
+) -> StdResult<Vec<Addr>> {
+    APPROVER_REGISTRY
+        .prefix(denom)
+        .keys(
+            storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect()
+}
+
+/// The delegation of each approver of a denom, keyed by denom and delegator. Delegating again
+/// replaces the earlier delegation.
+pub const APPROVAL_DELEGATIONS: Map<(&str, &Addr), ApprovalDelegation> =
+    Map::new("approval_delegations");
+
+/// Returns a page of the delegations of the denom still active at the block, in delegator order
+pub fn get_approval_delegations(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    denom: &str,
+    start_after: Option<&Addr>,
+    limit: usize,
+) -> StdResult<Vec<ApprovalDelegation>> {
+    APPROVAL_DELEGATIONS
+        .prefix(denom)
+        .range(
+            storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .map(|item| item.map(|(_, delegation)| delegation))
+        .filter(|item| {
+            item.as_ref()
+                .map_or(true, |delegation| delegation.is_active(block))
+        })
+        .take(limit)
+        .collect()
+}
+
+/// Compliance address allowed to veto transfers of any denom
+pub const GLOBAL_VETO: Item<Addr> = Item::new("global_veto");
+
+/// Compliance addresses allowed to veto transfers of a single denom
+pub const DENOM_VETO: Map<&str, Addr> = Map::new("denom_veto");
+
+/// Settlement submessages sent but not yet replied to, by reply id
+pub const SETTLEMENT_ATTEMPTS: Map<u64, SettlementAttempt> = Map::new("settlement_attempt");
+
+/// Reply id the next settlement submessage is sent with
+pub const NEXT_SETTLEMENT_REPLY_ID: Item<u64> = Item::new("next_settlement_reply_id");
+
+/// Settlement submessages that failed, by transfer key and the reply id they were sent with
+pub const FAILED_SETTLEMENTS: Map<(&[u8], u64), FailedSettlement> = Map::new("failed_settlement");
+
+/// returns true while settlements of the transfer under the key have failed and not been retried
+pub fn has_failed_settlements(storage: &dyn Storage, key: &[u8]) -> bool {
+    FAILED_SETTLEMENTS
+        .prefix(key)
+        .keys_raw(storage, None, None, Order::Ascending)
+        .next()
+        .is_some()
+}
+
+/// Why and by whom a pending transfer was frozen. Its escrow stays held until it is unfrozen.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransferFreeze {
+    pub reason: String,
+    pub frozen_by: Addr,
+    pub frozen_at: Timestamp,
+}
+
+/// Freezes of pending transfers by transfer key, set and lifted by the compliance role
+pub const TRANSFER_FREEZES: Map<&[u8], TransferFreeze> = Map::new("transfer_freezes");
+
+/// The storage key of a transfer: the 16 bytes of its id, whatever case the id was written in
+pub fn transfer_key(id: &str) -> StdResult<[u8; 16]> {
+    Uuid::parse_str(id)
+        .map(|uuid| *uuid.as_bytes())
+        .map_err(|error| StdError::parse_err("Uuid", error))
+}
+
+/// Version of the uuids the contract derives for the transfers it creates from a recurring
+/// transfer or an allowance. Senders may not give their own transfers an id of this version.
+pub const DERIVED_TRANSFER_ID_VERSION: usize = 8;
+
+/// The id of the `number`th transfer created from the recurring transfer or allowance keyed by
+/// `source_key`: a version 8 uuid hashed from the kind of source, its key and the number, so no
+/// two sources share an id and no sender can claim one first
+pub fn derived_transfer_id(source: &str, source_key: &[u8; 16], number: u32) -> String {
+    let hash = Sha256::new()
+        .chain_update(source.as_bytes())
+        .chain_update(source_key)
+        .chain_update(number.to_be_bytes())
+        .finalize();
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&hash[..16]);
+    bytes[6] = (bytes[6] & 0x0f) | ((DERIVED_TRANSFER_ID_VERSION as u8) << 4);
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    Uuid::from_bytes(bytes).to_string()
+}
+
+/// Transfers were once keyed by the bytes of their id string, some may still be until they
+/// are migrated
+pub fn is_legacy_key(key: &[u8]) -> bool {
+    key.len() != 16
+}
+
+/// Loads an open or completed transfer, falling back to its legacy key
+pub fn load_transfer(storage: &dyn Storage, id: &str) -> StdResult<Transfer> {
+    let key = transfer_key(id)?;
+    match TRANSFER_STORAGE.may_load(storage, &key)? {
+        Some(StoredTransfer(transfer)) => Ok(transfer),
+        None => match TRANSFER_STORAGE.may_load(storage, id.as_bytes())? {
+            Some(StoredTransfer(transfer)) => Ok(transfer),
+            None => load_completed_transfer(storage, &key),
+        },
+    }
+}
+
+pub fn may_load_transfer(storage: &dyn Storage, id: &str) -> StdResult<Option<Transfer>> {
+    match may_load_transfer_by_key(storage, &transfer_key(id)?)? {
+        Some(transfer) => Ok(Some(transfer)),
+        None => Ok(TRANSFER_STORAGE
+            .may_load(storage, id.as_bytes())?
+            .map(|stored| stored.0)),
+    }
+}
+
+fn load_transfer_by_key(storage: &dyn Storage, key: &[u8]) -> StdResult<Transfer> {
+    match TRANSFER_STORAGE.may_load(storage, key)? {
+        Some(StoredTransfer(transfer)) => Ok(transfer),
+        None => load_completed_transfer(storage, key),
+    }
+}
+
+fn may_load_transfer_by_key(storage: &dyn Storage, key: &[u8]) -> StdResult<Option<Transfer>> {
+    match TRANSFER_STORAGE.may_load(storage, key)? {
+        Some(StoredTransfer(transfer)) => Ok(Some(transfer)),
+        None => match TRANSFER_HISTORY.may_load(storage, key)? {
+            Some(StoredTransfer(transfer)) => Ok(Some(transfer)),
+            None => Ok(TRANSFER_ARCHIVE
+                .may_load(storage, key)?
+                .map(|stored| stored.0)),
+        },
+    }
+}
+
+/// Loads a completed transfer from the history, falling back to the archive
+fn load_completed_transfer(storage: &dyn Storage, key: &[u8]) -> StdResult<Transfer> {
+    match TRANSFER_HISTORY.may_load(storage, key)? {
+        Some(StoredTransfer(transfer)) => Ok(transfer),
+        None => TRANSFER_ARCHIVE.load(storage, key).map(|stored| stored.0),
+    }
+}
+
+/// Stores the transfer, moving it between the status counts and its sender's pending count when
+/// its status changed and updating the volume of its denoms by the change in escrowed and
+/// settled coins. A new transfer is also added to the creation indexes, and a pending one kept
+/// in the pending queue. The denom counters change at `height`.
+pub fn save_transfer(storage: &mut dyn Storage, height: u64, transfer: &Transfer) -> StdResult<()> {
+    let key = transfer_key(&transfer.id)?;
+    // a transfer still under its legacy key is moved before it changes
+    let legacy_key = transfer.id.as_bytes();
+    if let Some(StoredTransfer(legacy)) = TRANSFER_STORAGE.may_load(storage, legacy_key)? {
+        migrate_legacy_transfer(storage, legacy_key, &legacy)?;
+    }
+    let previous = may_load_transfer_by_key(storage, &key)?;
+
+    if let (None, Some(created)) = (&previous, &transfer.created) {
+        TRANSFER_CREATED_HEIGHT.save(storage, (created.height, &key), &Empty {})?;
+        TRANSFER_CREATED_TIME.save(storage, (created.time.nanos(), &key), &Empty {})?;
+    }
+    if previous.is_none() {
+        for tag in &transfer.tags {
+            TRANSFER_TAGS.save(storage, (tag, &key), &Empty {})?;
+        }
+    }
+
+    // the queue entry moves when the priority changes and goes once the transfer is decided
+    let queued = |transfer: &Transfer| match transfer.status {
+        TransferStatus::Pending => transfer.queue_entry(),
+        _ => None,
+    };
+    if let Some(previous) = &previous {
+        if let Some((rank, position)) = queued(previous) {
+            PENDING_QUEUE.remove(storage, (rank, position, &key));
+            PENDING_QUEUE_BY_DENOM.remove(storage, (&previous.denom, (rank, position, &key)));
+        }
+    }
+    if let Some((rank, position)) = queued(transfer) {
+        PENDING_QUEUE.save(storage, (rank, position, &key), &Empty {})?;
+        PENDING_QUEUE_BY_DENOM.save(
+            storage,
+            (&transfer.denom, (rank, position, &key)),
+            &Empty {},
+        )?;
+    }
+
+    let previous_status = previous.as_ref().map(|previous| previous.status);
+    if previous_status != Some(transfer.status) {
+        let mut stats = TRANSFER_STATS.may_load(storage)?.unwrap_or_default();
+        match previous_status {
+            Some(previous) => stats.record_transition(previous, transfer.status),
+            None => stats.record(transfer.status),
+        }
+        TRANSFER_STATS.save(storage, &stats)?;
+
+        let mut totals = TRANSFER_TOTALS.may_load(storage)?.unwrap_or_default();
+        totals.record(previous_status, transfer.status);
+        TRANSFER_TOTALS.save(storage, &totals)?;
+    }
+
+    update_denom_volumes(storage, previous.as_ref(), Some(transfer))?;
+    update_denom_counters(storage, height, previous.as_ref(), Some(transfer))?;
+
+    // the sender's pending count moves as the transfer enters or leaves pending
+    let was_pending = previous_status == Some(TransferStatus::Pending);
+    if was_pending != (transfer.status == TransferStatus::Pending) {
+        let pending = match was_pending {
+            true => count_pending_by_sender(storage, &transfer.sender)?.saturating_sub(1),
+            false => count_pending_by_sender(storage, &transfer.sender)? + 1,
+        };
+        match pending {
+            0 => PENDING_BY_SENDER.remove(storage, &transfer.sender),
+            pending => PENDING_BY_SENDER.save(storage, &transfer.sender, &pending)?,
+        }
+    }
+
+    // a settled transfer whose settlement failed is taken back out of the history
+    if let Some(previous) = previous.filter(|previous| !previous.status.is_open()) {
+        TRANSFER_HISTORY.remove(storage, &key)?;
+        let completed = previous.updated_at.map_or(0, |time| time.nanos());
+        TRANSFER_COMPLETED_TIME.remove(storage, (completed, &key));
+    }
+
+    if transfer.status.is_open() {
+        return TRANSFER_STORAGE.save(storage, &key, &StoredTransfer(transfer.clone()));
+    }
+    archive_transfer(storage, &key, transfer)
+}
+
+/// Moves a completed transfer from the open transfers into the history
+pub fn archive_transfer(
+    storage: &mut dyn Storage,
+    key: &[u8],
+    transfer: &Transfer,
+) -> StdResult<()> {
+    TRANSFER_STORAGE.remove(storage, key)?;
+    TRANSFER_HISTORY.save(storage, key, &StoredTransfer(transfer.clone()))?;
+    // transfers completed before update times were recorded sort first and are pruned first
+    let completed = transfer.updated_at.map_or(0, |time| time.nanos());
+    TRANSFER_COMPLETED_TIME.save(storage, (completed, key), &Empty {})
+}
+
+/// Moves a transfer from its legacy key to the 16 bytes of its uuid along with its creation
+/// index entries, archiving it when it has completed
+fn migrate_legacy_transfer(
+    storage: &mut dyn Storage,
+    legacy_key: &[u8],
+    transfer: &Transfer,
+) -> StdResult<()> {
+    let key = transfer_key(&transfer.id)?;
+    TRANSFER_STORAGE.remove(storage, legacy_key)?;
+    if let Some(created) = &transfer.created {
+        for (index, value) in [
+            (&TRANSFER_CREATED_HEIGHT, created.height),
+            (&TRANSFER_CREATED_TIME, created.time.nanos()),
+        ] {
+            index.remove(storage, (value, legacy_key));
+            index.save(storage, (value, &key), &Empty {})?;
+        }
+    }
+
+    if transfer.status.is_open() {
+        return TRANSFER_STORAGE.save(storage, &key, &StoredTransfer(transfer.clone()));
+    }
+    archive_transfer(storage, &key, transfer)
+}
+
+/// Looks at the next `limit` open transfers after the migration cursor and moves those still
+/// stored under their legacy key. Returns how many were moved and whether the batch reached the
+/// last open transfer, after which the next batch starts over from the first.
+pub fn migrate_legacy_transfers(storage: &mut dyn Storage, limit: usize) -> StdResult<(u32, bool)> {
+    let cursor = LEGACY_MIGRATION_CURSOR.may_load(storage)?;
+    let transfers = TRANSFER_STORAGE
+        .range(
+            storage,
+            cursor.as_deref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let complete = transfers.len() < limit;
+    match transfers.last() {
+        Some((key, _)) if !complete => LEGACY_MIGRATION_CURSOR.save(storage, key)?,
+        _ => LEGACY_MIGRATION_CURSOR.remove(storage),
+    }
+
+    let mut migrated = 0;
+    for (key, StoredTransfer(transfer)) in &transfers {
+        if is_legacy_key(key) {
+            migrate_legacy_transfer(storage, key, transfer)?;
+            migrated += 1;
+        }
+    }
+    Ok((migrated, complete))
+}
+
+/// Removes up to `limit` transfers completed before `older_than` from the history, along with
+/// their index entries, and returns how many were removed. Transfers with failed settlements are
+/// kept, and status counts and denom volumes are left as they are.
+pub fn prune_history(
+    storage: &mut dyn Storage,
+    older_than: Timestamp,
+    limit: usize,
+) -> StdResult<u32> {
+    let max = Bound::exclusive((older_than.nanos(), &[] as &[u8]));
+    let keys = TRANSFER_COMPLETED_TIME
+        .keys(storage, None, Some(max), Order::Ascending)
+        // the coins of failed settlements are still held for the transfer until they are retried
+        .filter(|item| {
+            item.as_ref()
+                .map_or(true, |(_, key)| !has_failed_settlements(storage, key))
+        })
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (_, key) in &keys {
+        let StoredTransfer(transfer) = TRANSFER_HISTORY.load(storage, key)?;
+        remove_from_history(storage, key, &transfer)?;
+    }
+
+    Ok(keys.len() as u32)
+}
+
+/// Moves a completed transfer from the history into the archive, dropping its index entries.
+/// Returns false when the transfer is not in the history.
+pub fn move_to_archive(storage: &mut dyn Storage, key: &[u8]) -> StdResult<bool> {
+    let transfer = match TRANSFER_HISTORY.may_load(storage, key)? {
+        Some(StoredTransfer(transfer)) => transfer,
+        None => return Ok(false),
+    };
+    remove_from_history(storage, key, &transfer)?;
+    TRANSFER_ARCHIVE.save(storage, key, &StoredTransfer(transfer))?;
+    Ok(true)
+}
+
+/// Removes a transfer from the history along with its index entries
+fn remove_from_history(
+    storage: &mut dyn Storage,
+    key: &[u8],
+    transfer: &Transfer,
+) -> StdResult<()> {
+    if let Some(created) = &transfer.created {
+        TRANSFER_CREATED_HEIGHT.remove(storage, (created.height, key));
+        TRANSFER_CREATED_TIME.remove(storage, (created.time.nanos(), key));
+    }
+    for tag in &transfer.tags {
+        TRANSFER_TAGS.remove(storage, (tag, key));
+    }
+    TRANSFER_HISTORY.remove(storage, key)?;
+    let completed = transfer.updated_at.map_or(0, |time| time.nanos());
+    TRANSFER_COMPLETED_TIME.remove(storage, (completed, key));
+    Ok(())
+}
+
+/// Replaces the previous escrowed and settled coins of a transfer with the next ones in the
+/// volume of each denom
+pub fn update_denom_volumes(
+    storage: &mut dyn Storage,
+    previous: Option<&Transfer>,
+    next: Option<&Transfer>,
+) -> StdResult<()> {
+    let mut volumes: BTreeMap<String, DenomVolume> = BTreeMap::new();
+
+    // additions are applied before removals so the totals never dip below zero
+    for (transfer, add) in [(next, true), (previous, false)] {
+        let transfer = match transfer {
+            Some(transfer) => transfer,
+            None => continue,
+        };
+        let changes = transfer
+            .escrowed_coins()
+            .into_iter()
+            .map(|coin| (coin, true))
+            .chain(
+                transfer
+                    .settled_coins()
+                    .into_iter()
+                    .map(|coin| (coin, false)),
+            );
+
+        for (coin, escrowed) in changes {
+            if coin.amount.is_zero() {
+                continue;
+            }
+            if !volumes.contains_key(&coin.denom) {
+                let volume = DENOM_VOLUME
+                    .may_load(storage, &coin.denom)?
+                    .unwrap_or_default();
+                volumes.insert(coin.denom.to_owned(), volume);
+            }
+            let volume = volumes.get_mut(&coin.denom).unwrap();
+            let total = match escrowed {
+                true => &mut volume.escrowed,
+                false => &mut volume.settled,
+            };
+            *total = match add {
+                true => total.checked_add(coin.amount)?,
+                false => total.saturating_sub(coin.amount),
+            };
+        }
+    }
+
+    for (denom, volume) in volumes {
+        DENOM_VOLUME.save(storage, &denom, &volume)?;
+    }
+    Ok(())
+}
+
+/// Moves the pending count and escrowed total of each denom of the transfer at `height` from
+/// what its previous version held to what the next one does
+pub fn update_denom_counters(
+    storage: &mut dyn Storage,
+    height: u64,
+    previous: Option<&Transfer>,
+    next: Option<&Transfer>,
+) -> StdResult<()> {
+    // the stored counters of each denom alongside the ones being changed
+    let mut counters: BTreeMap<String, (DenomCounters, DenomCounters)> = BTreeMap::new();
+
+    // additions are applied before removals so the counters never dip below zero
+    for (transfer, add) in [(next, true), (previous, false)] {
+        let transfer = match transfer {
+            Some(transfer) => transfer,
+            None => continue,
+        };
+        let pending = transfer.status == TransferStatus::Pending;
+
+        for coin in transfer.escrowed_coins() {
+            if coin.amount.is_zero() {
+                continue;
+            }
+            if !counters.contains_key(&coin.denom) {
+                let counter = DENOM_COUNTERS
+                    .may_load(storage, &coin.denom)?
+                    .unwrap_or_default();
+                counters.insert(coin.denom.to_owned(), (counter.clone(), counter));
+            }
+            let (_, counter) = counters.get_mut(&coin.denom).unwrap();
+            match add {
+                true => {
+                    counter.escrowed = counter.escrowed.checked_add(coin.amount)?;
+                    counter.pending += u64::from(pending);
+                }
+                false => {
+                    counter.escrowed = counter.escrowed.saturating_sub(coin.amount);
+                    counter.pending = counter.pending.saturating_sub(u64::from(pending));
+                }
+            }
+        }
+    }
+
+    for (denom, (stored, counter)) in counters {
+        // unchanged counters are not saved, so a block only checkpoints the denoms it moved
+        if stored != counter {
+            DENOM_COUNTERS.save(storage, &denom, &counter, height)?;
+        }
+    }
+    Ok(())
+}
+
+/// Transfers returned by a paged query when no limit is given
+pub const DEFAULT_PAGE_LIMIT: u32 = 30;
+
+/// Most transfers a paged query returns when an operator has not set a limit
+pub const MAX_PAGE_LIMIT: u32 = 100;
+
+/// Most transfers a paged query returns, set by an operator
+pub const PAGE_LIMIT: Item<u32> = Item::new("page_limit");
+
+/// Seconds a completed transfer is kept in the history before anyone may purge it, set by an
+/// operator. Completed transfers are kept until pruned by an operator when unset.
+pub const RETENTION_PERIOD: Item<u64> = Item::new("retention_period");
+
+/// Sequence number last assigned to a transfer of each sender
+pub const SENDER_SEQUENCE: Map<&Addr, u64> = Map::new("sender_sequence");
+
+/// Assigns the sender's next sequence number, starting from 1
+pub fn next_sequence(storage: &mut dyn Storage, sender: &Addr) -> StdResult<u64> {
+    let sequence = SENDER_SEQUENCE
+        .may_load(storage, sender)?
+        .unwrap_or_default()
+        + 1;
+    SENDER_SEQUENCE.save(storage, sender, &sequence)?;
+    Ok(sequence)
+}
+
+/// Position last assigned to a transfer of any sender
+pub const TRANSFER_POSITION: Item<u64> = Item::new("transfer_position");
+
+/// Assigns the next position in creation order, starting from 1
+pub fn next_position(storage: &mut dyn Storage) -> StdResult<u64> {
+    let position = TRANSFER_POSITION.may_load(storage)?.unwrap_or_default() + 1;
+    TRANSFER_POSITION.save(storage, &position)?;
+    Ok(position)
+}
+
+/// A state-changing action recorded in the audit log. `id` is the transfer, swap or
+/// recurring transfer acted on, unset for actions spanning several or none.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuditEntry {
+    pub sequence: u64,
+    pub action: String,
+    pub actor: Addr,
+    pub id: Option<String>,
+    pub height: u64,
+    pub time: Timestamp,
+}
+
+/// Every state-changing action by its sequence number. Entries are only ever appended.
+pub const AUDIT_LOG: Map<u64, AuditEntry> = Map::new("audit_log");
+
+/// Sequence number of the last audit entry
+pub const AUDIT_SEQUENCE: Item<u64> = Item::new("audit_sequence");
+
+/// Appends an entry for the action taken by the actor at the block, numbering entries from 1
+pub fn append_audit_entry(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    action: String,
+    actor: &Addr,
+    id: Option<String>,
+) -> StdResult<u64> {
+    let sequence = AUDIT_SEQUENCE.may_load(storage)?.unwrap_or_default() + 1;
+    AUDIT_SEQUENCE.save(storage, &sequence)?;
+    AUDIT_LOG.save(
+        storage,
+        sequence,
+        &AuditEntry {
+            sequence,
+            action,
+            actor: actor.to_owned(),
+            id,
+            height: block.height,
+            time: block.time,
+        },
+    )?;
+    Ok(sequence)
+}
+
+/// Returns up to `limit` audit entries following the `start_after` sequence number, oldest first
+pub fn get_audit_log(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: usize,
+) -> StdResult<Vec<AuditEntry>> {
+    AUDIT_LOG
+        .range(
+            storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| item.map(|(_, entry)| entry))
+        .collect()
+}
+
+/// Returns up to `limit` of the sender's templates in id order, following `start_after`
+pub fn get_templates(
+    storage: &dyn Storage,
+    sender: &Addr,
+    start_after: Option<&str>,
+    limit: usize,
+) -> StdResult<Vec<TransferTemplate>> {
+    TRANSFER_TEMPLATES
+        .prefix(sender)
+        .range(
+            storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| item.map(|(_, template)| template))
+        .collect()
+}
+
+/// Largest amount of any denom a single transfer may move, set by the compliance role as a
+/// backstop to the limits of each denom. Unlimited when unset
+pub const MAX_TRANSFER_AMOUNT: Item<Uint128> = Item::new("max_transfer_amount");
+
+/// Largest fee rate in basis points, charging the whole amount
+pub const MAX_FEE_BPS: u16 = 10_000;
+
+/// Fee deducted from every transfer as it settles, in basis points of the amount paid out, and
+/// where it is paid
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SettlementFee {
+    pub bps: u16,
+    pub destination: FeeDestination,
+}
+
+/// Where fees are paid
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeDestination {
+    /// A fixed collector address
+    Collector { address: Addr },
+    /// The address holding admin permission on the marker of the denom charged, so issuers are
+    /// paid for the transfers of their own markers
+    MarkerAdmin {},
+    /// Kept by the contract and recorded in `ACCRUED_FEES` until the fee manager withdraws it
+    Accrue {},
+}
+
+/// Settlement fee set by the fee manager, transfers settle in full when unset
+pub const SETTLEMENT_FEE: Item<SettlementFee> = Item::new("settlement_fee");
+
+/// Settlement fees the contract kept, by denom, that the fee manager has yet to withdraw
+pub const ACCRUED_FEES: Map<&str, Uint128> = Map::new("accrued_fees");
+
+/// The settlement fee charged on transfers of a denom in place of the settlement fee's rate
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeSchedule {
+    /// A fixed amount of the denom per transfer, never more than the transfer's amount
+    Flat { amount: Uint128 },
+    /// Basis points of the amount transferred
+    Bps { bps: u16 },
+    /// Basis points of the tier with the highest minimum the amount reaches, in ascending order of
+    /// their minimums. Amounts below the first tier are not charged
+    Tiered { tiers: Vec<FeeTier> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeeTier {
+    pub min_amount: Uint128,
+    pub bps: u16,
+}
+
+impl FeeSchedule {
+    /// the fee charged on `amount` of the denom
+    pub fn fee(&self, amount: Uint128) -> Uint128 {
+        let bps = match self {
+            FeeSchedule::Flat { amount: fee } => return std::cmp::min(*fee, amount),
+            FeeSchedule::Bps { bps } => *bps,
+            FeeSchedule::Tiered { tiers } => tiers
+                .iter()
+                .take_while(|tier| tier.min_amount <= amount)
+                .last()
+                .map(|tier| tier.bps)
+                .unwrap_or_default(),
+        };
+        amount.multiply_ratio(bps, MAX_FEE_BPS)
+    }
+}
+
+/// Fee schedules of denoms charged other than at the settlement fee's rate
+pub const DENOM_FEE_SCHEDULES: Map<&str, FeeSchedule> = Map::new("denom_fee_schedules");
+
+/// Denom of the fees levied through the msg-fees module until the fee manager sets another, and
+/// always of the incentives paid to approvers
+pub const DEFAULT_FEE_DENOM: &str = "nhash";
+
+/// Fee denom set by the fee manager, an unrestricted coin the contract can hold and forward
+pub const FEE_DENOM: Item<String> = Item::new("fee_denom");
+
+/// The denom msg fees are currently collected in
+pub fn fee_denom(storage: &dyn Storage) -> StdResult<String> {
+    Ok(FEE_DENOM
+        .may_load(storage)?
+        .unwrap_or_else(|| DEFAULT_FEE_DENOM.to_owned()))
+}
+
+/// Fees levied through the Provenance msg-fees module on the transactions creating and approving
+/// transfers, in the fee denom. The recipient receives `recipient_bps` basis points of each,
+/// the rest going to the module's fee collection.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MsgFees {
+    pub creation_fee: Option<Uint128>,
+    pub approval_fee: Option<Uint128>,
+    pub recipient: Option<Addr>,
+    pub recipient_bps: u16,
+}
+
+/// Msg fees set by the fee manager, transactions pay only their usual fees when unset
+pub const MSG_FEES: Item<MsgFees> = Item::new("msg_fees");
+
+/// Addresses the admin exempted from the contract's fees, such as an issuer's treasury. Transfers
+/// they send settle without a settlement fee and the transactions they sign are levied no msg fees.
+pub const FEE_EXEMPTIONS: Map<&Addr, Empty> = Map::new("fee_exemptions");
+
+/// Deposit attached to each transfer created, returned to the sender once the transfer settles or
+/// the sender cancels it, and forfeited to the collector when it is rejected or left to expire
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CreationDeposit {
+    pub amount: Coin,
+    pub collector: Addr,
+}
+
+/// Deposit set by the fee manager, transfers are created without one when unset
+pub const CREATION_DEPOSIT: Item<CreationDeposit> = Item::new("creation_deposit");
+
+/// Deposits held for open transfers on the terms they were made, keyed by `transfer_key`
+pub const TRANSFER_DEPOSITS: Map<&[u8], CreationDeposit> = Map::new("transfer_deposits");
+
+/// Incentives senders attached in nhash for the approver settling their transfer, keyed
+/// by `transfer_key` until it is paid or refunded
+pub const TRANSFER_INCENTIVES: Map<&[u8], Coin> = Map::new("transfer_incentives");
+
+/// Most transfers a sender may have pending at once, unlimited when unset
+pub const PENDING_LIMIT: Item<u32> = Item::new("pending_limit");
+
+/// Transfers each sender has pending, kept up to date by `save_transfer` so the pending limit is
+/// checked without reading the sender's transfers
+pub const PENDING_BY_SENDER: Map<&Addr, u32> = Map::new("pending_by_sender");
+
+/// Most transfers each sender may create within any `blocks` blocks
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CreationRateLimit {
+    pub max_creations: u32,
+    pub blocks: u64,
+}
+
+/// Creation rate limit of every sender, unlimited when unset
+pub const CREATION_RATE_LIMIT: Item<CreationRateLimit> = Item::new("creation_rate_limit");
+
+/// Transfers each sender created, by the block height they were created at. Only kept while a
+/// creation rate limit is set, and only for as long as its window.
+pub const SENDER_CREATIONS: Map<(&Addr, u64), u32> = Map::new("sender_creations");
+
+/// Returns how many transfers the sender created after the block height `since`
+pub fn creations_since(storage: &dyn Storage, sender: &Addr, since: u64) -> StdResult<u32> {
+    SENDER_CREATIONS
+        .prefix(sender)
+        .range(
+            storage,
+            Some(Bound::exclusive(since)),
+            None,
+            Order::Ascending,
+        )
+        .try_fold(0, |created, item| Ok(created + item?.1))
+}
+
+/// Counts a transfer created by the sender at the block height, first dropping the creations
+/// before the window of the limit
+pub fn record_creation(
+    storage: &mut dyn Storage,
+    sender: &Addr,
+    height: u64,
+    blocks: u64,
+) -> StdResult<()> {
+    let expired = SENDER_CREATIONS
+        .prefix(sender)
+        .keys(
+            storage,
+            None,
+            Some(Bound::inclusive(height.saturating_sub(blocks))),
+            Order::Ascending,
+        )
+        .collect::<StdResult<Vec<u64>>>()?;
+    for created in expired {
+        SENDER_CREATIONS.remove(storage, (sender, created));
+    }
+
+    SENDER_CREATIONS.update(storage, (sender, height), |created| -> StdResult<_> {
+        Ok(created.unwrap_or_default() + 1)
+    })?;
+    Ok(())
+}
+
+/// Most of a denom each sender may transfer within any `window` seconds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VelocityLimit {
+    pub amount: Uint128,
+    pub window: u64,
+}
+
+/// Velocity limits by denom, set by the compliance role or the marker's admin
+pub const VELOCITY_LIMITS: Map<&str, VelocityLimit> = Map::new("velocity_limit");
+
+/// Amounts of each denom a sender transferred, by the block time in seconds they were transferred
+/// at. Only kept for denoms with a velocity limit, and only for as long as the limit's window.
+pub const SENDER_VELOCITY: Map<(&Addr, &str, u64), Uint128> = Map::new("sender_velocity");
+
+/// Returns the amount of the denom the sender transferred after `since`, in seconds
+pub fn velocity_used(
+    storage: &dyn Storage,
+    sender: &Addr,
+    denom: &str,
+    since: u64,
+) -> StdResult<Uint128> {
+    SENDER_VELOCITY
+        .prefix((sender, denom))
+        .range(
+            storage,
+            Some(Bound::exclusive(since)),
+            None,
+            Order::Ascending,
+        )
+        .try_fold(Uint128::zero(), |used, item| Ok(used + item?.1))
+}
+
+/// Adds the amount to what the sender transferred of the denom at `now`, in seconds, first
+/// dropping the amounts transferred before the window of the limit
+pub fn record_velocity(
+    storage: &mut dyn Storage,
+    sender: &Addr,
+    denom: &str,
+    now: u64,
+    window: u64,
+    amount: Uint128,
+) -> StdResult<()> {
+    let expired = SENDER_VELOCITY
+        .prefix((sender, denom))
+        .keys(
+            storage,
+            None,
+            Some(Bound::inclusive(now.saturating_sub(window))),
+            Order::Ascending,
+        )
+        .collect::<StdResult<Vec<u64>>>()?;
+    for time in expired {
+        SENDER_VELOCITY.remove(storage, (sender, denom, time));
+    }
+
+    SENDER_VELOCITY.update(storage, (sender, denom, now), |used| -> StdResult<_> {
+        Ok(used.unwrap_or_default() + amount)
+    })?;
+    Ok(())
+}
+
+/// Returns how many transfers the sender has pending
+pub fn count_pending_by_sender(storage: &dyn Storage, sender: &Addr) -> StdResult<u32> {
+    Ok(PENDING_BY_SENDER
+        .may_load(storage, sender)?
+        .unwrap_or_default())
+}
+
+/// Direction a paged query reads transfers in
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+impl From<SortOrder> for Order {
+    fn from(order: SortOrder) -> Order {
+        match order {
+            SortOrder::Ascending => Order::Ascending,
+            SortOrder::Descending => Order::Descending,
+        }
+    }
+}
+
+type IdBound<'a> = Bound<'a, &'a [u8]>;
+
+type QueueBound<'a> = Bound<'a, QueueKey<'a>>;
+
+/// Where a paged query starts, how many transfers it returns and in which direction it reads.
+/// Pages read in descending order start before `start_after` rather than after it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PageRequest {
+    pub start_after: Option<String>,
+    pub limit: Option<u32>,
+    pub order: Option<SortOrder>,
+}
+
+impl PageRequest {
+    /// returns the number of transfers to return, capped at the configured limit
+    pub fn limit(&self, storage: &dyn Storage) -> StdResult<usize> {
+        let max = PAGE_LIMIT.may_load(storage)?.unwrap_or(MAX_PAGE_LIMIT);
+        let limit = self.limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(max);
+        Ok(limit as usize)
+    }
+
+    /// returns the range bounds of a page of transfers keyed by id
+    pub fn bounds(&self) -> StdResult<(Option<IdBound<'static>>, Option<IdBound<'static>>)> {
+        let start = match &self.start_after {
+            Some(id) => Some(Bound::ExclusiveRaw(transfer_key(id)?.to_vec())),
+            None => None,
+        };
+        Ok(match self.order() {
+            Order::Ascending => (start, None),
+            Order::Descending => (None, start),
+        })
+    }
+
+    pub fn order(&self) -> Order {
+        self.order.unwrap_or(SortOrder::Ascending).into()
+    }
+}
+
+/// Returns a page of transfers in id order
+pub fn get_all_transfers(storage: &dyn Storage, page: PageRequest) -> StdResult<Vec<Transfer>> {
+    let limit = page.limit(storage)?;
+    let (min, max) = page.bounds()?;
+
+    merge_pages(
+        TRANSFER_STORAGE.range(storage, min.clone(), max.clone(), page.order()),
+        TRANSFER_HISTORY.range(storage, min, max, page.order()),
+        page.order(),
+        limit,
+    )
+}
+
+/// Returns a page of the transfers sent by the address in id order
+pub fn get_transfers_by_sender(
+    storage: &dyn Storage,
+    sender: &Addr,
+    page: PageRequest,
+) -> StdResult<Vec<Transfer>> {
+    let limit = page.limit(storage)?;
+    let (min, max) = page.bounds()?;
+
+    merge_pages(
+        TRANSFER_STORAGE.idx.sender.prefix(sender.to_owned()).range(
+            storage,
+            min.clone(),
+            max.clone(),
+            page.order(),
+        ),
+        TRANSFER_HISTORY.idx.sender.prefix(sender.to_owned()).range(
+            storage,
+            min,
+            max,
+            page.order(),
+        ),
+        page.order(),
+        limit,
+    )
+}
+
+/// Returns a page of the transfers carrying the tag in id order
+pub fn get_transfers_by_tag(
+    storage: &dyn Storage,
+    tag: &str,
+    page: PageRequest,
+) -> StdResult<Vec<Transfer>> {
+    let limit = page.limit(storage)?;
+    let (min, max) = page.bounds()?;
+
+    TRANSFER_TAGS
+        .prefix(tag)
+        .keys(storage, min, max, page.order())
+        .take(limit)
+        .map(|key| load_transfer_by_key(storage, &key?))
+        .collect()
+}
+
+/// Returns a page of the archived transfers in id order
+pub fn get_archived_transfers(
+    storage: &dyn Storage,
+    page: PageRequest,
+) -> StdResult<Vec<Transfer>> {
+    let limit = page.limit(storage)?;
+    let (min, max) = page.bounds()?;
+
+    TRANSFER_ARCHIVE
+        .range(storage, min, max, page.order())
+        .take(limit)
+        .map(|item| item.map(|(_, StoredTransfer(transfer))| transfer))
+        .collect()
+}
+
+/// Returns a page of the pending transfers `filter` accepts in queue order. A page continues
+/// after the position `start_after` held in the queue, whether or not it is still pending.
+pub fn get_pending_queue(
+    storage: &dyn Storage,
+    page: PageRequest,
+    mut filter: impl FnMut(&Transfer) -> StdResult<bool>,
+) -> StdResult<Vec<Transfer>> {
+    let limit = page.limit(storage)?;
+    let (min, max) = pending_queue_bounds(storage, &page)?;
+
+    let mut transfers = vec![];
+    for item in PENDING_QUEUE.keys(storage, min, max, page.order()) {
+        if transfers.len() >= limit {
+            break;
+        }
+
+        let (_, _, key) = item?;
+        let transfer = load_transfer_by_key(storage, &key)?;
+        if filter(&transfer)? {
+            transfers.push(transfer);
+        }
+    }
+
+    Ok(transfers)
+}
+
+/// Returns a page of the pending transfers of the denom in queue order, continuing as
+/// `get_pending_queue` does
+pub fn get_pending_by_denom(
+    storage: &dyn Storage,
+    denom: &str,
+    page: PageRequest,
+) -> StdResult<Vec<Transfer>> {
+    let limit = page.limit(storage)?;
+    let (min, max) = pending_queue_bounds(storage, &page)?;
+
+    PENDING_QUEUE_BY_DENOM
+        .prefix(denom)
+        .keys(storage, min, max, page.order())
+        .take(limit)
+        .map(|item| load_transfer_by_key(storage, &item?.2))
+        .collect()
+}
+
+/// the bounds of a page of a pending queue, past the queue entry of `start_after`
+fn pending_queue_bounds(
+    storage: &dyn Storage,
+    page: &PageRequest,
+) -> StdResult<(Option<QueueBound<'static>>, Option<QueueBound<'static>>)> {
+    let start = match &page.start_after {
+        Some(id) => {
+            let (rank, position) = load_transfer(storage, id)?
+                .queue_entry()
+                .ok_or_else(|| StdError::not_found("pending queue entry"))?;
+            let key = transfer_key(id)?;
+            Some(Bound::ExclusiveRaw(
+                (rank, position, key.as_slice()).joined_key(),
+            ))
+        }
+        None => None,
+    };
+    Ok(match page.order() {
+        Order::Ascending => (start, None),
+        Order::Descending => (None, start),
+    })
+}
+
+/// Returns a page of the transfers that reached a terminal status in id order, only those in
+/// the given status when one is set
+pub fn get_transfer_history(
+    storage: &dyn Storage,
+    page: PageRequest,
+    status: Option<TransferStatus>,
+) -> StdResult<Vec<Transfer>> {
+    let limit = page.limit(storage)?;
+    let (min, max) = page.bounds()?;
+
+    TRANSFER_HISTORY
+        .range(storage, min, max, page.order())
+        .filter(|pair| {
+            pair.as_ref().map_or(true, |(_, StoredTransfer(transfer))| {
+                status.is_none_or(|status| status == transfer.status)
+            })
+        })
+        .take(limit)
+        .map(|pair| pair.map(|(_, StoredTransfer(transfer))| transfer))
+        .collect()
+}
+
+/// Returns a page of the transfers created within the range in creation order
+pub fn get_transfers_created(
+    storage: &dyn Storage,
+    range: &CreationRange,
+    page: PageRequest,
+) -> StdResult<Vec<Transfer>> {
+    let limit = page.limit(storage)?;
+    let (from, to) = range.bounds();
+
+    let start_after = match &page.start_after {
+        Some(id) => match load_transfer(storage, id)?.created {
+            Some(created) => Some((range.key(&created), transfer_key(id)?)),
+            None => None,
+        },
+        None => None,
+    };
+    let (min, max) = match (page.order(), &start_after) {
+        (Order::Ascending, Some((key, id))) if *key >= from => (
+            Bound::exclusive((*key, id as &[u8])),
+            Bound::exclusive((to, &[] as &[u8])),
+        ),
+        (Order::Descending, Some((key, id))) if *key < to => (
+            Bound::inclusive((from, &[] as &[u8])),
+            Bound::exclusive((*key, id as &[u8])),
+        ),
+        _ => (
+            Bound::inclusive((from, &[] as &[u8])),
+            Bound::exclusive((to, &[] as &[u8])),
+        ),
+    };
+
+    range
+        .index()
+        .keys(storage, Some(min), Some(max), page.order())
+        .take(limit)
+        .map(|key| {
+            let (_, id) = key?;
+            load_transfer_by_key(storage, &id)
+        })
+        .collect()
+}
+
+/// Returns the pending transfers of the denom from the sender to the recipient, up to a page
+pub fn find_pending_transfers(
+    storage: &dyn Storage,
+    sender: &Addr,
+    recipient: &Addr,
+    denom: &str,
+) -> StdResult<Vec<Transfer>> {
+    let limit = PageRequest {
+        limit: Some(u32::MAX),
+        ..PageRequest::default()
+    }
+    .limit(storage)?;
 
-pub fn get_all_transfers(storage: &dyn Storage) -> Vec<Transfer> {
     TRANSFER_STORAGE
-        .range(storage, None, None, cosmwasm_std::Order::Ascending)
-        .map(|pair| pair.unwrap().1)
+        .idx
+        .sender
+        .prefix(sender.to_owned())
+        .range(storage, None, None, Order::Ascending)
+        .filter(|pair| {
+            pair.as_ref().map_or(true, |(_, StoredTransfer(transfer))| {
+                transfer.status == TransferStatus::Pending
+                    && transfer.recipient == *recipient
+                    && transfer.denom == denom
+            })
+        })
+        .take(limit)
+        .map(|pair| pair.map(|(_, StoredTransfer(transfer))| transfer))
         .collect()
 }
+
+/// Combines pages read in the same order from the open transfers and the history. As each
+/// is read in order, the first `limit` of both together make up the page.
+fn merge_pages<'a>(
+    open: impl Iterator<Item = StdResult<(Vec<u8>, StoredTransfer)>> + 'a,
+    completed: impl Iterator<Item = StdResult<(Vec<u8>, StoredTransfer)>> + 'a,
+    order: Order,
+    limit: usize,
+) -> StdResult<Vec<Transfer>> {
+    let mut transfers = open
+        .take(limit)
+        .chain(completed.take(limit))
+        .collect::<StdResult<Vec<_>>>()?;
+    transfers.sort_by(|(a, _), (b, _)| match order {
+        Order::Ascending => a.cmp(b),
+        Order::Descending => b.cmp(a),
+    });
+    transfers.truncate(limit);
+    Ok(transfers
+        .into_iter()
+        .map(|(_, StoredTransfer(transfer))| transfer)
+        .collect())
+}
+
+/// Writes made on top of another storage and held back until they are committed, so an action
+/// that fails part way leaves the storage it ran against as it was. Reads see the held writes.
+pub struct StorageTransaction<'a> {
+    storage: &'a dyn Storage,
+    // the value set at each key written, or none where it was removed
+    writes: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+}
+
+impl<'a> StorageTransaction<'a> {
+    pub fn new(storage: &'a dyn Storage) -> Self {
+        StorageTransaction {
+            storage,
+            writes: BTreeMap::new(),
+        }
+    }
+
+    /// Gives up the storage read from, returning the writes to apply to it
+    pub fn into_writes(self) -> StorageWrites {
+        StorageWrites(self.writes)
+    }
+}
+
+/// The writes held by a finished `StorageTransaction`
+pub struct StorageWrites(BTreeMap<Vec<u8>, Option<Vec<u8>>>);
+
+impl StorageWrites {
+    /// Applies the writes to the storage the transaction read from
+    pub fn commit(self, storage: &mut dyn Storage) {
+        for (key, value) in self.0 {
+            match value {
+                Some(value) => storage.set(&key, &value),
+                None => storage.remove(&key),
+            }
+        }
+    }
+}
+
+impl<'a> Storage for StorageTransaction<'a> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        match self.writes.get(key) {
+            Some(value) => value.to_owned(),
+            None => self.storage.get(key),
+        }
+    }
+
+    fn range<'b>(
+        &'b self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Record> + 'b> {
+        let base = self.storage.range(start, end, order);
+        // an empty range, which the map of writes would panic on
+        if matches!((start, end), (Some(start), Some(end)) if start >= end) {
+            return base;
+        }
+        let bounds = (
+            start.map_or(ops::Bound::Unbounded, |start| {
+                ops::Bound::Included(start.to_vec())
+            }),
+            end.map_or(ops::Bound::Unbounded, |end| {
+                ops::Bound::Excluded(end.to_vec())
+            }),
+        );
+        let writes: Box<dyn Iterator<Item = HeldWrite>> = match order {
+            Order::Ascending => Box::new(self.writes.range(bounds)),
+            Order::Descending => Box::new(self.writes.range(bounds).rev()),
+        };
+        Box::new(MergedRange {
+            base: base.peekable(),
+            writes: writes.peekable(),
+            order,
+        })
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.writes.insert(key.to_vec(), Some(value.to_vec()));
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.writes.insert(key.to_vec(), None);
+    }
+}
+
+/// A key written in a transaction and the value set at it, none where it was removed
+type HeldWrite<'b> = (&'b Vec<u8>, &'b Option<Vec<u8>>);
+
+/// The records of a range of the underlying storage with the held writes of the same range
+/// laid over them, both read in the same order
+struct MergedRange<'b, B: Iterator<Item = Record>, W: Iterator<Item = HeldWrite<'b>>> {
+    base: Peekable<B>,
+    writes: Peekable<W>,
+    order: Order,
+}
+
+impl<'b, B, W> Iterator for MergedRange<'b, B, W>
+where
+    B: Iterator<Item = Record>,
+    W: Iterator<Item = HeldWrite<'b>>,
+{
+    type Item = Record;
+
+    fn next(&mut self) -> Option<Record> {
+        loop {
+            // the next key in range order, a written key replacing the stored one
+            let next = match (self.base.peek(), self.writes.peek()) {
+                (None, None) => return None,
+                (Some(_), None) => cmp::Ordering::Less,
+                (None, Some(_)) => cmp::Ordering::Greater,
+                (Some((stored, _)), Some((written, _))) => match self.order {
+                    Order::Ascending => stored.cmp(written),
+                    Order::Descending => written.cmp(&stored),
+                },
+            };
+            match next {
+                cmp::Ordering::Less => return self.base.next(),
+                cmp::Ordering::Equal => {
+                    self.base.next();
+                }
+                cmp::Ordering::Greater => {}
+            }
+            if let Some((key, Some(value))) = self.writes.next() {
+                return Some((key.to_owned(), value.to_owned()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::{from_slice, to_vec};
+
+    use super::*;
+
+    const TRANSFER_ID: &str = "56253028-12f5-4d2a-a691-ebdfd2a7b865";
+
+    fn test_transfer(id: &str, sender: &str) -> Transfer {
+        Transfer {
+            id: id.into(),
+            sender: Addr::unchecked(sender),
+            denom: "restricted_1".into(),
+            amount: Uint128::new(3),
+            recipient: Addr::unchecked("recipient"),
+            status: TransferStatus::Pending,
+            rejection_reason: None,
+            require_acceptance: false,
+            approvals: vec![],
+            required_approvers: vec![],
+            legs: vec![],
+            additional_coins: vec![],
+            vesting: None,
+            claimed: Uint128::zero(),
+            memo: None,
+            created: Some(Creation {
+                height: 10,
+                time: Timestamp::from_seconds(100),
+            }),
+            updated_at: None,
+            sequence: None,
+            tags: vec![],
+            position: None,
+            priority: false,
+            expires: None,
+        }
+    }
+
+    #[test]
+    fn save_transfer_stores_and_indexes() {
+        let mut storage = MockStorage::new();
+        let transfer = test_transfer(TRANSFER_ID, "sender");
+        save_transfer(&mut storage, 1, &transfer).unwrap();
+
+        assert_eq!(transfer, load_transfer(&storage, TRANSFER_ID).unwrap());
+        // the key is the uuid, so any case of the id finds the transfer
+        assert_eq!(
+            Some(transfer.clone()),
+            may_load_transfer(&storage, &TRANSFER_ID.to_uppercase()).unwrap()
+        );
+        assert!(TRANSFER_STORAGE.has(&storage, &transfer_key(TRANSFER_ID).unwrap()));
+        assert!(TRANSFER_CREATED_HEIGHT.has(&storage, (10, &transfer_key(TRANSFER_ID).unwrap())));
+        assert_eq!(
+            TransferStats {
+                pending: 1,
+                ..TransferStats::default()
+            },
+            TRANSFER_STATS.load(&storage).unwrap()
+        );
+        assert_eq!(
+            1,
+            count_pending_by_sender(&storage, &Addr::unchecked("sender")).unwrap()
+        );
+        assert_eq!(
+            DenomVolume {
+                escrowed: Uint128::new(3),
+                settled: Uint128::zero(),
+            },
+            DENOM_VOLUME.load(&storage, "restricted_1").unwrap()
+        );
+        assert_eq!(
+            vec![transfer],
+            find_pending_transfers(
+                &storage,
+                &Addr::unchecked("sender"),
+                &Addr::unchecked("recipient"),
+                "restricted_1"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn transfer_indexes_read_the_transfers_of_a_sender_recipient_and_denom() {
+        let mut storage = MockStorage::new();
+        let ids = [
+            "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61",
+            "1f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61",
+            "2f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61",
+        ];
+        save_transfer(&mut storage, 1, &test_transfer(ids[0], "sender")).unwrap();
+        save_transfer(
+            &mut storage,
+            1,
+            &Transfer {
+                recipient: Addr::unchecked("other_recipient"),
+                ..test_transfer(ids[1], "sender")
+            },
+        )
+        .unwrap();
+        save_transfer(
+            &mut storage,
+            1,
+            &Transfer {
+                denom: "restricted_2".into(),
+                ..test_transfer(ids[2], "other_sender")
+            },
+        )
+        .unwrap();
+
+        fn ids_of(
+            items: impl Iterator<Item = StdResult<(Vec<u8>, StoredTransfer)>>,
+        ) -> Vec<String> {
+            items
+                .map(|item| item.unwrap().1 .0.id)
+                .collect::<Vec<String>>()
+        }
+        let by_sender = |storage: &MockStorage, sender: &str| {
+            ids_of(
+                TRANSFER_STORAGE
+                    .idx
+                    .sender
+                    .prefix(Addr::unchecked(sender))
+                    .range(storage, None, None, Order::Ascending),
+            )
+        };
+        let by_recipient = |storage: &MockStorage, recipient: &str| {
+            ids_of(
+                TRANSFER_STORAGE
+                    .idx
+                    .recipient
+                    .prefix(Addr::unchecked(recipient))
+                    .range(storage, None, None, Order::Ascending),
+            )
+        };
+        let by_denom = |storage: &MockStorage, denom: &str| {
+            ids_of(TRANSFER_STORAGE.idx.denom.prefix(denom.to_string()).range(
+                storage,
+                None,
+                None,
+                Order::Ascending,
+            ))
+        };
+
+        assert_eq!(vec![ids[0], ids[1]], by_sender(&storage, "sender"));
+        assert_eq!(vec![ids[2]], by_sender(&storage, "other_sender"));
+        assert_eq!(vec![ids[0], ids[2]], by_recipient(&storage, "recipient"));
+        assert_eq!(vec![ids[1]], by_recipient(&storage, "other_recipient"));
+        assert_eq!(vec![ids[0], ids[1]], by_denom(&storage, "restricted_1"));
+        assert_eq!(vec![ids[2]], by_denom(&storage, "restricted_2"));
+
+        // a changed recipient moves the transfer between recipient rows
+        save_transfer(
+            &mut storage,
+            2,
+            &Transfer {
+                recipient: Addr::unchecked("other_recipient"),
+                ..test_transfer(ids[0], "sender")
+            },
+        )
+        .unwrap();
+        assert_eq!(vec![ids[2]], by_recipient(&storage, "recipient"));
+        assert_eq!(
+            vec![ids[0], ids[1]],
+            by_recipient(&storage, "other_recipient")
+        );
+
+        // the sender query reads the sender's rows
+        assert_eq!(
+            vec![ids[0], ids[1]],
+            get_transfers_by_sender(&storage, &Addr::unchecked("sender"), PageRequest::default())
+                .unwrap()
+                .into_iter()
+                .map(|transfer| transfer.id)
+                .collect::<Vec<String>>()
+        );
+    }
+
+    #[test]
+    fn storage_transaction_holds_writes_until_committed() {
+        let mut storage = MockStorage::new();
+        for key in [b"a", b"c", b"e"] {
+            storage.set(key, key);
+        }
+
+        let mut transaction = StorageTransaction::new(&storage);
+        transaction.set(b"b", b"written");
+        transaction.set(b"c", b"replaced");
+        transaction.remove(b"e");
+        assert_eq!(Some(b"replaced".to_vec()), transaction.get(b"c"));
+        assert_eq!(None, transaction.get(b"e"));
+
+        let keys = |order| {
+            transaction
+                .range(None, None, order)
+                .map(|(key, value)| (String::from_utf8(key).unwrap(), value))
+                .collect::<Vec<_>>()
+        };
+        let expected = vec![
+            (String::from("a"), b"a".to_vec()),
+            (String::from("b"), b"written".to_vec()),
+            (String::from("c"), b"replaced".to_vec()),
+        ];
+        assert_eq!(expected, keys(Order::Ascending));
+        assert_eq!(
+            expected.iter().rev().cloned().collect::<Vec<_>>(),
+            keys(Order::Descending)
+        );
+        assert_eq!(
+            vec![b"b".to_vec()],
+            transaction
+                .range(Some(b"b"), Some(b"c"), Order::Ascending)
+                .map(|(key, _)| key)
+                .collect::<Vec<_>>()
+        );
+
+        // nothing reaches the storage until the writes are committed
+        let writes = transaction.into_writes();
+        assert_eq!(Some(b"c".to_vec()), storage.get(b"c"));
+        writes.commit(&mut storage);
+        assert_eq!(Some(b"written".to_vec()), storage.get(b"b"));
+        assert_eq!(Some(b"replaced".to_vec()), storage.get(b"c"));
+        assert_eq!(None, storage.get(b"e"));
+    }
+
+    #[test]
+    fn save_transfer_archives_completed_transfers() {
+        let mut storage = MockStorage::new();
+        let mut transfer = test_transfer(TRANSFER_ID, "sender");
+        save_transfer(&mut storage, 1, &transfer).unwrap();
+
+        transfer.status = TransferStatus::Settled;
+        transfer.updated_at = Some(Timestamp::from_seconds(200));
+        save_transfer(&mut storage, 1, &transfer).unwrap();
+
+        let key = transfer_key(TRANSFER_ID).unwrap();
+        assert!(!TRANSFER_STORAGE.has(&storage, &key));
+        assert!(TRANSFER_HISTORY.has(&storage, &key));
+        assert_eq!(transfer, load_transfer(&storage, TRANSFER_ID).unwrap());
+        // a settled transfer no longer counts against its sender's pending limit
+        assert_eq!(
+            0,
+            count_pending_by_sender(&storage, &Addr::unchecked("sender")).unwrap()
+        );
+        assert_eq!(
+            TransferStats {
+                settled: 1,
+                ..TransferStats::default()
+            },
+            TRANSFER_STATS.load(&storage).unwrap()
+        );
+        assert_eq!(
+            DenomVolume {
+                escrowed: Uint128::zero(),
+                settled: Uint128::new(3),
+            },
+            DENOM_VOLUME.load(&storage, "restricted_1").unwrap()
+        );
+
+        assert_eq!(
+            0,
+            prune_history(&mut storage, Timestamp::from_seconds(200), 10).unwrap()
+        );
+        assert_eq!(
+            1,
+            prune_history(&mut storage, Timestamp::from_seconds(201), 10).unwrap()
+        );
+        assert_eq!(None, may_load_transfer(&storage, TRANSFER_ID).unwrap());
+    }
+
+    #[test]
+    fn denom_counters_read_at_earlier_heights() {
+        let mut storage = MockStorage::new();
+        let mut transfer = test_transfer(TRANSFER_ID, "sender");
+        save_transfer(&mut storage, 5, &transfer).unwrap();
+
+        transfer.status = TransferStatus::Approved;
+        save_transfer(&mut storage, 6, &transfer).unwrap();
+
+        transfer.status = TransferStatus::Settled;
+        save_transfer(&mut storage, 8, &transfer).unwrap();
+
+        let counters_at = |height| {
+            DENOM_COUNTERS
+                .may_load_at_height(&storage, "restricted_1", height)
+                .unwrap()
+        };
+        let counters = |pending, escrowed| DenomCounters {
+            pending,
+            escrowed: Uint128::new(escrowed),
+        };
+
+        // a height reads the counters as they stood at the start of its block
+        assert_eq!(None, counters_at(5));
+        assert_eq!(Some(counters(1, 3)), counters_at(6));
+        assert_eq!(Some(counters(0, 3)), counters_at(7));
+        assert_eq!(Some(counters(0, 3)), counters_at(8));
+        assert_eq!(Some(counters(0, 0)), counters_at(9));
+    }
+
+    #[test]
+    fn load_transfer_rejects_invalid_ids() {
+        let storage = MockStorage::new();
+
+        assert!(matches!(
+            load_transfer(&storage, "not-a-uuid"),
+            Err(StdError::ParseErr { .. })
+        ));
+        assert!(matches!(
+            load_transfer(&storage, TRANSFER_ID),
+            Err(StdError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn paged_queries_read_open_and_completed_transfers() {
+        let mut storage = MockStorage::new();
+        let ids = [
+            "0f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61",
+            "1f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61",
+            "2f1b4bd6-3fa4-4f4b-8b6e-5d2b1c8c7e61",
+        ];
+        for (id, sender) in ids.iter().zip(["sender", "other_sender", "sender"]) {
+            save_transfer(&mut storage, 1, &test_transfer(id, sender)).unwrap();
+        }
+        let settled = Transfer {
+            status: TransferStatus::Settled,
+            ..test_transfer(ids[1], "other_sender")
+        };
+        save_transfer(&mut storage, 1, &settled).unwrap();
+
+        let page = |start_after: Option<&str>, limit| PageRequest {
+            start_after: start_after.map(String::from),
+            limit: Some(limit),
+            order: None,
+        };
+        let page_ids = |transfers: Vec<Transfer>| -> Vec<String> {
+            transfers.into_iter().map(|transfer| transfer.id).collect()
+        };
+
+        assert_eq!(
+            vec![ids[0], ids[1]],
+            page_ids(get_all_transfers(&storage, page(None, 2)).unwrap())
+        );
+        assert_eq!(
+            vec![ids[2]],
+            page_ids(get_all_transfers(&storage, page(Some(ids[1]), 2)).unwrap())
+        );
+        assert_eq!(
+            vec![ids[0], ids[2]],
+            page_ids(
+                get_transfers_by_sender(&storage, &Addr::unchecked("sender"), page(None, 10))
+                    .unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn stored_transfer_round_trips_as_an_array() {
+        let transfer = Transfer {
+            status: TransferStatus::Approved,
+            rejection_reason: Some("reason".into()),
+            require_acceptance: true,
+            approvals: vec![Approval {
+                approver: Addr::unchecked("approver"),
+                height: 11,
+                time: Timestamp::from_seconds(110),
+                amount: Some(Uint128::new(3)),
+            }],
+            required_approvers: vec![Addr::unchecked("approver")],
+            legs: vec![TransferLeg {
+                address: Addr::unchecked("recipient"),
+                amount: Uint128::new(3),
+            }],
+            additional_coins: vec![Coin::new(5, "restricted_2")],
+            vesting: Some(Vesting {
+                duration: 60,
+                start: Some(Timestamp::from_seconds(110)),
+            }),
+            claimed: Uint128::new(1),
+            memo: Some("memo".into()),
+            updated_at: Some(Timestamp::from_seconds(110)),
+            sequence: Some(2),
+            tags: vec!["tranche-A".into()],
+            position: None,
+            priority: false,
+            expires: None,
+            ..test_transfer(TRANSFER_ID, "sender")
+        };
+
+        let compact = to_vec(&StoredTransfer(transfer.clone())).unwrap();
+        assert_eq!(b'[', compact[0]);
+        assert!(compact.len() < to_vec(&transfer).unwrap().len());
+        assert_eq!(
+            StoredTransfer(transfer),
+            from_slice::<StoredTransfer>(&compact).unwrap()
+        );
+    }
+
+    #[test]
+    fn stored_transfer_reads_objects_and_shorter_arrays() {
+        let transfer = test_transfer(TRANSFER_ID, "sender");
+
+        // records written before the compact encoding
+        assert_eq!(
+            StoredTransfer(transfer.clone()),
+            from_slice::<StoredTransfer>(&to_vec(&transfer).unwrap()).unwrap()
+        );
+
+        // fields missing from the end take their defaults
+        let fields = format!(
+            r#"["{}","sender","restricted_1","3","recipient"]"#,
+            TRANSFER_ID
+        );
+        assert_eq!(
+            StoredTransfer(Transfer {
+                created: None,
+                ..transfer
+            }),
+            from_slice::<StoredTransfer>(fields.as_bytes()).unwrap()
+        );
+        assert!(from_slice::<StoredTransfer>(br#"["id","sender"]"#).is_err());
+    }
+}